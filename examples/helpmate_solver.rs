@@ -2,7 +2,7 @@
 //! **NOTE**: rschess prioritizes feature-richness and ease of use over performance,
 //! which makes it unsuitable for this purpose.
 
-use rschess::{Board, Fen};
+use rschess::{Board, Fen, MateKind};
 use std::{
     env, fmt,
     io::{self, Error as IoError, Write},
@@ -10,6 +10,7 @@ use std::{
     time::Instant,
 };
 
+#[allow(dead_code)]
 fn input(prompt: &str) -> Result<String, IoError> {
     print!("{prompt}");
     let _ = io::stdout().flush();
@@ -23,23 +24,6 @@ fn error<T: fmt::Display>(e: T) -> ! {
     process::exit(1);
 }
 
-fn search(board: Board, maxdepth: usize) -> Vec<Board> {
-    if maxdepth == 0 {
-        return Vec::new();
-    }
-    let mut sols = Vec::new();
-    for m in board.gen_legal_moves() {
-        let mut new_board = board.clone();
-        new_board.make_move(m).unwrap();
-        if new_board.is_checkmate() && new_board.checkmated_side().unwrap().is_black() {
-            sols.push(new_board)
-        } else {
-            sols.append(&mut search(new_board, maxdepth - 1))
-        }
-    }
-    sols
-}
-
 fn main() {
     let maxdepth: usize = if let Some(n) = env::args().nth(1) { n.parse().expect("Invalid max depth") } else { 4 };
     // let fen = Fen::try_from(input("Enter the position FEN: ").expect("Failed to read user input").as_str()).unwrap_or_else(|e| error(e));
@@ -50,7 +34,7 @@ fn main() {
     let board = Board::from_fen(fen);
     println!("{board}\nSearching for helpmates (depth = {maxdepth})...");
     let start = Instant::now();
-    let sols = search(board, maxdepth);
+    let sols = board.solve_mate(maxdepth, MateKind::Helpmate);
     let end = Instant::now();
     let dur = end.duration_since(start);
     println!("Finished searching in {dur:?}");
@@ -59,7 +43,11 @@ fn main() {
     } else {
         println!("Solution(s):");
         for sol in sols {
-            println!("{}", sol.gen_movetext());
+            let mut board = board.clone();
+            for move_ in sol {
+                board.make_move(move_).unwrap();
+            }
+            println!("{}", board.gen_movetext());
         }
     }
 }
@@ -0,0 +1,58 @@
+use super::{fen::FenRules, Fen, GameResult, InvalidFenError, Move, PieceType, Position, SpecialMoveType, Variant, VariantBoard, WinType};
+
+/// A game of Antichess (see [`AntichessRules`]).
+pub type Antichess = VariantBoard<AntichessRules>;
+
+/// The rules of Antichess (also known as giveaway or losing chess), in which the aim is to lose all of one's pieces.
+///
+/// The rules differ from standard chess as follows:
+/// * captures are mandatory: if a capture is possible, one must be played (though any capture may be chosen)
+/// * there is no check, checkmate, or castling, and kings are ordinary pieces which may be captured
+/// * pawns may also promote to kings
+/// * a side wins by losing all of its pieces, or by being stalemated (having no legal moves)
+///
+/// In FENs, each side may have any number of kings (including none), and there must be no castling rights.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub struct AntichessRules;
+
+impl Variant for AntichessRules {
+    type State = ();
+
+    /// The standard starting position, without castling rights.
+    fn starting_fen(&self) -> &'static str {
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1"
+    }
+
+    fn parse_fen(&self, fen: &str) -> Result<Fen, InvalidFenError> {
+        Fen::parse_with(
+            fen,
+            FenRules {
+                royal_kings: false,
+                ..FenRules::STANDARD
+            },
+        )
+    }
+
+    fn legal_moves(&self, position: &Position) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for sq in 0..64 {
+            for move_ in position.gen_pseudolegal_moves_sq(sq) {
+                moves.push(move_);
+                if move_.2 == Some(SpecialMoveType::Promotion(PieceType::Q)) {
+                    moves.push(Move(move_.0, move_.1, Some(SpecialMoveType::Promotion(PieceType::K))));
+                }
+            }
+        }
+        if moves.iter().any(|move_| position.captures(move_)) {
+            moves.retain(|move_| position.captures(move_));
+        }
+        moves
+    }
+
+    /// A side which loses all of its pieces or is stalemated wins with [`WinType::Variant`].
+    fn result(&self, position: &Position, _state: &(), legal_moves: &[Move]) -> Option<GameResult> {
+        let side = position.side;
+        let has_pieces = position.content.iter().flatten().any(|piece| piece.1 == side);
+        (!has_pieces || legal_moves.is_empty()).then_some(GameResult::Wins(side, WinType::Variant))
+    }
+}
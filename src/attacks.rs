@@ -0,0 +1,99 @@
+use super::{tables, Bitboard, Color, Piece, PieceType};
+
+/// Represents the attacks of the pieces of a position (see [`Position::attack_map`](crate::Position::attack_map)):
+/// for every square, the pieces of each side attacking it. As with [`Position::attackers_of`](crate::Position::attackers_of),
+/// a piece attacks a square if it could capture an enemy piece on that square, so pieces also attack (defend) squares occupied by their own side.
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub struct AttackMap {
+    /// The squares of the attackers of each square, indexed by side (white first) and then by square
+    attackers: [[Bitboard; 64]; 2],
+}
+
+impl AttackMap {
+    /// Computes the attack map of the position with the given content.
+    pub(crate) fn of(content: &[Option<Piece>; 64]) -> Self {
+        let mut attackers = [[0; 64]; 2];
+        let occupied = tables::occupancy(content);
+        for (sq, piece) in content.iter().enumerate() {
+            if let Some(Piece(_, color)) = piece {
+                let mut attacks = attacks_with_occupancy(content, sq, occupied);
+                while attacks != 0 {
+                    attackers[side_index(*color)][attacks.trailing_zeros() as usize] |= 1 << sq;
+                    attacks &= attacks - 1;
+                }
+            }
+        }
+        Self { attackers }
+    }
+
+    /// Returns the squares of the pieces of color `side` attacking the square `sq`.
+    pub fn attackers(&self, sq: usize, side: Color) -> Bitboard {
+        self.attackers[side_index(side)][sq]
+    }
+
+    /// Counts the pieces of color `side` attacking the square `sq`.
+    pub fn count(&self, sq: usize, side: Color) -> u32 {
+        self.attackers(sq, side).count_ones()
+    }
+
+    /// Returns the squares attacked by at least one piece of color `side`.
+    pub fn attacked_squares(&self, side: Color) -> Bitboard {
+        (0..64).filter(|&sq| self.attackers(sq, side) != 0).fold(0, |attacked, sq| attacked | 1 << sq)
+    }
+}
+
+fn side_index(side: Color) -> usize {
+    if side.is_white() {
+        0
+    } else {
+        1
+    }
+}
+
+/// Returns the squares attacked by the piece on the square `sq` (nothing if the square is empty).
+/// Long-range pieces attack along their lines up to and including the first occupied square.
+pub(crate) fn attacks_from(content: &[Option<Piece>; 64], sq: usize) -> Bitboard {
+    attacks_with_occupancy(content, sq, tables::occupancy(content))
+}
+
+/// Returns the squares attacked by the piece on the square `sq`, given the occupied squares of the position.
+fn attacks_with_occupancy(content: &[Option<Piece>; 64], sq: usize, occupied: Bitboard) -> Bitboard {
+    match content[sq] {
+        None => 0,
+        Some(Piece(PieceType::P, color)) => tables::pawn_attacks(color, sq),
+        Some(Piece(PieceType::N, _)) => tables::KNIGHT_ATTACKS[sq],
+        Some(Piece(PieceType::K, _)) => tables::KING_ATTACKS[sq],
+        Some(Piece(PieceType::B, _)) => tables::bishop_attacks(sq, occupied),
+        Some(Piece(PieceType::R, _)) => tables::rook_attacks(sq, occupied),
+        Some(Piece(PieceType::Q, _)) => tables::queen_attacks(sq, occupied),
+    }
+}
+
+/// Returns the squares attacked by the pieces of color `side`.
+pub(crate) fn attacked_squares(content: &[Option<Piece>; 64], side: Color) -> Bitboard {
+    let occupied = tables::occupancy(content);
+    (0..64)
+        .filter(|&sq| matches!(content[sq], Some(Piece(_, color)) if color == side))
+        .fold(0, |attacked, sq| attacked | attacks_with_occupancy(content, sq, occupied))
+}
+
+/// Returns the squares of the pieces of color `side` attacking the square `sq`, given the occupied squares of the position,
+/// by looking outwards from `sq` for attackers instead of generating the attacks of every piece.
+pub(crate) fn attackers(content: &[Option<Piece>; 64], sq: usize, side: Color, occupied: Bitboard) -> Bitboard {
+    let of_types = |candidates: Bitboard, piece_types: &[PieceType]| {
+        tables::squares(candidates)
+            .filter(|&i| matches!(content[i], Some(Piece(pt, color)) if color == side && piece_types.contains(&pt)))
+            .fold(0, |attackers, i| attackers | 1 << i)
+    };
+    // a pawn of color `side` attacks `sq` from the squares a pawn of the other color on `sq` would attack
+    of_types(tables::pawn_attacks(!side, sq), &[PieceType::P])
+        | of_types(tables::KNIGHT_ATTACKS[sq], &[PieceType::N])
+        | of_types(tables::KING_ATTACKS[sq], &[PieceType::K])
+        | of_types(tables::rook_attacks(sq, occupied), &[PieceType::R, PieceType::Q])
+        | of_types(tables::bishop_attacks(sq, occupied), &[PieceType::B, PieceType::Q])
+}
+
+/// Checks whether any piece of color `side` attacks the square `sq`.
+pub(crate) fn is_attacked(content: &[Option<Piece>; 64], sq: usize, side: Color) -> bool {
+    attackers(content, sq, side, tables::occupancy(content)) != 0
+}
@@ -1,10 +1,103 @@
 use super::{
-    helpers, Color, DrawType, Fen, GameOverError, GameResult, IllegalMoveError, InvalidSanMoveError, InvalidSquareNameError, InvalidUciMoveError, Move, NoMovesPlayedError, Piece, PieceType, Position,
-    WinType,
+    helpers, hint,
+    metrics::{self, ParseKind},
+    problem, BoardEvent, BoardInvariantError, Color, DrawClaimError, DrawType, Epd, EpdOperation, Fen, GameBuilder, GameOverError, GameResult, Hint, HintError, IllegalMoveError, IllegalMoveReason,
+    InvalidEpdError, InvalidLanMoveError, InvalidSanMoveError, InvalidSquareNameError, InvalidUciLineError, InvalidUciMoveError, LegalMoves, MateKind, Move, NavigationError, NoMovesPlayedError,
+    Outcome, Piece, PieceType, Position, RepetitionKey, Rules, SpecialMoveType, TakebackError, TakebackOffer, TimeControl, WinType,
 };
-use std::fmt;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    hash::{Hash, Hasher},
+    iter,
+    sync::OnceLock,
+    time::Duration,
+};
+
+/// Determines what happens to the moves ahead of the current ply (after stepping back with [`Board::back`] or [`Board::goto_ply`])
+/// when a move other than the next of them is made.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub enum FuturePolicy {
+    /// The moves ahead are discarded.
+    #[default]
+    Truncate,
+    /// The moves ahead are kept as a branch, which can be retrieved with [`Board::branches`].
+    Branch,
+}
+
+/// Determines which draws end a game automatically, and which only allow a player to claim a draw (see [`Board::claimable_draws`]).
+/// Checkmate and stalemate always end the game.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub enum TerminationPolicy {
+    /// Every draw of the rule set (see [`Rules`]) ends the game automatically.
+    #[default]
+    Automatic,
+    /// The draws of the rule set by repetition, the halfmove limit, insufficient material, and dead positions do not end the game,
+    /// but can be claimed, as when a server leaves such draws to the players.
+    ByClaim,
+}
+
+/// Represents the changes made to the moves of a game by [`Board::sync_moves`].
+#[derive(Eq, PartialEq, Hash, Clone, Debug, Default)]
+pub struct MoveListDiff {
+    /// The number of moves which were kept (the length of the common prefix of the old and new moves)
+    pub kept: usize,
+    /// The moves which were removed after the kept moves, in order
+    pub removed: Vec<Move>,
+    /// The moves which were added after the kept moves, in order
+    pub added: Vec<Move>,
+}
+
+impl MoveListDiff {
+    /// Checks whether no moves were changed.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+}
+
+/// Determines after which moves FEN checkpoints are written into movetext (see [`Board::gen_movetext_with_checkpoints`]).
+/// A checkpoint is written after a move if any of the enabled conditions holds, and none are written by default.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub struct FenCheckpoints {
+    /// Write a checkpoint after every _n_th halfmove from the initial position (`Some(20)` for every 10 moves)
+    pub interval: Option<usize>,
+    /// Write a checkpoint after every capture
+    pub captures: bool,
+}
+
+impl FenCheckpoints {
+    /// Checks whether a checkpoint is written after the move leading to the given ply (counting from 1), given whether it is a capture.
+    fn after(&self, ply: usize, capture: bool) -> bool {
+        self.interval.is_some_and(|interval| interval > 0 && ply.is_multiple_of(interval)) || (self.captures && capture)
+    }
+}
+
+/// The legal moves and check status of the current position of a [`Board`], computed on first use and discarded whenever the position changes,
+/// so that querying several of them (as a GUI does every frame) generates moves only once. It is ignored when boards are compared or hashed.
+#[derive(Clone, Debug, Default)]
+struct PositionCache {
+    /// The legal moves in the position (regardless of whether the game is over)
+    legal_moves: OnceLock<Vec<Move>>,
+    /// The side in check, if any
+    checked_side: OnceLock<Option<Color>>,
+}
+
+impl PartialEq for PositionCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for PositionCache {}
+
+impl Hash for PositionCache {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
 
 /// The structure for a chessboard/game
+///
+/// Two boards compare equal with `==` only if their full game state (including history and clocks) is equal;
+/// use [`Board::same_position`] to compare only the current positions.
 #[derive(Eq, PartialEq, Hash, Clone, Debug)]
 pub struct Board {
     /// The position on the board
@@ -17,33 +110,79 @@ pub struct Board {
     ongoing: bool,
     /// The list of positions that have occurred on the board
     position_history: Vec<Position>,
+    /// The repetition keys of the positions that have occurred on the board, including the current one, kept in step with the positions
+    repetition_keys: Vec<RepetitionKey>,
     /// The list of moves that have occurred on the board
     move_history: Vec<Move>,
     /// The halfmove clock values that have occured
     halfmove_clock_history: Vec<usize>,
     /// The FEN string representing the initial game state
     initial_fen: Fen,
-    /// The side that has resigned (or lost by timeout)
+    /// The side that has resigned
     resigned_side: Option<Color>,
-    /// Whether a draw has been made by agreement (or claimed)
+    /// The side that has run out of time
+    flagged_side: Option<Color>,
+    /// Whether a draw has been made by agreement
     draw_agreed: bool,
+    /// The draw that has been claimed, if any
+    claimed_draw: Option<DrawType>,
+    /// The rule set used to adjudicate the game
+    rules: Rules,
+    /// The time control of the game
+    time_control: Option<TimeControl>,
+    /// The time remaining on the clock of the side which played each move, just after the move (if recorded)
+    move_clocks: Vec<Option<Duration>>,
+    /// Metadata about the game, such as the names of the players
+    metadata: BTreeMap<String, String>,
+    /// The pending takeback offer
+    takeback_offer: Option<TakebackOffer>,
+    /// The side that has offered a draw, if the offer is pending
+    draw_offer: Option<Color>,
+    /// The events that have been recorded and not yet drained, or `None` if events are not being recorded
+    events: Option<Vec<BoardEvent>>,
+    /// The moves ahead of the current ply, which can be replayed with `forward`, with the next move last
+    future: Vec<Move>,
+    /// What happens to the moves ahead of the current ply when a different move is made
+    future_policy: FuturePolicy,
+    /// Which draws end the game automatically
+    termination_policy: TerminationPolicy,
+    /// The lines of moves which have been branched off from, each in the format (_ply_, _moves_)
+    branches: Vec<(usize, Vec<Move>)>,
+    /// The cached legal moves and check status of the current position
+    cache: PositionCache,
 }
 
 impl Board {
     /// Constructs a `Board` from a `Fen` object.
     pub fn from_fen(fen: Fen) -> Self {
         let (position, halfmove_clock, fullmove_number) = (fen.position().clone(), fen.halfmove_clock(), fen.fullmove_number());
+        let repetition_keys = vec![position.repetition_key()];
         let mut board = Self {
             position,
             halfmove_clock,
             fullmove_number,
-            ongoing: halfmove_clock < 150,
+            ongoing: true,
             position_history: Vec::new(),
+            repetition_keys,
             move_history: Vec::new(),
             halfmove_clock_history: Vec::new(),
             initial_fen: fen,
             resigned_side: None,
+            flagged_side: None,
             draw_agreed: false,
+            claimed_draw: None,
+            rules: Rules::default(),
+            time_control: None,
+            move_clocks: Vec::new(),
+            metadata: BTreeMap::new(),
+            takeback_offer: None,
+            draw_offer: None,
+            events: None,
+            future: Vec::new(),
+            future_policy: FuturePolicy::default(),
+            termination_policy: TerminationPolicy::default(),
+            branches: Vec::new(),
+            cache: PositionCache::default(),
         };
         board.update_status();
         board
@@ -58,38 +197,224 @@ impl Board {
         }
     }
 
+    /// Returns an EPD record of the current position with the given operations,
+    /// returning an error if the operands of a move opcode (such as `bm`) are invalid or illegal.
+    pub fn to_epd(&self, operations: Vec<EpdOperation>) -> Result<Epd, InvalidEpdError> {
+        Epd::new(self.position.clone(), operations)
+    }
+
     /// Represents a `Move` in SAN, returning an error if the move is illegal.
     pub fn move_to_san(&self, move_: Move) -> Result<String, IllegalMoveError> {
-        let move_ = helpers::as_legal(move_, &self.gen_legal_moves()).ok_or(IllegalMoveError(move_))?;
-        self.position.move_to_san(move_)
+        if !self.ongoing {
+            return Err(IllegalMoveError(move_));
+        }
+        self.position.move_to_san_among(move_, self.cached_legal_moves())
+    }
+
+    /// Represents every legal move in SAN, in the order of [`Board::gen_legal_moves`] (see [`Position::legal_moves_san`]).
+    pub fn legal_moves_san(&self) -> Vec<String> {
+        if self.ongoing {
+            self.position.legal_moves_san_among(self.cached_legal_moves())
+        } else {
+            Vec::new()
+        }
     }
 
     /// Constructs a `Move` from a SAN representation, returning an error if it is invalid or illegal.
     pub fn san_to_move(&self, san: &str) -> Result<Move, InvalidSanMoveError> {
-        match self.position.san_to_move(san) {
-            Ok(m) => {
-                if self.is_legal(m) {
-                    Ok(m)
-                } else {
-                    Err(InvalidSanMoveError(san.to_owned()))
+        metrics::record_parse(
+            ParseKind::San,
+            match self.position.san_to_move(san) {
+                Ok(m) => {
+                    if self.is_legal(m) {
+                        Ok(m)
+                    } else {
+                        Err(InvalidSanMoveError(san.to_owned()))
+                    }
                 }
-            }
-            e => e,
-        }
+                e => e,
+            },
+        )
+    }
+
+    /// Constructs a `Move` from a SAN representation, accepting the non-standard SAN described in [`Position::san_to_move_lenient`].
+    /// Returns an error if the move is invalid, illegal, or ambiguous.
+    pub fn san_to_move_lenient(&self, san: &str) -> Result<Move, InvalidSanMoveError> {
+        metrics::record_parse(
+            ParseKind::San,
+            match self.position.san_to_move_lenient(san) {
+                Ok(m) if !self.is_legal(m) => Err(InvalidSanMoveError(san.to_owned())),
+                res => res,
+            },
+        )
+    }
+
+    /// Constructs a `Move` from the exact SAN produced by [`Board::move_to_san`] (see [`Position::san_to_move_strict`]),
+    /// returning an error for any other SAN or if the move is illegal.
+    pub fn san_to_move_strict(&self, san: &str) -> Result<Move, InvalidSanMoveError> {
+        metrics::record_parse(
+            ParseKind::San,
+            match self.position.san_to_move_strict(san) {
+                Ok(m) if !self.is_legal(m) => Err(InvalidSanMoveError(san.to_owned())),
+                res => res,
+            },
+        )
+    }
+
+    /// Rewrites a leniently written SAN move as the exact SAN produced by [`Board::move_to_san`] (see [`Position::normalize_san`]),
+    /// returning an error if it is invalid, illegal, or ambiguous.
+    pub fn normalize_san(&self, san: &str) -> Result<String, InvalidSanMoveError> {
+        self.move_to_san(self.san_to_move_lenient(san)?).map_err(|_| InvalidSanMoveError(san.to_owned()))
+    }
+
+    /// Represents a `Move` in figurine algebraic notation (such as `♘f3`), returning an error if the move is illegal.
+    pub fn move_to_fan(&self, move_: Move) -> Result<String, IllegalMoveError> {
+        let move_ = helpers::as_legal(move_, self.legal_move_slice()).ok_or(IllegalMoveError(move_))?;
+        self.position.move_to_fan(move_)
+    }
+
+    /// Represents a `Move` in long algebraic notation, returning an error if the move is illegal.
+    pub fn move_to_lan(&self, move_: Move) -> Result<String, IllegalMoveError> {
+        let move_ = helpers::as_legal(move_, self.legal_move_slice()).ok_or(IllegalMoveError(move_))?;
+        self.position.move_to_lan(move_)
+    }
+
+    /// Constructs a `Move` from long algebraic notation, returning an error if it is invalid or illegal.
+    pub fn lan_to_move(&self, lan: &str) -> Result<Move, InvalidLanMoveError> {
+        metrics::record_parse(
+            ParseKind::Lan,
+            match self.position.lan_to_move(lan) {
+                Ok(m) if !self.is_legal(m) => Err(InvalidLanMoveError(lan.to_owned())),
+                res => res,
+            },
+        )
     }
 
     /// Generates the legal moves in the position.
     pub fn gen_legal_moves(&self) -> Vec<Move> {
+        self.legal_move_slice().to_vec()
+    }
+
+    /// Returns the legal moves in the position, which are empty if the game is over.
+    fn legal_move_slice(&self) -> &[Move] {
         if self.ongoing {
-            self.position.gen_non_illegal_moves()
+            self.cached_legal_moves()
+        } else {
+            &[]
+        }
+    }
+
+    /// Returns the legal moves in the current position regardless of whether the game is over, generating them if they are not cached.
+    fn cached_legal_moves(&self) -> &[Move] {
+        self.cache.legal_moves.get_or_init(|| self.position.gen_non_illegal_moves_uncached())
+    }
+
+    /// Discards the cached legal moves and check status, after the position has changed.
+    fn invalidate_cache(&mut self) {
+        self.cache = PositionCache::default();
+    }
+
+    /// Returns a lazy iterator over the legal moves in the position (which yields nothing if the game is over).
+    pub fn legal_moves(&self) -> LegalMoves<'_> {
+        if self.ongoing {
+            self.position.legal_moves()
+        } else {
+            LegalMoves::empty(&self.position)
+        }
+    }
+
+    /// Generates the legal moves of the piece on the given square (such as the destinations to highlight when a user picks up a piece).
+    /// This is empty if the game is over, the square is empty or out of range, or the piece does not belong to the side to move.
+    pub fn legal_moves_from(&self, square: usize) -> Vec<Move> {
+        if self.ongoing && square < 64 {
+            self.position.gen_non_illegal_moves_sq(square)
         } else {
             Vec::new()
         }
     }
 
+    /// Maps the square of every piece of the side to move to the destination squares of its legal moves (see [`Position::mobility_map`]),
+    /// so that a GUI can look up the squares to highlight for any piece picked up without generating moves again. This is empty if the game is over.
+    pub fn mobility_map(&self) -> BTreeMap<usize, Vec<usize>> {
+        if self.ongoing {
+            self.position.mobility_map()
+        } else {
+            BTreeMap::new()
+        }
+    }
+
+    /// Generates the legal moves of the pieces of the given type belonging to the side to move (which is empty if the game is over).
+    pub fn legal_moves_of(&self, piece_type: PieceType) -> Vec<Move> {
+        if self.ongoing {
+            self.position.gen_non_illegal_moves_of(piece_type)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Returns a hash of the pawns and kings in the position (see [`Position::pawn_hash`]).
+    pub fn pawn_hash(&self) -> u64 {
+        self.position.pawn_hash()
+    }
+
+    /// Returns the Zobrist hash of the current position (see [`Position::zobrist_hash`]).
+    pub fn zobrist_hash(&self) -> u64 {
+        self.position.zobrist_hash()
+    }
+
+    /// Checks whether the side to move has any legal move, stopping at the first one found.
+    pub fn has_legal_move(&self) -> bool {
+        self.legal_moves().next().is_some()
+    }
+
     /// Checks whether a move is legal in the position.
     pub fn is_legal(&self, move_: Move) -> bool {
-        helpers::as_legal(move_, &self.gen_legal_moves()).is_some()
+        helpers::as_legal(move_, self.legal_move_slice()).is_some()
+    }
+
+    /// Explains why the given move is illegal on the board (see [`Position::why_illegal`]), returning `None` if it is legal.
+    pub fn why_illegal(&self, move_: Move) -> Option<IllegalMoveReason> {
+        if !self.ongoing {
+            return Some(IllegalMoveReason::GameOver);
+        }
+        self.position.why_illegal(move_)
+    }
+
+    /// Returns a hint for the side to move, at the given level: level 1 names the type of piece to move, level 2 the square of the piece,
+    /// and level 3 the full move. Every level refers to the same move, which is picked by a shallow search that looks for checkmates and material gains.
+    pub fn hint(&self, level: u8) -> Result<Hint, HintError> {
+        if !self.ongoing {
+            return Err(HintError::GameOver);
+        }
+        if !(1..=3).contains(&level) {
+            return Err(HintError::InvalidLevel(level));
+        }
+        let move_ = hint::hint_move(&self.position).ok_or(HintError::GameOver)?;
+        Ok(match level {
+            1 => Hint::Piece(self.position.content[move_.0].unwrap().0),
+            2 => {
+                let (file, rank) = move_.from_square();
+                Hint::Square(file, rank)
+            }
+            _ => Hint::Move {
+                move_,
+                san: self.position.move_to_san(move_).unwrap(),
+            },
+        })
+    }
+
+    /// Solves a mate problem with the given stipulation in the current position within the given number of plies (halfmoves),
+    /// returning every solution line as a sequence of moves ending in checkmate, or no lines if there is no solution (or the game is over).
+    /// A mate in _n_ moves takes 2_n_ - 1 plies in direct mates, and 2_n_ plies in helpmates and selfmates.
+    ///
+    /// Where a side chooses its moves (the side to move in direct mates and selfmates, and both sides in helpmates),
+    /// the lines branch on every move which reaches checkmate in time, including duals; where a side defends, they branch on every legal reply.
+    /// As in chess problems, the fifty-move rule and repetitions are not taken into account.
+    pub fn solve_mate(&self, max_plies: usize, kind: MateKind) -> Vec<Vec<Move>> {
+        if !self.ongoing {
+            return Vec::new();
+        }
+        problem::solve_mate(&self.position, max_plies, kind)
     }
 
     /// Checks whether the given move is a capture, returning an error if the move is illegal.
@@ -100,34 +425,104 @@ impl Board {
         self.position.is_capture(move_)
     }
 
+    /// Returns the piece captured by the given move (`None` if it is not a capture), returning an error if the move is illegal.
+    pub fn captured_piece(&self, move_: Move) -> Result<Option<Piece>, IllegalMoveError> {
+        if !self.ongoing {
+            return Err(IllegalMoveError(move_));
+        }
+        self.position.captured_piece(move_)
+    }
+
+    /// Checks whether the given move gives check, returning an error if the move is illegal.
+    pub fn gives_check(&self, move_: Move) -> Result<bool, IllegalMoveError> {
+        if !self.ongoing {
+            return Err(IllegalMoveError(move_));
+        }
+        self.position.gives_check(move_)
+    }
+
+    /// Checks whether the given move is castling, returning an error if the move is illegal.
+    pub fn is_castling(&self, move_: Move) -> Result<bool, IllegalMoveError> {
+        if !self.ongoing {
+            return Err(IllegalMoveError(move_));
+        }
+        self.position.is_castling(move_)
+    }
+
     /// Plays on the board the given move, returning an error if the move is illegal.
+    /// Playing a move clears any pending takeback or draw offer (see [`Board::offer_takeback`] and [`Board::offer_draw`]).
     pub fn make_move(&mut self, move_: Move) -> Result<(), IllegalMoveError> {
-        let move_ = match helpers::as_legal(move_, &self.gen_legal_moves()) {
+        let move_ = match helpers::as_legal(move_, self.legal_move_slice()) {
             Some(m) => m,
             _ => return Err(IllegalMoveError(move_)),
         };
         let mut halfmove_clock = self.halfmove_clock;
         let fullmove_number = self.fullmove_number + if self.position.side.is_black() { 1 } else { 0 };
-        let Move(move_src, move_dest, ..) = move_;
+        if let Some(&next) = self.future.last() {
+            if next == move_ {
+                self.future.pop();
+            } else {
+                self.leave_future();
+            }
+        }
+        let Move(move_src, move_dest, spec) = move_;
         let (moved_piece, dest_occ) = (self.position.content[move_src], self.position.content[move_dest]);
+        if self.events.is_some() {
+            let side = self.position.side;
+            let mut events = vec![BoardEvent::MoveMade {
+                move_,
+                san: self.position.move_to_san(move_).unwrap(),
+                uci: move_.to_uci(),
+            }];
+            match spec {
+                Some(SpecialMoveType::EnPassant) => events.push(BoardEvent::Capture(Piece(PieceType::P, !side))),
+                Some(SpecialMoveType::CastlingKingside) => events.push(BoardEvent::Castling { side, kingside: true }),
+                Some(SpecialMoveType::CastlingQueenside) => events.push(BoardEvent::Castling { side, kingside: false }),
+                _ => {
+                    if let Some(captured) = dest_occ {
+                        events.push(BoardEvent::Capture(captured));
+                    }
+                    if let Some(SpecialMoveType::Promotion(piece_type)) = spec {
+                        events.push(BoardEvent::Promotion(piece_type));
+                    }
+                }
+            }
+            for event in events {
+                self.emit(event);
+            }
+        }
         if matches!(moved_piece, Some(Piece(PieceType::P, _))) || dest_occ.is_some() {
             halfmove_clock = 0;
         } else {
             halfmove_clock += 1;
         }
         self.position_history.push(self.position.clone());
-        self.position = self.position.with_move_made(move_).unwrap();
+        self.position = self.position.with_legal_move_made(move_);
+        self.invalidate_cache();
+        self.repetition_keys.push(self.position.repetition_key());
         self.move_history.push(move_);
+        self.move_clocks.push(None);
         self.halfmove_clock_history.push(self.halfmove_clock);
         (self.halfmove_clock, self.fullmove_number) = (halfmove_clock, fullmove_number);
+        self.takeback_offer = None;
+        self.draw_offer = None;
         self.update_status();
+        if let Some(side) = self.checked_side() {
+            self.emit(BoardEvent::Check(side));
+        }
+        self.emit_game_over();
+        self.debug_verify();
+        metrics::record(|m| m.move_made());
         Ok(())
     }
 
     /// Attempts to parse the UCI representation of a move and play it on the board, returning an error if the move is invalid or illegal.
     pub fn make_move_uci(&mut self, uci: &str) -> Result<(), InvalidUciMoveError> {
-        let move_ = Move::from_uci(uci).map_err(|_| InvalidUciMoveError::InvalidUci(uci.to_owned()))?;
-        self.make_move(move_).map_err(|_| InvalidUciMoveError::IllegalMove(uci.to_owned()))
+        let res = match Move::from_uci(uci) {
+            Ok(move_) => self.make_move(move_).map_err(|_| InvalidUciMoveError::IllegalMove(uci.to_owned())),
+            Err(_) => Err(InvalidUciMoveError::InvalidUci(uci.to_owned())),
+        };
+        metrics::record_parse(ParseKind::Uci, res)
     }
 
     /// Attempts to interpret the SAN representation of a move and play it on the board, returning an error if it is invalid or illegal.
@@ -136,13 +531,26 @@ impl Board {
         self.make_move(move_).map_err(|_| InvalidSanMoveError(san.to_owned()))
     }
 
-    /// Attempts to play the given line of UCI moves (separated by spaces, **excluding move numbers**) on the board,
-    /// returning an error if any move is illegal. If an error is returned, the board is left unchanged, i.e. no moves
-    /// are played on the board.
-    pub fn make_moves_uci(&mut self, line: &str) -> Result<(), InvalidUciMoveError> {
+    /// Attempts to interpret a leniently written SAN move (see [`Position::san_to_move_lenient`]) and play it on the board,
+    /// returning an error if it is invalid, illegal, or ambiguous.
+    pub fn make_move_san_lenient(&mut self, san: &str) -> Result<(), InvalidSanMoveError> {
+        let move_ = self.san_to_move_lenient(san)?;
+        self.make_move(move_).map_err(|_| InvalidSanMoveError(san.to_owned()))
+    }
+
+    /// Attempts to interpret a move in long algebraic notation and play it on the board, returning an error if it is invalid or illegal.
+    pub fn make_move_lan(&mut self, lan: &str) -> Result<(), InvalidLanMoveError> {
+        let move_ = self.lan_to_move(lan)?;
+        self.make_move(move_).map_err(|_| InvalidLanMoveError(lan.to_owned()))
+    }
+
+    /// Attempts to play the given line of UCI moves (separated by whitespace, such as an engine's principal variation
+    /// or the moves of a UCI `position` command) on the board, returning an error with the index of the first move
+    /// which is invalid or illegal. If an error is returned, the board is left unchanged, i.e. no moves are played on the board.
+    pub fn make_moves_uci(&mut self, line: &str) -> Result<(), InvalidUciLineError> {
         let mut board = self.clone();
-        for uci in line.split_ascii_whitespace() {
-            board.make_move_uci(uci)?;
+        for (index, uci) in line.split_whitespace().enumerate() {
+            board.make_move_uci(uci).map_err(|error| InvalidUciLineError { index, error })?;
         }
         *self = board;
         Ok(())
@@ -163,27 +571,284 @@ impl Board {
     /// Undoes the most recent move, returning an error if no moves have been played.
     /// Note that if the game had ended, calling this function sets the game to ongoing again.
     /// This will override any resignation or draw by agreement.
+    ///
+    /// Undone moves are discarded permanently, along with any moves ahead of the current ply; use [`Board::back`] to step back through the game instead.
     pub fn undo_move(&mut self) -> Result<(), NoMovesPlayedError> {
         if self.move_history.is_empty() {
             return Err(NoMovesPlayedError);
         }
+        self.future.clear();
+        self.unmake_move();
+        Ok(())
+    }
+
+    /// Undoes the most recent move (which must exist), returning it.
+    fn unmake_move(&mut self) -> Move {
         self.fullmove_number -= if self.side_to_move().is_white() { 1 } else { 0 };
-        self.move_history.pop();
+        let move_ = self.move_history.pop().unwrap();
+        self.move_clocks.pop();
+        self.emit(BoardEvent::MoveUndone(move_));
         self.position = self.position_history.pop().unwrap();
+        self.invalidate_cache();
+        self.repetition_keys.pop();
         self.halfmove_clock = self.halfmove_clock_history.pop().unwrap();
         self.ongoing = true;
         self.resigned_side = None;
+        self.flagged_side = None;
         self.draw_agreed = false;
+        self.claimed_draw = None;
+        self.takeback_offer = None;
+        self.draw_offer = None;
+        self.debug_verify();
+        move_
+    }
+
+    /// Updates the moves of the game to the given moves (played from the initial position), keeping the moves they have in common with
+    /// the moves played so far and only undoing and playing the moves after them, as when following successive (possibly corrected) versions
+    /// of a live broadcast. The kept moves stay at the same plies, so they can be identified by ply across updates.
+    ///
+    /// Returns the changes made, or an error (leaving the board unchanged) if one of the new moves is illegal. Any moves ahead of the current ply are discarded.
+    pub fn sync_moves(&mut self, moves: &[Move]) -> Result<MoveListDiff, IllegalMoveError> {
+        let kept = self.move_history.iter().zip(moves).take_while(|(a, b)| a == b).count();
+        let mut board = self.clone();
+        board.future.clear();
+        let removed = board.move_history[kept..].to_vec();
+        for _ in kept..board.move_history.len() {
+            board.unmake_move();
+        }
+        let mut added = Vec::new();
+        for &move_ in &moves[kept..] {
+            board.make_move(move_)?;
+            added.push(board.last_move().unwrap());
+        }
+        *self = board;
+        Ok(MoveListDiff { kept, removed, added })
+    }
+
+    /// Adopts the result of a game which ended by resignation, timeout, or agreement from another board with the same moves, or reopens the game if the other board's game is ongoing.
+    /// The time control and the recorded clock times are adopted too.
+    #[cfg(feature = "pgn")]
+    pub(crate) fn adopt_result(&mut self, other: &Self) {
+        self.time_control = other.time_control;
+        self.move_clocks.clone_from(&other.move_clocks);
+        self.ongoing = other.ongoing;
+        self.resigned_side = other.resigned_side;
+        self.flagged_side = other.flagged_side;
+        self.draw_agreed = other.draw_agreed;
+        self.claimed_draw = other.claimed_draw;
+    }
+
+    /// Steps back one move, keeping the move so that it can be replayed with [`Board::forward`], and returning an error if no moves have been played.
+    /// Like [`Board::undo_move`], this sets the game to ongoing again, overriding any resignation or draw by agreement.
+    pub fn back(&mut self) -> Result<(), NavigationError> {
+        if self.move_history.is_empty() {
+            return Err(NavigationError::AtStart);
+        }
+        let move_ = self.unmake_move();
+        self.future.push(move_);
+        Ok(())
+    }
+
+    /// Replays the next move ahead of the current ply (see [`Board::back`]), returning an error if there is none or if the game is over.
+    pub fn forward(&mut self) -> Result<(), NavigationError> {
+        let move_ = *self.future.last().ok_or(NavigationError::AtEnd)?;
+        self.make_move(move_).map_err(|_| NavigationError::GameOver)
+    }
+
+    /// Steps back or forward to the given ply (the number of halfmoves played from the initial position), returning an error
+    /// (and leaving the board unchanged) if the ply is beyond the moves ahead of the current ply.
+    pub fn goto_ply(&mut self, ply: usize) -> Result<(), NavigationError> {
+        let last_ply = self.move_history.len() + self.future.len();
+        if ply > last_ply {
+            return Err(NavigationError::PlyOutOfRange(ply, last_ply));
+        }
+        let mut board = self.clone();
+        while board.current_ply() > ply {
+            board.back()?;
+        }
+        while board.current_ply() < ply {
+            board.forward()?;
+        }
+        *self = board;
+        Ok(())
+    }
+
+    /// Returns the current ply: the number of halfmoves played from the initial position to the current position.
+    pub fn current_ply(&self) -> usize {
+        self.move_history.len()
+    }
+
+    /// Returns the moves ahead of the current ply, which can be replayed with [`Board::forward`], in order.
+    pub fn future_moves(&self) -> Vec<Move> {
+        self.future.iter().rev().copied().collect()
+    }
+
+    /// Returns what happens to the moves ahead of the current ply when a move other than the next of them is made.
+    pub fn future_policy(&self) -> FuturePolicy {
+        self.future_policy
+    }
+
+    /// Sets what happens to the moves ahead of the current ply when a move other than the next of them is made ([`FuturePolicy::Truncate`] by default).
+    pub fn set_future_policy(&mut self, policy: FuturePolicy) {
+        self.future_policy = policy;
+    }
+
+    /// Returns the lines of moves which have been branched off from under [`FuturePolicy::Branch`], in the order in which they were left,
+    /// each in the format (_ply_, _moves_), where _ply_ is the ply of the position from which the moves were played.
+    pub fn branches(&self) -> &[(usize, Vec<Move>)] {
+        &self.branches
+    }
+
+    /// Leaves the moves ahead of the current ply, discarding them or keeping them as a branch depending on the future policy.
+    fn leave_future(&mut self) {
+        let mut moves = std::mem::take(&mut self.future);
+        if self.future_policy == FuturePolicy::Branch {
+            moves.reverse();
+            self.branches.push((self.current_ply(), moves));
+        }
+    }
+
+    /// Undoes the given number of most recent moves, returning an error (and leaving the board unchanged) if fewer moves have been played.
+    /// Like [`Board::undo_move`], this sets the game to ongoing again.
+    pub fn undo_moves(&mut self, plies: usize) -> Result<(), NoMovesPlayedError> {
+        if plies > self.move_history.len() {
+            return Err(NoMovesPlayedError);
+        }
+        for _ in 0..plies {
+            self.undo_move()?;
+        }
+        Ok(())
+    }
+
+    /// Offers on behalf of the given side to take back the given number of most recent halfmoves (use [`TakebackOffer::plies_for_last_move`]
+    /// to take back the side's last move). Only one offer can be pending at a time, and it is cleared when any move is played, so the opponent
+    /// replying with a move declines it implicitly.
+    pub fn offer_takeback(&mut self, side: Color, plies: usize) -> Result<(), TakebackError> {
+        if !self.ongoing {
+            return Err(TakebackError::GameOver);
+        }
+        if plies == 0 || plies > self.move_history.len() {
+            return Err(TakebackError::Plies(plies, self.move_history.len()));
+        }
+        if self.takeback_offer.is_some() {
+            return Err(TakebackError::AlreadyPending);
+        }
+        let offer = TakebackOffer::new(side, plies);
+        self.takeback_offer = Some(offer);
+        self.emit(BoardEvent::TakebackOffered(offer));
+        Ok(())
+    }
+
+    /// Returns the pending takeback offer, if any.
+    pub fn takeback_offer(&self) -> Option<TakebackOffer> {
+        self.takeback_offer
+    }
+
+    /// Accepts the pending takeback offer on behalf of the given side, taking back the offered number of halfmoves all at once.
+    /// An error is returned (and the board is left unchanged) if there is no pending offer, if the side made the offer itself, or if the game is over.
+    pub fn accept_takeback(&mut self, side: Color) -> Result<(), TakebackError> {
+        let offer = self.takeback_offer.ok_or(TakebackError::NoPendingOffer)?;
+        if offer.side() == side {
+            return Err(TakebackError::OwnOffer(side));
+        }
+        if !self.ongoing {
+            return Err(TakebackError::GameOver);
+        }
+        self.undo_moves(offer.plies()).map_err(|_| TakebackError::Plies(offer.plies(), self.move_history.len()))
+    }
+
+    /// Declines the pending takeback offer on behalf of the given side, returning an error if there is no pending offer or if the side made the offer itself.
+    pub fn decline_takeback(&mut self, side: Color) -> Result<(), TakebackError> {
+        let offer = self.takeback_offer.ok_or(TakebackError::NoPendingOffer)?;
+        if offer.side() == side {
+            return Err(TakebackError::OwnOffer(side));
+        }
+        self.takeback_offer = None;
         Ok(())
     }
 
+    /// Withdraws the pending takeback offer made by the given side, returning an error if that side has no pending offer.
+    pub fn withdraw_takeback(&mut self, side: Color) -> Result<(), TakebackError> {
+        match self.takeback_offer {
+            Some(offer) if offer.side() == side => {
+                self.takeback_offer = None;
+                Ok(())
+            }
+            _ => Err(TakebackError::NoPendingOffer),
+        }
+    }
+
+    /// Starts or stops recording events (disabled by default). While recording, every event that happens on the board
+    /// (see [`BoardEvent`]) is queued until it is retrieved with [`Board::drain_events`]. Stopping discards any queued events.
+    pub fn record_events(&mut self, enabled: bool) {
+        match (enabled, self.events.is_some()) {
+            (true, false) => self.events = Some(Vec::new()),
+            (false, true) => self.events = None,
+            _ => (),
+        }
+    }
+
+    /// Returns the events that have been recorded since the last call, in the order in which they happened.
+    pub fn drain_events(&mut self) -> Vec<BoardEvent> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Records an event, if events are being recorded.
+    fn emit(&mut self, event: BoardEvent) {
+        if let Some(events) = &mut self.events {
+            events.push(event);
+        }
+    }
+
+    /// Records the end of the game, if it is over.
+    fn emit_game_over(&mut self) {
+        if let Some(result) = self.game_result() {
+            self.emit(BoardEvent::GameOver(result));
+        }
+    }
+
+    /// Checks whether the game has ended by the rules (rather than by resignation, timeout, agreement, or a claim), given the termination policy.
+    fn has_ended_by_rules(&self) -> bool {
+        self.is_stalemate() || self.is_checkmate() || (self.termination_policy == TerminationPolicy::Automatic && !self.rule_draws().is_empty())
+    }
+
+    /// Returns the draws of the rule set which apply in the current position and which end the game under [`TerminationPolicy::Automatic`].
+    fn rule_draws(&self) -> Vec<DrawType> {
+        let mut draws = Vec::new();
+        draws.extend(self.repetition_limit_draw());
+        draws.extend(self.halfmove_limit_draw());
+        if self.rules.insufficient_material_draw && self.is_insufficient_material() {
+            draws.push(DrawType::InsufficientMaterial);
+        }
+        if self.rules.dead_position_draw && self.is_dead_position() {
+            draws.push(DrawType::DeadPosition);
+        }
+        draws
+    }
+
     /// Updates the `ongoing` property of the `Board` if the game is over.
     fn update_status(&mut self) {
-        if self.is_fivefold_repetition() || self.is_seventy_five_move_rule() || self.is_stalemate() || self.is_insufficient_material() || self.is_checkmate() {
+        if self.has_ended_by_rules() {
             self.ongoing = false;
         }
     }
 
+    /// Re-checks the status of the game after the rules or the termination policy change: an ongoing game ends if it would have ended
+    /// under them, and a game which only ended by the rules is reopened if it would not have.
+    fn recheck_status(&mut self) {
+        if self.ongoing {
+            self.update_status();
+            self.emit_game_over();
+        } else if !self.is_decided() {
+            self.ongoing = !self.has_ended_by_rules();
+        }
+    }
+
+    /// Checks whether the game was decided by resignation, timeout, agreement, or a claim.
+    fn is_decided(&self) -> bool {
+        self.resigned_side.is_some() || self.flagged_side.is_some() || self.draw_agreed || self.claimed_draw.is_some()
+    }
+
     /// Checks whether the game is still ongoing.
     pub fn is_ongoing(&self) -> bool {
         self.ongoing
@@ -194,6 +859,112 @@ impl Board {
         !self.ongoing
     }
 
+    /// Checks the internal consistency of the board, returning the first violation found: that the recorded histories have the same length,
+    /// that every move played is legal and leads to the next position, that the halfmove clocks and the fullmove number follow from the moves,
+    /// that the castling rights and en passant target squares are consistent with the board, and that the status of the game agrees with its result.
+    ///
+    /// A board only becomes inconsistent through a bug, so this detects state corruption (such as after a sequence of moves, undos, and navigation)
+    /// when it happens, rather than when the game is exported. It replays the whole game, so its cost grows with the number of moves played.
+    /// In debug builds, the ply reached by each move and undo is checked automatically, panicking on a violation.
+    pub fn verify_invariants(&self) -> Result<(), BoardInvariantError> {
+        self.verify_history_lengths()?;
+        for ply in 0..=self.current_ply() {
+            self.verify_ply(ply)?;
+        }
+        self.verify_current_state()
+    }
+
+    /// Checks the invariants affected by the latest move or undo (see [`Board::verify_invariants`]) in debug builds, panicking if one is violated.
+    fn debug_verify(&self) {
+        if cfg!(debug_assertions) {
+            if let Err(error) = self
+                .verify_history_lengths()
+                .and_then(|_| self.verify_ply(self.current_ply()))
+                .and_then(|_| self.verify_current_state())
+            {
+                panic!("{error}");
+            }
+        }
+    }
+
+    /// Checks that the recorded histories have the same length.
+    fn verify_history_lengths(&self) -> Result<(), BoardInvariantError> {
+        let (positions, moves, halfmove_clocks, move_clocks) = (self.position_history.len(), self.move_history.len(), self.halfmove_clock_history.len(), self.move_clocks.len());
+        if [moves, halfmove_clocks, move_clocks].iter().any(|&len| len != positions) {
+            return Err(BoardInvariantError::HistoryLengths {
+                positions,
+                moves,
+                halfmove_clocks,
+                move_clocks,
+            });
+        }
+        if self.repetition_keys.len() != positions + 1 {
+            return Err(BoardInvariantError::RepetitionKey(self.repetition_keys.len().min(positions + 1)));
+        }
+        Ok(())
+    }
+
+    /// Checks the position and halfmove clock at the given ply (which must have been reached) against the initial FEN or the ply before it.
+    fn verify_ply(&self, ply: usize) -> Result<(), BoardInvariantError> {
+        let position = self.position_at_ply(ply).unwrap();
+        let halfmove_clock = self.halfmove_clock_history.get(ply).copied().unwrap_or(self.halfmove_clock);
+        if position.castling_rights != position.castling_rights().rooks() {
+            return Err(BoardInvariantError::CastlingRights(ply));
+        }
+        if !position.has_valid_ep_target() {
+            return Err(BoardInvariantError::EnPassantTarget(ply));
+        }
+        if self.repetition_keys[ply] != position.repetition_key() {
+            return Err(BoardInvariantError::RepetitionKey(ply));
+        }
+        if ply == 0 {
+            if position != self.initial_fen.position() {
+                return Err(BoardInvariantError::InitialPosition);
+            }
+            if halfmove_clock != self.initial_fen.halfmove_clock() {
+                return Err(BoardInvariantError::HalfmoveClock(0, self.initial_fen.halfmove_clock(), halfmove_clock));
+            }
+            return Ok(());
+        }
+        let (previous, move_) = (self.position_at_ply(ply - 1).unwrap(), self.move_history[ply - 1]);
+        if !previous.gen_non_illegal_moves().contains(&move_) {
+            return Err(BoardInvariantError::IllegalMove(ply, move_));
+        }
+        if previous.with_legal_move_made(move_) != *position {
+            return Err(BoardInvariantError::Position(ply));
+        }
+        let Move(move_src, move_dest, _) = move_;
+        let expected = if matches!(previous.content[move_src], Some(Piece(PieceType::P, _))) || previous.content[move_dest].is_some() {
+            0
+        } else {
+            self.halfmove_clock_history[ply - 1] + 1
+        };
+        if halfmove_clock != expected {
+            return Err(BoardInvariantError::HalfmoveClock(ply, expected, halfmove_clock));
+        }
+        Ok(())
+    }
+
+    /// Checks the fullmove number, the status of the game, and the pending takeback offer against the moves played.
+    fn verify_current_state(&self) -> Result<(), BoardInvariantError> {
+        let black_started = self.initial_fen.position().side.is_black();
+        let fullmove_number = self.initial_fen.fullmove_number() + (self.current_ply() + black_started as usize) / 2;
+        if self.fullmove_number != fullmove_number {
+            return Err(BoardInvariantError::FullmoveNumber(fullmove_number, self.fullmove_number));
+        }
+        let decided = self.is_decided();
+        if self.ongoing && (decided || self.has_ended_by_rules()) {
+            return Err(BoardInvariantError::OngoingAfterEnd);
+        }
+        if !self.ongoing && !decided && !self.has_ended_by_rules() {
+            return Err(BoardInvariantError::EndedWithoutResult);
+        }
+        match self.takeback_offer {
+            Some(offer) if offer.plies() > self.current_ply() => Err(BoardInvariantError::TakebackOffer(offer.plies(), self.current_ply())),
+            _ => Ok(()),
+        }
+    }
+
     /// Returns an optional game result (`None` if the game is ongoing).
     pub fn game_result(&self) -> Option<GameResult> {
         if self.ongoing {
@@ -201,8 +972,16 @@ impl Board {
         } else {
             Some(if self.draw_agreed {
                 GameResult::Draw(DrawType::Agreement)
+            } else if let Some(draw_type) = self.claimed_draw {
+                GameResult::Draw(draw_type)
             } else if let Some(s) = self.resigned_side {
                 GameResult::Wins(!s, WinType::Resignation)
+            } else if let Some(s) = self.flagged_side {
+                if self.rules.timeout_wins(&self.position, !s) {
+                    GameResult::Wins(!s, WinType::Timeout)
+                } else {
+                    GameResult::Draw(DrawType::TimeoutVsInsufficientMaterial)
+                }
             } else {
                 match self.checkmated_side() {
                     Some(Color::Black) => GameResult::Wins(Color::White, WinType::Checkmate),
@@ -210,12 +989,12 @@ impl Board {
                     None => {
                         if let Some(s) = self.stalemated_side() {
                             GameResult::Draw(DrawType::Stalemate(s))
-                        } else if self.is_fivefold_repetition() {
-                            GameResult::Draw(DrawType::FivefoldRepetition)
-                        } else if self.is_seventy_five_move_rule() {
-                            GameResult::Draw(DrawType::SeventyFiveMoveRule)
-                        } else if self.is_insufficient_material() {
+                        } else if let Some(draw) = self.repetition_limit_draw().or(self.halfmove_limit_draw()) {
+                            GameResult::Draw(draw)
+                        } else if self.rules.insufficient_material_draw && self.is_insufficient_material() {
                             GameResult::Draw(DrawType::InsufficientMaterial)
+                        } else if self.rules.dead_position_draw && self.is_dead_position() {
+                            GameResult::Draw(DrawType::DeadPosition)
                         } else {
                             panic!("the universe is malfunctioning")
                         }
@@ -225,6 +1004,21 @@ impl Board {
         }
     }
 
+    /// Returns the outcome of the game ([`Outcome::Unknown`] if the game is ongoing).
+    pub fn outcome(&self) -> Outcome {
+        match self.game_result() {
+            Some(res) => res.outcome(),
+            None => Outcome::Unknown,
+        }
+    }
+
+    /// Checks whether the current positions on this board and another are the same for the purposes of repetition
+    /// (see [`Position::same_position`]). Unlike `==`, which compares the full game state including history and clocks,
+    /// this ignores how the positions were reached.
+    pub fn same_position(&self, other: &Self) -> bool {
+        self.position.same_position(&other.position)
+    }
+
     /// Returns the number of halfmoves played since the last pawn push or capture.
     pub fn halfmove_clock(&self) -> usize {
         self.halfmove_clock
@@ -235,19 +1029,37 @@ impl Board {
         self.fullmove_number
     }
 
+    /// Returns the number of times the current position has occurred in the game, including the current occurrence.
+    /// Positions are compared by their repetition keys (see [`Position::repetition_key`]), which are recorded as moves are made and undone.
+    pub fn repetition_count(&self) -> usize {
+        let key = self.repetition_keys.last().unwrap();
+        // pawn moves and captures cannot be reversed, so no position before the last of them can repeat the current one
+        self.repetition_keys.iter().rev().take(self.halfmove_clock + 1).filter(|&k| k == key).count()
+    }
+
     /// Checks whether a threefold repetition of the position has occurred.
     pub fn is_threefold_repetition(&self) -> bool {
-        self.position_history.iter().fold(0, |acc, pos| if pos == &self.position { acc + 1 } else { acc }) == 3
+        self.repetition_count() >= 3
     }
 
     /// Checks whether a fivefold repetition of the position has occurred.
     pub fn is_fivefold_repetition(&self) -> bool {
-        self.position_history.iter().fold(0, |acc, pos| if pos == &self.position { acc + 1 } else { acc }) == 5
+        self.repetition_count() >= 5
+    }
+
+    /// Returns the draw by the repetition limit of the rule set, if it has been reached.
+    fn repetition_limit_draw(&self) -> Option<DrawType> {
+        self.rules.repetition_limit.filter(|&n| self.repetition_count() >= n).map(DrawType::repetition_limit)
+    }
+
+    /// Returns the draw by the halfmove limit of the rule set, if it has been reached.
+    fn halfmove_limit_draw(&self) -> Option<DrawType> {
+        self.rules.halfmove_limit.filter(|&n| self.halfmove_clock >= n).map(DrawType::halfmove_limit)
     }
 
     /// Checks whether a draw can be claimed by the fifty-move rule.
     pub fn is_fifty_move_rule(&self) -> bool {
-        self.halfmove_clock == 100
+        self.halfmove_clock >= 100
     }
 
     /// Checks whether the game is drawn by the seventy-five-move rule.
@@ -257,7 +1069,7 @@ impl Board {
 
     /// Checks whether the game is drawn by stalemate. Use [`Board::stalemated_side`] to know which side is in stalemate.
     pub fn is_stalemate(&self) -> bool {
-        self.position.is_stalemate()
+        self.checked_side().is_none() && self.cached_legal_moves().is_empty()
     }
 
     /// Checks whether the game is drawn by insufficient material.
@@ -274,29 +1086,150 @@ impl Board {
         !self.is_insufficient_material()
     }
 
+    /// Checks whether the position is dead (see [`Position::is_dead_position`]).
+    pub fn is_dead_position(&self) -> bool {
+        self.position.is_dead_position()
+    }
+
+    /// Enables or disables ending the game automatically in dead positions other than insufficient material (disabled by default).
+    /// This is shorthand for setting [`Rules::dead_position_draw`].
+    pub fn set_dead_position_detection(&mut self, enabled: bool) {
+        self.set_rules(Rules {
+            dead_position_draw: enabled,
+            ..self.rules
+        });
+    }
+
+    /// Returns a [`GameBuilder`] for configuring a new game.
+    pub fn builder() -> GameBuilder {
+        GameBuilder::new()
+    }
+
+    /// Returns a new game from the same starting position with the same rules and time control, for a rematch between the same players.
+    /// The metadata is carried over with the colors swapped (tags such as _White_ and _WhiteElo_ are exchanged with _Black_ and _BlackElo_),
+    /// and the _Result_ tag is removed.
+    pub fn rematch(&self) -> Self {
+        let mut board = self.with_settings(Self::from_fen(self.initial_fen.clone()));
+        board.metadata = board
+            .metadata
+            .into_iter()
+            .map(|(key, value)| match (key.strip_prefix("White"), key.strip_prefix("Black")) {
+                (Some(rest), _) => (format!("Black{rest}"), value),
+                (_, Some(rest)) => (format!("White{rest}"), value),
+                _ => (key, value),
+            })
+            .collect();
+        board
+    }
+
+    /// Returns a new game starting from the current position, with the same rules, time control, and metadata
+    /// (apart from the _Result_ tag, which is removed). The _SetUp_ and _FEN_ tags are set to describe the new starting position.
+    pub fn continue_from_current(&self) -> Self {
+        let fen = self.to_fen();
+        let mut board = self.with_settings(Self::from_fen(fen.clone()));
+        board.set_metadata("SetUp", "1");
+        board.set_metadata("FEN", &fen.to_string());
+        board
+    }
+
+    /// Carries over the rules, time control, and metadata (apart from the _Result_ tag) of this game to a new game.
+    fn with_settings(&self, board: Self) -> Self {
+        let mut board = board;
+        board.termination_policy = self.termination_policy;
+        board.set_rules(self.rules);
+        board.time_control = self.time_control;
+        board.metadata = self.metadata.clone();
+        board.metadata.remove("Result");
+        board
+    }
+
+    /// Returns the time control of the game, if any.
+    pub fn time_control(&self) -> Option<TimeControl> {
+        self.time_control
+    }
+
+    /// Sets the time control of the game.
+    pub fn set_time_control(&mut self, time_control: Option<TimeControl>) {
+        self.time_control = time_control;
+    }
+
+    /// Returns, for each move played, the time remaining on the clock of the side which played it just after the move (including any increment),
+    /// if it was recorded. Clock times are recorded by [`Session`](crate::Session) and read from `[%clk]` comments in PGN.
+    pub fn move_clocks(&self) -> &[Option<Duration>] {
+        &self.move_clocks
+    }
+
+    /// Records the time remaining on the clock of the side which played the most recent move, returning an error if no moves have been played.
+    pub fn record_clock(&mut self, remaining: Duration) -> Result<(), NoMovesPlayedError> {
+        *self.move_clocks.last_mut().ok_or(NoMovesPlayedError)? = Some(remaining);
+        Ok(())
+    }
+
+    /// Returns the most recently recorded time remaining on the clock of the given side, if any (see [`Board::move_clocks`]).
+    pub fn clock_remaining(&self, side: Color) -> Option<Duration> {
+        (0..self.move_clocks.len())
+            .rev()
+            .filter(|&ply| self.position_history[ply].side == side)
+            .find_map(|ply| self.move_clocks[ply])
+    }
+
+    /// Returns the metadata about the game, such as the names of the players, in the form of key-value pairs like PGN tag pairs.
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// Sets a metadata entry about the game, replacing any existing value for the key.
+    pub fn set_metadata(&mut self, key: &str, value: &str) {
+        self.metadata.insert(key.to_owned(), value.to_owned());
+    }
+
+    /// Returns the rule set used to adjudicate the game.
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// Returns which draws end the game automatically.
+    pub fn termination_policy(&self) -> TerminationPolicy {
+        self.termination_policy
+    }
+
+    /// Sets which draws end the game automatically ([`TerminationPolicy::Automatic`] by default). If the game is ongoing, it ends if it would have ended under the new policy,
+    /// and if it only ended because of the old policy, it is reopened.
+    pub fn set_termination_policy(&mut self, policy: TerminationPolicy) {
+        self.termination_policy = policy;
+        self.recheck_status();
+    }
+
+    /// Sets the rule set used to adjudicate the game (FIDE by default). If the game is ongoing, it ends if it would have ended under the new rules,
+    /// and if it only ended because of the old rules, it is reopened.
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+        self.recheck_status();
+    }
+
     /// Checks whether any side is in check (a checkmate is also considered a check). Use [`Board::checked_side`] to know which side is in check.
     pub fn is_check(&self) -> bool {
-        self.position.is_check()
+        self.checked_side().is_some()
     }
 
     /// Checks whether any side is in checkmate. Use [`Board::checkmated_side`] to know which side is in checkmate.
     pub fn is_checkmate(&self) -> bool {
-        self.position.is_checkmate()
+        self.is_check() && self.cached_legal_moves().is_empty()
     }
 
     /// Returns an optional `Color` representing the side in stalemate (`None` if neither side is in stalemate).
     pub fn stalemated_side(&self) -> Option<Color> {
-        self.position.stalemated_side()
+        self.is_stalemate().then_some(self.position.side)
     }
 
     /// Returns an optional `Color` representing the side in check (`None` if neither side is in check).
     pub fn checked_side(&self) -> Option<Color> {
-        self.position.checked_side()
+        *self.cache.checked_side.get_or_init(|| self.position.checked_side())
     }
 
     /// Returns an optional `Color` representing the side in checkmate (`None` if neither side is in checkmate).
     pub fn checkmated_side(&self) -> Option<Color> {
-        self.position.checkmated_side()
+        self.is_checkmate().then_some(self.position.side)
     }
 
     /// Pretty-prints the position to a string, from the perspective of the side `perspective`.
@@ -316,57 +1249,227 @@ impl Board {
         Ok(self.position.content[super::sq_to_idx(file, rank)?])
     }
 
-    /// Resigns the game for a certain side, if the game is ongoing. Currently, this function should also be used to represent a loss by timeout.
+    /// Resigns the game for a certain side, if the game is ongoing. Use [`Board::flag`] to represent a loss by timeout.
     pub fn resign(&mut self, side: Color) -> Result<(), GameOverError> {
         if !self.ongoing {
             return Err(GameOverError::Resignation);
         }
         self.ongoing = false;
         self.resigned_side = Some(side);
+        self.emit_game_over();
         Ok(())
     }
 
-    /// Makes a draw by agreement, if the game is ongoing. Currently, this function should also be used to represent a draw claim.
+    /// Ends the game because the given side has run out of time, if the game is ongoing.
+    /// Depending on the opponent's material and the rule set (see [`TimeoutRule`](crate::TimeoutRule)), the opponent wins or the game is drawn.
+    pub fn flag(&mut self, side: Color) -> Result<(), GameOverError> {
+        if !self.ongoing {
+            return Err(GameOverError::Timeout);
+        }
+        self.ongoing = false;
+        self.flagged_side = Some(side);
+        self.emit_game_over();
+        Ok(())
+    }
+
+    /// Makes a draw by agreement, if the game is ongoing. Use [`Board::claim_draw`] to represent a draw claim.
     pub fn agree_draw(&mut self) -> Result<(), GameOverError> {
         if !self.ongoing {
             return Err(GameOverError::AgreementDraw);
         }
         self.ongoing = false;
         self.draw_agreed = true;
+        self.draw_offer = None;
+        self.emit_game_over();
         Ok(())
     }
 
+    /// Returns the draws which can currently be claimed, if the game is ongoing: a [threefold repetition](DrawType::ThreefoldRepetition)
+    /// and the [fifty-move rule](DrawType::FiftyMoveRule), and under [`TerminationPolicy::ByClaim`] the draws of the rule set which do not end the game
+    /// (reaching the repetition or halfmove limit, insufficient material, and dead positions).
+    pub fn claimable_draws(&self) -> Vec<DrawType> {
+        if !self.ongoing {
+            return Vec::new();
+        }
+        let mut draws = Vec::new();
+        if self.is_threefold_repetition() {
+            draws.push(DrawType::ThreefoldRepetition);
+        }
+        if self.is_fifty_move_rule() {
+            draws.push(DrawType::FiftyMoveRule);
+        }
+        if self.termination_policy == TerminationPolicy::ByClaim {
+            draws.extend(self.rule_draws());
+        }
+        draws
+    }
+
+    /// Ends the game in a draw of the given type, if the draw can currently be claimed (see [`Board::claimable_draws`]).
+    pub fn claim_draw(&mut self, draw_type: DrawType) -> Result<(), DrawClaimError> {
+        if !self.ongoing {
+            return Err(DrawClaimError::GameOver);
+        }
+        if !self.claimable_draws().contains(&draw_type) {
+            return Err(DrawClaimError::NotClaimable(draw_type));
+        }
+        self.ongoing = false;
+        self.claimed_draw = Some(draw_type);
+        self.draw_offer = None;
+        self.emit_game_over();
+        Ok(())
+    }
+
+    /// Returns the draw that has been claimed, if any.
+    pub fn claimed_draw(&self) -> Option<DrawType> {
+        self.claimed_draw
+    }
+
+    /// Offers a draw on behalf of the given side, if the game is ongoing. The offer is pending until the opponent accepts it
+    /// with [`Board::agree_draw`] or a move is played.
+    pub fn offer_draw(&mut self, side: Color) -> Result<(), GameOverError> {
+        if !self.ongoing {
+            return Err(GameOverError::DrawOffer);
+        }
+        self.draw_offer = Some(side);
+        self.emit(BoardEvent::DrawOffered(side));
+        Ok(())
+    }
+
+    /// Returns the side that has offered a draw, if the offer is pending.
+    pub fn draw_offer(&self) -> Option<Color> {
+        self.draw_offer
+    }
+
     /// Returns an optional `Color` representing the side that has resigned (`None` if neither side has resigned).
     pub fn resigned_side(&self) -> Option<Color> {
         self.resigned_side
     }
 
+    /// Returns an optional `Color` representing the side that has run out of time (`None` if neither side has).
+    pub fn flagged_side(&self) -> Option<Color> {
+        self.flagged_side
+    }
+
     /// Checks whether a draw has been agreed upon.
     pub fn draw_agreed(&self) -> bool {
         self.draw_agreed
     }
 
+    /// Returns the types of the pieces captured by the given side during the game, in the order in which they were captured.
+    pub fn captured_pieces(&self, side: Color) -> Vec<PieceType> {
+        self.position_history
+            .iter()
+            .zip(&self.move_history)
+            .filter(|(position, _)| position.side == side)
+            .filter_map(|(position, &Move(_, dest, spec))| match spec {
+                Some(SpecialMoveType::EnPassant) => Some(PieceType::P),
+                _ => position.content[dest].filter(|p| p.1 != side).map(|p| p.0),
+            })
+            .collect()
+    }
+
+    /// Returns the most recently played move, if any.
+    pub fn last_move(&self) -> Option<Move> {
+        self.move_history.last().copied()
+    }
+
+    /// Returns the positions that have occurred on the board before the current one, from the initial position onwards.
+    pub fn position_history(&self) -> &[Position] {
+        &self.position_history
+    }
+
+    /// Returns the moves that have been played on the board.
+    pub fn move_history(&self) -> &[Move] {
+        &self.move_history
+    }
+
+    /// Returns the moves that have been played on the board, each with its representation in SAN.
+    pub fn history(&self) -> Vec<(Move, String)> {
+        self.move_history
+            .iter()
+            .zip(&self.position_history)
+            .map(|(&move_, position)| (move_, position.move_to_san(move_).unwrap()))
+            .collect()
+    }
+
+    /// Returns the moves that have been played on the board as a line of UCI moves separated by spaces, such as `e2e4 e7e5 g1f3`
+    /// (the inverse of [`Board::make_moves_uci`]), for the moves of a UCI `position` command or datasets of games in UCI notation.
+    pub fn to_uci_moves(&self) -> String {
+        self.move_history.iter().map(Move::to_uci).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Returns the position at the given ply (the number of halfmoves played from the initial position), if it has been reached.
+    pub fn position_at_ply(&self, ply: usize) -> Option<&Position> {
+        match ply.cmp(&self.position_history.len()) {
+            std::cmp::Ordering::Less => Some(&self.position_history[ply]),
+            std::cmp::Ordering::Equal => Some(&self.position),
+            std::cmp::Ordering::Greater => None,
+        }
+    }
+
+    /// Returns an iterator over the FENs of every position of the game, from the initial position (at ply 0) to the current one.
+    pub fn fens(&self) -> impl Iterator<Item = Fen> + '_ {
+        let black_started = self.initial_fen.position().side.is_black();
+        let clocks = self.halfmove_clock_history.iter().copied().chain(iter::once(self.halfmove_clock));
+        self.position_history
+            .iter()
+            .chain(iter::once(&self.position))
+            .zip(clocks)
+            .enumerate()
+            .map(move |(ply, (position, halfmove_clock))| Fen {
+                position: position.clone(),
+                halfmove_clock,
+                fullmove_number: self.initial_fen.fullmove_number() + (ply + black_started as usize) / 2,
+            })
+    }
+
     /// Returns the initial FEN of the game.
     pub fn initial_fen(&self) -> &Fen {
         &self.initial_fen
     }
 
     /// Generates the SAN movetext of the game thus far (excluding the game result).
+    /// Recorded clock times (see [`Board::move_clocks`]) are included as `[%clk]` comments after their moves.
     pub fn gen_movetext(&self) -> String {
+        self.gen_movetext_with_checkpoints(FenCheckpoints::default())
+    }
+
+    /// Generates the SAN movetext of the game thus far like [`Board::gen_movetext`], with the FENs of the positions after the moves selected
+    /// by `checkpoints` included as `[%fen]` comments, as in `1. e4 {[%fen rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1]}`.
+    /// When a PGN with such comments is parsed, the positions are verified against them, so that long games can be checked for corruption
+    /// and replayed from the nearest checkpoint.
+    pub fn gen_movetext_with_checkpoints(&self, checkpoints: FenCheckpoints) -> String {
         let mut movetext = String::new();
         let initial_side = self.initial_fen.position().side;
         let initial_fullmove_number: usize = self.initial_fen.fullmove_number();
         let mut current_side = initial_side;
         let mut current_fullmove_number = initial_fullmove_number;
+        let mut after_comment = false;
         for (movei, &move_) in self.move_history.iter().enumerate() {
             let pos = &self.position_history[movei];
             let san = pos.move_to_san(move_).unwrap();
             if current_side.is_black() {
-                movetext.push_str(&format!("{}{san} ", if movei == 0 { format!("{current_fullmove_number}... ") } else { String::new() }));
+                movetext.push_str(&format!("{}{san} ", if movei == 0 || after_comment { format!("{current_fullmove_number}... ") } else { String::new() }));
                 current_fullmove_number += 1;
             } else {
                 movetext.push_str(&format!("{current_fullmove_number}. {san} "))
             }
+            let mut commands = Vec::new();
+            if let Some(remaining) = self.move_clocks[movei] {
+                commands.push(format!("[%clk {}]", format_clock(remaining)));
+            }
+            if checkpoints.after(movei + 1, pos.captures(&move_)) {
+                let fen = Fen {
+                    position: self.position_at_ply(movei + 1).unwrap().clone(),
+                    halfmove_clock: self.halfmove_clock_history.get(movei + 1).copied().unwrap_or(self.halfmove_clock),
+                    fullmove_number: current_fullmove_number,
+                };
+                commands.push(format!("[%fen {fen}]"));
+            }
+            after_comment = !commands.is_empty();
+            if after_comment {
+                movetext.push_str(&format!("{{{}}} ", commands.join(" ")));
+            }
             current_side = !current_side;
         }
         movetext.trim().to_owned()
@@ -391,3 +1494,14 @@ impl fmt::Display for Board {
         self.position.fmt(f)
     }
 }
+
+/// Formats a clock time as in a PGN `[%clk]` comment (`H:MM:SS`), with fractions of a second to the millisecond if there are any.
+fn format_clock(time: Duration) -> String {
+    let secs = time.as_secs();
+    let mut formatted = format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+    let millis = time.subsec_millis();
+    if millis != 0 {
+        formatted.push_str(format!(".{millis:03}").trim_end_matches('0'));
+    }
+    formatted
+}
@@ -0,0 +1,209 @@
+use super::{Board, Color, Fen, InvalidGameConfigError, InvalidTimeControlError, Piece, PieceType, Position, Rules};
+use std::{collections::BTreeMap, fmt, str::FromStr, time::Duration};
+
+/// Represents a time control: a base time for each side, plus an increment added after every move.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub increment: Duration,
+}
+
+impl TimeControl {
+    /// Creates a time control from a base time and an increment, both in seconds.
+    pub fn from_secs(base: u64, increment: u64) -> Self {
+        Self {
+            base: Duration::from_secs(base),
+            increment: Duration::from_secs(increment),
+        }
+    }
+}
+
+impl fmt::Display for TimeControl {
+    /// Represents the time control in the format of the PGN _TimeControl_ tag (such as `300+3`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.base.as_secs())?;
+        if !self.increment.is_zero() {
+            write!(f, "+{}", self.increment.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = InvalidTimeControlError;
+
+    /// Parses a time control in the format of the PGN _TimeControl_ tag, with a base time in seconds and an optional increment (such as `300+3` or `600`).
+    /// Other formats of the tag (such as moves per period or sandclock time controls) cannot be represented and return an error.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (base, increment) = s.split_once('+').unwrap_or((s, "0"));
+        match (base.parse(), increment.parse()) {
+            (Ok(base), Ok(increment)) => Ok(Self::from_secs(base, increment)),
+            _ => Err(InvalidTimeControlError(s.to_owned())),
+        }
+    }
+}
+
+/// Represents the material that a side gives as odds, removed from its starting position.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum Odds {
+    /// The f-pawn
+    Pawn,
+    /// The queen's knight
+    Knight,
+    /// The queen's rook (along with queenside castling rights)
+    Rook,
+    /// The queen
+    Queen,
+}
+
+/// Represents the starting position of a game configured by a [`GameBuilder`].
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+enum Start {
+    Fen(Box<Fen>),
+    Chess960(u16),
+}
+
+/// A builder which configures a game (its starting position, odds, time control, rules, and metadata) and produces a ready [`Board`].
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct GameBuilder {
+    start: Start,
+    odds: Vec<(Color, Odds)>,
+    time_control: Option<TimeControl>,
+    rules: Rules,
+    metadata: BTreeMap<String, String>,
+}
+
+impl GameBuilder {
+    /// Creates a builder for a game starting from the standard starting position, with the default rules and no time control.
+    pub fn new() -> Self {
+        Self {
+            start: Start::Fen(Box::new(Board::default().to_fen())),
+            odds: Vec::new(),
+            time_control: None,
+            rules: Rules::default(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// Starts the game from the given FEN.
+    pub fn fen(mut self, fen: Fen) -> Self {
+        self.start = Start::Fen(Box::new(fen));
+        self
+    }
+
+    /// Starts the game from the Chess960 starting position with the given number (`0..960`, where 518 is the standard starting position).
+    pub fn chess960(mut self, number: u16) -> Self {
+        self.start = Start::Chess960(number);
+        self
+    }
+
+    /// Removes the given material from the starting position of the given side. Odds can be given multiple times.
+    pub fn odds(mut self, side: Color, odds: Odds) -> Self {
+        self.odds.push((side, odds));
+        self
+    }
+
+    /// Sets the time control of the game.
+    pub fn time_control(mut self, time_control: TimeControl) -> Self {
+        self.time_control = Some(time_control);
+        self
+    }
+
+    /// Sets the rule set used to adjudicate the game.
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Adds metadata about the game (such as the names of the players or the event), in the form of a key-value pair like a PGN tag pair.
+    pub fn metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata.insert(key.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Builds the game, returning an error if the configuration is invalid.
+    pub fn build(self) -> Result<Board, InvalidGameConfigError> {
+        let mut fen = match self.start {
+            Start::Fen(fen) => *fen,
+            Start::Chess960(number) => Fen {
+                position: chess960_position(number)?,
+                halfmove_clock: 0,
+                fullmove_number: 1,
+            },
+        };
+        for (side, odds) in self.odds {
+            remove_odds(&mut fen.position, side, odds)?;
+        }
+        let mut board = Board::from_fen(fen);
+        board.set_rules(self.rules);
+        board.set_time_control(self.time_control);
+        for (key, value) in self.metadata {
+            board.set_metadata(&key, &value);
+        }
+        Ok(board)
+    }
+}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Generates the Chess960 starting position with the given number, using Scharnagl's numbering scheme.
+fn chess960_position(number: u16) -> Result<Position, InvalidGameConfigError> {
+    if number >= 960 {
+        return Err(InvalidGameConfigError::Chess960Number(number));
+    }
+    let mut backrank = [None; 8];
+    let n = number as usize;
+    backrank[n % 4 * 2 + 1] = Some(PieceType::B);
+    backrank[n / 4 % 4 * 2] = Some(PieceType::B);
+    let place_nth_empty = |backrank: &mut [Option<PieceType>; 8], nth: usize, piece_type| {
+        let file = (0..8).filter(|&f| backrank[f].is_none()).nth(nth).unwrap();
+        backrank[file] = Some(piece_type);
+    };
+    place_nth_empty(&mut backrank, n / 16 % 6, PieceType::Q);
+    let (n1, n2) = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)][n / 96];
+    place_nth_empty(&mut backrank, n2, PieceType::N);
+    place_nth_empty(&mut backrank, n1, PieceType::N);
+    for piece_type in [PieceType::R, PieceType::K, PieceType::R] {
+        place_nth_empty(&mut backrank, 0, piece_type);
+    }
+    let rooks: Vec<_> = (0..8).filter(|&f| backrank[f] == Some(PieceType::R)).collect();
+    let mut content = [None; 64];
+    for (file, piece_type) in backrank.into_iter().enumerate() {
+        content[file] = piece_type.map(|pt| Piece(pt, Color::White));
+        content[8 + file] = Some(Piece(PieceType::P, Color::White));
+        content[48 + file] = Some(Piece(PieceType::P, Color::Black));
+        content[56 + file] = piece_type.map(|pt| Piece(pt, Color::Black));
+    }
+    Ok(Position {
+        content,
+        side: Color::White,
+        castling_rights: [Some(rooks[1]), Some(rooks[0]), Some(56 + rooks[1]), Some(56 + rooks[0])],
+        ep_target: None,
+    })
+}
+
+/// Removes the material given as odds from the given side's starting position, returning an error if it is not on its starting square.
+fn remove_odds(position: &mut Position, side: Color, odds: Odds) -> Result<(), InvalidGameConfigError> {
+    let offset = if side.is_white() { 0 } else { 56 };
+    let pawn_offset = if side.is_white() { 8 } else { 48 };
+    let (sq, piece_type) = match odds {
+        Odds::Pawn => (pawn_offset + 5, PieceType::P),
+        Odds::Knight => (offset + 1, PieceType::N),
+        Odds::Rook => (offset, PieceType::R),
+        Odds::Queen => (offset + 3, PieceType::Q),
+    };
+    if position.content[sq] != Some(Piece(piece_type, side)) {
+        return Err(InvalidGameConfigError::Odds(side, odds));
+    }
+    position.content[sq] = None;
+    for right in position.castling_rights.iter_mut() {
+        if *right == Some(sq) {
+            *right = None;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A token for cooperatively cancelling long-running operations such as [`Position::perft_cancellable`](crate::Position::perft_cancellable).
+/// Clones of a token share the same state, so a token can be handed to an operation running on another thread and cancelled from this one.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Creates a new token which has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation of every operation using this token (or a clone of it).
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed)
+    }
+
+    /// Checks whether cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Represents the result of an operation that can be cancelled; if the operation was cancelled, the result is partial.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum Cancellable<T> {
+    Completed(T),
+    Cancelled(T),
+}
+
+impl<T> Cancellable<T> {
+    /// Checks whether the operation was cancelled before it completed.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled(_))
+    }
+
+    /// Returns the (possibly partial) result of the operation.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Completed(t) | Self::Cancelled(t) => t,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use super::{Color, Piece, PieceType};
+
+/// Represents the castling rights in a position: for each side and direction, the square of the rook the side may still castle with.
+///
+/// Castling rights are only valid if the rook and the king of the side stand on its back rank, with the rook on the side of the king
+/// it castles towards; rights which are inconsistent with the board are dropped when the castling rights of a position are read.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub struct CastlingRights {
+    /// The squares of the rooks in the format [K, Q, k, q]
+    rooks: [Option<usize>; 4],
+}
+
+impl CastlingRights {
+    /// Reads the castling rights of a position (in the format [K, Q, k, q]), dropping the rights which are inconsistent with its content.
+    pub(crate) fn validated(rooks: [Option<usize>; 4], content: &[Option<Piece>; 64]) -> Self {
+        let mut rights = Self::default();
+        for (i, rook) in rooks.into_iter().enumerate() {
+            let (side, kingside) = Self::slot(i);
+            let back_rank = if side.is_white() { 0..8 } else { 56..64 };
+            let Some(king) = back_rank.clone().find(|&sq| content[sq] == Some(Piece(PieceType::K, side))) else {
+                continue;
+            };
+            rights.rooks[i] = rook.filter(|&rook| back_rank.contains(&rook) && content[rook] == Some(Piece(PieceType::R, side)) && (rook > king) == kingside && rook != king);
+        }
+        rights
+    }
+
+    /// Returns the squares of the rooks in the format [K, Q, k, q].
+    pub(crate) fn rooks(&self) -> [Option<usize>; 4] {
+        self.rooks
+    }
+
+    /// Returns the side and direction (`true` for kingside) of the castling right at the given index of the format [K, Q, k, q].
+    fn slot(i: usize) -> (Color, bool) {
+        (if i < 2 { Color::White } else { Color::Black }, i.is_multiple_of(2))
+    }
+
+    /// Returns the square of the rook the given side may castle kingside with, if it has kingside castling rights.
+    pub fn kingside(&self, side: Color) -> Option<usize> {
+        self.rooks[if side.is_white() { 0 } else { 2 }]
+    }
+
+    /// Returns the square of the rook the given side may castle queenside with, if it has queenside castling rights.
+    pub fn queenside(&self, side: Color) -> Option<usize> {
+        self.rooks[if side.is_white() { 1 } else { 3 }]
+    }
+
+    /// Checks whether neither side may castle.
+    pub fn is_empty(&self) -> bool {
+        self.rooks.iter().all(Option::is_none)
+    }
+
+    /// Generates the castling availability field of an FEN for these rights, given the content of the position they were read from
+    /// (so that, as the rights were validated against it, every right has its king and rook).
+    /// A right is written as `K`, `Q`, `k`, or `q` if its rook is the only rook of the side between the king and that edge of the board,
+    /// and as the file of the rook otherwise (as in Shredder-FEN), uppercase for white.
+    pub(crate) fn to_fen_field(self, content: &[Option<Piece>; 64]) -> String {
+        let mut field = String::new();
+        for (i, rook) in self.rooks.into_iter().enumerate() {
+            let Some(rook) = rook else {
+                continue;
+            };
+            let (side, kingside) = Self::slot(i);
+            let back_rank = if side.is_white() { 0..8 } else { 56..64 };
+            let king = back_rank.clone().find(|&sq| content[sq] == Some(Piece(PieceType::K, side))).unwrap();
+            let rooks_on_side = back_rank.filter(|&sq| content[sq] == Some(Piece(PieceType::R, side)) && (sq > king) == kingside).count();
+            let letter = if rooks_on_side == 1 {
+                if kingside {
+                    'k'
+                } else {
+                    'q'
+                }
+            } else {
+                (b'a' + (rook % 8) as u8) as char
+            };
+            field.push(if side.is_white() { letter.to_ascii_uppercase() } else { letter });
+        }
+        if field.is_empty() {
+            field.push('-');
+        }
+        field
+    }
+}
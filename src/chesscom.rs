@@ -0,0 +1,93 @@
+//! Import games from the [Chess.com published-data API](https://www.chess.com/news/view/published-data-api), such as the monthly archives
+//! of a player's games, which embed the PGN of each game alongside metadata like its time class and the accuracies of the players.
+
+use super::{pgn::Pgn, ChessComImportError, Color};
+use serde_json::Value;
+
+/// Represents a player of a Chess.com game.
+#[derive(PartialEq, Clone, Debug)]
+pub struct ChessComPlayer {
+    /// The username of the player
+    pub username: String,
+    /// The rating of the player after the game
+    pub rating: Option<u64>,
+    /// The result code of the player, such as `win`, `checkmated`, `resigned`, `timeout`, `agreed`, or `repetition`
+    pub result: String,
+    /// The accuracy of the player's moves (between 0 and 100), if the game has been reviewed
+    pub accuracy: Option<f64>,
+}
+
+/// Represents a game from the Chess.com API.
+#[derive(PartialEq, Clone, Debug)]
+pub struct ChessComGame {
+    /// The URL of the game
+    pub url: String,
+    /// The game, parsed from its embedded PGN
+    pub pgn: Pgn,
+    /// The time class of the game (`bullet`, `blitz`, `rapid`, or `daily`)
+    pub time_class: String,
+    /// The time control of the game, as in the PGN _TimeControl_ tag (such as `180+2`, or `1/86400` for daily games)
+    pub time_control: String,
+    /// The rules of the game (`chess` or `chess960`)
+    pub rules: String,
+    /// Whether the game was rated
+    pub rated: bool,
+    /// The time the game ended, as a Unix timestamp in seconds
+    pub end_time: Option<u64>,
+    /// The white player
+    pub white: ChessComPlayer,
+    /// The black player
+    pub black: ChessComPlayer,
+}
+
+impl ChessComGame {
+    /// Parses a Chess.com game object. Standard and Chess960 games are supported, and other variants return an error.
+    pub fn from_json(json: &str) -> Result<Self, ChessComImportError> {
+        Self::from_value(&serde_json::from_str(json)?)
+    }
+
+    /// Returns the player of the given side.
+    pub fn player(&self, side: Color) -> &ChessComPlayer {
+        if side.is_white() {
+            &self.white
+        } else {
+            &self.black
+        }
+    }
+
+    fn from_value(game: &Value) -> Result<Self, ChessComImportError> {
+        let string = |field: &'static str| game[field].as_str().map(str::to_owned).ok_or(ChessComImportError::Field(field));
+        let rules = game["rules"].as_str().unwrap_or("chess");
+        if !matches!(rules, "chess" | "chess960") {
+            return Err(ChessComImportError::UnsupportedVariant(rules.to_owned()));
+        }
+        let player = |color: &'static str| -> Result<ChessComPlayer, ChessComImportError> {
+            let player = &game[color];
+            Ok(ChessComPlayer {
+                username: player["username"].as_str().ok_or(ChessComImportError::Field(color))?.to_owned(),
+                rating: player["rating"].as_u64(),
+                result: player["result"].as_str().ok_or(ChessComImportError::Field(color))?.to_owned(),
+                accuracy: game["accuracies"][color].as_f64(),
+            })
+        };
+        Ok(Self {
+            url: string("url")?,
+            pgn: Pgn::try_from(string("pgn")?.as_str())?,
+            time_class: string("time_class")?,
+            time_control: string("time_control")?,
+            rules: rules.to_owned(),
+            rated: game["rated"].as_bool().unwrap_or_default(),
+            end_time: game["end_time"].as_u64(),
+            white: player("white")?,
+            black: player("black")?,
+        })
+    }
+}
+
+/// Parses a monthly archive of games from the Chess.com API (an object with a `games` array), returning an error if the archive itself is invalid.
+/// Each game is parsed separately (see [`ChessComGame::from_json`]), so that an unsupported or invalid game does not prevent reading the others.
+pub fn parse_archive(json: &str) -> Result<Vec<Result<ChessComGame, ChessComImportError>>, ChessComImportError> {
+    let archive: Value = serde_json::from_str(json)?;
+    let games = archive["games"].as_array().ok_or(ChessComImportError::Field("games"))?;
+    Ok(games.iter().map(ChessComGame::from_value).collect())
+}
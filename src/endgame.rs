@@ -0,0 +1,113 @@
+use super::{helpers, Color, Piece, PieceType};
+use std::fmt;
+
+/// The maximum non-pawn material (in standard point values, see [`PieceType::value`]) that each side may have for a position to be an endgame.
+const ENDGAME_MATERIAL_LIMIT: u32 = 13;
+
+/// Represents the class of an endgame, as determined by the material signature (the pieces other than kings and pawns) of a position.
+///
+/// A position is an endgame if neither side has more than 13 points of non-pawn material (for example, two rooks and a minor piece).
+/// Endgames are then classified as follows, where "pieces" means pieces other than kings and pawns, and pawns may be present in any class:
+/// * [`EndgameClass::Pawn`]: no pieces
+/// * [`EndgameClass::Knight`]: only knights
+/// * [`EndgameClass::OppositeColoredBishops`]: exactly one bishop each, on squares of opposite colors
+/// * [`EndgameClass::Bishop`]: only bishops, otherwise
+/// * [`EndgameClass::BishopVsKnight`]: only bishops on one side and only knights on the other
+/// * [`EndgameClass::MinorPiece`]: only minor pieces (knights and bishops), otherwise
+/// * [`EndgameClass::Rook`]: only rooks
+/// * [`EndgameClass::RookAndMinorPiece`]: only rooks and minor pieces, with a rook on both sides
+/// * [`EndgameClass::RookVsMinorPiece`]: only rooks on one side and only minor pieces on the other
+/// * [`EndgameClass::Queen`]: only queens
+/// * [`EndgameClass::QueenVsRook`]: only queens on one side and only rooks on the other
+/// * [`EndgameClass::Mixed`]: any other combination of pieces
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum EndgameClass {
+    /// The position is not an endgame.
+    NotEndgame,
+    Pawn,
+    Knight,
+    Bishop,
+    OppositeColoredBishops,
+    BishopVsKnight,
+    MinorPiece,
+    Rook,
+    RookAndMinorPiece,
+    RookVsMinorPiece,
+    Queen,
+    QueenVsRook,
+    Mixed,
+}
+
+impl EndgameClass {
+    /// Classifies the position with the given content.
+    pub(crate) fn of(content: &[Option<Piece>; 64]) -> Self {
+        let pieces_of = |side: Color| -> Vec<(usize, PieceType)> {
+            (0..64)
+                .filter_map(|sq| match content[sq] {
+                    Some(Piece(pt, color)) if color == side && !matches!(pt, PieceType::K | PieceType::P) => Some((sq, pt)),
+                    _ => None,
+                })
+                .collect()
+        };
+        let (white, black) = (pieces_of(Color::White), pieces_of(Color::Black));
+        let material = |pieces: &[(usize, PieceType)]| pieces.iter().map(|(_, pt)| pt.value()).sum::<u32>();
+        if material(&white) > ENDGAME_MATERIAL_LIMIT || material(&black) > ENDGAME_MATERIAL_LIMIT {
+            return Self::NotEndgame;
+        }
+        let only = |pieces: &[(usize, PieceType)], types: &[PieceType]| pieces.iter().all(|(_, pt)| types.contains(pt));
+        let both_only = |types: &[PieceType]| only(&white, types) && only(&black, types);
+        let one_vs_other = |a: &[PieceType], b: &[PieceType]| (!white.is_empty() && !black.is_empty()) && ((only(&white, a) && only(&black, b)) || (only(&white, b) && only(&black, a)));
+        let minors = [PieceType::B, PieceType::N];
+        if white.is_empty() && black.is_empty() {
+            Self::Pawn
+        } else if both_only(&[PieceType::N]) {
+            Self::Knight
+        } else if both_only(&[PieceType::B]) {
+            match (white.as_slice(), black.as_slice()) {
+                ([(w, _)], [(b, _)]) if helpers::color_complex_of(*w) != helpers::color_complex_of(*b) => Self::OppositeColoredBishops,
+                _ => Self::Bishop,
+            }
+        } else if one_vs_other(&[PieceType::B], &[PieceType::N]) {
+            Self::BishopVsKnight
+        } else if both_only(&minors) {
+            Self::MinorPiece
+        } else if both_only(&[PieceType::R]) {
+            Self::Rook
+        } else if one_vs_other(&[PieceType::R], &minors) {
+            Self::RookVsMinorPiece
+        } else if both_only(&[PieceType::R, PieceType::B, PieceType::N]) && [&white, &black].iter().all(|pieces| pieces.iter().any(|(_, pt)| *pt == PieceType::R)) {
+            Self::RookAndMinorPiece
+        } else if both_only(&[PieceType::Q]) {
+            Self::Queen
+        } else if one_vs_other(&[PieceType::Q], &[PieceType::R]) {
+            Self::QueenVsRook
+        } else {
+            Self::Mixed
+        }
+    }
+}
+
+impl fmt::Display for EndgameClass {
+    /// Represents the class as a label, such as "rook endgame" or "opposite-colored bishops".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::NotEndgame => "not an endgame",
+                Self::Pawn => "pawn endgame",
+                Self::Knight => "knight endgame",
+                Self::Bishop => "bishop endgame",
+                Self::OppositeColoredBishops => "opposite-colored bishops",
+                Self::BishopVsKnight => "bishop vs knight",
+                Self::MinorPiece => "minor piece endgame",
+                Self::Rook => "rook endgame",
+                Self::RookAndMinorPiece => "rook and minor piece endgame",
+                Self::RookVsMinorPiece => "rook vs minor piece",
+                Self::Queen => "queen endgame",
+                Self::QueenVsRook => "queen vs rook",
+                Self::Mixed => "mixed endgame",
+            }
+        )
+    }
+}
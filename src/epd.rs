@@ -0,0 +1,227 @@
+use super::{
+    metrics::{self, ParseKind},
+    Fen, InvalidEpdError, Move, Position,
+};
+use std::fmt;
+
+/// The opcodes whose operands are moves in SAN, which are resolved against the position when an `Epd` is constructed.
+/// The operands of `pv` form a sequence of moves, while the operands of the other opcodes are each moves in the position itself.
+pub const EPD_MOVE_OPCODES: [&str; 5] = ["am", "bm", "pm", "sm", "pv"];
+
+/// Represents an EPD (Extended Position Description) record: a position followed by a list of operations.
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub struct Epd {
+    position: Position,
+    operations: Vec<EpdOperation>,
+}
+
+/// Represents an operation in an EPD record, consisting of an opcode (such as `bm` or `id`) and its operands.
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub struct EpdOperation {
+    opcode: String,
+    operands: Vec<String>,
+    moves: Vec<Move>,
+}
+
+impl EpdOperation {
+    /// Constructs an operation from an opcode and its operands.
+    pub fn new<S: Into<String>>(opcode: &str, operands: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            opcode: opcode.to_owned(),
+            operands: operands.into_iter().map(Into::into).collect(),
+            moves: Vec::new(),
+        }
+    }
+
+    /// Returns the opcode of the operation.
+    pub fn opcode(&self) -> &str {
+        &self.opcode
+    }
+
+    /// Returns the operands of the operation, as they appear in the EPD record.
+    pub fn operands(&self) -> &[String] {
+        &self.operands
+    }
+
+    /// Returns the moves represented by the operands if the opcode is one of [`EPD_MOVE_OPCODES`], or an empty slice otherwise.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Resolves the SAN operands of a move opcode against the given position, returning an error if any of them is invalid or illegal.
+    fn resolve_moves(&mut self, position: &Position) -> Result<(), InvalidEpdError> {
+        if !EPD_MOVE_OPCODES.contains(&self.opcode.as_str()) {
+            return Ok(());
+        }
+        let mut current = position.clone();
+        self.moves.clear();
+        for operand in &self.operands {
+            let err = || InvalidEpdError::Move(self.opcode.clone(), operand.clone());
+            let move_ = current.san_to_move_lenient(operand).map_err(|_| err())?;
+            if self.opcode == "pv" {
+                current = current.with_move_made(move_).map_err(|_| err())?;
+            }
+            self.moves.push(move_);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for EpdOperation {
+    /// Represents the operation as it appears in an EPD record, quoting operands where necessary (such as `id "WAC.001";`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.opcode)?;
+        let always_quoted = self.opcode == "id" || (self.opcode.len() == 2 && self.opcode.starts_with('c') && self.opcode.ends_with(|c: char| c.is_ascii_digit()));
+        for operand in &self.operands {
+            if always_quoted || operand.is_empty() || operand.contains(|c: char| c.is_whitespace() || c == ';' || c == '"') {
+                write!(f, " \"{operand}\"")?;
+            } else {
+                write!(f, " {operand}")?;
+            }
+        }
+        write!(f, ";")
+    }
+}
+
+impl Epd {
+    /// Constructs an EPD record from a position and a list of operations, returning an error if the operands of a move opcode are invalid or illegal.
+    pub fn new(position: Position, operations: Vec<EpdOperation>) -> Result<Self, InvalidEpdError> {
+        let mut operations = operations;
+        for operation in &mut operations {
+            operation.resolve_moves(&position)?;
+        }
+        Ok(Self { position, operations })
+    }
+
+    /// Returns the position described by the EPD record.
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Returns the operations in the EPD record.
+    pub fn operations(&self) -> &[EpdOperation] {
+        &self.operations
+    }
+
+    /// Returns the first operation with the given opcode, if any.
+    pub fn operation(&self, opcode: &str) -> Option<&EpdOperation> {
+        self.operations.iter().find(|op| op.opcode == opcode)
+    }
+
+    /// Returns the identifier of the record (the `id` opcode).
+    pub fn id(&self) -> Option<&str> {
+        self.operation("id").and_then(|op| op.operands.first()).map(String::as_str)
+    }
+
+    /// Returns the best moves (the `bm` opcode).
+    pub fn best_moves(&self) -> &[Move] {
+        self.operation("bm").map_or(&[], EpdOperation::moves)
+    }
+
+    /// Returns the moves to avoid (the `am` opcode).
+    pub fn avoid_moves(&self) -> &[Move] {
+        self.operation("am").map_or(&[], EpdOperation::moves)
+    }
+
+    /// Returns the predicted variation (the `pv` opcode).
+    pub fn pv(&self) -> &[Move] {
+        self.operation("pv").map_or(&[], EpdOperation::moves)
+    }
+
+    /// Returns the centipawn evaluation (the `ce` opcode), if it is present and valid.
+    pub fn centipawn_evaluation(&self) -> Option<i32> {
+        self.operation("ce").and_then(|op| op.operands.first()).and_then(|ce| ce.parse().ok())
+    }
+
+    /// Returns an FEN object for the position, taking the halfmove clock and fullmove number from the `hmvc` and `fmvn` opcodes
+    /// (defaulting to 0 and 1 respectively).
+    pub fn to_fen(&self) -> Fen {
+        let clock = |opcode, default| self.operation(opcode).and_then(|op| op.operands.first()).and_then(|n| n.parse().ok()).unwrap_or(default);
+        Fen {
+            position: self.position.clone(),
+            halfmove_clock: clock("hmvc", 0),
+            fullmove_number: clock("fmvn", 1),
+        }
+    }
+}
+
+impl TryFrom<&str> for Epd {
+    type Error = InvalidEpdError;
+
+    /// Attempts to construct an `Epd` object from a string slice, returning an error if it is invalid.
+    fn try_from(epd: &str) -> Result<Self, Self::Error> {
+        metrics::record_parse(ParseKind::Epd, Self::parse(epd))
+    }
+}
+
+impl Epd {
+    /// Parses an EPD record without reporting failures to the metrics sink.
+    fn parse(epd: &str) -> Result<Self, InvalidEpdError> {
+        let mut rest = epd.trim();
+        let mut fields = Vec::new();
+        for _ in 0..4 {
+            let (field, remaining) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            if field.is_empty() {
+                return Err(InvalidEpdError::FourFields);
+            }
+            fields.push(field);
+            rest = remaining.trim_start();
+        }
+        let position = Fen::parse(&format!("{} 0 1", fields.join(" "))).map_err(InvalidEpdError::Position)?.position;
+        let mut operations = Vec::new();
+        let mut tokens: Vec<String> = Vec::new();
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                ';' => {
+                    if tokens.is_empty() {
+                        return Err(InvalidEpdError::Operation("empty operation".to_owned()));
+                    }
+                    let opcode = tokens.remove(0);
+                    operations.push(EpdOperation::new(&opcode, tokens.drain(..)));
+                }
+                '"' => {
+                    let mut operand = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some(c) => operand.push(c),
+                            None => return Err(InvalidEpdError::Operation(format!("unterminated string operand \"{operand}"))),
+                        }
+                    }
+                    if tokens.is_empty() {
+                        return Err(InvalidEpdError::Operation(format!("expected an opcode, got string \"{operand}\"")));
+                    }
+                    tokens.push(operand);
+                }
+                c if c.is_whitespace() => (),
+                c => {
+                    let mut token = c.to_string();
+                    while let Some(&c) = chars.peek() {
+                        if c.is_whitespace() || c == ';' || c == '"' {
+                            break;
+                        }
+                        token.push(c);
+                        chars.next();
+                    }
+                    tokens.push(token);
+                }
+            }
+        }
+        if !tokens.is_empty() {
+            return Err(InvalidEpdError::Operation(format!("operation '{}' is not terminated by a semicolon", tokens.join(" "))));
+        }
+        Self::new(position, operations)
+    }
+}
+
+impl fmt::Display for Epd {
+    /// Returns an EPD string representing this object.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.position.to_fen())?;
+        for operation in &self.operations {
+            write!(f, " {operation}")?;
+        }
+        Ok(())
+    }
+}
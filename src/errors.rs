@@ -22,6 +22,38 @@ pub enum InvalidFenError {
     FullmoveNumber,
 }
 
+/// Conveys that the configuration of a game is invalid.
+#[derive(Error, Debug)]
+pub enum InvalidGameConfigError {
+    #[error("Invalid game configuration: {0} is not a Chess960 starting position number, which must be in the range 0..960")]
+    Chess960Number(u16),
+    #[error("Invalid game configuration: {0:?} cannot give {1:?} odds, as that piece is not on its starting square")]
+    Odds(super::Color, super::Odds),
+}
+
+/// Conveys that the given time control is invalid or cannot be represented.
+#[derive(Error, Debug)]
+#[error("Invalid time control: '{0}' must be a base time in seconds, optionally followed by '+' and an increment in seconds")]
+pub struct InvalidTimeControlError(pub String);
+
+/// Conveys that the given EPD is invalid.
+#[derive(Error, Debug)]
+pub enum InvalidEpdError {
+    #[error("Invalid EPD: expected at least four fields separated by a space")]
+    FourFields,
+    #[error("Invalid EPD position: {0}")]
+    Position(InvalidFenError),
+    #[error("Invalid EPD operation: {0}")]
+    Operation(String),
+    #[error("Invalid EPD: '{1}' is not a valid move for the '{0}' opcode")]
+    Move(String, String),
+}
+
+/// Conveys that a metrics sink has already been registered.
+#[derive(Error, Debug)]
+#[error("A metrics sink has already been registered")]
+pub struct MetricsAlreadySetError;
+
 /// Conveys that the given piece character is invalid.
 #[derive(Error, Debug)]
 #[error("Invalid piece character: '{0}'; a valid piece character must be /[KkQqRrBbNnPp]/gi")]
@@ -53,6 +85,64 @@ pub struct IllegalMoveError(pub Move);
 #[error("No moves have been played, so the requested action cannot be carried out.")]
 pub struct NoMovesPlayedError;
 
+/// Conveys that the board cannot step through the game as requested.
+#[derive(Error, Debug)]
+pub enum NavigationError {
+    #[error("Navigation error: the board is at the initial position")]
+    AtStart,
+    #[error("Navigation error: there are no moves ahead of the current ply")]
+    AtEnd,
+    #[error("Navigation error: ply {0} is out of range, as the game has {1} halfmoves")]
+    PlyOutOfRange(usize, usize),
+    #[error("Navigation error: moves cannot be replayed when the game is over")]
+    GameOver,
+}
+
+/// Conveys that the state of a board is internally inconsistent (see [`Board::verify_invariants`](crate::Board::verify_invariants)).
+/// Plies are counted from the initial position of the game.
+#[derive(Error, Eq, PartialEq, Debug)]
+pub enum BoardInvariantError {
+    #[error("Board invariant violated: the histories of positions ({positions}), moves ({moves}), halfmove clocks ({halfmove_clocks}), and clock times ({move_clocks}) differ in length")]
+    HistoryLengths {
+        positions: usize,
+        moves: usize,
+        halfmove_clocks: usize,
+        move_clocks: usize,
+    },
+    #[error("Board invariant violated: the initial position differs from the initial FEN")]
+    InitialPosition,
+    #[error("Board invariant violated: the move {1} leading to ply {0} is illegal")]
+    IllegalMove(usize, Move),
+    #[error("Board invariant violated: the position at ply {0} does not follow from the move leading to it")]
+    Position(usize),
+    #[error("Board invariant violated: the halfmove clock at ply {0} is {2}, but should be {1}")]
+    HalfmoveClock(usize, usize, usize),
+    #[error("Board invariant violated: the fullmove number is {1}, but should be {0}")]
+    FullmoveNumber(usize, usize),
+    #[error("Board invariant violated: the castling rights at ply {0} are inconsistent with the board")]
+    CastlingRights(usize),
+    #[error("Board invariant violated: the en passant target square at ply {0} is inconsistent with the board")]
+    EnPassantTarget(usize),
+    #[error("Board invariant violated: the recorded repetition key at ply {0} is missing or inconsistent with the position")]
+    RepetitionKey(usize),
+    #[error("Board invariant violated: the game is marked as ongoing, but it is over or has a result")]
+    OngoingAfterEnd,
+    #[error("Board invariant violated: the game is marked as over, but it has no result")]
+    EndedWithoutResult,
+    #[error("Board invariant violated: a takeback of {0} halfmoves is pending, but only {1} have been played")]
+    TakebackOffer(usize, usize),
+}
+
+/// Conveys that a sequence of moves is not a legal game from the standard starting position
+/// (see [`verify_proof_game`](crate::verify::verify_proof_game)). Plies are counted from the starting position.
+#[derive(Error, Eq, PartialEq, Debug)]
+pub enum ProofGameError {
+    #[error("Invalid proof game: the move {1} at ply {0} is illegal")]
+    IllegalMove(usize, Move),
+    #[error("Invalid proof game: the game is already over at ply {0}")]
+    GameOver(usize),
+}
+
 /// Conveys that the given UCI move is either invalid or illegal.
 #[derive(Error, Debug)]
 pub enum InvalidUciMoveError {
@@ -62,11 +152,53 @@ pub enum InvalidUciMoveError {
     IllegalMove(String),
 }
 
+/// Conveys that a move in the given line of UCI moves is either invalid or illegal.
+#[derive(Error, Debug)]
+#[error("Invalid UCI move line: move {index} (counting from 0) is invalid: {error}")]
+pub struct InvalidUciLineError {
+    /// The index of the move in the line
+    pub index: usize,
+    /// The error of the move
+    pub error: InvalidUciMoveError,
+}
+
+/// Conveys that the given UCI command is invalid.
+#[derive(Error, Debug)]
+pub enum UciCommandError {
+    #[error("Invalid UCI command: '{0}' is not the expected command")]
+    UnexpectedCommand(String),
+    #[error("Invalid UCI command: expected 'startpos' or 'fen' after 'position'")]
+    MissingPosition,
+    #[error("Invalid UCI command: {0}")]
+    InvalidFen(#[from] InvalidFenError),
+    #[error("Invalid UCI command: {0}")]
+    InvalidMoves(#[from] InvalidUciLineError),
+    #[error("Invalid UCI command: unexpected token '{0}'")]
+    UnexpectedToken(String),
+    #[error("Invalid UCI command: '{0}' requires a value")]
+    MissingValue(String),
+    #[error("Invalid UCI command: '{1}' is not a valid value for '{0}'")]
+    InvalidValue(String, String),
+}
+
+/// Conveys that no legal game matches the candidate readings of a scoresheet.
+#[derive(Error, Debug)]
+#[error("Unresolvable scoresheet: no legal game matches the candidates up to halfmove {ply} (counting from 0)")]
+pub struct UnresolvableScoresheetError {
+    /// The index of the first halfmove which no candidate game could be extended with
+    pub ply: usize,
+}
+
 /// Conveys that the given SAN move is either invalid or illegal.
 #[derive(Error, Debug)]
 #[error("Invalid SAN move: '{0}' is either invalid or illegal in this position")]
 pub struct InvalidSanMoveError(pub String);
 
+/// Conveys that the given LAN move is either invalid or illegal.
+#[derive(Error, Debug)]
+#[error("Invalid LAN move: '{0}' is either invalid or illegal in this position")]
+pub struct InvalidLanMoveError(pub String);
+
 /// Conveys that the given square name is invalid.
 #[derive(Error, Debug)]
 #[error("Invalid square name: {0}{1}")]
@@ -77,6 +209,21 @@ pub struct InvalidSquareNameError(pub char, pub char);
 #[error("Invalid square index: {0}, a square index must be in the range 0..=63")]
 pub struct InvalidSquareIndexError(pub usize);
 
+/// Conveys that the given file letter is invalid.
+#[derive(Error, Debug)]
+#[error("Invalid file: '{0}', a file must be a letter from 'a' to 'h'")]
+pub struct InvalidFileError(pub char);
+
+/// Conveys that the given rank digit is invalid.
+#[derive(Error, Debug)]
+#[error("Invalid rank: '{0}', a rank must be a digit from '1' to '8'")]
+pub struct InvalidRankError(pub char);
+
+/// Conveys that the given game result token is invalid.
+#[derive(Error, Debug)]
+#[error("Invalid result token: '{0}', a valid result token must be '1-0', '0-1', '1/2-1/2', or '*'")]
+pub struct InvalidResultTokenError(pub String);
+
 /// Conveys that this action cannot be taken after the game is over.
 #[derive(Error, Debug)]
 pub enum GameOverError {
@@ -84,6 +231,54 @@ pub enum GameOverError {
     Resignation,
     #[error("Game over: players cannot agree to a draw when the game is over")]
     AgreementDraw,
+    #[error("Game over: a player cannot run out of time when the game is over")]
+    Timeout,
+    #[error("Game over: a player cannot offer a draw when the game is over")]
+    DrawOffer,
+}
+
+/// Conveys that a draw cannot be claimed (see [`Board::claim_draw`](crate::Board::claim_draw)).
+#[derive(Error, Eq, PartialEq, Debug)]
+pub enum DrawClaimError {
+    #[error("Draw claim error: a draw cannot be claimed when the game is over")]
+    GameOver,
+    #[error("Draw claim error: a draw of type {0:?} cannot be claimed in the current position")]
+    NotClaimable(super::DrawType),
+}
+
+/// Conveys that an action cannot be taken in a game of a [`Session`](super::Session).
+#[derive(Error, Debug)]
+pub enum SessionError {
+    #[error("Session error: there is no game with the given id")]
+    UnknownGame,
+    #[error("Session error: the game is over")]
+    GameOver,
+    #[error("Session error: illegal move: {0}")]
+    IllegalMove(Move),
+}
+
+/// Conveys that a takeback cannot be offered, accepted, or declined.
+#[derive(Error, Debug)]
+pub enum TakebackError {
+    #[error("Takeback error: takebacks cannot be offered or accepted when the game is over")]
+    GameOver,
+    #[error("Takeback error: {0} halfmoves cannot be taken back, as {1} have been played")]
+    Plies(usize, usize),
+    #[error("Takeback error: a takeback offer is already pending")]
+    AlreadyPending,
+    #[error("Takeback error: there is no pending takeback offer")]
+    NoPendingOffer,
+    #[error("Takeback error: {0:?} cannot respond to its own takeback offer")]
+    OwnOffer(super::Color),
+}
+
+/// Conveys that a hint cannot be given.
+#[derive(Error, Debug)]
+pub enum HintError {
+    #[error("Hint error: hints cannot be given when the game is over")]
+    GameOver,
+    #[error("Hint error: {0} is not a valid hint level (the levels are 1, 2, and 3)")]
+    InvalidLevel(u8),
 }
 
 /// Conveys that the given PGN text is invalid.
@@ -102,6 +297,70 @@ pub enum InvalidPgnError {
     InvalidMove(InvalidSanMoveError),
     #[error("Invalid PGN: invalid result, {0}")]
     InvalidResult(String),
+    #[error("Invalid PGN: the position after halfmove {0} (counting from 0) does not match its FEN checkpoint '{1}'")]
+    Checkpoint(usize, String),
+    #[error("Invalid PGN: {0}")]
+    InvalidFen(InvalidFenError),
+}
+
+/// Conveys that a game could not be read from a PGN database.
+#[cfg(feature = "pgn")]
+#[derive(Error, Debug)]
+pub enum PgnReadError {
+    #[error("Failed to read PGN: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    InvalidPgn(#[from] InvalidPgnError),
+}
+
+/// Conveys that a PGN could not be written.
+#[cfg(feature = "pgn")]
+#[derive(Error, Debug)]
+pub enum PgnWriteError {
+    #[error("Failed to write PGN: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to write PGN: '{0}' cannot be represented in the chosen encoding")]
+    Unrepresentable(char),
+}
+
+/// Conveys that the given annotation is invalid.
+#[cfg(feature = "pgn")]
+#[derive(Error, Debug)]
+#[error("Invalid annotation: '{0}' must be a color (G, R, Y, or B) followed by a square name (for a highlighted square) or two square names (for an arrow)")]
+pub struct InvalidAnnotationError(pub String);
+
+/// Conveys that a game could not be imported from the Chess.com API.
+#[cfg(feature = "chesscom")]
+#[derive(Error, Debug)]
+pub enum ChessComImportError {
+    #[error("Invalid Chess.com game: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid Chess.com game: the '{0}' field is missing or has the wrong type")]
+    Field(&'static str),
+    #[error("Invalid Chess.com game: the '{0}' rules are not supported")]
+    UnsupportedVariant(String),
+    #[error("Invalid Chess.com game: {0}")]
+    InvalidPgn(#[from] InvalidPgnError),
+}
+
+/// Conveys that a game could not be imported from the Lichess API.
+#[cfg(feature = "lichess")]
+#[derive(Error, Debug)]
+pub enum LichessImportError {
+    #[error("Failed to read Lichess games: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid Lichess game: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid Lichess game: the '{0}' field is missing or has the wrong type")]
+    Field(&'static str),
+    #[error("Invalid Lichess game: the '{0}' variant is not supported")]
+    UnsupportedVariant(String),
+    #[error("Invalid Lichess game: {0}")]
+    InvalidFen(#[from] InvalidFenError),
+    #[error("Invalid Lichess game: halfmove {0} (counting from 0) is invalid, {1}")]
+    InvalidMove(usize, InvalidSanMoveError),
+    #[error("Invalid Lichess game: {0}")]
+    InvalidPgn(#[from] InvalidPgnError),
 }
 
 /// Conveys that the given hex color is invalid.
@@ -110,6 +369,12 @@ pub enum InvalidPgnError {
 #[error("Invalid hex: '{0}' is not a valid hex color")]
 pub struct InvalidHexError(pub String);
 
+/// Conveys that the given name is not the name of a built-in theme.
+#[cfg(feature = "img")]
+#[derive(Error, Debug)]
+#[error("Unknown theme: '{0}' is not a recognized built-in theme")]
+pub struct UnknownThemeError(pub String);
+
 /// Conveys that the given position image properties are invalid.
 #[cfg(feature = "img")]
 #[derive(Error, Debug)]
@@ -120,4 +385,43 @@ pub enum InvalidPositionImagePropertiesError {
     InvalidBuiltinPieceSet(String),
     #[error("Invalid position image properties: the piece set '{0:?}' does not contain all the necessary pieces")]
     InvalidCustomPieceSet(super::img::PieceSet),
+    #[error("Invalid position image properties: {0} is not a valid square index for an arrow or highlighted square")]
+    InvalidSquare(usize),
+    #[error("Invalid position image properties: the custom coordinate font does not contain the character '{0}'")]
+    InvalidCustomFont(char),
+}
+
+/// Conveys that a piece set could not be loaded from a directory.
+#[cfg(feature = "img")]
+#[derive(Error, Debug)]
+pub enum PieceSetLoadError {
+    #[error("Failed to load piece set: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to load piece set: there is no SVG or PNG file for the piece '{0}'")]
+    MissingPiece(String),
+    #[error("Failed to load piece set: the file {0:?} is not a valid image")]
+    InvalidFile(std::path::PathBuf),
+    #[cfg(feature = "zip")]
+    #[error("Failed to load piece set: the archive could not be read: {0}")]
+    Archive(String),
+}
+
+/// Conveys that an image in a batch could not be rendered.
+#[cfg(feature = "img")]
+#[derive(Error, Debug)]
+pub enum BatchRenderError {
+    #[error("{0}")]
+    Properties(#[from] InvalidPositionImagePropertiesError),
+    #[error("Failed to encode image: {0}")]
+    Encoding(String),
+}
+
+/// Conveys that an animation of a game could not be created.
+#[cfg(feature = "img")]
+#[derive(Error, Debug)]
+pub enum GameAnimationError {
+    #[error("{0}")]
+    Properties(#[from] InvalidPositionImagePropertiesError),
+    #[error("Failed to encode animation: {0}")]
+    Encoding(String),
 }
@@ -0,0 +1,28 @@
+use super::{Color, GameResult, Move, Piece, PieceType, TakebackOffer};
+
+/// Represents something that happened on a [`Board`](crate::Board), as recorded when event recording is enabled
+/// (see [`Board::record_events`](crate::Board::record_events)).
+///
+/// A single move can produce several events: for example, a capture which gives checkmate produces
+/// [`BoardEvent::MoveMade`], [`BoardEvent::Capture`], [`BoardEvent::Check`], and [`BoardEvent::GameOver`], in that order.
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub enum BoardEvent {
+    /// A move was played, with its representations in SAN and UCI.
+    MoveMade { move_: Move, san: String, uci: String },
+    /// The given piece was captured.
+    Capture(Piece),
+    /// A pawn was promoted to the given type of piece.
+    Promotion(PieceType),
+    /// The given side castled, kingside or queenside.
+    Castling { side: Color, kingside: bool },
+    /// The given side was put in check.
+    Check(Color),
+    /// The game ended with the given result.
+    GameOver(GameResult),
+    /// The given side offered a draw.
+    DrawOffered(Color),
+    /// A takeback was offered.
+    TakebackOffered(TakebackOffer),
+    /// The given move was undone.
+    MoveUndone(Move),
+}
@@ -0,0 +1,150 @@
+//! Build opening explorers from collections of games: an [`OpeningTree`] records, for every position reached in the games,
+//! the moves played from it and how the games continuing with each move ended.
+
+use super::{pgn::Pgn, Board, Color, Move, Outcome, PgnReadError, Position};
+use std::collections::HashMap;
+
+/// Represents the statistics of a move in an [`OpeningTree`].
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub struct MoveStats {
+    /// The number of games in which the move was played
+    pub games: u64,
+    /// The number of those games won by white
+    pub white_wins: u64,
+    /// The number of those games drawn
+    pub draws: u64,
+    /// The number of those games won by black (games with unknown results are counted in none of the three)
+    pub black_wins: u64,
+    /// The number of those games in which the rating of at least one player is known
+    pub rated_games: u64,
+    /// The sum of the ratings of those games, where the rating of a game is the average of the known ratings of its players
+    pub rating_sum: u64,
+}
+
+impl MoveStats {
+    /// Returns the average rating of the games in which the move was played, or `None` if none of them are rated.
+    pub fn average_rating(&self) -> Option<f64> {
+        (self.rated_games > 0).then(|| self.rating_sum as f64 / self.rated_games as f64)
+    }
+
+    /// Returns the average score of the given side (1 for a win, 0.5 for a draw, and 0 for a loss) in the games with known results,
+    /// or `None` if there are none.
+    pub fn score(&self, side: Color) -> Option<f64> {
+        let decided = self.white_wins + self.draws + self.black_wins;
+        let wins = if side.is_white() { self.white_wins } else { self.black_wins };
+        (decided > 0).then(|| (wins as f64 + self.draws as f64 / 2.) / decided as f64)
+    }
+
+    /// Adds a game to the statistics.
+    fn record(&mut self, outcome: Outcome, rating: Option<u64>) {
+        self.games += 1;
+        match outcome {
+            Outcome::Wins(Color::White) => self.white_wins += 1,
+            Outcome::Wins(Color::Black) => self.black_wins += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Unknown => (),
+        }
+        if let Some(rating) = rating {
+            self.rated_games += 1;
+            self.rating_sum += rating;
+        }
+    }
+}
+
+/// An opening tree, which maps positions (by their Zobrist hashes, see [`Position::zobrist_hash`]) to the moves played from them
+/// in a collection of games, with statistics for each move. Positions reached by transposition share their moves.
+#[derive(Clone, Debug, Default)]
+pub struct OpeningTree {
+    /// The moves played from each position, in the order they were first played
+    nodes: HashMap<u64, Vec<(Move, MoveStats)>>,
+    /// The number of plies of each game which are added to the tree, if limited
+    max_plies: Option<usize>,
+    /// The number of games added to the tree
+    games: u64,
+}
+
+impl OpeningTree {
+    /// Creates an empty opening tree, to which every move of each game is added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the moves added to the tree to the first `plies` halfmoves of each game, which keeps the tree small
+    /// when only the opening phase is of interest.
+    pub fn with_max_plies(mut self, plies: usize) -> Self {
+        self.max_plies = Some(plies);
+        self
+    }
+
+    /// Adds the moves of a game to the tree. The result of the game is read from the board of the PGN, and the ratings of the players
+    /// from its `WhiteElo` and `BlackElo` tags (if present and valid).
+    pub fn add_game(&mut self, pgn: &Pgn) {
+        let board = pgn.board();
+        let outcome = board.outcome();
+        let ratings: Vec<u64> = ["WhiteElo", "BlackElo"].iter().filter_map(|tag| pgn.tag_pairs().get(*tag)?.trim().parse().ok()).collect();
+        let rating = (!ratings.is_empty()).then(|| ratings.iter().sum::<u64>() / ratings.len() as u64);
+        let plies = self.max_plies.map_or(board.move_history().len(), |max| max.min(board.move_history().len()));
+        for (ply, &move_) in board.move_history()[..plies].iter().enumerate() {
+            let hash = board.position_at_ply(ply).unwrap().zobrist_hash();
+            let moves = self.nodes.entry(hash).or_default();
+            let index = moves.iter().position(|(m, _)| *m == move_).unwrap_or_else(|| {
+                moves.push((move_, MoveStats::default()));
+                moves.len() - 1
+            });
+            moves[index].1.record(outcome, rating);
+        }
+        self.games += 1;
+    }
+
+    /// Adds every game which was parsed successfully to the tree (see [`OpeningTree::add_game`]), skipping games which could not be read
+    /// or parsed, such as those yielded by a [`PgnReader`](crate::pgn::PgnReader) streaming a large database. Returns the number of games added.
+    pub fn add_games(&mut self, games: impl IntoIterator<Item = Result<Pgn, PgnReadError>>) -> usize {
+        let mut added = 0;
+        for pgn in games.into_iter().flatten() {
+            self.add_game(&pgn);
+            added += 1;
+        }
+        added
+    }
+
+    /// Returns the moves played from the current position of a board, with their statistics, most frequently played first.
+    pub fn moves_after(&self, board: &Board) -> Vec<(Move, MoveStats)> {
+        self.moves_after_position(board.position())
+    }
+
+    /// Returns the moves played from a position, with their statistics, most frequently played first.
+    pub fn moves_after_position(&self, position: &Position) -> Vec<(Move, MoveStats)> {
+        let mut moves = self.nodes.get(&position.zobrist_hash()).cloned().unwrap_or_default();
+        moves.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.games));
+        moves
+    }
+
+    /// Returns the combined statistics of the moves played from the current position of a board, i.e. of the games which reached it
+    /// (and did not end there), or `None` if no game continued from it.
+    pub fn stats(&self, board: &Board) -> Option<MoveStats> {
+        let moves = self.nodes.get(&board.zobrist_hash())?;
+        Some(moves.iter().fold(MoveStats::default(), |total, (_, stats)| MoveStats {
+            games: total.games + stats.games,
+            white_wins: total.white_wins + stats.white_wins,
+            draws: total.draws + stats.draws,
+            black_wins: total.black_wins + stats.black_wins,
+            rated_games: total.rated_games + stats.rated_games,
+            rating_sum: total.rating_sum + stats.rating_sum,
+        }))
+    }
+
+    /// Returns the number of games added to the tree.
+    pub fn games(&self) -> u64 {
+        self.games
+    }
+
+    /// Returns the number of positions in the tree from which moves were played.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Checks whether the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
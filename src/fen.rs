@@ -1,4 +1,8 @@
-use super::{helpers, Color, InvalidFenError, Piece, PieceType, Position};
+use super::{
+    helpers,
+    metrics::{self, ParseKind},
+    Color, InvalidFenError, Piece, PieceType, Position,
+};
 use std::fmt;
 
 /// Represents FEN (Forsyth-Edwards Notation).
@@ -24,14 +28,50 @@ impl Fen {
     pub fn fullmove_number(&self) -> usize {
         self.fullmove_number
     }
+
+    /// Returns a canonical form of the FEN, in which the en passant target square is only present if an en passant capture is legal.
+    pub fn normalized(&self) -> Self {
+        let mut fen = self.clone();
+        fen.position.ep_target = self.position.legal_ep_target();
+        fen
+    }
+
+    /// Checks whether this FEN and another describe the same position for the purposes of repetition,
+    /// ignoring the halfmove clock, the fullmove number, and en passant target squares where no en passant capture is legal.
+    pub fn eq_ignore_clocks(&self, other: &Self) -> bool {
+        self.position.same_position(&other.position)
+    }
 }
 
-impl TryFrom<&str> for Fen {
-    type Error = InvalidFenError;
+/// The constraints on the pieces in an FEN, which differ between chess variants.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub(crate) struct FenRules {
+    /// Whether kings are royal: each side must have exactly one king, and the side not to move must not be in check.
+    /// If kings are not royal, each side may have any number of kings, and there are no castling rights.
+    pub(crate) royal_kings: bool,
+    /// Whether white has no king (as in Horde), in which case white must not have a king or castling rights, and only black's king is royal.
+    pub(crate) kingless_white: bool,
+    /// Whether white pawns may stand on the first rank (as in Horde).
+    pub(crate) first_rank_pawns: bool,
+}
 
-    /// Attempts to construct a `Fen` object from a string slice, returning an error if it is invalid.
-    /// **Shredder-FEN is NOT supported**.
-    fn try_from(fen: &str) -> Result<Self, Self::Error> {
+impl FenRules {
+    /// The constraints of standard chess.
+    pub(crate) const STANDARD: Self = Self {
+        royal_kings: true,
+        kingless_white: false,
+        first_rank_pawns: false,
+    };
+}
+
+impl Fen {
+    /// Parses an FEN string without reporting failures to the metrics sink.
+    pub(crate) fn parse(fen: &str) -> Result<Self, InvalidFenError> {
+        Self::parse_with(fen, FenRules::STANDARD)
+    }
+
+    /// Parses an FEN string under the given constraints, without reporting failures to the metrics sink.
+    pub(crate) fn parse_with(fen: &str, rules: FenRules) -> Result<Self, InvalidFenError> {
         let mut content = [None; 64];
         let fields: Vec<_> = fen.trim().split(' ').collect();
         let nfields = fields.len();
@@ -73,6 +113,10 @@ impl TryFrom<&str> for Fen {
                     content[ptr] = match piece_char.try_into() {
                         Ok(piece) => {
                             match piece {
+                                Piece(PieceType::K, _) if !rules.royal_kings => (),
+                                Piece(PieceType::K, Color::White) if rules.kingless_white => {
+                                    return Err(InvalidFenError::BoardData("white cannot have a king".to_owned()));
+                                }
                                 Piece(PieceType::K, Color::White) => {
                                     if wk_seen {
                                         return Err(InvalidFenError::BoardData("white cannot have more than one king".to_owned()));
@@ -87,10 +131,9 @@ impl TryFrom<&str> for Fen {
                                     bk_seen = true;
                                     bk_pos = ptr;
                                 }
-                                Piece(PieceType::P, _) => {
-                                    if !(8..56).contains(&ptr) {
-                                        return Err(InvalidFenError::BoardData("there cannot be pawns on the 1st and 8th ranks".to_owned()));
-                                    }
+                                Piece(PieceType::P, Color::White) if rules.first_rank_pawns && ptr < 8 => (),
+                                Piece(PieceType::P, _) if !(8..56).contains(&ptr) => {
+                                    return Err(InvalidFenError::BoardData("there cannot be pawns on the 1st and 8th ranks".to_owned()));
                                 }
                                 _ => (),
                             }
@@ -107,7 +150,10 @@ impl TryFrom<&str> for Fen {
             }
             rankn -= 1;
         }
-        if !(wk_seen && bk_seen) {
+        if rules.royal_kings && rules.kingless_white && !bk_seen {
+            return Err(InvalidFenError::BoardData("black must have one king".to_owned()));
+        }
+        if rules.royal_kings && !rules.kingless_white && !(wk_seen && bk_seen) {
             return Err(InvalidFenError::BoardData("a valid chess position must have one white king and one black king".to_owned()));
         }
         let turn = fields[1];
@@ -115,7 +161,8 @@ impl TryFrom<&str> for Fen {
             Ok(c) => c,
             _ => return Err(InvalidFenError::ActiveColor),
         };
-        if helpers::king_capture_pseudolegal(&content, side) {
+        // without a white king, only black can be in check, which is only invalid if it is white's turn
+        if rules.royal_kings && !(rules.kingless_white && side.is_black()) && helpers::king_capture_pseudolegal(&content, side) {
             return Err(InvalidFenError::BoardData("when one side is in check, it cannot be the other side's turn to move".to_owned()));
         }
         let castling = fields[2];
@@ -124,6 +171,12 @@ impl TryFrom<&str> for Fen {
             return Err(InvalidFenError::CastlingRights("expected castling rights to be 1 to 4 characters long".to_owned()));
         }
         let mut castling_rights_old = [false; 4];
+        if !rules.royal_kings && castling != "-" {
+            return Err(InvalidFenError::CastlingRights("there is no castling without royal kings".to_owned()));
+        }
+        if rules.kingless_white && castling.contains(['K', 'Q']) {
+            return Err(InvalidFenError::CastlingRights("white cannot castle without a king".to_owned()));
+        }
         if castling != "-" {
             for ch in castling.chars() {
                 match ch {
@@ -236,9 +289,20 @@ impl TryFrom<&str> for Fen {
     }
 }
 
+impl TryFrom<&str> for Fen {
+    type Error = InvalidFenError;
+
+    /// Attempts to construct a `Fen` object from a string slice, returning an error if it is invalid.
+    /// **Shredder-FEN is NOT supported**.
+    fn try_from(fen: &str) -> Result<Self, Self::Error> {
+        metrics::record_parse(ParseKind::Fen, Self::parse(fen))
+    }
+}
+
 impl fmt::Display for Fen {
     /// Returns an FEN string representing this object.
-    /// If standard FEN is inadequate for representing castling rights, a mixture of standard FEN and Shredder-FEN will be generated.
+    /// If standard FEN is inadequate for representing castling rights, a mixture of standard FEN and Shredder-FEN will be generated
+    /// (see [`Position::castling_rights`](crate::Position::castling_rights)).
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", [self.position.to_fen(), self.halfmove_clock.to_string(), self.fullmove_number.to_string()].join(" "))
     }
@@ -1,48 +1,191 @@
-use super::Color;
+use super::{Color, InvalidResultTokenError};
 use std::fmt;
 
-/// Represents game results.
+/// Represents game results. A game result is the pair of an [`Outcome`] (who scored what)
+/// and a [`Termination`] (why the game ended); see [`GameResult::into_parts`] and [`GameResult::from_parts`].
+///
+/// It is an enum rather than a struct of the two parts so that only consistent pairs can be represented:
+/// a win always has a [`WinType`] and a winner, and a draw a [`DrawType`] (never a draw by checkmate, or an unknown outcome with a reason).
+/// To filter results by score regardless of the reason, compare [`GameResult::outcome`].
 #[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
 pub enum GameResult {
     Wins(Color, WinType),
     Draw(DrawType),
 }
 
+impl GameResult {
+    /// Returns the outcome of the game, without the reason the game ended.
+    pub fn outcome(&self) -> Outcome {
+        match self {
+            Self::Wins(c, _) => Outcome::Wins(*c),
+            Self::Draw(_) => Outcome::Draw,
+        }
+    }
+
+    /// Returns the reason the game ended, without the outcome of the game.
+    pub fn termination(&self) -> Termination {
+        match self {
+            Self::Wins(_, w) => Termination::Win(*w),
+            Self::Draw(d) => Termination::Draw(*d),
+        }
+    }
+
+    /// Splits the game result into its outcome and termination reason.
+    pub fn into_parts(self) -> (Outcome, Termination) {
+        (self.outcome(), self.termination())
+    }
+
+    /// Combines an outcome and a termination reason into a game result, returning `None` if they are inconsistent
+    /// (such as a draw by checkmate) or the outcome is unknown.
+    pub fn from_parts(outcome: Outcome, termination: Termination) -> Option<Self> {
+        match (outcome, termination) {
+            (Outcome::Wins(c), Termination::Win(w)) => Some(Self::Wins(c, w)),
+            (Outcome::Draw, Termination::Draw(d)) => Some(Self::Draw(d)),
+            _ => None,
+        }
+    }
+
+    /// Returns the score of the given side (1 for a win, 0.5 for a draw, and 0 for a loss).
+    pub fn score_for(&self, side: Color) -> f32 {
+        self.outcome().score_for(side).unwrap()
+    }
+
+    /// Returns the PGN result token representing the game result ("1-0" if white wins, "0-1" if black wins, or "1/2-1/2" in the case of a draw).
+    pub fn to_pgn_token(&self) -> &'static str {
+        self.outcome().to_pgn_token()
+    }
+
+    /// Attempts to interpret a PGN result token ("1-0", "0-1", "1/2-1/2", or "*"), returning `None` if the token
+    /// is "*" (the game is ongoing or the result is unknown), and an error if the token is invalid.
+    /// Since a result token does not state how the game ended, a win is represented as a win by resignation
+    /// and a draw as a draw by agreement.
+    pub fn from_pgn_token(token: &str) -> Result<Option<Self>, InvalidResultTokenError> {
+        Ok(match Outcome::from_pgn_token(token)? {
+            Outcome::Wins(c) => Some(Self::Wins(c, WinType::Resignation)),
+            Outcome::Draw => Some(Self::Draw(DrawType::Agreement)),
+            Outcome::Unknown => None,
+        })
+    }
+}
+
 impl fmt::Display for GameResult {
     /// Represents the game result as a string (1-0 if white wins, 0-1 if black wins, or 1/2-1/2 in the case of a draw).
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::Wins(c, _) =>
-                    if c.is_white() {
-                        "1-0"
-                    } else {
-                        "0-1"
-                    },
-                Self::Draw(_) => "1/2-1/2",
-            }
-        )
+        write!(f, "{}", self.to_pgn_token())
+    }
+}
+
+/// Represents the outcome of a game (who scored what), regardless of how the game ended.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum Outcome {
+    Wins(Color),
+    Draw,
+    /// Represents a game that is ongoing, or whose outcome is unknown.
+    Unknown,
+}
+
+impl Outcome {
+    /// Returns the score of the given side (1 for a win, 0.5 for a draw, and 0 for a loss), or `None` if the outcome is unknown.
+    pub fn score_for(&self, side: Color) -> Option<f32> {
+        match self {
+            Self::Wins(c) => Some(if *c == side { 1. } else { 0. }),
+            Self::Draw => Some(0.5),
+            Self::Unknown => None,
+        }
+    }
+
+    /// Returns the PGN result token representing the outcome ("1-0", "0-1", "1/2-1/2", or "*").
+    pub fn to_pgn_token(&self) -> &'static str {
+        match self {
+            Self::Wins(Color::White) => "1-0",
+            Self::Wins(Color::Black) => "0-1",
+            Self::Draw => "1/2-1/2",
+            Self::Unknown => "*",
+        }
+    }
+
+    /// Attempts to interpret a PGN result token ("1-0", "0-1", "1/2-1/2", or "*"), returning an error if it is invalid.
+    pub fn from_pgn_token(token: &str) -> Result<Self, InvalidResultTokenError> {
+        match token.trim() {
+            "1-0" => Ok(Self::Wins(Color::White)),
+            "0-1" => Ok(Self::Wins(Color::Black)),
+            "1/2-1/2" => Ok(Self::Draw),
+            "*" => Ok(Self::Unknown),
+            _ => Err(InvalidResultTokenError(token.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    /// Represents the outcome as a PGN result token.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_pgn_token())
     }
 }
 
+/// Represents the reason a game ended, regardless of who won.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum Termination {
+    Win(WinType),
+    Draw(DrawType),
+}
+
 /// Represents types of wins.
 #[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
 pub enum WinType {
     Checkmate,
-    /// Currently, a loss by timeout is also considered a resignation.
     Resignation,
+    /// Represents a win because the opponent ran out of time.
+    Timeout,
+    /// Represents a win by a rule specific to a chess variant, such as losing all of one's pieces in [`Antichess`](crate::Antichess).
+    Variant,
 }
 
 /// Represents types of draws.
 #[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
 pub enum DrawType {
+    /// Represents a draw by fivefold repetition (the default repetition limit of the rule set).
     FivefoldRepetition,
+    /// Represents a draw by the seventy-five-move rule (the default halfmove limit of the rule set).
     SeventyFiveMoveRule,
+    /// Represents a draw by reaching a repetition limit of the rule set other than fivefold (see [`Rules::repetition_limit`](crate::Rules::repetition_limit)),
+    /// with the tuple value being the number of occurrences of the position.
+    RepetitionLimit(usize),
+    /// Represents a draw by reaching a halfmove limit of the rule set other than 150 halfmoves (see [`Rules::halfmove_limit`](crate::Rules::halfmove_limit)),
+    /// with the tuple value being the number of halfmoves.
+    HalfmoveLimit(usize),
+    /// Represents a draw claimed by a threefold repetition (see [`Board::claim_draw`](crate::Board::claim_draw)).
+    ThreefoldRepetition,
+    /// Represents a draw claimed by the fifty-move rule (see [`Board::claim_draw`](crate::Board::claim_draw)).
+    FiftyMoveRule,
     /// Represents a stalemate, with the tuple value being the side in stalemate.
     Stalemate(Color),
     InsufficientMaterial,
-    /// Currently, a claimed draw and a draw by timeout vs. insufficient checkmating material are also considered a draw by agreement.
+    /// Represents a dead position other than insufficient material, such as a blocked pawn wall (see [`Board::set_dead_position_detection`](crate::Board::set_dead_position_detection)).
+    DeadPosition,
     Agreement,
+    /// Represents a draw because a side ran out of time, but its opponent could not win (see [`TimeoutRule`](crate::TimeoutRule)).
+    TimeoutVsInsufficientMaterial,
+    /// Represents a draw by a rule specific to a chess variant, such as both kings reaching the eighth rank in [`RacingKings`](crate::RacingKings).
+    Variant,
+}
+
+impl DrawType {
+    /// Returns the draw by reaching the given repetition limit: [`DrawType::FivefoldRepetition`] for 5, and [`DrawType::RepetitionLimit`] otherwise.
+    pub(crate) fn repetition_limit(limit: usize) -> Self {
+        if limit == 5 {
+            Self::FivefoldRepetition
+        } else {
+            Self::RepetitionLimit(limit)
+        }
+    }
+
+    /// Returns the draw by reaching the given halfmove limit: [`DrawType::SeventyFiveMoveRule`] for 150, and [`DrawType::HalfmoveLimit`] otherwise.
+    pub(crate) fn halfmove_limit(limit: usize) -> Self {
+        if limit == 150 {
+            Self::SeventyFiveMoveRule
+        } else {
+            Self::HalfmoveLimit(limit)
+        }
+    }
 }
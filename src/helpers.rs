@@ -1,5 +1,8 @@
-use super::{Color, Move, Piece, PieceType, Position, SpecialMoveType};
-use std::ops::RangeBounds;
+use super::{attacks, Color, File, Move, Piece, PieceType, Rank, SpecialMoveType};
+use std::{
+    ops::RangeBounds,
+    time::{Duration, Instant},
+};
 
 /// Converts a square name in the format (<file>, <rank>) to a square index.
 pub fn sq_to_idx(file: char, rank: char) -> usize {
@@ -8,19 +11,7 @@ pub fn sq_to_idx(file: char, rank: char) -> usize {
 
 /// Converts a square index to a square name in the format (<file>, <rank>).
 pub fn idx_to_sq(idx: usize) -> (char, char) {
-    ((idx % 8 + 97) as u8 as char, char::from_digit((idx / 8 + 1) as u32, 10).unwrap())
-}
-
-/// Checks whether a long-range piece can move on the axis `axis_direction` from the square `sq`
-pub fn long_range_can_move(sq: usize, axis_direction: isize) -> bool {
-    !(axis_direction == 1 && (sq + 1) % 8 == 0
-        || axis_direction == -1 && sq % 8 == 0
-        || axis_direction == 8 && sq >= 56
-        || axis_direction == -8 && sq < 8
-        || axis_direction == 7 && (sq >= 56 || sq % 8 == 0)
-        || axis_direction == -7 && (sq < 8 || (sq + 1) % 8 == 0)
-        || axis_direction == 9 && (sq >= 56 || (sq + 1) % 8 == 0)
-        || axis_direction == -9 && (sq < 8 || sq % 8 == 0))
+    (File::of(idx).into(), Rank::of(idx).into())
 }
 
 /// Counts the number of pieces on the board identical to the `piece` provided that are within the provided square range.
@@ -31,14 +22,6 @@ where
     find_pieces(piece, rng, content).len()
 }
 
-/// Counts the number of pieces on the board that are within the provided square range.
-pub fn count_pieces<R>(rng: R, content: &[Option<Piece>; 64]) -> usize
-where
-    R: RangeBounds<usize> + Iterator<Item = usize>,
-{
-    rng.fold(0, |acc, sq| if content[sq].is_some() { acc + 1 } else { acc })
-}
-
 /// Finds the indices of all occurrences of a piece identical to the given `piece` on the board in the square range `rng`.
 pub fn find_pieces<R>(piece: Piece, rng: R, content: &[Option<Piece>; 64]) -> Vec<usize>
 where
@@ -48,34 +31,15 @@ where
     rng.filter(|&sq| content[sq] == piece).collect()
 }
 
-/// Finds the indices of all occurrences pieces on the board in the square range `rng`.
-pub fn find_all_pieces<R>(rng: R, content: &[Option<Piece>; 64]) -> Vec<usize>
-where
-    R: RangeBounds<usize> + Iterator<Item = usize>,
-{
-    rng.filter(|&sq| content[sq].is_some()).collect()
-}
-
-/// Checks whether capturing a king is pseudolegal for the specified side in the given position.
+/// Checks whether capturing a king is pseudolegal for the specified side in the given position
+/// (which is never the case if the other side has no king, as in Horde).
 pub fn king_capture_pseudolegal(content: &[Option<Piece>; 64], side: Color) -> bool {
-    let enemy_king = find_king(!side, content);
-    Position {
-        content: *content,
-        side,
-        castling_rights: [None, None, None, None],
-        ep_target: None,
-    }
-    .controls_square(enemy_king, side)
+    find_king(!side, content).is_some_and(|king| attacks::is_attacked(content, king, side))
 }
 
-/// Returns the square index of the king of color `color`.
-pub fn find_king(color: Color, content: &[Option<Piece>; 64]) -> usize {
-    content
-        .iter()
-        .enumerate()
-        .find(|(_, o)| if let Some(Piece(PieceType::K, s)) = o { *s == color } else { false })
-        .unwrap()
-        .0
+/// Returns the square index of the king of color `color`, or `None` if that side has no king (as in Horde).
+pub fn find_king(color: Color, content: &[Option<Piece>; 64]) -> Option<usize> {
+    content.iter().position(|&piece| piece == Some(Piece(PieceType::K, color)))
 }
 
 /// Changes the board content based on the given move.
@@ -147,6 +111,86 @@ pub fn squares_in_rank(rank: char) -> Vec<usize> {
     vec
 }
 
+/// Represents the components of a move in (possibly over-disambiguated) algebraic notation.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct SanComponents {
+    pub piece_type: PieceType,
+    pub src_file: Option<char>,
+    pub src_rank: Option<char>,
+    pub dest: usize,
+    pub promotion: Option<PieceType>,
+}
+
+impl SanComponents {
+    /// Checks whether the given move matches these components in the given board content.
+    pub fn matches(&self, move_: &Move, content: &[Option<Piece>; 64]) -> bool {
+        let (srcf, srcr) = idx_to_sq(move_.0);
+        matches!(content[move_.0], Some(Piece(pt, _)) if pt == self.piece_type)
+            && move_.1 == self.dest
+            && move_.promotion() == self.promotion
+            && self.src_file.is_none_or(|f| f == srcf)
+            && self.src_rank.is_none_or(|r| r == srcr)
+    }
+}
+
+/// Splits a non-castling move in algebraic notation (without check or annotation suffixes) into its components,
+/// ignoring capture markers and dashes, and accepting promotions with or without '='.
+pub fn parse_san_components(san: &str) -> Option<SanComponents> {
+    let mut chars: Vec<_> = san.chars().filter(|c| !['x', ':', '-'].contains(c)).collect();
+    let mut promotion = None;
+    if let Some(&last) = chars.last() {
+        if "QRBN".contains(last) {
+            promotion = Some(PieceType::try_from(last).ok()?);
+            chars.pop();
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+    }
+    let piece_type = match chars.first() {
+        Some(&c) if "KQRBN".contains(c) => {
+            chars.remove(0);
+            PieceType::try_from(c).ok()?
+        }
+        _ => PieceType::P,
+    };
+    if chars.len() < 2 {
+        return None;
+    }
+    let (destf, destr) = (chars[chars.len() - 2], chars[chars.len() - 1]);
+    if !(('a'..='h').contains(&destf) && ('1'..='8').contains(&destr)) {
+        return None;
+    }
+    let (mut src_file, mut src_rank) = (None, None);
+    for &c in &chars[..chars.len() - 2] {
+        match c {
+            'a'..='h' if src_file.is_none() && src_rank.is_none() => src_file = Some(c),
+            '1'..='8' if src_rank.is_none() => src_rank = Some(c),
+            _ => return None,
+        }
+    }
+    Some(SanComponents {
+        piece_type,
+        src_file,
+        src_rank,
+        dest: sq_to_idx(destf, destr),
+        promotion,
+    })
+}
+
+/// Replaces figurines (of either color) in a SAN or LAN string with the corresponding piece letters.
+/// Pawn figurines are removed, since pawn moves have no piece letter, as are the variation selectors
+/// which often follow figurines in text from the web (requesting text or emoji presentation).
+pub fn figurines_to_letters(san: &str) -> String {
+    san.chars()
+        .filter_map(|c| match (c, PieceType::from_figurine(c)) {
+            ('\u{fe0e}' | '\u{fe0f}', _) | (_, Some(PieceType::P)) => None,
+            (_, Some(pt)) => Some(char::from(pt)),
+            (c, None) => Some(c),
+        })
+        .collect()
+}
+
 pub fn as_legal(move_: Move, legal: &[Move]) -> Option<Move> {
     if legal.contains(&move_) {
         Some(move_)
@@ -159,3 +203,16 @@ pub fn as_legal(move_: Move, legal: &[Move]) -> Option<Move> {
         None
     }
 }
+
+/// Returns the current instant, or `None` on `wasm32-unknown-unknown`, which has no clock (`Instant::now` panics there).
+pub(crate) fn now() -> Option<Instant> {
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    return None;
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    Some(Instant::now())
+}
+
+/// Returns the time elapsed since an instant from [`now`], or zero if there is no clock.
+pub(crate) fn elapsed_since(start: Option<Instant>) -> Duration {
+    start.map_or(Duration::ZERO, |start| start.elapsed())
+}
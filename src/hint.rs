@@ -0,0 +1,45 @@
+use super::{Move, PieceType, Position};
+
+/// Represents a hint for the side to move, as returned by [`Board::hint`](crate::Board::hint).
+/// Hints are graded: each level gives away more of the suggested move than the last.
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub enum Hint {
+    /// Level 1: the type of piece to move.
+    Piece(PieceType),
+    /// Level 2: the square of the piece to move, in the format (_file_, _rank_).
+    Square(char, char),
+    /// Level 3: the full move, with its representation in SAN.
+    Move { move_: Move, san: String },
+}
+
+/// The score of a position in which the side to move has been checkmated.
+const MATED_SCORE: i32 = -1_000_000;
+
+/// The number of halfmoves searched to pick the move suggested by hints.
+const HINT_DEPTH: usize = 2;
+
+/// Picks the move suggested by hints in a position: the first of the moves with the best score in a shallow search
+/// which counts material and recognizes checkmates, or `None` if there are no legal moves. The searched positions
+/// bypass the legal move cache, which would otherwise keep growing with every hint.
+pub(crate) fn hint_move(position: &Position) -> Option<Move> {
+    let mut best: Option<(Move, i32)> = None;
+    for move_ in position.gen_non_illegal_moves_uncached() {
+        let score = -negamax(&position.with_legal_move_made(move_), HINT_DEPTH - 1);
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((move_, score));
+        }
+    }
+    best.map(|(move_, _)| move_)
+}
+
+/// Scores a position from the point of view of the side to move, searching the given number of halfmoves.
+fn negamax(position: &Position, depth: usize) -> i32 {
+    let moves = position.gen_non_illegal_moves_uncached();
+    if moves.is_empty() {
+        return if position.is_check() { MATED_SCORE } else { 0 };
+    }
+    if depth == 0 {
+        return position.material_balance() * position.side_to_move().sign();
+    }
+    moves.into_iter().map(|move_| -negamax(&position.with_legal_move_made(move_), depth - 1)).max().unwrap()
+}
@@ -0,0 +1,87 @@
+use super::{fen::FenRules, Color, DrawType, Fen, GameResult, InvalidFenError, Move, Piece, PieceType, Position, Variant, VariantBoard, WinType};
+
+/// A game of Horde (see [`HordeRules`]).
+pub type Horde = VariantBoard<HordeRules>;
+
+/// The rules of Horde, in which white's "horde" of 36 pawns (and no king) faces black's standard army.
+///
+/// The rules differ from standard chess as follows:
+/// * white has no king, so white's moves are never restricted by check, and white cannot castle
+/// * white pawns on the first rank may move two squares, like pawns on the second rank (though without allowing en passant captures)
+/// * black wins by capturing all of white's pieces, while white wins by checkmating black
+///
+/// In FENs, white must have no king (and no castling rights), and white pawns may stand on the first rank.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub struct HordeRules;
+
+impl HordeRules {
+    /// Checks whether black's king is in check in a position.
+    fn is_check(position: &Position) -> bool {
+        position.checked_side() == Some(Color::Black)
+    }
+}
+
+impl Variant for HordeRules {
+    type State = ();
+
+    /// Black's standard army against 36 white pawns.
+    fn starting_fen(&self) -> &'static str {
+        "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+    }
+
+    fn parse_fen(&self, fen: &str) -> Result<Fen, InvalidFenError> {
+        Fen::parse_with(
+            fen,
+            FenRules {
+                kingless_white: true,
+                first_rank_pawns: true,
+                ..FenRules::STANDARD
+            },
+        )
+    }
+
+    fn legal_moves(&self, position: &Position) -> Vec<Move> {
+        if position.side.is_black() {
+            return position.gen_non_illegal_moves();
+        }
+        let mut moves = position.gen_pseudolegal_moves();
+        for sq in 0..8 {
+            if position.content[sq] == Some(Piece(PieceType::P, Color::White)) && position.content[sq + 8].is_none() && position.content[sq + 16].is_none() {
+                moves.push(Move(sq, sq + 16, None));
+            }
+        }
+        moves
+    }
+
+    fn position_after(&self, position: &Position, move_: Move) -> Position {
+        let mut after = position.with_legal_move_made(move_);
+        let Move(src, dest, _) = move_;
+        if src < 8 && dest == src + 16 {
+            after.ep_target = None;
+        }
+        after
+    }
+
+    /// Black wins with [`WinType::Variant`] by capturing all of white's pieces.
+    fn result(&self, position: &Position, _state: &(), legal_moves: &[Move]) -> Option<GameResult> {
+        let side = position.side;
+        if !position.content.iter().flatten().any(|piece| piece.1.is_white()) {
+            Some(GameResult::Wins(Color::Black, WinType::Variant))
+        } else if legal_moves.is_empty() {
+            Some(if side.is_black() && Self::is_check(position) {
+                GameResult::Wins(Color::White, WinType::Checkmate)
+            } else {
+                GameResult::Draw(DrawType::Stalemate(side))
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl Horde {
+    /// Checks whether black's king is in check.
+    pub fn is_check(&self) -> bool {
+        HordeRules::is_check(self.position())
+    }
+}
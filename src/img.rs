@@ -1,10 +1,30 @@
 //! Generate `image-rs` images of `Position`s.
+//!
+//! Rendering is deterministic: for a given position, set of properties, and version of rschess, the same image is produced on every platform,
+//! so images can be compared with golden images in tests (see [`image_digest`]). All drawing is done with basic floating-point arithmetic,
+//! whose results IEEE 754 fixes exactly (transcendental functions, whose precision differs between platforms' math libraries, are not used),
+//! and text is drawn in a bitmap font (or with the images of a custom font), so no system fonts are involved.
+//! SVG pieces are rasterized by nanosvg at a fixed resolution of 96 DPI; as it is compiled C code, its output could in principle differ
+//! between C compilers which contract floating-point operations differently.
 
-use super::{helpers, Color, InvalidHexError, InvalidPositionImagePropertiesError, Position};
+use super::{
+    helpers, metrics, BatchRenderError, Board, Color, GameAnimationError, InvalidHexError, InvalidPositionImagePropertiesError, Move, Piece, PieceSetLoadError, PieceType, Position, UnknownThemeError,
+};
+#[cfg(feature = "pgn")]
+use super::{
+    pgn::{self, Annotation, AnnotationColor},
+    InvalidAnnotationError,
+};
 use image::{imageops, Rgba, RgbaImage};
 use include_dir::{include_dir, Dir};
 use nsvg;
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, iter,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 static ASSETS_DIR: Dir = include_dir!("assets");
 
@@ -55,6 +75,82 @@ pub enum PieceSet {
     /// representing the pieces ("wK", "wN", "bP", etc.) and the values
     /// depicting the pieces.
     Custom(HashMap<String, RgbaImage>),
+    /// A piece set loaded from a directory with [`PieceSet::from_dir`] (or from an archive with `PieceSet::from_zip`).
+    Directory(PieceDirectory),
+}
+
+impl PieceSet {
+    /// Attempts to load a piece set from a directory containing a file for every piece, named in the standard way
+    /// ("wK.svg", "wN.svg", "bP.svg", etc.), returning an error if a piece is missing or a file cannot be read.
+    /// Pieces can be SVG or PNG files (SVG files are preferred if both exist).
+    ///
+    /// Unlike with [`PieceSet::Custom`], the pieces are rasterized at the resolution needed by each render,
+    /// and rasterized pieces are cached, so that rendering many images with the piece set (or clones of it) is fast.
+    /// This function is not available on `wasm32-unknown-unknown`, which has no file system.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn from_dir(path: impl AsRef<Path>) -> Result<Self, PieceSetLoadError> {
+        let path = path.as_ref();
+        Self::from_files(path, |fname| {
+            let file_path = path.join(fname);
+            Ok(if file_path.is_file() { Some(std::fs::read(file_path)?) } else { None })
+        })
+    }
+
+    /// Attempts to load a piece set from the bytes of a ZIP archive, in which a directory (or the root of the archive) contains a file
+    /// for every piece, as with [`PieceSet::from_dir`]. Other files in the archive are ignored.
+    #[cfg(feature = "zip")]
+    pub fn from_zip(bytes: &[u8]) -> Result<Self, PieceSetLoadError> {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).map_err(|e| PieceSetLoadError::Archive(e.to_string()))?;
+        // the pieces are in the directory of the first white king found
+        let dir = archive
+            .file_names()
+            .filter_map(|name| {
+                let path = Path::new(name);
+                matches!(path.file_name()?.to_str()?, "wK.svg" | "wK.png").then(|| path.parent().unwrap_or(Path::new("")).to_owned())
+            })
+            .min_by_key(|dir| dir.components().count())
+            .ok_or_else(|| PieceSetLoadError::MissingPiece("wK".to_owned()))?;
+        Self::from_files(&dir, |fname| {
+            let name = dir.join(fname).to_string_lossy().replace('\\', "/");
+            let mut file = match archive.by_name(&name) {
+                Ok(file) => file,
+                Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+                Err(e) => return Err(PieceSetLoadError::Archive(e.to_string())),
+            };
+            let mut contents = Vec::new();
+            std::io::Read::read_to_end(&mut file, &mut contents)?;
+            Ok(Some(contents))
+        })
+    }
+
+    /// Loads a piece set from the directory at the given path, reading files by name with `read` (which returns `None` if a file does not exist).
+    fn from_files(path: &Path, mut read: impl FnMut(&str) -> Result<Option<Vec<u8>>, PieceSetLoadError>) -> Result<Self, PieceSetLoadError> {
+        let mut sources = BTreeMap::new();
+        for color in ['w', 'b'] {
+            for piece_type in ['K', 'Q', 'R', 'B', 'N', 'P'] {
+                let piece_str = format!("{color}{piece_type}");
+                let (svg_name, png_name) = (format!("{piece_str}.svg"), format!("{piece_str}.png"));
+                let source = if let Some(svg) = read(&svg_name)? {
+                    let svg = String::from_utf8(svg).map_err(|_| PieceSetLoadError::InvalidFile(path.join(&svg_name)))?;
+                    if nsvg::parse_str(&svg, nsvg::Units::Pixel, 96.).is_err() {
+                        return Err(PieceSetLoadError::InvalidFile(path.join(svg_name)));
+                    }
+                    PieceSource::Svg(svg)
+                } else if let Some(png) = read(&png_name)? {
+                    let image = image::load_from_memory_with_format(&png, image::ImageFormat::Png).map_err(|_| PieceSetLoadError::InvalidFile(path.join(png_name)))?;
+                    PieceSource::Png(image.into_rgba8())
+                } else {
+                    return Err(PieceSetLoadError::MissingPiece(piece_str));
+                };
+                sources.insert(piece_str, source);
+            }
+        }
+        Ok(Self::Directory(PieceDirectory {
+            path: path.to_owned(),
+            sources,
+            cache: Arc::default(),
+        }))
+    }
 }
 
 impl Default for PieceSet {
@@ -63,9 +159,263 @@ impl Default for PieceSet {
     }
 }
 
+/// Represents the source of a piece in a [`PieceDirectory`].
+#[derive(Eq, PartialEq, Clone, Debug)]
+enum PieceSource {
+    Svg(String),
+    Png(RgbaImage),
+}
+
+/// A piece set loaded from a directory with [`PieceSet::from_dir`] (or from an archive with `PieceSet::from_zip`).
+/// Clones of a piece directory share its cache of rasterized pieces.
+#[derive(Clone)]
+pub struct PieceDirectory {
+    /// The path of the directory
+    path: PathBuf,
+    /// The sources of the pieces, keyed by piece ("wK", "wN", "bP", etc.)
+    sources: BTreeMap<String, PieceSource>,
+    /// The rasterized pieces, keyed by piece and size
+    cache: Arc<Mutex<HashMap<(String, u32), RgbaImage>>>,
+}
+
+impl PieceDirectory {
+    /// Returns the path of the directory the piece set was loaded from (relative to the root of the archive, for piece sets loaded from archives).
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl PartialEq for PieceDirectory {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path && self.sources == other.sources
+    }
+}
+
+impl Eq for PieceDirectory {}
+
+impl fmt::Debug for PieceDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PieceDirectory").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "pgn")]
+impl From<AnnotationColor> for Rgb {
+    /// Converts the color of an annotation to the shade used for it by Lichess.
+    fn from(color: AnnotationColor) -> Self {
+        match color {
+            AnnotationColor::Green => Self(0x15, 0x78, 0x1b),
+            AnnotationColor::Red => Self(0x88, 0x20, 0x20),
+            AnnotationColor::Yellow => Self(0xe6, 0x8f, 0x00),
+            AnnotationColor::Blue => Self(0x00, 0x30, 0x88),
+        }
+    }
+}
+
+/// Represents an arrow drawn over the board, from the center of one square to the center of another.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct Arrow {
+    /// The index of the square the arrow starts from
+    pub from: usize,
+    /// The index of the square the arrow points to
+    pub to: usize,
+    /// The color of the arrow, which is drawn slightly transparent
+    pub color: Rgb,
+}
+
+/// Represents a highlighted square, whose color is blended with the given color.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct HighlightedSquare {
+    /// The index of the square
+    pub square: usize,
+    /// The color to highlight the square with
+    pub color: Rgb,
+}
+
+/// Represents where coordinate labels are drawn.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum CoordinatePlacement {
+    /// Labels are drawn in the corners of the squares along the bottom and left edges of the board.
+    Inside,
+    /// Labels are drawn in a border around the board, which makes the image larger than the board.
+    Outside,
+}
+
+/// Represents the font used to draw coordinate labels.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum CoordinateFont {
+    /// A built-in bitmap font, drawn in the color of the coordinates.
+    Builtin,
+    /// A custom font must include a `HashMap` with the keys representing the characters ('a' to 'h' and '1' to '8')
+    /// and the values depicting the characters, which are scaled to the height of a label and drawn as they are.
+    Custom(HashMap<char, RgbaImage>),
+}
+
+/// Represents the properties of the coordinate labels (file letters and rank numbers) drawn along the edges of the board.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Coordinates {
+    /// Where the labels are drawn
+    pub placement: CoordinatePlacement,
+    /// The color of the labels
+    pub color: Rgb,
+    /// The color of the border around the board, if the labels are drawn outside the board
+    pub border_color: Rgb,
+    /// The font of the labels
+    pub font: CoordinateFont,
+}
+
+impl Default for Coordinates {
+    /// The default `Coordinates` are drawn inside the board in the built-in font, colored `#2d313d`,
+    /// with a border colored `#f3f3f4` if they are drawn outside the board.
+    fn default() -> Self {
+        Self {
+            placement: CoordinatePlacement::Inside,
+            color: Rgb::from_hex("#2d313d").unwrap(),
+            border_color: Rgb::from_hex("#f3f3f4").unwrap(),
+            font: CoordinateFont::Builtin,
+        }
+    }
+}
+
+/// Represents image textures for the light and dark squares of the board, drawn instead of solid colors.
+/// Each texture is scaled to the size of the board, and every square shows the part of its texture beneath it,
+/// so that patterns (such as the veins of marble) continue from square to square.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct SquareTextures {
+    /// The texture of the light squares
+    pub light: RgbaImage,
+    /// The texture of the dark squares
+    pub dark: RgbaImage,
+}
+
+/// The names of the built-in themes, which can be loaded with [`Theme::builtin`].
+pub const BUILTIN_THEMES: [&str; 9] = [
+    "default",
+    "lichess-brown",
+    "lichess-blue",
+    "lichess-green",
+    "lichess-purple",
+    "chesscom-green",
+    "chesscom-brown",
+    "grey",
+    "blue-marble",
+];
+
+/// Represents a board theme: the colors of the light and dark squares, and optionally textures drawn instead of the colors.
+/// Themes are applied to image properties with [`PositionImageProperties::with_theme`].
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Theme {
+    /// The color of the light squares
+    pub light_square_color: Rgb,
+    /// The color of the dark squares
+    pub dark_square_color: Rgb,
+    /// The textures of the squares, if any
+    pub square_textures: Option<SquareTextures>,
+}
+
+impl Theme {
+    /// Creates a theme with the given square colors and no textures.
+    pub fn new(light_square_color: Rgb, dark_square_color: Rgb) -> Self {
+        Self {
+            light_square_color,
+            dark_square_color,
+            square_textures: None,
+        }
+    }
+
+    /// Attempts to load one of the built-in themes (see [`BUILTIN_THEMES`]) by name, returning an error if there is no such theme.
+    /// The colors of the Lichess and Chess.com themes match those of the respective sites; "blue-marble" has generated marble textures.
+    pub fn builtin(name: &str) -> Result<Self, UnknownThemeError> {
+        let hex = |hex| Rgb::from_hex(hex).unwrap();
+        let (light, dark) = match name {
+            "default" => ("#f3f3f4", "#639a59"),
+            "lichess-brown" => ("#f0d9b5", "#b58863"),
+            "lichess-blue" => ("#dee3e6", "#8ca2ad"),
+            "lichess-green" => ("#ffffdd", "#86a666"),
+            "lichess-purple" => ("#9f90b0", "#7d4a8d"),
+            "chesscom-green" => ("#eeeed2", "#769656"),
+            "chesscom-brown" => ("#edd6b0", "#b88762"),
+            "grey" => ("#dcdcdc", "#ababab"),
+            "blue-marble" => {
+                let (light, dark) = (hex("#d9e2e8"), hex("#7f9db4"));
+                return Ok(Self {
+                    light_square_color: light,
+                    dark_square_color: dark,
+                    square_textures: Some(SquareTextures {
+                        light: marble_texture(light, hex("#a9bccb"), 1),
+                        dark: marble_texture(dark, hex("#5a7a93"), 2),
+                    }),
+                });
+            }
+            _ => return Err(UnknownThemeError(name.to_owned())),
+        };
+        Ok(Self::new(hex(light), hex(dark)))
+    }
+}
+
+/// The width and height in pixels of the generated marble textures.
+const MARBLE_TEXTURE_SIZE: u32 = 256;
+
+/// Generates a marble texture with veins of the color `vein` over the color `base`, using turbulence from value noise seeded by `seed`.
+fn marble_texture(base: Rgb, vein: Rgb, seed: u32) -> RgbaImage {
+    let (Rgb(r, g, b), Rgb(vr, vg, vb)) = (base, vein);
+    RgbaImage::from_fn(MARBLE_TEXTURE_SIZE, MARBLE_TEXTURE_SIZE, |x, y| {
+        let (fx, fy) = (x as f64 / MARBLE_TEXTURE_SIZE as f64, y as f64 / MARBLE_TEXTURE_SIZE as f64);
+        let turbulence: f64 = (0..4)
+            .map(|octave| value_noise(fx * 4. * 2f64.powi(octave), fy * 4. * 2f64.powi(octave), seed + octave as u32) / 2f64.powi(octave))
+            .sum();
+        let vein_strength = (1. - portable_sin((fx * 3. + fy * 2.) * std::f64::consts::TAU + turbulence * 4.).abs()).powi(6) * 0.8;
+        let blend = |c: u8, v: u8| (v as f64 * vein_strength + c as f64 * (1. - vein_strength)).round() as u8;
+        Rgba([blend(r, vr), blend(g, vg), blend(b, vb), 255])
+    })
+}
+
+/// Computes the sine of `x` with a Taylor series, so that the result is identical on every platform (unlike [`f64::sin`]).
+fn portable_sin(x: f64) -> f64 {
+    let x = x - (x / std::f64::consts::TAU).round() * std::f64::consts::TAU;
+    let (mut term, mut sum) = (x, x);
+    for n in 1..=12 {
+        term *= -x * x / ((2 * n) * (2 * n + 1)) as f64;
+        sum += term;
+    }
+    sum
+}
+
+/// Computes _e_ to the power of `x` with a Taylor series, so that the result is identical on every platform (unlike [`f64::exp`]).
+fn portable_exp(x: f64) -> f64 {
+    let x = x.clamp(-700., 700.);
+    // e^x = 2^k * e^r, where |r| <= ln(2) / 2
+    let k = (x / std::f64::consts::LN_2).round();
+    let r = x - k * std::f64::consts::LN_2;
+    let (mut term, mut sum) = (1., 1.);
+    for n in 1..=16 {
+        term *= r / n as f64;
+        sum += term;
+    }
+    sum * f64::from_bits(((k as i64 + 1023) as u64) << 52)
+}
+
+/// Returns smoothly interpolated pseudorandom noise (in the range 0..=1) at the given point, seeded by `seed`.
+fn value_noise(x: f64, y: f64, seed: u32) -> f64 {
+    let lattice = |ix: i64, iy: i64| {
+        let mut h = (ix as u32).wrapping_mul(0x27d4eb2d) ^ (iy as u32).wrapping_mul(0x165667b1) ^ seed.wrapping_mul(0x9e3779b9);
+        h = (h ^ (h >> 15)).wrapping_mul(0x85ebca6b);
+        h ^= h >> 13;
+        h as f64 / u32::MAX as f64
+    };
+    let (ix, iy) = (x.floor() as i64, y.floor() as i64);
+    let smooth = |t: f64| t * t * (3. - 2. * t);
+    let (tx, ty) = (smooth(x - ix as f64), smooth(y - iy as f64));
+    let top = lattice(ix, iy) * (1. - tx) + lattice(ix + 1, iy) * tx;
+    let bottom = lattice(ix, iy + 1) * (1. - tx) + lattice(ix + 1, iy + 1) * tx;
+    top * (1. - ty) + bottom * ty
+}
+
 /// Represents the properties of an image generated from a position.
 /// The board theme can be customized with custom colors for the
 /// light and dark squares, the size of the board, and custom piece sets.
+/// Arrows and square highlights can be drawn over the board.
+/// To highlight the last move and a checked king automatically, use [`board_to_image`].
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct PositionImageProperties {
     /// The color to be used for the light squares of the board
@@ -76,108 +426,1128 @@ pub struct PositionImageProperties {
     pub piece_set: PieceSet,
     /// The width and height of the board in pixels; this value must be greater than or equal to 8
     pub size: usize,
+    /// The arrows to draw over the board and pieces
+    pub arrows: Vec<Arrow>,
+    /// The squares to highlight
+    pub highlighted_squares: Vec<HighlightedSquare>,
+    /// The squares to highlight with a radial gradient beneath the piece (like the highlight under a king in check)
+    pub radial_highlights: Vec<HighlightedSquare>,
+    /// The coordinate labels to draw, if any
+    pub coordinates: Option<Coordinates>,
+    /// The evaluation bar to draw to the left of the board, if any
+    pub evaluation_bar: Option<EvaluationBar>,
+    /// The trays of captured pieces to draw above and below the board, if any.
+    /// Since captured pieces are determined from the moves of a game, trays are only drawn by [`board_to_image`] and [`board_to_svg`].
+    pub material_tray: Option<MaterialTray>,
+    /// The textures to draw the squares with instead of the square colors, if any
+    pub square_textures: Option<SquareTextures>,
+}
+
+impl PositionImageProperties {
+    /// Adds the arrows and highlighted squares described by annotations in the format of [`parse_annotations`](crate::pgn::parse_annotations)
+    /// (such as `"Ge2e4 Rd5 Yc8h3"`), returning an error if an annotation is invalid.
+    #[cfg(feature = "pgn")]
+    pub fn with_annotations(mut self, annotations: &str) -> Result<Self, InvalidAnnotationError> {
+        for annotation in pgn::parse_annotations(annotations)? {
+            match annotation {
+                Annotation::Arrow { from, to, color } => self.arrows.push(Arrow { from, to, color: color.into() }),
+                Annotation::Square { square, color } => self.highlighted_squares.push(HighlightedSquare { square, color: color.into() }),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Applies a theme (such as one loaded with [`Theme::builtin`]), replacing the square colors and textures.
+    pub fn with_theme(self, theme: Theme) -> Self {
+        Self {
+            light_square_color: theme.light_square_color,
+            dark_square_color: theme.dark_square_color,
+            square_textures: theme.square_textures,
+            ..self
+        }
+    }
+}
+
+/// Represents an evaluation of a position, from white's point of view.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum Evaluation {
+    /// An advantage in centipawns (hundredths of a pawn), which is positive if white is better and negative if black is better
+    Centipawns(i32),
+    /// A forced checkmate by the given side in the given number of moves
+    Mate(Color, u32),
+}
+
+impl Evaluation {
+    /// Returns the expected share of the points for white (in the range 0..=1), as shown by an evaluation bar.
+    /// Centipawns are converted with the logistic model used by Lichess; a forced checkmate fills the bar.
+    pub fn white_share(&self) -> f64 {
+        match self {
+            Self::Centipawns(cp) => 1. / (1. + portable_exp(-0.00368208 * *cp as f64)),
+            Self::Mate(side, _) => {
+                if side.is_white() {
+                    1.
+                } else {
+                    0.
+                }
+            }
+        }
+    }
+
+    /// Returns the label shown on an evaluation bar: the advantage in pawns (such as "1.5", without a sign), or the number of moves to checkmate (such as "M3").
+    pub fn label(&self) -> String {
+        match self {
+            Self::Centipawns(cp) if cp.abs() >= 1000 => format!("{}", cp.abs() / 100),
+            Self::Centipawns(cp) => format!("{:.1}", cp.abs() as f64 / 100.),
+            Self::Mate(_, moves) => format!("M{moves}"),
+        }
+    }
+}
+
+/// Represents the properties of an evaluation bar, drawn to the left of the board and filled with white's expected share of the points
+/// (see [`Evaluation::white_share`]), like the bars shown by popular chess sites. The evaluation is labeled at the end of the side that is better.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct EvaluationBar {
+    /// The evaluation to show
+    pub evaluation: Evaluation,
+    /// The color of white's part of the bar
+    pub white_color: Rgb,
+    /// The color of black's part of the bar
+    pub black_color: Rgb,
+}
+
+impl EvaluationBar {
+    /// Creates an evaluation bar showing the given evaluation, with white's part colored `#ffffff` and black's part colored `#403d39`.
+    pub fn new(evaluation: Evaluation) -> Self {
+        Self {
+            evaluation,
+            white_color: Rgb(255, 255, 255),
+            black_color: Rgb(64, 61, 57),
+        }
+    }
+}
+
+/// Represents the properties of the trays drawn above and below the board, showing the pieces captured by each side
+/// (the side at the bottom of the board has its captures shown below it) and the material difference beside the side that is ahead.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct MaterialTray {
+    /// The background color of the trays
+    pub background: Rgb,
+    /// The color of the material difference
+    pub text_color: Rgb,
+}
+
+impl Default for MaterialTray {
+    /// The default `MaterialTray` has a background colored `#f3f3f4` and text colored `#2d313d`.
+    fn default() -> Self {
+        Self {
+            background: Rgb::from_hex("#f3f3f4").unwrap(),
+            text_color: Rgb::from_hex("#2d313d").unwrap(),
+        }
+    }
 }
 
 impl Default for PositionImageProperties {
     /// The default `PositionImageProperties` has light squares colored `#f3f3f4`, dark squares
     /// colored `#639a59`, the default piece set ([CBurnett's SVG chess pieces](https://commons.wikimedia.org/wiki/Category:SVG_chess_pieces#/media/File:Chess_Pieces_Sprite.svg)),
-    /// and a 512px by 512px board.
+    /// and a 512px by 512px board, without arrows, highlights, coordinates, an evaluation bar, material trays, or square textures.
     fn default() -> Self {
         Self {
             light_square_color: Rgb::from_hex("#f3f3f4").unwrap(),
             dark_square_color: Rgb::from_hex("#639a59").unwrap(),
             piece_set: PieceSet::Builtin("default".to_owned()),
             size: 512,
+            arrows: Vec::new(),
+            highlighted_squares: Vec::new(),
+            radial_highlights: Vec::new(),
+            coordinates: None,
+            evaluation_bar: None,
+            material_tray: None,
+            square_textures: None,
         }
     }
 }
 
 /// Creates an image of a `Position`, from the perspective of the side `perspective`.
 pub fn position_to_image(position: &Position, props: PositionImageProperties, perspective: Color) -> Result<RgbaImage, InvalidPositionImagePropertiesError> {
+    Renderer::new().position_to_image(position, props, perspective)
+}
+
+/// Creates an image of the current position of a `Board`, from the perspective of the side `perspective`,
+/// tinting the squares of the last move and highlighting the king if it is in check.
+/// Highlights given in `props` are drawn in addition to (and over) these, and material trays are drawn if enabled.
+pub fn board_to_image(board: &Board, props: PositionImageProperties, perspective: Color) -> Result<RgbaImage, InvalidPositionImagePropertiesError> {
+    Renderer::new().board_to_image(board, props, perspective)
+}
+
+/// Returns a digest of an image (its dimensions and the values of its pixels), as 32 hexadecimal digits, for comparing rendered images
+/// with golden images in tests without storing them. As rendering is deterministic (see the [module documentation](self)), the digest of
+/// an image rendered from the same position and properties only changes if the rendering changes in a new version of rschess.
+///
+/// The digest is computed with 128-bit FNV-1a, which is not a cryptographic hash.
+pub fn image_digest(image: &RgbaImage) -> String {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+    let hash = image
+        .width()
+        .to_le_bytes()
+        .iter()
+        .chain(&image.height().to_le_bytes())
+        .chain(image.as_raw())
+        .fold(OFFSET_BASIS, |hash, &byte| (hash ^ byte as u128).wrapping_mul(PRIME));
+    format!("{hash:032x}")
+}
+
+/// A reusable renderer, for rendering many images (such as every position of a game, or a batch of puzzles).
+/// A renderer caches rasterized pieces and empty boards between renders, so rendering many images with the same square colors,
+/// square textures, and piece set is much faster than with [`position_to_image`] and [`board_to_image`], which use a new renderer every time.
+/// The cache is cleared whenever an image is rendered with different square colors, square textures, or a different piece set.
+#[derive(Clone, Default)]
+pub struct Renderer {
+    cache: RenderCache,
+}
+
+impl Renderer {
+    /// Creates a renderer with an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an image of a `Position` like [`position_to_image`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(?perspective)))]
+    pub fn position_to_image(&mut self, position: &Position, props: PositionImageProperties, perspective: Color) -> Result<RgbaImage, InvalidPositionImagePropertiesError> {
+        let start = helpers::now();
+        let res = render_position(position, props, perspective, &mut self.cache);
+        if res.is_ok() {
+            metrics::record(|m| m.image_rendered(helpers::elapsed_since(start)));
+        }
+        res
+    }
+
+    /// Creates an image of the current position of a `Board` like [`board_to_image`].
+    pub fn board_to_image(&mut self, board: &Board, props: PositionImageProperties, perspective: Color) -> Result<RgbaImage, InvalidPositionImagePropertiesError> {
+        let (piece_set, material_tray, size) = (props.piece_set.clone(), props.material_tray, props.size);
+        let board_image = self.position_to_image(board.position(), with_board_highlights(board, props), perspective)?;
+        let Some(material_tray) = material_tray else {
+            return Ok(board_image);
+        };
+        let (tray_height, items) = material_tray_items(board, size, board_image.height() as f64, perspective);
+        let Rgb(r, g, b) = material_tray.background;
+        let mut image = RgbaImage::from_pixel(board_image.width(), board_image.height() + 2 * tray_height as u32, Rgba([r, g, b, 255]));
+        imageops::replace(&mut image, &board_image, 0, tray_height as i64);
+        for (item, x, y, height) in items {
+            match item {
+                TrayItem::Piece(piece) => {
+                    let piece_image = self.cache.piece(&piece_set, &piece_str(piece), height.round() as u32)?;
+                    imageops::overlay(&mut image, piece_image, x.round() as i64, y.round() as i64);
+                }
+                TrayItem::Character(c) => {
+                    let Rgb(r, g, b) = material_tray.text_color;
+                    for (gx, gy, pixel_size) in builtin_glyph_pixels(c, x, y, height) {
+                        for py in gy.round() as u32..(gy + pixel_size).round() as u32 {
+                            for px in gx.round() as u32..(gx + pixel_size).round() as u32 {
+                                image.put_pixel(px, py, Rgba([r, g, b, 255]));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(image)
+    }
+}
+
+/// The PNG-encoded bytes of an image.
+pub type ImageBytes = Vec<u8>;
+
+/// Represents what a [`RenderJob`] renders.
+#[derive(Clone, Debug)]
+pub enum RenderSubject {
+    /// A position, rendered like [`position_to_image`]
+    Position(Box<Position>),
+    /// The current position of a game, rendered like [`board_to_image`]
+    Board(Box<Board>),
+}
+
+/// Represents an image to render in a batch with [`render_batch`].
+#[derive(Clone, Debug)]
+pub struct RenderJob {
+    /// What to render
+    pub subject: RenderSubject,
+    /// The properties of the image
+    pub props: PositionImageProperties,
+    /// The side from whose perspective the board is shown
+    pub perspective: Color,
+}
+
+/// Renders a batch of images (such as thumbnails for many puzzles or games) and encodes them as PNG, returning the result of each job in order.
+///
+/// Rendering is shared between jobs like with a [`Renderer`], so the setup of each render (rasterizing pieces and drawing empty boards)
+/// is only done once for each theme, rather than for every image. With the `rayon` feature, the jobs are rendered in parallel on rayon's
+/// global thread pool, with a renderer (and cache) per worker thread.
+pub fn render_batch(jobs: &[RenderJob]) -> Vec<Result<ImageBytes, BatchRenderError>> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        jobs.par_iter().map_init(Renderer::new, render_job).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        let mut renderer = Renderer::new();
+        jobs.iter().map(|job| render_job(&mut renderer, job)).collect()
+    }
+}
+
+/// Renders a job of a batch with the given renderer, and encodes the image as PNG.
+fn render_job(renderer: &mut Renderer, job: &RenderJob) -> Result<ImageBytes, BatchRenderError> {
+    let image = match &job.subject {
+        RenderSubject::Position(position) => renderer.position_to_image(position, job.props.clone(), job.perspective)?,
+        RenderSubject::Board(board) => renderer.board_to_image(board, job.props.clone(), job.perspective)?,
+    };
+    let mut png = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut png, image::ImageFormat::Png).map_err(|e| BatchRenderError::Encoding(e.to_string()))?;
+    Ok(png.into_inner())
+}
+
+impl fmt::Debug for Renderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Renderer")
+            .field("cached_pieces", &self.cache.pieces.len())
+            .field("cached_backgrounds", &self.cache.backgrounds.len())
+            .finish()
+    }
+}
+
+/// The images cached by a [`Renderer`], which are valid for the square colors, square textures, and piece set in `theme`.
+#[derive(Clone, Default)]
+struct RenderCache {
+    /// The square colors, square textures, and piece set of the cached images
+    theme: Option<(Rgb, Rgb, Option<SquareTextures>, PieceSet)>,
+    /// Rasterized pieces, keyed by piece ("wK", "wN", "bP", etc.) and size
+    pieces: HashMap<(String, u32), RgbaImage>,
+    /// Boards without pieces or highlights, keyed by size and whether they are from white's perspective
+    backgrounds: HashMap<(usize, bool), RgbaImage>,
+}
+
+impl RenderCache {
+    /// Clears the cache if its images were rendered with different square colors, square textures, or a different piece set.
+    fn prepare(&mut self, light_square_color: Rgb, dark_square_color: Rgb, square_textures: &Option<SquareTextures>, piece_set: &PieceSet) {
+        let unchanged = self
+            .theme
+            .as_ref()
+            .is_some_and(|(light, dark, textures, pieces)| (*light, *dark) == (light_square_color, dark_square_color) && textures == square_textures && pieces == piece_set);
+        if !unchanged {
+            self.theme = Some((light_square_color, dark_square_color, square_textures.clone(), piece_set.clone()));
+            self.pieces.clear();
+            self.backgrounds.clear();
+        }
+    }
+
+    /// Returns the rasterized image of a piece with the given width and height, rasterizing it if it is not cached.
+    fn piece(&mut self, piece_set: &PieceSet, piece_str: &str, size: u32) -> Result<&RgbaImage, InvalidPositionImagePropertiesError> {
+        let key = (piece_str.to_owned(), size);
+        if !self.pieces.contains_key(&key) {
+            let image = rasterize_piece(piece_set, piece_str, size)?;
+            self.pieces.insert(key.clone(), image);
+        }
+        Ok(&self.pieces[&key])
+    }
+
+    /// Returns an image of the board of the given size without pieces or highlights, drawing it if it is not cached.
+    fn background(&mut self, size: usize, perspective: Color) -> &RgbaImage {
+        let (light_square_color, dark_square_color, square_textures, _) = self.theme.as_ref().unwrap();
+        self.backgrounds.entry((size, perspective.is_white())).or_insert_with(|| {
+            let textures = square_textures
+                .as_ref()
+                .map(|textures| [&textures.light, &textures.dark].map(|texture| imageops::resize(texture, size as u32, size as u32, imageops::FilterType::Triangle)));
+            let piece_size = size / 8;
+            let mut image = RgbaImage::new(size as u32, size as u32);
+            for sq in 0..64 {
+                let (file, rank) = (sq % 8, sq / 8);
+                let (col, row) = if perspective.is_white() { (file, 7 - rank) } else { (7 - file, rank) };
+                let light = helpers::color_complex_of(sq);
+                for y in row * piece_size..(row + 1) * piece_size {
+                    for x in col * piece_size..(col + 1) * piece_size {
+                        let pixel = match &textures {
+                            Some(textures) => *textures[if light { 0 } else { 1 }].get_pixel(x as u32, y as u32),
+                            None => {
+                                let Rgb(r, g, b) = if light { *light_square_color } else { *dark_square_color };
+                                Rgba([r, g, b, 255])
+                            }
+                        };
+                        let Rgba([r, g, b, _]) = pixel;
+                        image.put_pixel(x as u32, y as u32, Rgba([r, g, b, 255]));
+                    }
+                }
+            }
+            image
+        })
+    }
+}
+
+/// Represents an item drawn in a material tray.
+enum TrayItem {
+    /// A captured piece
+    Piece(Piece),
+    /// A character of the material difference, drawn in the built-in font
+    Character(char),
+}
+
+/// Returns the height of a material tray, and the items to draw in the trays above and below the board (of the given height),
+/// each in the format (_item_, _x_, _y_, _height_), where (_x_, _y_) is the top-left corner of the item.
+fn material_tray_items(board: &Board, size: usize, board_height: f64, perspective: Color) -> (f64, Vec<(TrayItem, f64, f64, f64)>) {
+    const ORDER: [PieceType; 5] = [PieceType::P, PieceType::N, PieceType::B, PieceType::R, PieceType::Q];
+    let tray_height = (size as f64 / 8. * 0.6).round().max(1.);
+    let (piece_height, text_height, padding) = (tray_height * 0.8, tray_height * 0.45, tray_height * 0.15);
+    let mut items = Vec::new();
+    for (side, tray_y) in [(!perspective, 0.), (perspective, tray_height + board_height)] {
+        let mut captured = board.captured_pieces(side);
+        captured.sort_by_key(|pt| ORDER.iter().position(|o| o == pt));
+        let mut x = padding;
+        for (i, &piece_type) in captured.iter().enumerate() {
+            if i > 0 {
+                x += piece_height * if captured[i - 1] == piece_type { 0.4 } else { 0.8 };
+            }
+            items.push((TrayItem::Piece(Piece(piece_type, !side)), x, tray_y + (tray_height - piece_height) / 2., piece_height));
+        }
+        if !captured.is_empty() {
+            x += piece_height + padding;
+        }
+        let balance = board.position().material_balance() * side.sign();
+        if balance > 0 {
+            for c in format!("+{balance}").chars() {
+                items.push((TrayItem::Character(c), x, tray_y + (tray_height - text_height) / 2., text_height));
+                x += text_height * 6. / 7.;
+            }
+        }
+    }
+    (tray_height, items)
+}
+
+/// Adds the highlights of the last move and of a checked king in the current position of a `Board` to `props`.
+fn with_board_highlights(board: &Board, props: PositionImageProperties) -> PositionImageProperties {
+    with_highlights(board.position(), board.last_move(), props)
+}
+
+/// Adds the highlights of the given last move and of a checked king in the position to `props`.
+fn with_highlights(position: &Position, last_move: Option<Move>, props: PositionImageProperties) -> PositionImageProperties {
+    let mut props = props;
+    if let Some(Move(from, to, _)) = last_move {
+        let last_move = [from, to].map(|square| HighlightedSquare { square, color: LAST_MOVE_COLOR });
+        props.highlighted_squares.splice(0..0, last_move);
+    }
+    if let Some(king) = position.checked_side().and_then(|side| helpers::find_king(side, &position.content)) {
+        props.radial_highlights.insert(0, HighlightedSquare { square: king, color: CHECK_COLOR });
+    }
+    props
+}
+
+/// Creates an image of the position `after`, from the perspective of the side `perspective`, highlighting how its pieces differ from those of `before`:
+/// pieces which moved are highlighted in blue on both their old and new squares, pieces which appeared (such as a promoted piece) in green,
+/// and pieces which disappeared without being replaced in red. A piece which moved onto an occupied square (such as a capture) is highlighted as moved.
+/// Highlights given in `props` are drawn in addition to (and over) these.
+pub fn diff_image(before: &Position, after: &Position, props: PositionImageProperties, perspective: Color) -> Result<RgbaImage, InvalidPositionImagePropertiesError> {
+    let mut props = props;
+    props.highlighted_squares.splice(0..0, diff_highlights(before, after));
+    position_to_image(after, props, perspective)
+}
+
+/// Returns the highlights of the squares whose pieces differ between `before` and `after`, as described in [`diff_image`].
+fn diff_highlights(before: &Position, after: &Position) -> Vec<HighlightedSquare> {
+    let changed = |sq: &usize| before.content[*sq] != after.content[*sq];
+    let mut sources: Vec<usize> = (0..64).filter(changed).filter(|&sq| before.content[sq].is_some()).collect();
+    let mut highlights = Vec::new();
+    for dest in (0..64).filter(changed).filter(|&sq| after.content[sq].is_some()) {
+        match sources.iter().position(|&src| before.content[src] == after.content[dest]) {
+            Some(i) => {
+                let src = sources.remove(i);
+                highlights.extend([src, dest].map(|square| HighlightedSquare { square, color: DIFF_MOVED_COLOR }));
+            }
+            None => highlights.push(HighlightedSquare {
+                square: dest,
+                color: DIFF_ADDED_COLOR,
+            }),
+        }
+    }
+    for src in sources.into_iter().filter(|&sq| after.content[sq].is_none()) {
+        highlights.push(HighlightedSquare {
+            square: src,
+            color: DIFF_REMOVED_COLOR,
+        });
+    }
+    highlights
+}
+
+/// Renders every position of a game (from its initial position to its current one) into a frame, highlighted like in [`board_to_image`].
+fn game_frames(board: &Board, props: PositionImageProperties, perspective: Color) -> Result<Vec<RgbaImage>, InvalidPositionImagePropertiesError> {
+    let positions = board.position_history().iter().chain(iter::once(board.position()));
+    let last_moves = iter::once(None).chain(board.move_history().iter().copied().map(Some));
+    let mut renderer = Renderer::new();
+    positions
+        .zip(last_moves)
+        .map(|(position, last_move)| renderer.position_to_image(position, with_highlights(position, last_move, props.clone()), perspective))
+        .collect()
+}
+
+/// Creates an animated GIF of a game, with a frame for every position from its initial position to its current one, each shown for `frame_delay`.
+/// The animation loops forever. To animate a game from a PGN, pass [`Pgn::board`](crate::pgn::Pgn::board).
+pub fn game_to_gif(board: &Board, props: PositionImageProperties, perspective: Color, frame_delay: Duration) -> Result<Vec<u8>, GameAnimationError> {
+    use image::{
+        codecs::gif::{GifEncoder, Repeat},
+        Delay, Frame,
+    };
+
+    let frames = game_frames(board, props, perspective)?;
+    let mut gif = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut gif);
+        encoder.set_repeat(Repeat::Infinite).map_err(|e| GameAnimationError::Encoding(e.to_string()))?;
+        let delay = Delay::from_saturating_duration(frame_delay);
+        encoder
+            .encode_frames(frames.into_iter().map(|frame| Frame::from_parts(frame, 0, 0, delay)))
+            .map_err(|e| GameAnimationError::Encoding(e.to_string()))?;
+    }
+    Ok(gif)
+}
+
+/// Creates an animated PNG (APNG) of a game, like [`game_to_gif`] but without the color limitations of GIF.
+pub fn game_to_apng(board: &Board, props: PositionImageProperties, perspective: Color, frame_delay: Duration) -> Result<Vec<u8>, GameAnimationError> {
+    let frames = game_frames(board, props, perspective)?;
+    let (width, height) = frames[0].dimensions();
+    let encoding_error = |e: png::EncodingError| GameAnimationError::Encoding(e.to_string());
+    let mut apng = Vec::new();
+    let mut encoder = png::Encoder::new(&mut apng, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frames.len() as u32, 0).map_err(encoding_error)?;
+    encoder.set_frame_delay(frame_delay.as_millis().min(u16::MAX as u128) as u16, 1000).map_err(encoding_error)?;
+    let mut writer = encoder.write_header().map_err(encoding_error)?;
+    for frame in frames {
+        writer.write_image_data(frame.as_raw()).map_err(encoding_error)?;
+    }
+    writer.finish().map_err(encoding_error)?;
+    Ok(apng)
+}
+
+fn render_position(position: &Position, props: PositionImageProperties, perspective: Color, cache: &mut RenderCache) -> Result<RgbaImage, InvalidPositionImagePropertiesError> {
+    validate_overlays(&props)?;
     let PositionImageProperties {
         light_square_color,
         dark_square_color,
         piece_set,
         size,
+        arrows,
+        highlighted_squares,
+        radial_highlights,
+        coordinates,
+        evaluation_bar,
+        material_tray: _,
+        square_textures,
     } = props;
     if size < 8 {
         return Err(InvalidPositionImagePropertiesError::InvalidSize(size));
     }
-    let piece_set_name = match &piece_set {
-        PieceSet::Builtin(name) => Some({
-            let name = name.trim().to_lowercase().replace(' ', "-");
-            match name.as_str() {
-                "default" | "normal" => "cburnett".to_owned(),
-                _ => name,
+    cache.prepare(light_square_color, dark_square_color, &square_textures, &piece_set);
+    let piece_size = size / 8;
+    let mut board_image = cache.background(size, perspective).clone();
+    for sq in 0..64 {
+        let (file, rank) = (sq % 8, sq / 8);
+        let (col, row) = if perspective.is_white() { (file, 7 - rank) } else { (7 - file, rank) };
+        let (sq_x, sq_y) = (col * piece_size, row * piece_size);
+        let radial_color = radial_highlights.iter().rfind(|h| h.square == sq).map(|h| h.color);
+        if radial_color.is_some() || highlighted_squares.iter().any(|h| h.square == sq) {
+            for y in 0..piece_size {
+                for x in 0..piece_size {
+                    let (put_x, put_y) = ((sq_x + x) as u32, (sq_y + y) as u32);
+                    let Rgba([r, g, b, _]) = *board_image.get_pixel(put_x, put_y);
+                    let Rgb(r, g, b) = highlighted_color(sq, Rgb(r, g, b), &highlighted_squares);
+                    let pixel = match radial_color {
+                        Some(Rgb(hr, hg, hb)) => {
+                            let half = piece_size as f64 / 2.;
+                            let distance = ((x as f64 + 0.5 - half).powi(2) + (y as f64 + 0.5 - half).powi(2)).sqrt() / (half * std::f64::consts::SQRT_2);
+                            let opacity = radial_opacity(distance);
+                            let blend = |c: u8, h: u8| (h as f64 * opacity + c as f64 * (1. - opacity)).round() as u8;
+                            Rgba([blend(r, hr), blend(g, hg), blend(b, hb), 255])
+                        }
+                        None => Rgba([r, g, b, 255]),
+                    };
+                    board_image.put_pixel(put_x, put_y, pixel);
+                }
+            }
+        }
+        if let Some(piece) = position.content[sq] {
+            let piece_image = cache.piece(&piece_set, &piece_str(piece), piece_size as u32)?;
+            for y in 0..piece_size {
+                for x in 0..piece_size {
+                    let px = *piece_image.get_pixel(x as u32, y as u32);
+                    if px.0[3] > 64 {
+                        board_image.put_pixel((sq_x + x) as u32, (sq_y + y) as u32, px);
+                    }
+                }
+            }
+        }
+    }
+    for arrow in arrows {
+        let polygon = arrow_polygon(arrow, size as f64 / 8., perspective);
+        let (min_x, max_x) = polygon.iter().fold((f64::MAX, f64::MIN), |(lo, hi), &(x, _)| (lo.min(x), hi.max(x)));
+        let (min_y, max_y) = polygon.iter().fold((f64::MAX, f64::MIN), |(lo, hi), &(_, y)| (lo.min(y), hi.max(y)));
+        let Rgb(r, g, b) = arrow.color;
+        for y in (min_y.max(0.) as u32)..(max_y.ceil().min(size as f64) as u32) {
+            for x in (min_x.max(0.) as u32)..(max_x.ceil().min(size as f64) as u32) {
+                if point_in_polygon((x as f64 + 0.5, y as f64 + 0.5), &polygon) {
+                    let Rgba([pr, pg, pb, pa]) = *board_image.get_pixel(x, y);
+                    let blend = |c: u8, p: u8| (c as f64 * ARROW_OPACITY + p as f64 * (1. - ARROW_OPACITY)).round() as u8;
+                    board_image.put_pixel(x, y, Rgba([blend(r, pr), blend(g, pg), blend(b, pb), pa]));
+                }
             }
-        }),
-        _ => None,
+        }
+    }
+    let image = match coordinates {
+        Some(coordinates) => draw_coordinates(board_image, size, &coordinates, perspective)?,
+        None => board_image,
     };
-    let mut content = position.content.into_iter().enumerate().collect::<Vec<_>>();
-    let ranks: Vec<_> = if perspective.is_white() {
-        content.chunks(8).rev().enumerate().collect()
+    Ok(match evaluation_bar {
+        Some(evaluation_bar) => draw_evaluation_bar(image, size, &evaluation_bar, perspective),
+        None => image,
+    })
+}
+
+/// Draws coordinate labels on an image of a board, adding a border around the board if the labels are drawn outside it.
+fn draw_coordinates(board_image: RgbaImage, size: usize, coordinates: &Coordinates, perspective: Color) -> Result<RgbaImage, InvalidPositionImagePropertiesError> {
+    let border = border_size(size, coordinates);
+    let mut image = if border > 0 {
+        let Rgb(r, g, b) = coordinates.border_color;
+        let mut image = RgbaImage::from_pixel((size + 2 * border) as u32, (size + 2 * border) as u32, Rgba([r, g, b, 255]));
+        imageops::replace(&mut image, &board_image, border as i64, border as i64);
+        image
     } else {
-        content.reverse();
-        content.chunks(8).rev().enumerate().collect()
+        board_image
     };
-    let piece_size = size / 8;
-    let mut board_image = RgbaImage::new(size as u32, size as u32);
-    for (ranki, rank) in ranks {
-        for (sqi, (sq, occ)) in rank.iter().enumerate() {
-            let sq_color = if helpers::color_complex_of(*sq) { light_square_color } else { dark_square_color };
-            let sq_x = sqi * piece_size;
-            let sq_y = ranki * piece_size;
-            if let Some(piece) = occ {
-                let piece_str = format!("{}{}", piece.color(), char::from(piece.piece_type()));
-                let piece_image = match &piece_set_name {
-                    Some(piece_set) => {
-                        let piece_svg_path = PathBuf::from("pieces").join(piece_set).join(format!("{piece_str}.svg"));
-                        let piece_svg = nsvg::parse_str(
-                            ASSETS_DIR
-                                .get_file(piece_svg_path)
-                                .ok_or(InvalidPositionImagePropertiesError::InvalidBuiltinPieceSet(piece_set.clone()))?
-                                .contents_utf8()
-                                .unwrap(),
-                            nsvg::Units::Pixel,
-                            96.,
-                        )
-                        .unwrap();
-                        piece_svg.rasterize(piece_size as f32 / piece_svg.width()).unwrap()
-                    }
-                    None => {
-                        if let PieceSet::Custom(hm) = &piece_set {
-                            let piece_img = hm.get(&piece_str).ok_or(InvalidPositionImagePropertiesError::InvalidCustomPieceSet(piece_set.clone()))?;
-                            nsvg::image::RgbaImage::from_vec(
-                                piece_size as u32,
-                                piece_size as u32,
-                                imageops::resize(piece_img, piece_size as u32, piece_size as u32, imageops::FilterType::Nearest).to_vec(),
-                            )
-                            .unwrap()
-                        } else {
-                            panic!("the universe is malfunctioning");
-                        }
-                    }
-                };
-                for y in 0..piece_size {
-                    for x in 0..piece_size {
-                        let px = piece_image.get_pixel(x as u32, y as u32);
-                        let (put_x, put_y) = ((sq_x + x) as u32, (sq_y + y) as u32);
-                        if px.data[3] > 64 {
-                            board_image.put_pixel(put_x, put_y, Rgba::from(px.data));
-                        } else {
-                            board_image.put_pixel(put_x, put_y, Rgba([sq_color.0, sq_color.1, sq_color.2, 255]));
+    for (c, x, y, height) in coordinate_labels(size, coordinates, perspective) {
+        match &coordinates.font {
+            CoordinateFont::Builtin => {
+                let Rgb(r, g, b) = coordinates.color;
+                for (gx, gy, pixel_size) in builtin_glyph_pixels(c, x, y, height) {
+                    for py in gy.round() as u32..(gy + pixel_size).round() as u32 {
+                        for px in gx.round() as u32..(gx + pixel_size).round() as u32 {
+                            image.put_pixel(px, py, Rgba([r, g, b, 255]));
                         }
                     }
                 }
-            } else {
-                for y in 0..piece_size {
-                    for x in 0..piece_size {
-                        let (put_x, put_y) = ((sq_x + x) as u32, (sq_y + y) as u32);
-                        board_image.put_pixel(put_x, put_y, Rgba([sq_color.0, sq_color.1, sq_color.2, 255]));
-                    }
+            }
+            CoordinateFont::Custom(hm) => {
+                let glyph = hm.get(&c).ok_or(InvalidPositionImagePropertiesError::InvalidCustomFont(c))?;
+                let width = (height * glyph.width() as f64 / glyph.height() as f64).round() as u32;
+                let glyph = imageops::resize(glyph, width.max(1), (height.round() as u32).max(1), imageops::FilterType::Triangle);
+                imageops::overlay(&mut image, &glyph, x.round() as i64, y.round() as i64);
+            }
+        }
+    }
+    Ok(image)
+}
+
+/// Draws an evaluation bar to the left of an image of a board.
+fn draw_evaluation_bar(board_image: RgbaImage, size: usize, evaluation_bar: &EvaluationBar, perspective: Color) -> RgbaImage {
+    let bar_width = evaluation_bar_width(size);
+    let height = board_image.height();
+    let (white_top, white_height, label, label_color) = evaluation_bar_layout(evaluation_bar, bar_width as f64, height as f64, perspective);
+    let (Rgb(wr, wg, wb), Rgb(br, bg, bb)) = (evaluation_bar.white_color, evaluation_bar.black_color);
+    let mut image = RgbaImage::from_pixel(board_image.width() + bar_width as u32, height, Rgba([br, bg, bb, 255]));
+    for y in white_top.round() as u32..(white_top + white_height).round() as u32 {
+        for x in 0..bar_width as u32 {
+            image.put_pixel(x, y, Rgba([wr, wg, wb, 255]));
+        }
+    }
+    let Rgb(r, g, b) = label_color;
+    for (c, x, y, char_height) in label {
+        for (gx, gy, pixel_size) in builtin_glyph_pixels(c, x, y, char_height) {
+            for py in gy.round() as u32..(gy + pixel_size).round() as u32 {
+                for px in gx.round() as u32..(gx + pixel_size).round() as u32 {
+                    image.put_pixel(px, py, Rgba([r, g, b, 255]));
+                }
+            }
+        }
+    }
+    imageops::replace(&mut image, &board_image, bar_width as i64, 0);
+    image
+}
+
+/// The built-in bitmap font for coordinate labels, material differences, and evaluations, with each character represented by seven rows of five pixels.
+const BUILTIN_GLYPHS: [(char, [u8; 7]); 21] = [
+    ('a', [0b00000, 0b00000, 0b01110, 0b00001, 0b01111, 0b10001, 0b01111]),
+    ('b', [0b10000, 0b10000, 0b11110, 0b10001, 0b10001, 0b10001, 0b11110]),
+    ('c', [0b00000, 0b00000, 0b01110, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('d', [0b00001, 0b00001, 0b01111, 0b10001, 0b10001, 0b10001, 0b01111]),
+    ('e', [0b00000, 0b00000, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110]),
+    ('f', [0b00110, 0b01001, 0b01000, 0b11100, 0b01000, 0b01000, 0b01000]),
+    ('g', [0b00000, 0b01111, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110]),
+    ('h', [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+];
+
+/// Returns the width of the border around the board (zero if the coordinates are drawn inside the board).
+fn border_size(size: usize, coordinates: &Coordinates) -> usize {
+    match coordinates.placement {
+        CoordinatePlacement::Inside => 0,
+        CoordinatePlacement::Outside => (size / 16).max(1),
+    }
+}
+
+/// Characters to draw, each in the format (_character_, _x_, _y_, _height_), where (_x_, _y_) is the top-left corner of the character.
+type Label = Vec<(char, f64, f64, f64)>;
+
+/// Returns the width of an evaluation bar next to a board of the given size.
+fn evaluation_bar_width(size: usize) -> usize {
+    (size / 20).max(1)
+}
+
+/// Returns the width and height of the image of a board drawn with the given properties, including its border and evaluation bar
+/// (but not its material trays).
+fn board_image_dimensions(props: &PositionImageProperties) -> (usize, usize) {
+    let size = props.size + props.coordinates.as_ref().map_or(0, |coordinates| 2 * border_size(props.size, coordinates));
+    (size + props.evaluation_bar.map_or(0, |_| evaluation_bar_width(props.size)), size)
+}
+
+/// Returns the layout of an evaluation bar of the given width and height: the top and height of white's part of the bar,
+/// the characters of the label, and the color of the label.
+fn evaluation_bar_layout(evaluation_bar: &EvaluationBar, bar_width: f64, height: f64, perspective: Color) -> (f64, f64, Label, Rgb) {
+    let share = evaluation_bar.evaluation.white_share();
+    let white_height = height * share;
+    let white_top = if perspective.is_white() { height - white_height } else { 0. };
+    let label = evaluation_bar.evaluation.label();
+    let advance = 6. / 7.;
+    let char_height = (bar_width * 0.3).min(bar_width * 0.9 / (label.len() as f64 * advance));
+    let padding = bar_width * 0.2;
+    let white_better = share >= 0.5;
+    let at_bottom = white_better == perspective.is_white();
+    let y = if at_bottom { height - padding - char_height } else { padding };
+    let mut x = (bar_width - (label.len() as f64 * advance - 1. / 7.) * char_height) / 2.;
+    let mut chars = Vec::new();
+    for c in label.chars() {
+        chars.push((c, x, y, char_height));
+        x += char_height * advance;
+    }
+    let label_color = if white_better { evaluation_bar.black_color } else { evaluation_bar.white_color };
+    (white_top, white_height, chars, label_color)
+}
+
+/// Returns the coordinate labels to draw, each in the format (_character_, _x_, _y_, _height_), where (_x_, _y_) is the top-left corner of the label
+/// in the image. Labels are five-sevenths as wide as they are tall.
+fn coordinate_labels(size: usize, coordinates: &Coordinates, perspective: Color) -> Vec<(char, f64, f64, f64)> {
+    let sq_size = size as f64 / 8.;
+    let border = border_size(size, coordinates) as f64;
+    let (files, ranks): (Vec<_>, Vec<_>) = if perspective.is_white() {
+        (('a'..='h').collect(), ('1'..='8').rev().collect())
+    } else {
+        (('a'..='h').rev().collect(), ('1'..='8').collect())
+    };
+    let mut labels = Vec::new();
+    match coordinates.placement {
+        CoordinatePlacement::Inside => {
+            let (height, padding) = (sq_size * 0.22, sq_size * 0.06);
+            for (col, file) in files.into_iter().enumerate() {
+                labels.push((file, (col + 1) as f64 * sq_size - padding - height * 5. / 7., size as f64 - padding - height, height));
+            }
+            for (row, rank) in ranks.into_iter().enumerate() {
+                labels.push((rank, padding, row as f64 * sq_size + padding, height));
+            }
+        }
+        CoordinatePlacement::Outside => {
+            let height = border * 0.6;
+            let width = height * 5. / 7.;
+            for (col, file) in files.into_iter().enumerate() {
+                labels.push((file, border + (col as f64 + 0.5) * sq_size - width / 2., border + size as f64 + (border - height) / 2., height));
+            }
+            for (row, rank) in ranks.into_iter().enumerate() {
+                labels.push((rank, (border - width) / 2., border + (row as f64 + 0.5) * sq_size - height / 2., height));
+            }
+        }
+    }
+    labels
+}
+
+/// Returns the pixels of a character in the built-in font drawn at the given position and height, each in the format (_x_, _y_, _size_).
+fn builtin_glyph_pixels(c: char, x: f64, y: f64, height: f64) -> Vec<(f64, f64, f64)> {
+    let rows = BUILTIN_GLYPHS.iter().find(|(glyph, _)| *glyph == c).unwrap().1;
+    let pixel_size = height / 7.;
+    let mut pixels = Vec::new();
+    for (row, bits) in rows.into_iter().enumerate() {
+        for col in 0..5 {
+            if bits & (0b10000 >> col) != 0 {
+                pixels.push((x + col as f64 * pixel_size, y + row as f64 * pixel_size, pixel_size));
+            }
+        }
+    }
+    pixels
+}
+
+/// The opacity with which arrows are drawn.
+const ARROW_OPACITY: f64 = 0.8;
+
+/// The opacity with which highlight colors are blended with square colors.
+const HIGHLIGHT_OPACITY: f64 = 0.5;
+
+/// The color with which [`board_to_image`] highlights the squares of the last move.
+const LAST_MOVE_COLOR: Rgb = Rgb(155, 199, 0);
+
+/// The color with which [`board_to_image`] highlights a king in check.
+const CHECK_COLOR: Rgb = Rgb(231, 0, 0);
+
+/// The colors with which [`diff_image`] highlights pieces which appeared, disappeared, and moved.
+const DIFF_ADDED_COLOR: Rgb = Rgb(0, 200, 0);
+const DIFF_REMOVED_COLOR: Rgb = Rgb(220, 0, 0);
+const DIFF_MOVED_COLOR: Rgb = Rgb(0, 100, 255);
+
+/// The distances from the center of a square (relative to the distance from the center to a corner)
+/// at which a radial highlight starts to fade out and becomes fully transparent.
+const RADIAL_FADE_START: f64 = 0.25;
+const RADIAL_FADE_END: f64 = 0.9;
+
+/// Returns the opacity of a radial highlight at the given relative distance from the center of the square.
+fn radial_opacity(distance: f64) -> f64 {
+    ((RADIAL_FADE_END - distance) / (RADIAL_FADE_END - RADIAL_FADE_START)).clamp(0., 1.)
+}
+
+/// Returns an error if an arrow or highlighted square refers to an invalid square index.
+fn validate_overlays(props: &PositionImageProperties) -> Result<(), InvalidPositionImagePropertiesError> {
+    let squares = props
+        .arrows
+        .iter()
+        .flat_map(|a| [a.from, a.to])
+        .chain(props.highlighted_squares.iter().chain(&props.radial_highlights).map(|h| h.square));
+    for sq in squares {
+        if sq >= 64 {
+            return Err(InvalidPositionImagePropertiesError::InvalidSquare(sq));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the color of a square, blended with the color of its last highlight, if any.
+fn square_color(sq: usize, light_square_color: Rgb, dark_square_color: Rgb, highlighted_squares: &[HighlightedSquare]) -> Rgb {
+    highlighted_color(sq, if helpers::color_complex_of(sq) { light_square_color } else { dark_square_color }, highlighted_squares)
+}
+
+/// Returns the given color of (a pixel of) a square, blended with the color of the square's last highlight, if any.
+fn highlighted_color(sq: usize, Rgb(r, g, b): Rgb, highlighted_squares: &[HighlightedSquare]) -> Rgb {
+    match highlighted_squares.iter().rfind(|h| h.square == sq) {
+        Some(HighlightedSquare { color: Rgb(hr, hg, hb), .. }) => {
+            let blend = |c: u8, h: u8| (h as f64 * HIGHLIGHT_OPACITY + c as f64 * (1. - HIGHLIGHT_OPACITY)).round() as u8;
+            Rgb(blend(r, *hr), blend(g, *hg), blend(b, *hb))
+        }
+        None => Rgb(r, g, b),
+    }
+}
+
+/// Returns the coordinates of the center of a square in an image, given the size of a square.
+fn square_center(sq: usize, sq_size: f64, perspective: Color) -> (f64, f64) {
+    let (file, rank) = (sq % 8, sq / 8);
+    let (col, row) = if perspective.is_white() { (file, 7 - rank) } else { (7 - file, rank) };
+    ((col as f64 + 0.5) * sq_size, (row as f64 + 0.5) * sq_size)
+}
+
+/// Returns the vertices of the polygon depicting an arrow, given the size of a square.
+fn arrow_polygon(arrow: Arrow, sq_size: f64, perspective: Color) -> [(f64, f64); 7] {
+    let (x1, y1) = square_center(arrow.from, sq_size, perspective);
+    let (x2, y2) = square_center(arrow.to, sq_size, perspective);
+    let len = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt().max(f64::EPSILON);
+    let (ux, uy) = ((x2 - x1) / len, (y2 - y1) / len);
+    let (nx, ny) = (-uy, ux);
+    let (shaft, head_width, head_len) = (sq_size * 0.15, sq_size * 0.45, (sq_size * 0.45).min(len));
+    let point = |along: f64, across: f64| (x1 + ux * along + nx * across, y1 + uy * along + ny * across);
+    [
+        point(0., shaft / 2.),
+        point(len - head_len, shaft / 2.),
+        point(len - head_len, head_width / 2.),
+        point(len, 0.),
+        point(len - head_len, -head_width / 2.),
+        point(len - head_len, -shaft / 2.),
+        point(0., -shaft / 2.),
+    ]
+}
+
+/// Checks whether a point is inside a polygon, using the even-odd rule.
+fn point_in_polygon((x, y): (f64, f64), polygon: &[(f64, f64)]) -> bool {
+    let mut inside = false;
+    for i in 0..polygon.len() {
+        let ((xi, yi), (xj, yj)) = (polygon[i], polygon[(i + polygon.len() - 1) % polygon.len()]);
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Creates a standalone SVG document depicting the current position of a `Board`, highlighted like in [`board_to_image`].
+pub fn board_to_svg(board: &Board, props: PositionImageProperties, perspective: Color) -> Result<String, InvalidPositionImagePropertiesError> {
+    let (piece_set, material_tray, size) = (props.piece_set.clone(), props.material_tray, props.size);
+    let (width, board_height) = board_image_dimensions(&props);
+    let board_svg = position_to_svg(board.position(), with_board_highlights(board, props), perspective)?;
+    let Some(material_tray) = material_tray else {
+        return Ok(board_svg);
+    };
+    let (tray_height, items) = material_tray_items(board, size, board_height as f64, perspective);
+    let height = board_height as f64 + 2. * tray_height;
+    let Rgb(r, g, b) = material_tray.background;
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}"><rect width="{width}" height="{height}" fill="#{r:02x}{g:02x}{b:02x}"/>{}"##,
+        board_svg.replacen(r#"<svg xmlns="http://www.w3.org/2000/svg" "#, &format!(r#"<svg y="{tray_height}" "#), 1)
+    );
+    for (item, x, y, item_height) in items {
+        match item {
+            TrayItem::Piece(piece) => svg.push_str(&format!(
+                r#"<image x="{x:.2}" y="{y:.2}" width="{item_height:.2}" height="{item_height:.2}" href="{}"/>"#,
+                piece_data_uri(&piece_set, &piece_str(piece))?
+            )),
+            TrayItem::Character(c) => {
+                let Rgb(r, g, b) = material_tray.text_color;
+                let path: String = builtin_glyph_pixels(c, x, y, item_height)
+                    .into_iter()
+                    .map(|(px, py, pixel_size)| format!("M{px:.2} {py:.2}h{pixel_size:.2}v{pixel_size:.2}h-{pixel_size:.2}z"))
+                    .collect();
+                svg.push_str(&format!(r##"<path d="{path}" fill="#{r:02x}{g:02x}{b:02x}"/>"##));
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+/// Creates a standalone SVG document depicting a `Position`, from the perspective of the side `perspective`.
+/// The `size` property determines the dimensions of the document; pieces from custom piece sets are embedded as PNG images.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(?perspective)))]
+pub fn position_to_svg(position: &Position, props: PositionImageProperties, perspective: Color) -> Result<String, InvalidPositionImagePropertiesError> {
+    let start = helpers::now();
+    validate_overlays(&props)?;
+    let PositionImageProperties {
+        light_square_color,
+        dark_square_color,
+        piece_set,
+        size,
+        arrows,
+        highlighted_squares,
+        radial_highlights,
+        coordinates,
+        evaluation_bar,
+        material_tray: _,
+        square_textures,
+    } = props;
+    if size < 8 {
+        return Err(InvalidPositionImagePropertiesError::InvalidSize(size));
+    }
+    let sq_size = size as f64 / 8.;
+    let mut defs = HashMap::new();
+    if let Some(textures) = &square_textures {
+        for (id, texture) in [("texture-light", &textures.light), ("texture-dark", &textures.dark)] {
+            defs.insert(
+                id.to_owned(),
+                format!(
+                    r#"<pattern id="{id}" patternUnits="userSpaceOnUse" width="{size}" height="{size}"><image width="{size}" height="{size}" preserveAspectRatio="none" href="{}"/></pattern>"#,
+                    png_data_uri(texture)
+                ),
+            );
+        }
+    }
+    let mut squares = String::new();
+    let mut pieces = String::new();
+    for sq in 0..64 {
+        let (file, rank) = (sq % 8, sq / 8);
+        let (col, row) = if perspective.is_white() { (file, 7 - rank) } else { (7 - file, rank) };
+        let (x, y) = (col as f64 * sq_size, row as f64 * sq_size);
+        if square_textures.is_some() {
+            let id = if helpers::color_complex_of(sq) { "texture-light" } else { "texture-dark" };
+            squares.push_str(&format!(r##"<rect x="{x}" y="{y}" width="{sq_size}" height="{sq_size}" fill="url(#{id})"/>"##));
+            if let Some(HighlightedSquare { color: Rgb(r, g, b), .. }) = highlighted_squares.iter().rfind(|h| h.square == sq) {
+                squares.push_str(&format!(
+                    r##"<rect x="{x}" y="{y}" width="{sq_size}" height="{sq_size}" fill="#{r:02x}{g:02x}{b:02x}" fill-opacity="{HIGHLIGHT_OPACITY}"/>"##
+                ));
+            }
+        } else {
+            let Rgb(r, g, b) = square_color(sq, light_square_color, dark_square_color, &highlighted_squares);
+            squares.push_str(&format!(r##"<rect x="{x}" y="{y}" width="{sq_size}" height="{sq_size}" fill="#{r:02x}{g:02x}{b:02x}"/>"##));
+        }
+        if let Some(HighlightedSquare { color: Rgb(r, g, b), .. }) = radial_highlights.iter().rfind(|h| h.square == sq) {
+            let id = format!("radial-{r:02x}{g:02x}{b:02x}");
+            defs.entry(id.clone()).or_insert_with(|| {
+                format!(
+                    r##"<radialGradient id="{id}" r="{:.4}"><stop offset="{:.4}" stop-color="#{r:02x}{g:02x}{b:02x}"/><stop offset="1" stop-color="#{r:02x}{g:02x}{b:02x}" stop-opacity="0"/></radialGradient>"##,
+                    RADIAL_FADE_END * std::f64::consts::FRAC_1_SQRT_2,
+                    RADIAL_FADE_START / RADIAL_FADE_END
+                )
+            });
+            squares.push_str(&format!(r##"<rect x="{x}" y="{y}" width="{sq_size}" height="{sq_size}" fill="url(#{id})"/>"##));
+        }
+        if let Some(piece) = position.content[sq] {
+            let piece_str = piece_str(piece);
+            if !defs.contains_key(&piece_str) {
+                let data_uri = piece_data_uri(&piece_set, &piece_str)?;
+                defs.insert(piece_str.clone(), format!(r#"<image id="{piece_str}" width="{sq_size}" height="{sq_size}" href="{data_uri}"/>"#));
+            }
+            pieces.push_str(&format!(r##"<use href="#{piece_str}" x="{x}" y="{y}"/>"##));
+        }
+    }
+    let mut defs: Vec<_> = defs.into_iter().collect();
+    defs.sort();
+    let defs: String = defs.into_iter().map(|(_, def)| def).collect();
+    for arrow in arrows {
+        let points: Vec<_> = arrow_polygon(arrow, sq_size, perspective).iter().map(|(x, y)| format!("{x:.2},{y:.2}")).collect();
+        let Rgb(r, g, b) = arrow.color;
+        pieces.push_str(&format!(r##"<polygon points="{}" fill="#{r:02x}{g:02x}{b:02x}" fill-opacity="{ARROW_OPACITY}"/>"##, points.join(" ")));
+    }
+    let mut board = format!("{squares}{pieces}");
+    let mut full_size = size;
+    if let Some(coordinates) = coordinates {
+        let border = border_size(size, &coordinates);
+        if border > 0 {
+            full_size += 2 * border;
+            let Rgb(r, g, b) = coordinates.border_color;
+            board = format!(r##"<rect width="{full_size}" height="{full_size}" fill="#{r:02x}{g:02x}{b:02x}"/><g transform="translate({border} {border})">{board}</g>"##);
+        }
+        for (c, x, y, height) in coordinate_labels(size, &coordinates, perspective) {
+            match &coordinates.font {
+                CoordinateFont::Builtin => {
+                    let Rgb(r, g, b) = coordinates.color;
+                    let path: String = builtin_glyph_pixels(c, x, y, height)
+                        .into_iter()
+                        .map(|(px, py, pixel_size)| format!("M{px:.2} {py:.2}h{pixel_size:.2}v{pixel_size:.2}h-{pixel_size:.2}z"))
+                        .collect();
+                    board.push_str(&format!(r##"<path d="{path}" fill="#{r:02x}{g:02x}{b:02x}"/>"##));
+                }
+                CoordinateFont::Custom(hm) => {
+                    let glyph = hm.get(&c).ok_or(InvalidPositionImagePropertiesError::InvalidCustomFont(c))?;
+                    let width = height * glyph.width() as f64 / glyph.height() as f64;
+                    board.push_str(&format!(r#"<image x="{x:.2}" y="{y:.2}" width="{width:.2}" height="{height:.2}" href="{}"/>"#, png_data_uri(glyph)));
                 }
             }
         }
     }
-    Ok(board_image)
+    let mut full_width = full_size;
+    if let Some(evaluation_bar) = evaluation_bar {
+        let bar_width = evaluation_bar_width(size);
+        full_width += bar_width;
+        let (white_top, white_height, label, Rgb(lr, lg, lb)) = evaluation_bar_layout(&evaluation_bar, bar_width as f64, full_size as f64, perspective);
+        let (Rgb(wr, wg, wb), Rgb(br, bg, bb)) = (evaluation_bar.white_color, evaluation_bar.black_color);
+        let path: String = label
+            .into_iter()
+            .flat_map(|(c, x, y, height)| builtin_glyph_pixels(c, x, y, height))
+            .map(|(px, py, pixel_size)| format!("M{px:.2} {py:.2}h{pixel_size:.2}v{pixel_size:.2}h-{pixel_size:.2}z"))
+            .collect();
+        board = format!(
+            r##"<rect width="{bar_width}" height="{full_size}" fill="#{br:02x}{bg:02x}{bb:02x}"/><rect y="{white_top:.2}" width="{bar_width}" height="{white_height:.2}" fill="#{wr:02x}{wg:02x}{wb:02x}"/><path d="{path}" fill="#{lr:02x}{lg:02x}{lb:02x}"/><g transform="translate({bar_width} 0)">{board}</g>"##
+        );
+    }
+    metrics::record(|m| m.image_rendered(helpers::elapsed_since(start)));
+    Ok(format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{full_width}" height="{full_size}" viewBox="0 0 {full_width} {full_size}"><defs>{defs}</defs>{board}</svg>"#
+    ))
+}
+
+/// Returns the string identifying a piece in a piece set (such as "wK" for the white king).
+fn piece_str(piece: Piece) -> String {
+    format!("{}{}", piece.color(), char::from(piece.piece_type()))
+}
+
+/// Renders the given piece of a piece set as a square image with the given width and height.
+fn rasterize_piece(piece_set: &PieceSet, piece_str: &str, size: u32) -> Result<RgbaImage, InvalidPositionImagePropertiesError> {
+    match piece_set {
+        PieceSet::Builtin(name) => Ok(rasterize_svg(builtin_piece_svg(&builtin_piece_set_dir(name), piece_str)?, size)),
+        PieceSet::Custom(hm) => {
+            let piece_img = hm.get(piece_str).ok_or(InvalidPositionImagePropertiesError::InvalidCustomPieceSet(piece_set.clone()))?;
+            Ok(imageops::resize(piece_img, size, size, imageops::FilterType::Nearest))
+        }
+        PieceSet::Directory(dir) => {
+            let key = (piece_str.to_owned(), size);
+            if let Some(image) = dir.cache.lock().unwrap().get(&key) {
+                return Ok(image.clone());
+            }
+            let image = match dir.sources.get(piece_str).ok_or(InvalidPositionImagePropertiesError::InvalidCustomPieceSet(piece_set.clone()))? {
+                PieceSource::Svg(svg) => rasterize_svg(svg, size),
+                PieceSource::Png(png) => imageops::resize(png, size, size, imageops::FilterType::Triangle),
+            };
+            dir.cache.lock().unwrap().insert(key, image.clone());
+            Ok(image)
+        }
+    }
+}
+
+/// Renders SVG source as a square image with the given width and height.
+fn rasterize_svg(svg: &str, size: u32) -> RgbaImage {
+    let svg = nsvg::parse_str(svg, nsvg::Units::Pixel, 96.).unwrap();
+    let svg_image = svg.rasterize(size as f32 / svg.width()).unwrap();
+    let (width, height) = (svg_image.width(), svg_image.height());
+    let image = RgbaImage::from_raw(width, height, svg_image.into_raw()).unwrap();
+    if (width, height) != (size, size) {
+        imageops::resize(&image, size, size, imageops::FilterType::Triangle)
+    } else {
+        image
+    }
+}
+
+/// Returns a data URI containing the image of the given piece of a piece set, for embedding in SVG documents.
+fn piece_data_uri(piece_set: &PieceSet, piece_str: &str) -> Result<String, InvalidPositionImagePropertiesError> {
+    Ok(match piece_set {
+        PieceSet::Builtin(name) => format!("data:image/svg+xml;base64,{}", base64(builtin_piece_svg(&builtin_piece_set_dir(name), piece_str)?.as_bytes())),
+        PieceSet::Custom(hm) => {
+            let piece_img = hm.get(piece_str).ok_or(InvalidPositionImagePropertiesError::InvalidCustomPieceSet(piece_set.clone()))?;
+            png_data_uri(piece_img)
+        }
+        PieceSet::Directory(dir) => match dir.sources.get(piece_str).ok_or(InvalidPositionImagePropertiesError::InvalidCustomPieceSet(piece_set.clone()))? {
+            PieceSource::Svg(svg) => format!("data:image/svg+xml;base64,{}", base64(svg.as_bytes())),
+            PieceSource::Png(png) => png_data_uri(png),
+        },
+    })
+}
+
+/// Returns a data URI containing an image encoded as PNG.
+fn png_data_uri(image: &RgbaImage) -> String {
+    let mut png = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut png, image::ImageFormat::Png).unwrap();
+    format!("data:image/png;base64,{}", base64(png.get_ref()))
+}
+
+/// Returns the name of the asset directory of the built-in piece set with the given name.
+fn builtin_piece_set_dir(name: &str) -> String {
+    let name = name.trim().to_lowercase().replace(' ', "-");
+    match name.as_str() {
+        "default" | "normal" => "cburnett".to_owned(),
+        _ => name,
+    }
+}
+
+/// Returns the SVG source of a piece ("wK", "wN", "bP", etc.) in a built-in piece set, returning an error if the piece set does not exist.
+fn builtin_piece_svg(piece_set: &str, piece_str: &str) -> Result<&'static str, InvalidPositionImagePropertiesError> {
+    let piece_svg_path = PathBuf::from("pieces").join(piece_set).join(format!("{piece_str}.svg"));
+    Ok(ASSETS_DIR
+        .get_file(piece_svg_path)
+        .ok_or(InvalidPositionImagePropertiesError::InvalidBuiltinPieceSet(piece_set.to_owned()))?
+        .contents_utf8()
+        .unwrap())
+}
+
+/// Encodes bytes as standard base64 with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
 }
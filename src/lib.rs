@@ -2,44 +2,125 @@
 //!
 //! Examples are available on the [GitHub repository page](https://github.com/Python3-8/rschess).
 
+mod antichess;
+mod attacks;
 mod board;
+mod builder;
+mod cancel;
+mod castling;
+#[cfg(feature = "chesscom")]
+pub mod chesscom;
+mod endgame;
+mod epd;
 pub mod errors;
+pub mod eval;
+mod event;
+#[cfg(feature = "pgn")]
+pub mod explorer;
 mod fen;
 mod game_result;
 mod helpers;
+mod hint;
+mod horde;
 #[cfg(feature = "img")]
 pub mod img;
+#[cfg(feature = "lichess")]
+pub mod lichess;
+pub mod match_play;
+pub mod metrics;
 mod move_;
+mod move_list;
+mod pattern;
+mod pawn_structure;
 #[cfg(feature = "pgn")]
 pub mod pgn;
+mod phase;
 mod piece;
 mod position;
+mod problem;
+mod progress;
+mod racing_kings;
+pub mod rating;
+mod retro;
+mod rules;
+pub mod scoresheet;
+#[cfg(feature = "search")]
+pub mod search;
+mod session;
+mod square;
+mod square_mapping;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+mod tables;
+mod takeback;
+pub mod uci;
+mod variant;
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+pub use antichess::{Antichess, AntichessRules};
+pub use attacks::AttackMap;
 pub use board::*;
+pub use builder::*;
+pub use cancel::*;
+pub use castling::CastlingRights;
+pub use endgame::EndgameClass;
+pub use epd::{Epd, EpdOperation, EPD_MOVE_OPCODES};
 pub(crate) use errors::*;
+pub use event::BoardEvent;
 pub use fen::Fen;
 pub use game_result::*;
+pub use hint::Hint;
+pub use horde::{Horde, HordeRules};
 pub use move_::*;
+pub use move_list::MoveList;
+pub use pattern::*;
+pub use pawn_structure::PawnStructure;
+pub use phase::GamePhase;
 pub use piece::*;
 pub use position::*;
+pub use problem::MateKind;
+pub use progress::*;
+pub use racing_kings::{RacingKings, RacingKingsRules};
+pub use rules::*;
+pub use session::*;
+pub use square::{File, Rank};
+pub use square_mapping::SquareMapping;
 use std::{fmt, ops::Not};
+pub use takeback::*;
+pub use variant::*;
 
 /// Converts a square index (`0..64`) to a square name, returning an error if the square index is invalid.
 pub fn idx_to_sq(idx: usize) -> Result<(char, char), InvalidSquareIndexError> {
-    if !(0..64).contains(&idx) {
-        return Err(InvalidSquareIndexError(idx));
-    }
-    Ok(helpers::idx_to_sq(idx))
+    let (file, rank) = idx_to_file_rank(idx)?;
+    Ok((file.into(), rank.into()))
 }
 
 /// Converts a square name to a square index, returning an error if the square name is invalid.
 pub fn sq_to_idx(file: char, rank: char) -> Result<usize, InvalidSquareNameError> {
-    if !(('a'..'h').contains(&file) && ('1'..'8').contains(&rank)) {
-        return Err(InvalidSquareNameError(file, rank));
+    match (File::try_from(file), Rank::try_from(rank)) {
+        (Ok(f), Ok(r)) => Ok(file_rank_to_idx(f, r)),
+        _ => Err(InvalidSquareNameError(file, rank)),
+    }
+}
+
+/// Converts a square index (`0..64`) to its file and rank, returning an error if the square index is invalid.
+pub fn idx_to_file_rank(idx: usize) -> Result<(File, Rank), InvalidSquareIndexError> {
+    if idx >= 64 {
+        return Err(InvalidSquareIndexError(idx));
     }
-    Ok(helpers::sq_to_idx(file, rank))
+    Ok((File::of(idx), Rank::of(idx)))
 }
 
+/// Converts a file and a rank to the index (`0..64`) of the square on them.
+pub fn file_rank_to_idx(file: File, rank: Rank) -> usize {
+    rank.index() * 8 + file.index()
+}
+
+/// A set of squares, in which bit `i` stands for square `i` (a1 is bit 0, and h8 is bit 63).
+pub type Bitboard = u64;
+
 /// Represents a side/color.
 #[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
 pub enum Color {
@@ -48,6 +129,9 @@ pub enum Color {
 }
 
 impl Color {
+    /// Both colors, white first.
+    pub const ALL: [Color; 2] = [Self::White, Self::Black];
+
     /// Checks if the color is white.
     pub fn is_white(&self) -> bool {
         matches!(self, Self::White)
@@ -57,6 +141,47 @@ impl Color {
     pub fn is_black(&self) -> bool {
         matches!(self, Self::Black)
     }
+
+    /// Returns 1 for white and -1 for black, for scores and directions that are relative to a side.
+    pub fn sign(&self) -> i32 {
+        match self {
+            Self::White => 1,
+            Self::Black => -1,
+        }
+    }
+
+    /// Returns the rank on which the pieces of this color start ('1' for white, and '8' for black).
+    pub fn backrank(&self) -> char {
+        match self {
+            Self::White => '1',
+            Self::Black => '8',
+        }
+    }
+
+    /// Returns the rank on which the pawns of this color start ('2' for white, and '7' for black).
+    pub fn pawn_rank(&self) -> char {
+        match self {
+            Self::White => '2',
+            Self::Black => '7',
+        }
+    }
+
+    /// Returns the difference in square index made by a single pawn push of this color (8 for white, and -8 for black).
+    pub fn pawn_direction(&self) -> isize {
+        self.sign() as isize * 8
+    }
+}
+
+#[cfg(feature = "rand")]
+impl rand::distr::Distribution<Color> for rand::distr::StandardUniform {
+    /// Picks white or black with equal probability.
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Color {
+        if rng.random() {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
 }
 
 impl TryFrom<&str> for Color {
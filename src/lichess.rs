@@ -0,0 +1,206 @@
+//! Import games exported by the [Lichess API](https://lichess.org/api#tag/Games) as NDJSON (a JSON game object on each line),
+//! including the clock times of their moves and their computer analysis.
+
+use super::{pgn::Pgn, Board, Color, Fen, LichessImportError, Move, TimeControl};
+use serde_json::Value;
+use std::{
+    io::{BufRead, Lines},
+    time::Duration,
+};
+
+/// Represents a computer evaluation from the analysis of a Lichess game, from white's point of view.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum LichessEval {
+    /// An advantage in centipawns (positive if white is better)
+    Centipawns(i32),
+    /// A forced mate in the given number of moves (positive if white mates)
+    Mate(i32),
+}
+
+/// Represents the analysis of a move of a Lichess game, from the `analysis` field of its game object.
+#[derive(Eq, PartialEq, Hash, Clone, Debug, Default)]
+pub struct MoveAnalysis {
+    /// The evaluation of the position after the move
+    pub eval: Option<LichessEval>,
+    /// The best move in the position before the move, given if the move was judged to be a mistake
+    pub best: Option<Move>,
+    /// The line of SAN moves starting with the best move
+    pub variation: Option<String>,
+    /// The judgment of the move (`Inaccuracy`, `Mistake`, or `Blunder`)
+    pub judgment: Option<String>,
+}
+
+/// Represents a game imported from a Lichess game object.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct LichessGame {
+    /// The ID of the game on Lichess
+    pub id: String,
+    /// The game, with tag pairs like those of Lichess PGN exports and the clock times of its moves (see [`Board::move_clocks`])
+    pub pgn: Pgn,
+    /// The analysis of the moves in order, up to the last move analyzed (Lichess does not analyze a move which ends the game),
+    /// or nothing if the game has not been analyzed
+    pub analysis: Vec<MoveAnalysis>,
+}
+
+impl LichessGame {
+    /// Parses a Lichess game object, as found on each line of an NDJSON export. Standard games, Chess960 games,
+    /// and games from custom positions are supported, and other variants return an error.
+    ///
+    /// The clock times of the `clocks` field (in centiseconds) are recorded after their moves, and the result of the game
+    /// is taken from its `status` and `winner` fields if it did not end on the board.
+    pub fn from_json(json: &str) -> Result<Self, LichessImportError> {
+        let game: Value = serde_json::from_str(json)?;
+        let id = game["id"].as_str().ok_or(LichessImportError::Field("id"))?.to_owned();
+        let variant = game["variant"].as_str().unwrap_or("standard");
+        if !matches!(variant, "standard" | "chess960" | "fromPosition") {
+            return Err(LichessImportError::UnsupportedVariant(variant.to_owned()));
+        }
+        let initial_fen = game["initialFen"].as_str().map(Fen::try_from).transpose()?;
+        let mut board = initial_fen.clone().map_or_else(Board::default, Board::from_fen);
+        let clock = &game["clock"];
+        if let (Some(initial), Some(increment)) = (clock["initial"].as_u64(), clock["increment"].as_u64()) {
+            board.set_time_control(Some(TimeControl::from_secs(initial, increment)));
+        }
+        let clocks = game["clocks"].as_array().map_or(&[][..], Vec::as_slice);
+        let analyzed = game["analysis"].as_array().map_or(&[][..], Vec::as_slice);
+        let mut analysis = Vec::new();
+        for (ply, san) in game["moves"].as_str().unwrap_or_default().split_whitespace().enumerate() {
+            if let Some(entry) = analyzed.get(ply) {
+                analysis.push(MoveAnalysis {
+                    eval: (entry["eval"].as_i64().map(|cp| LichessEval::Centipawns(cp as i32))).or_else(|| entry["mate"].as_i64().map(|n| LichessEval::Mate(n as i32))),
+                    best: entry["best"].as_str().and_then(|uci| board.gen_legal_moves().into_iter().find(|m| m.to_uci() == uci)),
+                    variation: entry["variation"].as_str().map(str::to_owned),
+                    judgment: entry["judgment"]["name"].as_str().map(str::to_owned),
+                });
+            }
+            board.make_move_san(san).map_err(|e| LichessImportError::InvalidMove(ply, e))?;
+            if let Some(centis) = clocks.get(ply).and_then(Value::as_u64) {
+                board.record_clock(Duration::from_millis(centis * 10)).unwrap();
+            }
+        }
+        let status = game["status"].as_str().unwrap_or_default();
+        let winner = match game["winner"].as_str() {
+            Some("white") => Some(Color::White),
+            Some("black") => Some(Color::Black),
+            _ => None,
+        };
+        if board.is_ongoing() {
+            match (status, winner) {
+                ("outoftime", Some(winner)) => board.flag(!winner),
+                ("outoftime", None) => board.flag(board.side_to_move()),
+                (_, Some(winner)) => board.resign(!winner),
+                ("draw" | "stalemate", None) => board.agree_draw(),
+                _ => Ok(()),
+            }
+            .unwrap();
+        }
+        let tag_pairs = tag_pairs(&game, &id, variant, status, initial_fen);
+        Ok(Self {
+            id,
+            pgn: Pgn::from_board(board, tag_pairs)?,
+            analysis,
+        })
+    }
+}
+
+/// Generates the tag pairs of a Lichess game object, like those of the PGN exports of Lichess.
+fn tag_pairs(game: &Value, id: &str, variant: &str, status: &str, initial_fen: Option<Fen>) -> Vec<(String, String)> {
+    let mut speed = game["speed"].as_str().unwrap_or_default().chars();
+    let speed = match speed.next() {
+        Some(first) => first.to_uppercase().chain(speed).collect(),
+        None => "Unknown".to_owned(),
+    };
+    let event = format!("{} {speed} game", if game["rated"].as_bool().unwrap_or_default() { "Rated" } else { "Casual" });
+    let (date, time) = game["createdAt"].as_u64().map_or(("????.??.??".to_owned(), "??:??:??".to_owned()), utc_date_time);
+    let mut tag_pairs = vec![
+        ("Event".to_owned(), event),
+        ("Site".to_owned(), format!("https://lichess.org/{id}")),
+        ("Date".to_owned(), date.clone()),
+        ("Round".to_owned(), "-".to_owned()),
+        ("UTCDate".to_owned(), date),
+        ("UTCTime".to_owned(), time),
+    ];
+    for (color, side) in [("white", "White"), ("black", "Black")] {
+        let player = &game["players"][color];
+        let name = (player["user"]["name"].as_str().map(str::to_owned))
+            .or_else(|| player["aiLevel"].as_u64().map(|level| format!("lichess AI level {level}")))
+            .unwrap_or_else(|| "Anonymous".to_owned());
+        tag_pairs.push((side.to_owned(), name));
+        if let Some(title) = player["user"]["title"].as_str() {
+            tag_pairs.push((format!("{side}Title"), title.to_owned()));
+        }
+        if let Some(rating) = player["rating"].as_u64() {
+            tag_pairs.push((format!("{side}Elo"), rating.to_string()));
+        }
+        if let Some(diff) = player["ratingDiff"].as_i64() {
+            tag_pairs.push((format!("{side}RatingDiff"), format!("{diff:+}")));
+        }
+    }
+    let variant = match variant {
+        "chess960" => "Chess960",
+        "fromPosition" => "From Position",
+        _ => "Standard",
+    };
+    tag_pairs.push(("Variant".to_owned(), variant.to_owned()));
+    for (tag, field) in [("ECO", "eco"), ("Opening", "name")] {
+        if let Some(value) = game["opening"][field].as_str() {
+            tag_pairs.push((tag.to_owned(), value.to_owned()));
+        }
+    }
+    let termination = match status {
+        "created" | "started" => "Unterminated",
+        "aborted" | "noStart" => "Abandoned",
+        "outoftime" | "timeout" => "Time forfeit",
+        "cheat" => "Rules infraction",
+        "unknownFinish" => "Unknown",
+        _ => "Normal",
+    };
+    tag_pairs.push(("Termination".to_owned(), termination.to_owned()));
+    if let Some(fen) = initial_fen {
+        tag_pairs.push(("SetUp".to_owned(), "1".to_owned()));
+        tag_pairs.push(("FEN".to_owned(), fen.to_string()));
+    }
+    tag_pairs
+}
+
+/// Converts a Unix timestamp in milliseconds to a UTC date and time, formatted as in the PGN _UTCDate_ and _UTCTime_ tags.
+fn utc_date_time(millis: u64) -> (String, String) {
+    let (days, secs) = ((millis / 86_400_000) as i64, millis / 1000 % 86_400);
+    // converts days since the Unix epoch to a date in the proleptic Gregorian calendar, with eras of 400 years starting on March 1
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (format!("{year:04}.{month:02}.{day:02}"), format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60))
+}
+
+/// Reads games from Lichess NDJSON, such as the response of the game export API, one line at a time. Blank lines are skipped.
+pub struct LichessReader<R: BufRead> {
+    lines: Lines<R>,
+}
+
+impl<R: BufRead> LichessReader<R> {
+    /// Creates a reader of the games in NDJSON read from the given reader.
+    pub fn new(reader: R) -> Self {
+        Self { lines: reader.lines() }
+    }
+}
+
+impl<R: BufRead> Iterator for LichessReader<R> {
+    type Item = Result<LichessGame, LichessImportError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.lines.next()? {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => return Some(LichessGame::from_json(&line)),
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
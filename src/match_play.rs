@@ -0,0 +1,380 @@
+//! Tools for running engine matches: [`run_match`] (or an [`EngineMatch`]) plays a series of games between two [`Engine`]s,
+//! and an [`Adjudicator`] ends games early by the common rules of tournament managers such as cutechess,
+//! resigning hopeless games, drawing dead-level ones, and deciding positions covered by endgame tablebases.
+
+use super::{helpers, Board, Color, DrawType, GameOverError, GameResult, Move, Outcome, Position, TimeControl, WinType};
+use std::time::Duration;
+
+/// Represents the result of probing a position in an endgame tablebase, from the point of view of the side to move.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum Wdl {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// A hook for probing endgame tablebases during adjudication (see [`Adjudicator::tablebase`]).
+/// It is implemented for closures taking a position and returning an optional [`Wdl`].
+pub trait TablebaseProbe {
+    /// Probes the position, returning `None` if it is not covered by the tablebases (such as when it has too many pieces).
+    fn probe(&self, position: &Position) -> Option<Wdl>;
+}
+
+impl<F: Fn(&Position) -> Option<Wdl>> TablebaseProbe for F {
+    fn probe(&self, position: &Position) -> Option<Wdl> {
+        self(position)
+    }
+}
+
+/// Represents a rule for resigning hopeless games, like the `-resign` option of cutechess.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct ResignRule {
+    /// The number of consecutive moves for which a side's score must be at or below `-score` for it to resign
+    pub move_count: usize,
+    /// The score threshold, in centipawns
+    pub score: i32,
+    /// Whether the opponent's score must also have been at or above `score` for the same number of consecutive moves
+    pub two_sided: bool,
+}
+
+/// Represents a rule for drawing dead-level games, like the `-draw` option of cutechess.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct DrawRule {
+    /// The fullmove number from which the rule applies
+    pub move_number: usize,
+    /// The number of consecutive moves (of each side) for which the scores of both sides must be within `score` of zero
+    pub move_count: usize,
+    /// The score threshold, in centipawns
+    pub score: i32,
+}
+
+/// Represents why an [`Adjudicator`] ended a game.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum AdjudicationReason {
+    /// The losing side's score was below the resign threshold (see [`ResignRule`])
+    Resignation,
+    /// The scores of both sides were close to zero (see [`DrawRule`])
+    Draw,
+    /// The position was found in the endgame tablebases
+    Tablebase,
+}
+
+/// Represents the decision of an [`Adjudicator`] to end a game.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct Adjudication {
+    /// The result of the game
+    pub result: GameResult,
+    /// Why the game was adjudicated
+    pub reason: AdjudicationReason,
+}
+
+impl Adjudication {
+    /// Ends the game on a board with the result of the adjudication: the losing side resigns, or the game is drawn by agreement.
+    pub fn apply(&self, board: &mut Board) -> Result<(), GameOverError> {
+        match self.result {
+            GameResult::Wins(winner, _) => board.resign(!winner),
+            GameResult::Draw(_) => board.agree_draw(),
+        }
+    }
+}
+
+/// Adjudicates engine games by the configured rules, given the score reported by the engine after each move. A new adjudicator
+/// (or one that has been [reset](Adjudicator::reset)) must be used for each game. No rules are enabled by default.
+///
+/// ```
+/// use rschess::{match_play::{Adjudicator, DrawRule}, Board};
+///
+/// let mut adjudicator = Adjudicator::new().draw(DrawRule { move_number: 1, move_count: 2, score: 10 });
+/// let mut board = Board::default();
+/// for (san, score) in [("Nf3", 15), ("Nf6", -5), ("Ng1", 0), ("Ng8", 3), ("Nc3", 8)] {
+///     board.make_move_san(san).unwrap();
+///     if let Some(adjudication) = adjudicator.update(&board, score) {
+///         adjudication.apply(&mut board).unwrap();
+///     }
+/// }
+/// assert!(board.is_game_over());
+/// ```
+#[derive(Default)]
+pub struct Adjudicator {
+    resign: Option<ResignRule>,
+    draw: Option<DrawRule>,
+    tablebase: Option<Box<dyn TablebaseProbe>>,
+    /// The number of consecutive moves of each side (white first) with a score at or below the negated resign threshold
+    losing: [usize; 2],
+    /// The number of consecutive moves of each side (white first) with a score at or above the resign threshold
+    winning: [usize; 2],
+    /// The number of consecutive halfmoves with scores within the draw threshold
+    level: usize,
+}
+
+impl Adjudicator {
+    /// Creates an adjudicator with no rules enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables the resign rule.
+    pub fn resign(mut self, rule: ResignRule) -> Self {
+        self.resign = Some(rule);
+        self
+    }
+
+    /// Enables the draw rule.
+    pub fn draw(mut self, rule: DrawRule) -> Self {
+        self.draw = Some(rule);
+        self
+    }
+
+    /// Enables adjudication by endgame tablebases, probing each position after a move with the given hook.
+    pub fn tablebase(mut self, probe: impl TablebaseProbe + 'static) -> Self {
+        self.tablebase = Some(Box::new(probe));
+        self
+    }
+
+    /// Forgets the scores reported so far, for adjudicating a new game.
+    pub fn reset(&mut self) {
+        (self.losing, self.winning, self.level) = ([0; 2], [0; 2], 0);
+    }
+
+    /// Records the score (in centipawns, from the point of view of the side that just moved) reported by an engine after a move
+    /// has been made on the board, returning an adjudication if the game should end. Games which are already over are not adjudicated.
+    /// Tablebase adjudication takes precedence over resignation, which takes precedence over drawing.
+    pub fn update(&mut self, board: &Board, score: i32) -> Option<Adjudication> {
+        if !board.is_ongoing() {
+            return None;
+        }
+        let position = board.position();
+        let mover = !position.side_to_move();
+        let index = |side: Color| if side.is_white() { 0 } else { 1 };
+        if let Some(resign) = self.resign {
+            let (losing, winning) = (&mut self.losing[index(mover)], &mut self.winning[index(mover)]);
+            *losing = if score <= -resign.score { *losing + 1 } else { 0 };
+            *winning = if score >= resign.score { *winning + 1 } else { 0 };
+        }
+        if let Some(draw) = self.draw {
+            self.level = if board.fullmove_number() >= draw.move_number && score.abs() <= draw.score {
+                self.level + 1
+            } else {
+                0
+            };
+        }
+        if let Some(wdl) = self.tablebase.as_ref().and_then(|tablebase| tablebase.probe(position)) {
+            let side = position.side_to_move();
+            let result = match wdl {
+                Wdl::Win => GameResult::Wins(side, WinType::Resignation),
+                Wdl::Loss => GameResult::Wins(!side, WinType::Resignation),
+                Wdl::Draw => GameResult::Draw(DrawType::Agreement),
+            };
+            return Some(Adjudication {
+                result,
+                reason: AdjudicationReason::Tablebase,
+            });
+        }
+        if let Some(resign) = self.resign {
+            let resigns = |side: Color| self.losing[index(side)] >= resign.move_count && (!resign.two_sided || self.winning[index(!side)] >= resign.move_count);
+            if let Some(loser) = [mover, !mover].into_iter().find(|&side| resigns(side)) {
+                return Some(Adjudication {
+                    result: GameResult::Wins(!loser, WinType::Resignation),
+                    reason: AdjudicationReason::Resignation,
+                });
+            }
+        }
+        match self.draw {
+            Some(draw) if self.level >= draw.move_count * 2 => Some(Adjudication {
+                result: GameResult::Draw(DrawType::Agreement),
+                reason: AdjudicationReason::Draw,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a move chosen by an [`Engine`].
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct EngineMove {
+    /// The move, whose special move type may be unclear (as with moves parsed from UCI)
+    pub move_: Move,
+    /// The engine's score of the position after the move, in centipawns from its own point of view, if known (used for adjudication)
+    pub score: Option<i32>,
+}
+
+/// A player in an engine match, such as a wrapper around a UCI engine process or a bot built on rschess.
+pub trait Engine {
+    /// Returns the name of the engine, for the _White_ and _Black_ tags of its games.
+    fn name(&self) -> String;
+
+    /// Prepares the engine for a new game. Does nothing by default.
+    fn new_game(&mut self) {}
+
+    /// Chooses a move in the current position of the board, given the time remaining on the clocks of white and black (if the game has a time control).
+    /// Returns `None` if the engine fails to move (for example, if its process has crashed), which forfeits the game.
+    fn go(&mut self, board: &Board, clocks: Option<[Duration; 2]>) -> Option<EngineMove>;
+}
+
+/// Represents the results of a match, from the point of view of the first engine (engine A).
+#[derive(Eq, PartialEq, Clone, Debug, Default)]
+pub struct MatchResult {
+    /// The number of games won by engine A
+    pub wins: usize,
+    /// The number of games drawn
+    pub draws: usize,
+    /// The number of games lost by engine A
+    pub losses: usize,
+    /// The games played, in order, with their PGN tags (including _Termination_ for games not ended by the rules) in their metadata
+    pub games: Vec<Board>,
+}
+
+impl MatchResult {
+    /// Returns the number of games played.
+    pub fn total(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Returns the score of engine A (1 for a win and 0.5 for a draw).
+    pub fn score(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.
+    }
+
+    /// Serializes the games of the match as a PGN database.
+    #[cfg(feature = "pgn")]
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for game in &self.games {
+            let tag_pairs = game.metadata().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+            let game_pgn = super::pgn::Pgn::from_board(game.clone(), tag_pairs).unwrap();
+            pgn.push_str(&format!("{game_pgn}\n\n"));
+        }
+        pgn
+    }
+}
+
+/// A match between two engines: every opening is played twice per round, once with each engine as white, starting with engine A.
+///
+/// Games are played on [`Board`]s, so they end by the rules as usual. An engine also loses a game if it plays an illegal move,
+/// fails to move, or runs out of time (the time taken by [`Engine::go`] is charged to its clock), and games can be ended early by an [`Adjudicator`].
+pub struct EngineMatch {
+    time_control: Option<TimeControl>,
+    openings: Vec<Board>,
+    rounds: usize,
+    adjudicator: Option<Adjudicator>,
+    event: String,
+}
+
+impl EngineMatch {
+    /// Creates a match of one round with the given time control (or no time control) and openings, which are boards to start the games from
+    /// (set up from a FEN and possibly with moves played). Without any openings, games start from the standard starting position.
+    pub fn new(time_control: Option<TimeControl>, openings: Vec<Board>) -> Self {
+        Self {
+            time_control,
+            openings,
+            rounds: 1,
+            adjudicator: None,
+            event: "Engine match".to_owned(),
+        }
+    }
+
+    /// Sets the number of rounds, i.e. how many times the openings are played.
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Adjudicates the games with the given adjudicator, using the scores reported by the engines.
+    pub fn adjudicator(mut self, adjudicator: Adjudicator) -> Self {
+        self.adjudicator = Some(adjudicator);
+        self
+    }
+
+    /// Sets the _Event_ tag of the games ("Engine match" by default).
+    pub fn event(mut self, event: &str) -> Self {
+        self.event = event.to_owned();
+        self
+    }
+
+    /// Plays the match between two engines, returning the results from the point of view of engine A.
+    pub fn run(&mut self, engine_a: &mut impl Engine, engine_b: &mut impl Engine) -> MatchResult {
+        let openings = if self.openings.is_empty() { vec![Board::default()] } else { self.openings.clone() };
+        let mut result = MatchResult::default();
+        for _ in 0..self.rounds {
+            for opening in &openings {
+                for a_is_white in [true, false] {
+                    let game = if a_is_white {
+                        self.play_game(engine_a, engine_b, opening, result.games.len() + 1)
+                    } else {
+                        self.play_game(engine_b, engine_a, opening, result.games.len() + 1)
+                    };
+                    let a_color = if a_is_white { Color::White } else { Color::Black };
+                    match game.outcome() {
+                        Outcome::Wins(side) if side == a_color => result.wins += 1,
+                        Outcome::Wins(_) => result.losses += 1,
+                        _ => result.draws += 1,
+                    }
+                    result.games.push(game);
+                }
+            }
+        }
+        result
+    }
+
+    /// Plays a game from an opening, returning the finished board.
+    fn play_game(&mut self, white: &mut dyn Engine, black: &mut dyn Engine, opening: &Board, round: usize) -> Board {
+        let mut board = opening.clone();
+        board.set_time_control(self.time_control);
+        for (key, value) in [("Event", self.event.clone()), ("Site", "?".to_owned()), ("Date", "????.??.??".to_owned()), ("Round", round.to_string())] {
+            board.set_metadata(key, &value);
+        }
+        board.set_metadata("White", &white.name());
+        board.set_metadata("Black", &black.name());
+        white.new_game();
+        black.new_game();
+        if let Some(adjudicator) = &mut self.adjudicator {
+            adjudicator.reset();
+        }
+        let mut clocks = self.time_control.map(|time_control| [time_control.base; 2]);
+        let index = |side: Color| if side.is_white() { 0 } else { 1 };
+        while board.is_ongoing() {
+            let side = board.side_to_move();
+            let start = helpers::now();
+            let reply = if side.is_white() { white.go(&board, clocks) } else { black.go(&board, clocks) };
+            let elapsed = helpers::elapsed_since(start);
+            if let (Some(clocks), Some(time_control)) = (&mut clocks, self.time_control) {
+                let remaining = &mut clocks[index(side)];
+                if elapsed >= *remaining {
+                    *remaining = Duration::ZERO;
+                    board.flag(side).unwrap();
+                    board.set_metadata("Termination", "time forfeit");
+                    break;
+                }
+                *remaining = *remaining - elapsed + time_control.increment;
+            }
+            let Some(reply) = reply else {
+                board.resign(side).unwrap();
+                board.set_metadata("Termination", "abandoned");
+                break;
+            };
+            if board.make_move(reply.move_).is_err() {
+                board.resign(side).unwrap();
+                board.set_metadata("Termination", "rules infraction");
+                break;
+            }
+            if let Some(clocks) = clocks {
+                board.record_clock(clocks[index(side)]).unwrap();
+            }
+            let adjudication = match (&mut self.adjudicator, reply.score) {
+                (Some(adjudicator), Some(score)) => adjudicator.update(&board, score),
+                _ => None,
+            };
+            if let Some(adjudication) = adjudication {
+                adjudication.apply(&mut board).unwrap();
+                board.set_metadata("Termination", "adjudication");
+            }
+        }
+        board
+    }
+}
+
+/// Plays a match of one round between two engines with the given time control (or no time control) and openings (see [`EngineMatch`]),
+/// returning the results from the point of view of engine A.
+pub fn run_match(engine_a: &mut impl Engine, engine_b: &mut impl Engine, time_control: Option<TimeControl>, openings: &[Board]) -> MatchResult {
+    EngineMatch::new(time_control, openings.to_vec()).run(engine_a, engine_b)
+}
@@ -0,0 +1,56 @@
+//! Lightweight counters and timings for services embedding rschess.
+//!
+//! Implement [`Metrics`] for a type that forwards to your metrics backend (such as Prometheus or statsd),
+//! and register it once with [`set_metrics`]. When no metrics sink is registered, reporting is a no-op.
+
+use super::MetricsAlreadySetError;
+use std::{sync::OnceLock, time::Duration};
+
+/// Represents the kinds of text that rschess parses, for reporting parse failures.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum ParseKind {
+    Fen,
+    Epd,
+    Pgn,
+    San,
+    Lan,
+    Uci,
+}
+
+/// A sink for the counters and timings reported by rschess. Every method has an empty default implementation,
+/// so implementors only need to override the ones they are interested in.
+pub trait Metrics: Send + Sync {
+    /// Called whenever a move is made on a `Board`.
+    fn move_made(&self) {}
+
+    /// Called whenever a PGN game is parsed successfully.
+    fn game_parsed(&self) {}
+
+    /// Called whenever parsing fails, with the kind of text that could not be parsed.
+    fn parse_failed(&self, _kind: ParseKind) {}
+
+    /// Called whenever an image is rendered, with the time it took to render.
+    fn image_rendered(&self, _duration: Duration) {}
+}
+
+static METRICS: OnceLock<Box<dyn Metrics>> = OnceLock::new();
+
+/// Registers the global metrics sink, returning an error if one has already been registered.
+pub fn set_metrics(metrics: impl Metrics + 'static) -> Result<(), MetricsAlreadySetError> {
+    METRICS.set(Box::new(metrics)).map_err(|_| MetricsAlreadySetError)
+}
+
+/// Reports to the global metrics sink, if one has been registered.
+pub(crate) fn record(f: impl FnOnce(&dyn Metrics)) {
+    if let Some(metrics) = METRICS.get() {
+        f(metrics.as_ref())
+    }
+}
+
+/// Reports a parse failure to the global metrics sink if the result is an error, passing the result through.
+pub(crate) fn record_parse<T, E>(kind: ParseKind, res: Result<T, E>) -> Result<T, E> {
+    if res.is_err() {
+        record(|m| m.parse_failed(kind));
+    }
+    res
+}
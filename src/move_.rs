@@ -16,6 +16,26 @@ impl Move {
         helpers::idx_to_sq(self.1)
     }
 
+    /// Returns the index (`0..64`) of the source square of the move.
+    /// The index can be converted to a square name using the [`idx_to_sq`](super::idx_to_sq) function.
+    pub fn from_index(&self) -> usize {
+        self.0
+    }
+
+    /// Returns the index (`0..64`) of the destination square of the move.
+    /// The index can be converted to a square name using the [`idx_to_sq`](super::idx_to_sq) function.
+    pub fn to_index(&self) -> usize {
+        self.1
+    }
+
+    /// Returns the type of piece that the pawn promotes to if this move is a promotion (otherwise `None`).
+    pub fn promotion(&self) -> Option<PieceType> {
+        match self.2 {
+            Some(SpecialMoveType::Promotion(pt)) => Some(pt),
+            _ => None,
+        }
+    }
+
     /// Returns the type of special move (castling/promotion/en passant) if this move is a special move (otherwise `None`).
     pub fn special_move_type(&self) -> Option<SpecialMoveType> {
         self.2
@@ -40,11 +60,10 @@ impl Move {
         let promotion = match promotion {
             Some(p) => Some({
                 let pt = PieceType::try_from(p).map_err(|_| InvalidUciError::InvalidPieceType(p))?;
-                if pt == PieceType::K {
+                if !pt.is_promotion_type() {
                     return Err(InvalidUciError::InvalidPieceType(p));
-                } else {
-                    pt
                 }
+                pt
             }),
             _ => None,
         };
@@ -88,3 +107,69 @@ pub enum SpecialMoveType {
     EnPassant,
     Unclear,
 }
+
+/// Represents the reason a move is illegal, as returned by [`Position::why_illegal`](crate::Position::why_illegal).
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum IllegalMoveReason {
+    /// The game is over, so no moves can be played.
+    GameOver,
+    /// The source or destination square index is not in the range `0..64`.
+    InvalidSquare(usize),
+    /// There is no piece on the source square.
+    NoPieceOnSource,
+    /// The piece on the source square belongs to the side that is not to move.
+    WrongColor,
+    /// The destination square is occupied by a piece of the side to move.
+    OwnPieceOnDestination,
+    /// The piece cannot move from the source square to the destination square.
+    InvalidPieceMovement(PieceType),
+    /// The move is blocked by the piece on the given square.
+    Blocked(usize),
+    /// A pawn cannot move diagonally unless it captures.
+    NothingToCapture,
+    /// A pawn moving to the last rank must promote, and only a pawn moving to the last rank can promote (to a queen, rook, bishop, or knight).
+    InvalidPromotion,
+    /// The side to move has lost the right to castle on this side.
+    NoCastlingRights,
+    /// A side cannot castle while in check.
+    CastlingOutOfCheck,
+    /// The king would pass through or land on the given square, which is attacked.
+    CastlingThroughCheck(usize),
+    /// The move would leave (or put) the side's own king in check.
+    LeavesKingInCheck,
+}
+
+impl fmt::Display for IllegalMoveReason {
+    /// Explains the reason in a sentence, such as "The king would be left in check."
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let square = |sq: usize| {
+            let (file, rank) = helpers::idx_to_sq(sq);
+            format!("{file}{rank}")
+        };
+        match self {
+            Self::GameOver => write!(f, "The game is over."),
+            Self::InvalidSquare(sq) => write!(f, "{sq} is not a valid square index."),
+            Self::NoPieceOnSource => write!(f, "There is no piece on the source square."),
+            Self::WrongColor => write!(f, "That piece belongs to the opponent."),
+            Self::OwnPieceOnDestination => write!(f, "A piece cannot capture a piece of its own color."),
+            Self::InvalidPieceMovement(pt) => {
+                let name = match pt {
+                    PieceType::K => "A king",
+                    PieceType::Q => "A queen",
+                    PieceType::R => "A rook",
+                    PieceType::B => "A bishop",
+                    PieceType::N => "A knight",
+                    PieceType::P => "A pawn",
+                };
+                write!(f, "{name} cannot move that way.")
+            }
+            Self::Blocked(sq) => write!(f, "The move is blocked by the piece on {}.", square(*sq)),
+            Self::NothingToCapture => write!(f, "A pawn can only move diagonally when it captures."),
+            Self::InvalidPromotion => write!(f, "A pawn must promote (to a queen, rook, bishop, or knight) when, and only when, it reaches the last rank."),
+            Self::NoCastlingRights => write!(f, "Castling on this side is no longer allowed, since the king or rook has moved."),
+            Self::CastlingOutOfCheck => write!(f, "A king cannot castle out of check."),
+            Self::CastlingThroughCheck(sq) => write!(f, "A king cannot castle through or into check, and {} is attacked.", square(*sq)),
+            Self::LeavesKingInCheck => write!(f, "The king would be left in check."),
+        }
+    }
+}
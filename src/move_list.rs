@@ -0,0 +1,115 @@
+use super::Move;
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// A list of moves stored inline in a fixed-size array, so that generating moves does not allocate.
+/// It holds up to 256 moves, which is more than any chess position has (pseudolegal or legal), and
+/// dereferences to a slice of its moves. Convert it to a `Vec` with `Vec::from` or `into`.
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MoveList::CAPACITY],
+    len: usize,
+}
+
+impl MoveList {
+    /// The number of moves a move list can hold.
+    pub const CAPACITY: usize = 256;
+
+    /// Creates an empty move list.
+    pub fn new() -> Self {
+        Self {
+            moves: [Move(0, 0, None); Self::CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Appends a move to the list, panicking if it is full.
+    pub fn push(&mut self, move_: Move) {
+        self.moves[self.len] = move_;
+        self.len += 1;
+    }
+
+    /// Removes all the moves from the list.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Keeps only the moves which satisfy a predicate, preserving their order.
+    pub fn retain(&mut self, mut f: impl FnMut(&Move) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len {
+            if f(&self.moves[i]) {
+                self.moves[kept] = self.moves[i];
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl PartialEq for MoveList {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for MoveList {}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Extend<Move> for MoveList {
+    fn extend<I: IntoIterator<Item = Move>>(&mut self, iter: I) {
+        for move_ in iter {
+            self.push(move_);
+        }
+    }
+}
+
+impl FromIterator<Move> for MoveList {
+    fn from_iter<I: IntoIterator<Item = Move>>(iter: I) -> Self {
+        let mut moves = Self::new();
+        moves.extend(iter);
+        moves
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl From<MoveList> for Vec<Move> {
+    fn from(moves: MoveList) -> Self {
+        moves.to_vec()
+    }
+}
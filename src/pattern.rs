@@ -0,0 +1,89 @@
+use super::{Color, Piece, PieceType, Position};
+
+/// Matches pieces by type and color, where `None` is a wildcard (matching any type or any color).
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub struct PieceMatcher {
+    /// The type of piece to match
+    pub piece_type: Option<PieceType>,
+    /// The color of piece to match
+    pub color: Option<Color>,
+}
+
+impl PieceMatcher {
+    /// Creates a matcher which matches any piece.
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    /// Creates a matcher which matches pieces of the given type and color.
+    pub fn new(piece_type: PieceType, color: Color) -> Self {
+        Self {
+            piece_type: Some(piece_type),
+            color: Some(color),
+        }
+    }
+
+    /// Creates a matcher which matches pieces of the given type, of either color.
+    pub fn of_type(piece_type: PieceType) -> Self {
+        Self {
+            piece_type: Some(piece_type),
+            color: None,
+        }
+    }
+
+    /// Creates a matcher which matches pieces of the given color, of any type.
+    pub fn of_color(color: Color) -> Self {
+        Self { piece_type: None, color: Some(color) }
+    }
+
+    /// Checks whether the given piece matches.
+    pub fn matches(&self, piece: Piece) -> bool {
+        self.piece_type.is_none_or(|pt| pt == piece.0) && self.color.is_none_or(|c| c == piece.1)
+    }
+}
+
+/// A pattern of pieces on squares, for searching positions (such as "white pawn on e5, black king on g8, any rook on the h-file"),
+/// checked with [`Position::matches_pattern`].
+///
+/// A pattern consists of requirements and prohibitions on sets of squares, represented as bit masks where bit `i` stands for
+/// square `i` (see [`sq_to_idx`](crate::sq_to_idx)). A requirement is satisfied if _at least one_ of its squares holds a matching
+/// piece, and a prohibition if _none_ of its squares does.
+#[derive(Eq, PartialEq, Hash, Clone, Debug, Default)]
+pub struct PiecePattern {
+    required: Vec<(u64, PieceMatcher)>,
+    forbidden: Vec<(u64, PieceMatcher)>,
+}
+
+impl PiecePattern {
+    /// Creates an empty pattern, which matches every position.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires a matching piece on at least one of the given squares.
+    pub fn require(mut self, squares: impl IntoIterator<Item = usize>, matcher: PieceMatcher) -> Self {
+        self.required.push((squares_to_mask(squares), matcher));
+        self
+    }
+
+    /// Forbids matching pieces on all of the given squares.
+    pub fn forbid(mut self, squares: impl IntoIterator<Item = usize>, matcher: PieceMatcher) -> Self {
+        self.forbidden.push((squares_to_mask(squares), matcher));
+        self
+    }
+
+    /// Requires the given squares to be empty.
+    pub fn require_empty(self, squares: impl IntoIterator<Item = usize>) -> Self {
+        self.forbid(squares, PieceMatcher::any())
+    }
+
+    /// Checks whether the pattern matches the placement of pieces in the given position.
+    pub fn matches(&self, position: &Position) -> bool {
+        self.required.iter().all(|&(mask, matcher)| position.piece_mask(matcher) & mask != 0) && self.forbidden.iter().all(|&(mask, matcher)| position.piece_mask(matcher) & mask == 0)
+    }
+}
+
+/// Converts square indices to a bit mask.
+fn squares_to_mask(squares: impl IntoIterator<Item = usize>) -> u64 {
+    squares.into_iter().fold(0, |mask, sq| mask | 1 << sq)
+}
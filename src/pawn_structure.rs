@@ -0,0 +1,79 @@
+use super::{Color, Piece, PieceType};
+
+/// Represents the pawn structure of one side of a position (see [`Position::pawn_structure`](crate::Position::pawn_structure)).
+/// Pawns are given by the indices of their squares in ascending order, and files by their letters in alphabetical order.
+///
+/// The pawn features are classified as follows:
+/// * doubled: on the same file as another pawn of the side (every such pawn is included)
+/// * isolated: with no pawns of the side on the adjacent files
+/// * backward: not isolated, but behind every pawn of the side on the adjacent files, with the square in front of it attacked by an opposing pawn
+/// * passed: with no opposing pawns in front of it on the same or adjacent files
+#[derive(Eq, PartialEq, Hash, Clone, Debug, Default)]
+pub struct PawnStructure {
+    /// The doubled pawns
+    pub doubled: Vec<usize>,
+    /// The isolated pawns
+    pub isolated: Vec<usize>,
+    /// The backward pawns
+    pub backward: Vec<usize>,
+    /// The passed pawns
+    pub passed: Vec<usize>,
+    /// The pawn islands (groups of pawns on adjacent files), each given by its files
+    pub islands: Vec<Vec<char>>,
+    /// The files with no pawns of either side
+    pub open_files: Vec<char>,
+    /// The files with opposing pawns but no pawns of the side
+    pub half_open_files: Vec<char>,
+}
+
+impl PawnStructure {
+    /// Analyzes the pawn structure of the given side in the position with the given content.
+    pub(crate) fn of(content: &[Option<Piece>; 64], side: Color) -> Self {
+        // ranks are counted from the side's point of view, so that its pawns advance to higher ranks
+        let relative_rank = |sq: usize| if side.is_white() { sq / 8 } else { 7 - sq / 8 };
+        let pawns_of = |color: Color| -> Vec<(usize, usize, usize)> {
+            (0..64)
+                .filter(|&sq| content[sq] == Some(Piece(PieceType::P, color)))
+                .map(|sq| (sq, sq % 8, relative_rank(sq)))
+                .collect()
+        };
+        let (own, opposing) = (pawns_of(side), pawns_of(!side));
+        let adjacent = |a: usize, b: usize| a.abs_diff(b) == 1;
+        let file_char = |file: usize| (b'a' + file as u8) as char;
+        let mut structure = Self::default();
+        for &(sq, file, rank) in &own {
+            let neighbors: Vec<_> = own.iter().filter(|(_, f, _)| adjacent(*f, file)).collect();
+            if own.iter().any(|&(s, f, _)| f == file && s != sq) {
+                structure.doubled.push(sq);
+            }
+            if neighbors.is_empty() {
+                structure.isolated.push(sq);
+            } else if neighbors.iter().all(|(_, _, r)| *r > rank) && opposing.iter().any(|(_, f, r)| adjacent(*f, file) && *r == rank + 2) {
+                structure.backward.push(sq);
+            }
+            if !opposing.iter().any(|(_, f, r)| f.abs_diff(file) <= 1 && *r > rank) {
+                structure.passed.push(sq);
+            }
+        }
+        let mut island: Vec<char> = Vec::new();
+        for file in 0..8 {
+            let (has_own, has_opposing) = (own.iter().any(|(_, f, _)| *f == file), opposing.iter().any(|(_, f, _)| *f == file));
+            if has_own {
+                island.push(file_char(file));
+            } else {
+                if !island.is_empty() {
+                    structure.islands.push(std::mem::take(&mut island));
+                }
+                if has_opposing {
+                    structure.half_open_files.push(file_char(file));
+                } else {
+                    structure.open_files.push(file_char(file));
+                }
+            }
+        }
+        if !island.is_empty() {
+            structure.islands.push(island);
+        }
+        structure
+    }
+}
@@ -1,11 +1,124 @@
 //! Handles PGN generation and manipulation.
 
-use super::{Board, Color, Fen, GameResult, InvalidPgnError};
+use super::{
+    helpers,
+    metrics::{self, ParseKind},
+    Board, Color, Fen, FenCheckpoints, GameResult, InvalidAnnotationError, InvalidPgnError, MoveListDiff, PgnReadError, PgnWriteError, Progress, ProgressCallback,
+};
 use regex::Regex;
-use std::{collections::HashMap, fmt};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    fmt,
+    io::{self, BufRead, Write},
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
 
+/// Represents the line ending used when writing PGN.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub enum Newline {
+    /// `\n`, as used on Unix-like systems
+    #[default]
+    Lf,
+    /// `\r\n`, as required by some Windows database programs
+    CrLf,
+}
+
+impl Newline {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Represents the character encoding used when writing PGN.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub enum PgnEncoding {
+    /// UTF-8, which can represent every character
+    #[default]
+    Utf8,
+    /// ISO 8859-1 (Latin-1), as used by older database programs, with the given policy for characters it cannot represent
+    Latin1(Latin1Fallback),
+}
+
+/// Represents what to do with characters which cannot be represented in Latin-1 when writing PGN.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub enum Latin1Fallback {
+    /// Typographic punctuation (such as `“` and `–`) is written as its ASCII equivalent, and other characters are replaced with `?`.
+    #[default]
+    Replace,
+    /// Writing fails with [`PgnWriteError::Unrepresentable`].
+    Error,
+}
+
+/// Options for writing PGN with [`Pgn::write_to`] and [`Pgn::to_bytes`]. The default options produce the same text as [`Pgn::to_string`](ToString::to_string).
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub struct PgnWriteOptions {
+    /// The line ending
+    pub newline: Newline,
+    /// The character encoding
+    pub encoding: PgnEncoding,
+    /// The moves after which the FEN of the position is written as a `[%fen]` comment (see [`Board::gen_movetext_with_checkpoints`])
+    pub checkpoints: FenCheckpoints,
+}
+
+/// Represents the colors of annotations, as in the `[%cal]` and `[%csl]` commands of PGN comments, where they are written as their initials.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum AnnotationColor {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
+/// Represents an annotation drawn over the board, as in the `[%cal]` and `[%csl]` commands of PGN comments.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum Annotation {
+    /// An arrow from the square `from` to the square `to`
+    Arrow { from: usize, to: usize, color: AnnotationColor },
+    /// A highlighted square
+    Square { square: usize, color: AnnotationColor },
+}
+
+/// Parses annotations written like the operands of `[%cal]` and `[%csl]` commands, separated by whitespace or commas:
+/// a color initial (`G`, `R`, `Y`, or `B`) followed by two square names for an arrow (such as `Ge2e4`) or one for a highlighted square (such as `Rd5`).
+/// For example, `"Ge2e4 Rd5 Yc8h3"` is a green arrow from e2 to e4, a red square on d5, and a yellow arrow from c8 to h3.
+pub fn parse_annotations(text: &str) -> Result<Vec<Annotation>, InvalidAnnotationError> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let invalid = || InvalidAnnotationError(token.to_owned());
+            let mut chars = token.chars();
+            let color = match chars.next() {
+                Some('G') => AnnotationColor::Green,
+                Some('R') => AnnotationColor::Red,
+                Some('Y') => AnnotationColor::Yellow,
+                Some('B') => AnnotationColor::Blue,
+                _ => return Err(invalid()),
+            };
+            let chars: Vec<_> = chars.collect();
+            let squares = chars
+                .chunks(2)
+                .map(|sq| match sq {
+                    &[file @ 'a'..='h', rank @ '1'..='8'] => Some(helpers::sq_to_idx(file, rank)),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(invalid)?;
+            match squares[..] {
+                [square] => Ok(Annotation::Square { square, color }),
+                [from, to] => Ok(Annotation::Arrow { from, to, color }),
+                _ => Err(invalid()),
+            }
+        })
+        .collect()
+}
+
 /// Represents PGN (Portable Game Notation).
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct Pgn {
@@ -14,7 +127,9 @@ pub struct Pgn {
 }
 
 impl Pgn {
-    /// Tokenizes PGN text.
+    /// Tokenizes PGN text. Figurines in the movetext are read as piece letters (see [`Position::san_to_move`](crate::Position::san_to_move)),
+    /// and comments are skipped, apart from the clock times in their `[%clk]` commands and the FEN checkpoints in their `[%fen]` commands.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     fn tokenize(text: &str) -> Vec<Token> {
         let tag_pair_regex = Regex::new(r#"\[(?<name>[A-Za-z]+)\s*"(?<value>((\\\\)|(\\")|[^"\\])*)"\]"#).unwrap();
         let fullmove_san_regex = Regex::new(r"(?<move_number>\d+)\.\s*(?<white_move>((O-O(-O)?)|(0-0(-0)?)|([a-h]((x[a-h][1-8])|[1-8])(=[QRBN])?)|([QRBN](([a-h][1-8]x?[a-h][1-8])|([1-8]x?[a-h][1-8])|([a-h]x?[a-h][1-8])|(x?[a-h][1-8])))|(Kx?[a-h][1-8]))\+?)\s+(?<black_move>((O-O(-O)?)|(0-0(-0)?)|([a-h]((x[a-h][1-8])|[1-8])(=[QRBN])?)|([QRBN](([a-h][1-8]x?[a-h][1-8])|([1-8]x?[a-h][1-8])|([a-h]x?[a-h][1-8])|(x?[a-h][1-8])))|(Kx?[a-h][1-8]))[+#]?)").unwrap();
@@ -24,6 +139,9 @@ impl Pgn {
         for caps in tag_pair_regex.captures_iter(text) {
             tokens.push(Token::TagPair(caps["name"].to_string(), caps["value"].replace(r"\\", r"\").replace(r#"\""#, r#"""#).to_string()));
         }
+        let text = &helpers::figurines_to_letters(&tag_pair_regex.replace_all(text, ""));
+        let (text, commands) = strip_comments(text);
+        let text = &text;
         for caps in fullmove_san_regex.captures_iter(text) {
             tokens.push(Token::FullmoveSan(caps["move_number"].parse().unwrap(), caps["white_move"].to_string(), caps["black_move"].to_string()));
         }
@@ -33,11 +151,13 @@ impl Pgn {
         for caps in result_regex.captures_iter(text) {
             tokens.push(Token::Result(caps["white_score"].to_string(), caps["black_score"].to_string()));
         }
+        tokens.extend(commands);
         tokens
     }
 
     /// Parses PGN from a collection of PGN tokens.
-    /// This function currently does **not** support PGN annotations.
+    /// This function currently does **not** support PGN annotations, apart from clock times.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(tokens = tokens.len())))]
     fn parse(tokens: Vec<Token>) -> Result<Pgn, InvalidPgnError> {
         let mut tag_pairs_done = false;
         let mut fullmove_san_done = false;
@@ -46,6 +166,8 @@ impl Pgn {
         let mut tag_pairs = HashMap::new();
         let mut moves = Vec::new();
         let mut result = None;
+        let mut clocks = HashMap::new();
+        let mut checkpoints = HashMap::new();
         for token in tokens {
             match token {
                 Token::TagPair(name, value) => {
@@ -98,21 +220,32 @@ impl Pgn {
                     result_done = true;
                     result = Some((w, b));
                 }
+                Token::Clock(ply, time) => {
+                    clocks.insert(ply, time);
+                }
+                Token::Checkpoint(ply, fen) => {
+                    checkpoints.insert(ply, fen);
+                }
             }
         }
         if SEVEN_TAG_ROSTER.iter().any(|&k| !tag_pairs.contains_key(k)) {
             return Err(InvalidPgnError::SevenTagRoster);
         }
         let mut board = match tag_pairs.get("FEN") {
-            Some(fen) => Board::from_fen(Fen::try_from(fen.as_str()).unwrap()),
+            Some(fen) => Board::from_fen(Fen::try_from(fen.as_str()).map_err(InvalidPgnError::InvalidFen)?),
             _ => Board::default(),
         };
-        for (_, w, b) in moves {
-            if let Some(m) = w {
-                board.make_move_san(&m).map_err(InvalidPgnError::InvalidMove)?;
+        board.set_time_control(tag_pairs.get("TimeControl").and_then(|tc| tc.parse().ok()));
+        for m in moves.into_iter().flat_map(|(_, w, b)| [w, b]).flatten() {
+            board.make_move_san(&m).map_err(InvalidPgnError::InvalidMove)?;
+            let ply = board.move_history().len() - 1;
+            if let Some(&time) = clocks.get(&ply) {
+                board.record_clock(time).unwrap();
             }
-            if let Some(m) = b {
-                board.make_move_san(&m).map_err(InvalidPgnError::InvalidMove)?;
+            if let Some(fen) = checkpoints.get(&ply) {
+                if !Fen::try_from(fen.as_str()).is_ok_and(|fen| fen.normalized() == board.to_fen().normalized()) {
+                    return Err(InvalidPgnError::Checkpoint(ply, fen.clone()));
+                }
             }
         }
         match board.game_result() {
@@ -133,10 +266,9 @@ impl Pgn {
             }
             None => {
                 if let Some(res) = result {
-                    match (res.0.as_str(), res.1.as_str()) {
-                        ("1", "0") => board.resign(Color::Black).unwrap(),
-                        ("0", "1") => board.resign(Color::White).unwrap(),
-                        ("1/2", "1/2") => board.agree_draw().unwrap(),
+                    match GameResult::from_pgn_token(&format!("{}-{}", res.0, res.1)) {
+                        Ok(Some(GameResult::Wins(side, _))) => board.resign(!side).unwrap(),
+                        Ok(Some(GameResult::Draw(_))) => board.agree_draw().unwrap(),
                         _ => return Err(InvalidPgnError::InvalidResult(format!("{}-{} is not a valid result", res.0, res.1))),
                     }
                 }
@@ -148,6 +280,7 @@ impl Pgn {
     /// Constructs a `Pgn` object from a `Board`.
     /// Tag pairs must be provided following the [Seven Tag Roster](https://en.wikipedia.org/wiki/Portable_Game_Notation#Seven_Tag_Roster>),
     /// except the _Result_ tag which will be retrieved from the game state.
+    /// If the game has a time control and no _TimeControl_ tag is provided, the tag is added from the time control.
     pub fn from_board(board: Board, tag_pairs: Vec<(String, String)>) -> Result<Self, InvalidPgnError> {
         let tag_pair_names = tag_pairs.iter().map(|(t, _)| t.as_str()).collect::<Vec<_>>();
         let mut required_tags = SEVEN_TAG_ROSTER.iter().take(6);
@@ -158,10 +291,13 @@ impl Pgn {
         for (name, value) in tag_pairs.into_iter() {
             tag_pairs_hm.insert(name, value);
         }
+        if let Some(time_control) = board.time_control() {
+            tag_pairs_hm.entry("TimeControl".to_owned()).or_insert_with(|| time_control.to_string());
+        }
         tag_pairs_hm.insert(
             "Result".to_owned(),
             match board.game_result() {
-                Some(res) => res.to_string(),
+                Some(res) => res.to_pgn_token().to_owned(),
                 None => "*".to_owned(),
             },
         );
@@ -177,22 +313,50 @@ impl Pgn {
     pub fn board(&self) -> &Board {
         &self.board
     }
-}
 
-impl TryFrom<&str> for Pgn {
-    type Error = InvalidPgnError;
+    /// Attempts to parse a PGN text like [`Pgn::try_from`], after normalizing the typography introduced by word processors and websites:
+    /// * typographic double quotes (such as `“` and `”`) are read as `"`, and typographic single quotes as `'`
+    /// * non-breaking and other Unicode spaces are read as spaces, and zero-width characters and byte order marks are ignored
+    /// * dashes (such as the en dash in `½–½`) are read as `-`, and `½` as `1/2`
+    pub fn parse_lenient(text: &str) -> Result<Pgn, InvalidPgnError> {
+        Self::try_from(normalize_typography(text).as_str())
+    }
 
-    /// Attempts to parse a PGN text, returning an error if it is invalid.
-    /// This function does **not** support PGN annotations.
-    /// Note that this function is not a PGN validator, meaning it may sometimes accept invalid PGN as valid.
-    fn try_from(text: &str) -> Result<Pgn, Self::Error> {
-        Self::parse(Self::tokenize(text))
+    /// Updates the PGN in place from a newer version of the same game's PGN text, such as the next version pushed by a live broadcast,
+    /// in which moves may have been appended and earlier moves corrected (see [`Board::sync_moves`]). The tag pairs and the result are replaced,
+    /// and the moves in common are kept at the same plies. Returns the changes made to the moves, or an error (leaving the PGN unchanged) if the text is invalid.
+    /// If the initial position has changed, the game is replaced entirely.
+    pub fn update(&mut self, text: &str) -> Result<MoveListDiff, InvalidPgnError> {
+        let new = Self::try_from(text)?;
+        let diff = if new.board.initial_fen() == self.board.initial_fen() {
+            let diff = self.board.sync_moves(new.board.move_history()).unwrap();
+            self.board.adopt_result(&new.board);
+            diff
+        } else {
+            let diff = MoveListDiff {
+                kept: 0,
+                removed: self.board.move_history().to_vec(),
+                added: new.board.move_history().to_vec(),
+            };
+            self.board = new.board;
+            diff
+        };
+        self.tag_pairs = new.tag_pairs;
+        Ok(diff)
     }
-}
 
-impl fmt::Display for Pgn {
-    /// Represents the `Pgn` object as PGN text.
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    /// Encodes the PGN text with the given line ending and encoding, returning an error if a character
+    /// cannot be represented in Latin-1 and the fallback is [`Latin1Fallback::Error`].
+    pub fn to_bytes(&self, options: &PgnWriteOptions) -> Result<Vec<u8>, PgnWriteError> {
+        let text = self.gen_text(options.checkpoints).replace('\n', options.newline.as_str());
+        match options.encoding {
+            PgnEncoding::Utf8 => Ok(text.into_bytes()),
+            PgnEncoding::Latin1(fallback) => encode_latin1(&text, fallback),
+        }
+    }
+
+    /// Generates the PGN text, with FEN checkpoints after the given moves.
+    fn gen_text(&self, checkpoints: FenCheckpoints) -> String {
         let mut pgn = String::new();
         let mut tag_pairs = self.tag_pairs.clone();
         tag_pairs.insert("FEN".to_owned(), self.board.initial_fen().to_string());
@@ -208,15 +372,366 @@ impl fmt::Display for Pgn {
             pgn.push_str(&line);
         }
         pgn.push('\n');
-        pgn.push_str(&self.board.gen_movetext());
+        pgn.push_str(&self.board.gen_movetext_with_checkpoints(checkpoints));
         pgn.push_str(&format!(
             " {}",
             match self.board.game_result() {
-                Some(res) => res.to_string(),
-                None => "*".to_owned(),
+                Some(res) => res.to_pgn_token(),
+                None => "*",
             }
         ));
-        write!(f, "{pgn}")
+        pgn
+    }
+
+    /// Writes the PGN text to a writer with the given line ending and encoding (see [`Pgn::to_bytes`]).
+    pub fn write_to<W: Write>(&self, mut writer: W, options: &PgnWriteOptions) -> Result<(), PgnWriteError> {
+        writer.write_all(&self.to_bytes(options)?)?;
+        Ok(())
+    }
+}
+
+impl TryFrom<&str> for Pgn {
+    type Error = InvalidPgnError;
+
+    /// Attempts to parse a PGN text, returning an error if it is invalid.
+    /// This function does **not** support PGN annotations, except that comments are skipped. The clock times in `[%clk]` comments
+    /// are recorded on the board (see [`Board::move_clocks`]), and the time control is read from the _TimeControl_ tag.
+    /// The positions are verified against the FENs in `[%fen]` comments (see [`Board::gen_movetext_with_checkpoints`]), and a mismatch is an error.
+    /// Note that this function is not a PGN validator, meaning it may sometimes accept invalid PGN as valid.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(len = text.len()), err))]
+    fn try_from(text: &str) -> Result<Pgn, Self::Error> {
+        let res = metrics::record_parse(ParseKind::Pgn, Self::parse(Self::tokenize(text)));
+        if res.is_ok() {
+            metrics::record(|m| m.game_parsed());
+        }
+        res
+    }
+}
+
+impl fmt::Display for Pgn {
+    /// Represents the `Pgn` object as PGN text.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.gen_text(FenCheckpoints::default()))
+    }
+}
+
+/// Replaces typographic characters in text with their plain ASCII equivalents, as described in [`Pgn::parse_lenient`].
+fn normalize_typography(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' | '\u{2033}' | '\u{00ab}' | '\u{00bb}' => normalized.push('"'),
+            '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' | '\u{2032}' => normalized.push('\''),
+            '\u{00a0}' | '\u{2000}'..='\u{200a}' | '\u{202f}' | '\u{205f}' | '\u{3000}' => normalized.push(' '),
+            '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}' => (),
+            '\u{2010}'..='\u{2015}' | '\u{2212}' => normalized.push('-'),
+            '\u{00bd}' => normalized.push_str("1/2"),
+            c => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+/// Removes the comments from movetext, along with the move numbers of black moves which follow comments (such as `1...`),
+/// returning the remaining movetext and the clock times of the `[%clk]` commands and the FENs of the `[%fen]` commands in the comments,
+/// each with the index of the ply it follows.
+fn strip_comments(text: &str) -> (String, Vec<Token>) {
+    let clock_regex = Regex::new(r"\[%clk\s+(?<h>\d{1,6}):(?<m>\d{1,2}):(?<s>\d{1,2})(\.(?<fraction>\d{1,9}))?\]").unwrap();
+    let fen_regex = Regex::new(r"\[%fen\s+(?<fen>[^\]]+?)\s*\]").unwrap();
+    let count_moves = |movetext: &str| {
+        movetext
+            .split_whitespace()
+            .filter(|token| !matches!(*token, "1-0" | "0-1" | "1/2-1/2" | "*") && !token.starts_with('$'))
+            .filter(|token| !token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.').is_empty())
+            .count()
+    };
+    let (mut stripped, mut commands, mut plies) = (String::new(), Vec::new(), 0);
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        plies += count_moves(&rest[..start]);
+        stripped.push_str(&rest[..start]);
+        stripped.push(' ');
+        let end = rest[start..].find('}').map_or(rest.len(), |end| start + end + 1);
+        if let Some(caps) = clock_regex.captures(&rest[start..end]).filter(|_| plies > 0) {
+            let [h, m, s]: [u64; 3] = ["h", "m", "s"].map(|unit| caps[unit].parse().unwrap());
+            let nanos = caps.name("fraction").map_or(0, |f| format!("{:0<9}", f.as_str()).parse().unwrap());
+            commands.push(Token::Clock(plies - 1, Duration::new(h * 3600 + m * 60 + s, nanos)));
+        }
+        if let Some(caps) = fen_regex.captures(&rest[start..end]).filter(|_| plies > 0) {
+            commands.push(Token::Checkpoint(plies - 1, caps["fen"].to_owned()));
+        }
+        rest = rest[end..].trim_start();
+        let number_len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if number_len > 0 && rest[number_len..].starts_with("...") {
+            rest = &rest[number_len + 3..];
+        }
+    }
+    stripped.push_str(rest);
+    (stripped, commands)
+}
+
+/// Encodes text as Latin-1, handling unrepresentable characters according to the given fallback.
+fn encode_latin1(text: &str, fallback: Latin1Fallback) -> Result<Vec<u8>, PgnWriteError> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        if let Ok(b) = u8::try_from(c) {
+            bytes.push(b);
+            continue;
+        }
+        match fallback {
+            Latin1Fallback::Error => return Err(PgnWriteError::Unrepresentable(c)),
+            Latin1Fallback::Replace => {
+                let replacement = normalize_typography(c.encode_utf8(&mut [0; 4]));
+                if replacement.chars().all(|r| u8::try_from(r).is_ok()) {
+                    bytes.extend(replacement.chars().map(|r| r as u8));
+                } else {
+                    bytes.push(b'?');
+                }
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// Reads PGN games one at a time from a PGN database (a sequence of games), such as a file wrapped in a `BufReader`.
+/// Iterating over the reader yields each game, or an error if it could not be read or parsed; reading continues after invalid games.
+pub struct PgnReader<R: BufRead> {
+    reader: R,
+    /// A line which has been read, but belongs to the next game
+    pending_line: Option<String>,
+    games_read: usize,
+    bytes_read: u64,
+    /// The number of bytes read from the underlying (possibly compressed) file, if the reader was opened from a file
+    file_bytes_read: Option<Rc<Cell<u64>>>,
+    start: Option<Instant>,
+    total_bytes: Option<u64>,
+    progress_callback: Option<ProgressCallback>,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    /// Creates a reader over the given PGN database.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            pending_line: None,
+            games_read: 0,
+            bytes_read: 0,
+            file_bytes_read: None,
+            start: helpers::now(),
+            total_bytes: None,
+            progress_callback: None,
+        }
+    }
+
+    /// Registers a callback which is called with the progress of the reader after every game it reads.
+    /// If the total size of the input is known (such as the length of a file), it is used to estimate the time remaining;
+    /// readers created with [`PgnReader::open`] already know the size of the file.
+    pub fn with_progress(mut self, total_bytes: Option<u64>, callback: impl FnMut(&Progress) + 'static) -> Self {
+        self.total_bytes = total_bytes.or(self.total_bytes);
+        self.progress_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns the progress of the reader.
+    pub fn progress(&self) -> Progress {
+        Progress {
+            items: self.games_read,
+            bytes: self.file_bytes_read.as_ref().map_or(self.bytes_read, |n| n.get()),
+            total_bytes: self.total_bytes,
+            elapsed: helpers::elapsed_since(self.start),
+        }
+    }
+
+    /// Reads the text of the next game without parsing it, returning `None` at the end of the input.
+    pub fn next_game_text(&mut self) -> Option<io::Result<String>> {
+        let mut text = self.pending_line.take().unwrap_or_default();
+        let mut splitter = GameSplitter::default();
+        splitter.is_boundary(text.as_bytes());
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(n) => self.bytes_read += n as u64,
+                Err(e) => return Some(Err(e)),
+            }
+            if splitter.is_boundary(line.as_bytes()) {
+                self.pending_line = Some(line);
+                break;
+            }
+            text.push_str(&line);
+        }
+        if text.trim().is_empty() {
+            return None;
+        }
+        self.games_read += 1;
+        let progress = self.progress();
+        if let Some(callback) = &mut self.progress_callback {
+            callback(&progress);
+        }
+        Some(Ok(text))
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl PgnReader<Box<dyn BufRead>> {
+    /// Opens the PGN database at the given path, transparently decompressing it if it is compressed with zstd (`.pgn.zst`, requires the `zstd` feature)
+    /// or gzip (`.pgn.gz`, requires the `gzip` feature). The compression format is detected from the contents of the file.
+    /// Progress is reported in terms of the bytes of the file, so that the time remaining can be estimated for compressed files too.
+    /// This function is not available on `wasm32-unknown-unknown`, which has no file system.
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        let total_bytes = file.metadata()?.len();
+        let file_bytes_read = Rc::new(Cell::new(0));
+        let mut file = io::BufReader::new(CountingReader(file, Rc::clone(&file_bytes_read)));
+        let magic = file.fill_buf()?;
+        let reader: Box<dyn BufRead> = if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            #[cfg(feature = "zstd")]
+            {
+                Box::new(io::BufReader::new(zstd::Decoder::with_buffer(file)?))
+            }
+            #[cfg(not(feature = "zstd"))]
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "zstd-compressed PGN requires the zstd feature"));
+        } else if magic.starts_with(&[0x1f, 0x8b]) {
+            #[cfg(feature = "gzip")]
+            {
+                Box::new(io::BufReader::new(flate2::bufread::MultiGzDecoder::new(file)))
+            }
+            #[cfg(not(feature = "gzip"))]
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "gzip-compressed PGN requires the gzip feature"));
+        } else {
+            Box::new(file)
+        };
+        let mut pgn_reader = Self::new(reader);
+        pgn_reader.total_bytes = Some(total_bytes);
+        pgn_reader.file_bytes_read = Some(file_bytes_read);
+        Ok(pgn_reader)
+    }
+}
+
+/// Wraps a reader, counting the bytes read from it.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+struct CountingReader<R: io::Read>(R, Rc<Cell<u64>>);
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl<R: io::Read> io::Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.0.read(buf)?;
+        self.1.set(self.1.get() + n as u64);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<Pgn, PgnReadError>;
+
+    /// Reads and parses the next game.
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(match self.next_game_text()? {
+            Ok(text) => Pgn::try_from(text.as_str()).map_err(PgnReadError::from),
+            Err(e) => Err(e.into()),
+        })
+    }
+}
+
+/// Splits PGN database text into the text of each game without copying, yielding string slices borrowed from the text.
+/// The text is given as bytes, and each game is validated as UTF-8 when it is produced, so that a large database can be
+/// split without a pass over all of it first; a game which is not valid UTF-8 is yielded as an error, and splitting continues after it.
+pub struct PgnSlices<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> PgnSlices<'a> {
+    /// Creates an iterator over the games in the given PGN database text.
+    pub fn new(text: &'a [u8]) -> Self {
+        Self { rest: text }
+    }
+}
+
+impl<'a> Iterator for PgnSlices<'a> {
+    type Item = Result<&'a str, std::str::Utf8Error>;
+
+    /// Returns the text of the next game, or an error if it is not valid UTF-8.
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut splitter = GameSplitter::default();
+        let mut end = self.rest.len();
+        let mut offset = 0;
+        for line in self.rest.split_inclusive(|&b| b == b'\n') {
+            if splitter.is_boundary(line) {
+                end = offset;
+                break;
+            }
+            offset += line.len();
+        }
+        let (game, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        if game.trim_ascii().is_empty() {
+            None
+        } else {
+            Some(std::str::from_utf8(game))
+        }
+    }
+}
+
+/// Tracks where a game of a PGN database is while it is read line by line, to detect the line which starts the next game:
+/// a tag pair after the movetext of the current game has begun, outside a comment. This is shared by [`PgnReader`] and
+/// [`PgnSlices`], so that both split databases into games in the same way.
+#[derive(Default)]
+struct GameSplitter {
+    /// Whether the movetext of the current game has begun
+    in_movetext: bool,
+    /// Whether the current line is inside a `{ ... }` comment
+    in_comment: bool,
+}
+
+impl GameSplitter {
+    /// Checks whether a line starts a new game, and records whether it begins the movetext or opens or closes a comment.
+    /// The line is given as bytes, since the characters which matter are all ASCII.
+    fn is_boundary(&mut self, line: &[u8]) -> bool {
+        let trimmed = line.trim_ascii_start();
+        if !self.in_comment && trimmed.starts_with(b"[") {
+            return self.in_movetext;
+        }
+        if !trimmed.is_empty() {
+            self.in_movetext = true;
+        }
+        for &b in trimmed {
+            match b {
+                b'{' if !self.in_comment => self.in_comment = true,
+                b'}' if self.in_comment => self.in_comment = false,
+                b';' if !self.in_comment => break,
+                _ => (),
+            }
+        }
+        false
+    }
+}
+
+/// A PGN database file which is memory-mapped, so that games can be sliced from it without copying.
+#[cfg(feature = "mmap")]
+pub struct MappedPgnDatabase {
+    mmap: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedPgnDatabase {
+    /// Memory-maps the PGN database at the given path, returning an error if it cannot be opened.
+    /// The file must not be modified while it is mapped.
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the map is read-only, and the caller is responsible for not modifying the file while it is mapped.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Self { mmap })
+    }
+
+    /// Returns the contents of the database, which have not been validated as UTF-8.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    /// Returns an iterator over the text of each game in the database, borrowed from the map. Each game is validated as UTF-8
+    /// as it is produced (see [`PgnSlices`]), so the first game is yielded without reading the rest of the file.
+    pub fn games(&self) -> PgnSlices<'_> {
+        PgnSlices::new(self.as_bytes())
     }
 }
 
@@ -227,4 +742,6 @@ enum Token {
     FullmoveSan(usize, String, String),
     HalfmoveSan(usize, String),
     Result(String, String),
+    Clock(usize, Duration),
+    Checkpoint(usize, String),
 }
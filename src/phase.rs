@@ -0,0 +1,49 @@
+use super::{Piece, PieceType};
+
+/// The game phase with all of the pieces of the starting position on the board.
+pub(crate) const MAX_PHASE: i32 = 24;
+
+/// The lowest game phase of an opening: the starting material, less at most a pair of minor pieces.
+const OPENING_PHASE: i32 = 22;
+
+/// The highest game phase of an endgame, such as a rook and a minor piece each, or a queen each.
+const ENDGAME_PHASE: i32 = 8;
+
+/// Represents the phase of a game, as determined by the material on the board (see [`Position::phase`](crate::Position::phase)).
+///
+/// Each knight and bishop counts 1 towards the phase, each rook 2, and each queen 4, so that the starting position has a phase of 24
+/// (pawns and kings do not count). Positions with a phase of 22 or more are openings, and those with a phase of 8 or less are endgames.
+/// Since only material is considered, a game in which few pieces have been exchanged is still an opening however long it has gone on.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum GamePhase {
+    Opening,
+    Middlegame,
+    Endgame,
+}
+
+impl GamePhase {
+    /// Determines the phase of the position with the given content.
+    pub(crate) fn of(content: &[Option<Piece>; 64]) -> Self {
+        match phase_value(content) {
+            OPENING_PHASE.. => Self::Opening,
+            ..=ENDGAME_PHASE => Self::Endgame,
+            _ => Self::Middlegame,
+        }
+    }
+}
+
+/// Returns the contribution of a type of piece to the game phase.
+pub(crate) fn weight(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::N | PieceType::B => 1,
+        PieceType::R => 2,
+        PieceType::Q => 4,
+        PieceType::P | PieceType::K => 0,
+    }
+}
+
+/// Returns the game phase of the position with the given content, which is at most [`MAX_PHASE`] (early promotions can take
+/// the sum of the weights beyond that of the starting position).
+pub(crate) fn phase_value(content: &[Option<Piece>; 64]) -> i32 {
+    content.iter().flatten().map(|Piece(piece_type, _)| weight(*piece_type)).sum::<i32>().min(MAX_PHASE)
+}
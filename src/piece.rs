@@ -63,6 +63,42 @@ pub enum PieceType {
     P,
 }
 
+impl PieceType {
+    /// All the types of pieces.
+    pub const ALL: [PieceType; 6] = [Self::K, Self::Q, Self::B, Self::N, Self::R, Self::P];
+
+    /// The types of pieces that a pawn can promote to.
+    pub const PROMOTION_TYPES: [PieceType; 4] = [Self::Q, Self::R, Self::B, Self::N];
+
+    /// Returns the standard point value of the piece type (the king is valued at 0).
+    /// Use [`PieceValues`] to work with custom values.
+    pub fn value(&self) -> u32 {
+        PieceValues::default().value_of(*self)
+    }
+
+    /// Checks whether a pawn can promote to this type of piece.
+    pub fn is_promotion_type(&self) -> bool {
+        Self::PROMOTION_TYPES.contains(self)
+    }
+
+    /// Checks whether this type of piece moves along lines (i.e. it is a queen, rook, or bishop).
+    pub fn is_long_range(&self) -> bool {
+        matches!(self, Self::Q | Self::R | Self::B)
+    }
+
+    /// Returns the figurine (Unicode chess symbol) representing the piece type, as used in figurine algebraic notation.
+    /// The white symbols are used regardless of color.
+    pub fn to_figurine(&self) -> char {
+        Piece(*self, Color::White).to_string().chars().next().unwrap()
+    }
+
+    /// Attempts to convert a figurine (Unicode chess symbol of either color) to a `PieceType`.
+    pub fn from_figurine(figurine: char) -> Option<Self> {
+        let offset = (figurine as u32).checked_sub(0x2654)?;
+        [Self::K, Self::Q, Self::R, Self::B, Self::N, Self::P].get(offset as usize % 6).filter(|_| offset < 12).copied()
+    }
+}
+
 impl TryFrom<char> for PieceType {
     type Error = InvalidPieceCharacterError;
 
@@ -102,3 +138,42 @@ impl fmt::Display for PieceType {
         write!(f, "{}", char::from(*self))
     }
 }
+
+/// Represents a set of point values for the types of pieces.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct PieceValues {
+    pub king: u32,
+    pub queen: u32,
+    pub rook: u32,
+    pub bishop: u32,
+    pub knight: u32,
+    pub pawn: u32,
+}
+
+impl PieceValues {
+    /// Returns the value of the given type of piece.
+    pub fn value_of(&self, piece_type: PieceType) -> u32 {
+        match piece_type {
+            PieceType::K => self.king,
+            PieceType::Q => self.queen,
+            PieceType::R => self.rook,
+            PieceType::B => self.bishop,
+            PieceType::N => self.knight,
+            PieceType::P => self.pawn,
+        }
+    }
+}
+
+impl Default for PieceValues {
+    /// The standard piece values: a queen is worth 9 points, a rook 5, a bishop or knight 3, and a pawn 1 (the king is valued at 0).
+    fn default() -> Self {
+        Self {
+            king: 0,
+            queen: 9,
+            rook: 5,
+            bishop: 3,
+            knight: 3,
+            pawn: 1,
+        }
+    }
+}
@@ -1,8 +1,12 @@
-use super::{helpers, Color, IllegalMoveError, InvalidSanMoveError, Move, Piece, PieceType, SpecialMoveType};
+use super::{
+    attacks, helpers, retro, tables, AttackMap, Bitboard, CancelToken, Cancellable, CastlingRights, Color, EndgameClass, GamePhase, IllegalMoveError, IllegalMoveReason, InvalidLanMoveError,
+    InvalidSanMoveError, Move, MoveList, PawnStructure, Piece, PieceMatcher, PiecePattern, PieceType, SpecialMoveType, SquareMapping,
+};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     fmt,
     sync::{Mutex, OnceLock},
+    vec,
 };
 
 /// Returns the cached positions and their legal moves.
@@ -11,6 +15,52 @@ fn legal_move_cache() -> &'static Mutex<HashMap<Position, Vec<Move>>> {
     LEGAL_MOVE_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
+/// Random keys for pawn hashing, indexed by [white pawn, black pawn, white king, black king] and square, generated with SplitMix64.
+const PAWN_HASH_KEYS: [[u64; 64]; 4] = {
+    let mut keys = [[0; 64]; 4];
+    let mut state: u64 = 0x5eed_0f9a_3bce_1100;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[i / 64][i % 64] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+};
+
+/// Returns the pawn hash key of a piece on a square, if the piece is a pawn or a king.
+fn pawn_hash_key(piece: Option<Piece>, sq: usize) -> u64 {
+    let index = match piece {
+        Some(Piece(PieceType::P, Color::White)) => 0,
+        Some(Piece(PieceType::P, Color::Black)) => 1,
+        Some(Piece(PieceType::K, Color::White)) => 2,
+        Some(Piece(PieceType::K, Color::Black)) => 3,
+        _ => return 0,
+    };
+    PAWN_HASH_KEYS[index][sq]
+}
+
+/// Random keys for Zobrist hashing, generated with SplitMix64. Rows `0..12` are indexed by piece (white pawn, knight, bishop, rook, queen, king,
+/// then black) and square, rows `12..16` by castling right (K, Q, k, q) and rook square, row 16 by en passant target square,
+/// and the first key of row 17 is for black to move.
+const ZOBRIST_KEYS: [[u64; 64]; 18] = {
+    let mut keys = [[0; 64]; 18];
+    let mut state: u64 = 0x2b0b_51c4_7e1d_ba5e;
+    let mut i = 0;
+    while i < 18 * 64 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[i / 64][i % 64] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+};
+
 /// The structure for a chess position
 #[derive(Eq, PartialEq, Hash, Clone, Debug)]
 pub struct Position {
@@ -24,15 +74,74 @@ pub struct Position {
     pub(crate) ep_target: Option<usize>,
 }
 
+/// Represents the parts of a position that are compared for repetition: the placement of pieces, the side to move,
+/// the castling rights, and the en passant target square (only if an en passant capture is legal).
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct RepetitionKey {
+    content: [Option<Piece>; 64],
+    side: Color,
+    castling_rights: [Option<usize>; 4],
+    ep_target: Option<usize>,
+}
+
+/// A lazy iterator over the legal moves in a position, created by [`Position::legal_moves`] and [`Board::legal_moves`](crate::Board::legal_moves).
+/// Moves are generated one square at a time, in the same order as [`Position::gen_non_illegal_moves`].
+#[derive(Clone, Debug)]
+pub struct LegalMoves<'a> {
+    position: &'a Position,
+    /// The next square to generate moves from
+    sq: usize,
+    /// The remaining moves generated from the previous square
+    moves: vec::IntoIter<Move>,
+}
+
+impl<'a> LegalMoves<'a> {
+    fn new(position: &'a Position) -> Self {
+        match legal_move_cache().lock().unwrap().get(position) {
+            Some(v) => Self {
+                position,
+                sq: 64,
+                moves: v.clone().into_iter(),
+            },
+            None => Self {
+                position,
+                sq: 0,
+                moves: Vec::new().into_iter(),
+            },
+        }
+    }
+
+    /// Creates an iterator which yields no moves, for positions in which the game is over.
+    pub(crate) fn empty(position: &'a Position) -> Self {
+        Self {
+            position,
+            sq: 64,
+            moves: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl Iterator for LegalMoves<'_> {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        loop {
+            if let Some(move_) = self.moves.next() {
+                return Some(move_);
+            }
+            if self.sq >= 64 {
+                return None;
+            }
+            self.moves = self.position.gen_non_illegal_moves_sq(self.sq).into_iter();
+            self.sq += 1;
+        }
+    }
+}
+
 impl Position {
     /// Generates an FEN string representing the board data, active color, castling rights, and en passant target in the position.
     pub fn to_fen(&self) -> String {
-        let Self {
-            content,
-            side,
-            castling_rights,
-            ep_target,
-        } = self;
+        let Self { content, side, ep_target, .. } = self;
         let mut rankstrs = Vec::new();
         for rank in content.chunks(8).rev() {
             let mut rankstr = String::new();
@@ -58,40 +167,7 @@ impl Position {
         }
         let board_data = rankstrs.join("/");
         let active_color = char::from(*side).to_string();
-        let mut castling_availability = String::new();
-        let count_rooks = |rng, color| helpers::count_piece(rng, Piece(PieceType::R, color), content);
-        let (wk, bk) = (helpers::find_king(Color::White, content), helpers::find_king(Color::Black, content));
-        if castling_rights[0].is_some() {
-            castling_availability.push(if count_rooks(wk + 1..8, Color::White) == 1 {
-                'K'
-            } else {
-                helpers::idx_to_sq(castling_rights[0].unwrap()).0.to_ascii_uppercase()
-            });
-        }
-        if castling_rights[1].is_some() {
-            castling_availability.push(if count_rooks(0..wk, Color::White) == 1 {
-                'Q'
-            } else {
-                helpers::idx_to_sq(castling_rights[1].unwrap()).0.to_ascii_uppercase()
-            });
-        }
-        if castling_rights[2].is_some() {
-            castling_availability.push(if count_rooks(bk + 1..64, Color::Black) == 1 {
-                'k'
-            } else {
-                helpers::idx_to_sq(castling_rights[2].unwrap()).0
-            });
-        }
-        if castling_rights[3].is_some() {
-            castling_availability.push(if count_rooks(56..bk, Color::Black) == 1 {
-                'q'
-            } else {
-                helpers::idx_to_sq(castling_rights[2].unwrap()).0
-            });
-        }
-        if castling_availability.is_empty() {
-            castling_availability.push('-');
-        }
+        let castling_availability = self.castling_rights().to_fen_field(content);
         let en_passant_target_square;
         if let Some(target) = ep_target {
             let (f, r) = helpers::idx_to_sq(*target);
@@ -103,30 +179,66 @@ impl Position {
     }
 
     /// Converts a `Move` to SAN, returning an error if the move is illegal.
+    ///
+    /// Moves are disambiguated minimally, as required by the PGN standard: only if another piece of the same type can legally move
+    /// to the same square, by the file of the moved piece if that suffices, otherwise by its rank if that suffices, and otherwise by both.
     pub fn move_to_san(&self, move_: Move) -> Result<String, IllegalMoveError> {
-        let legal = self.gen_non_illegal_moves();
-        let move_ = match helpers::as_legal(move_, &legal) {
+        self.move_to_san_among(move_, &self.gen_non_illegal_moves())
+    }
+
+    /// Converts a `Move` to SAN, given the legal moves in the position, returning an error if the move is not one of them.
+    pub(crate) fn move_to_san_among(&self, move_: Move, legal: &[Move]) -> Result<String, IllegalMoveError> {
+        let move_ = match helpers::as_legal(move_, legal) {
             Some(m) => m,
             _ => return Err(IllegalMoveError(move_)),
         };
+        let rivals = Self::rivals(legal, &self.content).remove(&(move_.1, self.content[move_.0].unwrap().0)).unwrap_or_default();
+        Ok(self.san_with_rivals(move_, &rivals))
+    }
+
+    /// Converts every legal move to SAN, in the order of [`Position::gen_non_illegal_moves`].
+    /// This is faster than calling [`Position::move_to_san`] for every move, since the work of disambiguating moves is shared.
+    pub fn legal_moves_san(&self) -> Vec<String> {
+        self.legal_moves_san_among(&self.gen_non_illegal_moves())
+    }
+
+    /// Converts the given legal moves in the position, which must be all of them, to SAN.
+    pub(crate) fn legal_moves_san_among(&self, legal: &[Move]) -> Vec<String> {
+        let rivals = Self::rivals(legal, &self.content);
+        legal
+            .iter()
+            .map(|&move_| self.san_with_rivals(move_, rivals.get(&(move_.1, self.content[move_.0].unwrap().0)).map_or(&[], Vec::as_slice)))
+            .collect()
+    }
+
+    /// Groups the source squares of the given moves by destination square and type of piece moved,
+    /// which determines how moves are disambiguated in SAN.
+    fn rivals(moves: &[Move], content: &[Option<Piece>; 64]) -> HashMap<(usize, PieceType), Vec<usize>> {
+        let mut rivals: HashMap<_, Vec<_>> = HashMap::new();
+        for &Move(src, dest, _) in moves {
+            if let Some(Piece(pt, _)) = content[src] {
+                let sources = rivals.entry((dest, pt)).or_default();
+                if !sources.contains(&src) {
+                    sources.push(src);
+                }
+            }
+        }
+        rivals
+    }
+
+    /// Converts a legal `Move` to SAN, given the source squares of the pieces of the same type that can move to the same square
+    /// (including the moved piece itself).
+    fn san_with_rivals(&self, move_: Move, rivals: &[usize]) -> String {
         let mut san = String::new();
         let Move(src, dest, spec) = move_;
         let Self { content, .. } = self;
         let (src_occ, dest_occ) = (content[src], content[dest]);
         let ((srcf, srcr), (destf, destr)) = (helpers::idx_to_sq(src), helpers::idx_to_sq(dest));
-        let new_content = self.with_move_made(move_).unwrap();
-        let suffix = if new_content.is_checkmate() {
-            "#"
-        } else if new_content.is_check() {
-            "+"
-        } else {
-            ""
-        };
-        let piece_type;
+        let suffix = self.check_suffix(move_);
         match src_occ {
             Some(Piece(pt, _)) => match pt {
                 PieceType::P => {
-                    return Ok(format!(
+                    return format!(
                         "{}{suffix}",
                         match spec {
                             Some(SpecialMoveType::EnPassant) => format!("{srcf}x{destf}{destr}"),
@@ -142,10 +254,10 @@ impl Position {
                                 }
                             ),
                         },
-                    ))
+                    )
                 }
                 PieceType::K => {
-                    return Ok(format!(
+                    return format!(
                         "{}{suffix}",
                         match spec {
                             Some(SpecialMoveType::CastlingKingside) => "O-O".to_owned(),
@@ -158,63 +270,15 @@ impl Position {
                                 }
                             ),
                         },
-                    ))
-                }
-                pt => {
-                    san.push(char::from(pt));
-                    piece_type = pt;
+                    )
                 }
+                pt => san.push(char::from(pt)),
             },
             _ => panic!("the universe is malfunctioning"),
         }
-        if legal
-            .iter()
-            .filter(|m| {
-                if m.1 == dest {
-                    if let Some(Piece(pt, _)) = content[m.0] {
-                        pt == piece_type
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
-            })
-            .count()
-            > 1
-        {
-            if legal
-                .iter()
-                .filter(|m| {
-                    if m.1 == dest {
-                        if let Some(Piece(pt, _)) = content[m.0] {
-                            pt == piece_type && helpers::squares_in_file(srcf).contains(&m.0)
-                        } else {
-                            false
-                        }
-                    } else {
-                        false
-                    }
-                })
-                .count()
-                > 1
-            {
-                if legal
-                    .iter()
-                    .filter(|m| {
-                        if m.1 == dest {
-                            if let Some(Piece(pt, _)) = content[m.0] {
-                                pt == piece_type && helpers::squares_in_rank(srcr).contains(&m.0)
-                            } else {
-                                false
-                            }
-                        } else {
-                            false
-                        }
-                    })
-                    .count()
-                    > 1
-                {
+        if rivals.len() > 1 {
+            if rivals.iter().filter(|sq| helpers::squares_in_file(srcf).contains(sq)).count() > 1 {
+                if rivals.iter().filter(|sq| helpers::squares_in_rank(srcr).contains(sq)).count() > 1 {
                     san.push(srcf);
                 }
                 san.push(srcr);
@@ -222,30 +286,146 @@ impl Position {
                 san.push(srcf);
             }
         }
-        Ok(format!(
+        format!(
             "{san}{}{destf}{destr}{suffix}",
             match dest_occ {
                 Some(_) => "x",
                 None => "",
             }
+        )
+    }
+
+    /// Converts a `Move` to figurine algebraic notation (such as `♘f3`), returning an error if the move is illegal.
+    /// This is SAN with the piece letters replaced by the figurines of [`PieceType::to_figurine`].
+    pub fn move_to_fan(&self, move_: Move) -> Result<String, IllegalMoveError> {
+        Ok(self
+            .move_to_san(move_)?
+            .chars()
+            .map(|c| match c {
+                'K' | 'Q' | 'R' | 'B' | 'N' => PieceType::try_from(c).unwrap().to_figurine(),
+                _ => c,
+            })
+            .collect())
+    }
+
+    /// Returns the suffix ("+" or "#") indicating whether the given legal move gives check or checkmate.
+    fn check_suffix(&self, move_: Move) -> &'static str {
+        let new_position = self.with_move_made(move_).unwrap();
+        if new_position.is_checkmate() {
+            "#"
+        } else if new_position.is_check() {
+            "+"
+        } else {
+            ""
+        }
+    }
+
+    /// Converts a `Move` to long algebraic notation (such as `Ng1-f3`, `e2-e4`, or `Bb5xc6+`), returning an error if the move is illegal.
+    pub fn move_to_lan(&self, move_: Move) -> Result<String, IllegalMoveError> {
+        let move_ = helpers::as_legal(move_, &self.gen_non_illegal_moves()).ok_or(IllegalMoveError(move_))?;
+        let Move(src, dest, spec) = move_;
+        let suffix = self.check_suffix(move_);
+        let piece = match spec {
+            Some(SpecialMoveType::CastlingKingside) => return Ok(format!("O-O{suffix}")),
+            Some(SpecialMoveType::CastlingQueenside) => return Ok(format!("O-O-O{suffix}")),
+            _ => match self.content[src] {
+                Some(Piece(PieceType::P, _)) | None => String::new(),
+                Some(Piece(pt, _)) => char::from(pt).to_string(),
+            },
+        };
+        let ((srcf, srcr), (destf, destr)) = (helpers::idx_to_sq(src), helpers::idx_to_sq(dest));
+        Ok(format!(
+            "{piece}{srcf}{srcr}{}{destf}{destr}{}{suffix}",
+            if self.is_capture(move_).unwrap() { 'x' } else { '-' },
+            match move_.promotion() {
+                Some(pt) => format!("={}", char::from(pt)),
+                None => String::new(),
+            }
         ))
     }
 
+    /// Constructs a `Move` from long algebraic notation (such as `Ng1-f3`, `e2e4`, or `Bb5xc6+`), returning an error if it is invalid or illegal.
+    /// Check and annotation suffixes are ignored.
+    pub fn lan_to_move(&self, lan: &str) -> Result<Move, InvalidLanMoveError> {
+        let err = || InvalidLanMoveError(lan.to_owned());
+        let cleaned = helpers::figurines_to_letters(lan.trim().trim_end_matches(['!', '?', '+', '#'])).replace('0', "O");
+        if cleaned.starts_with("O-O") {
+            return self.san_to_move(&cleaned).map_err(|_| err());
+        }
+        let components = helpers::parse_san_components(&cleaned).ok_or_else(err)?;
+        if components.src_file.is_none() || components.src_rank.is_none() {
+            return Err(err());
+        }
+        self.gen_non_illegal_moves().into_iter().find(|m| components.matches(m, &self.content)).ok_or_else(err)
+    }
+
     /// Constructs a `Move` from a SAN representation, returning an error if it is invalid or illegal.
+    /// Figurines of either color (such as in `♘f3` or `♞f6`) are accepted in place of piece letters.
     pub fn san_to_move(&self, san: &str) -> Result<Move, InvalidSanMoveError> {
-        let san = san.trim().replace('0', "O").replace(['+', '#'], "");
+        let normalized = helpers::figurines_to_letters(san.trim()).replace('0', "O").replace(['+', '#'], "");
         self.gen_non_illegal_moves()
             .into_iter()
-            .find(|&m| self.move_to_san(m).unwrap().replace(['+', '#'], "") == san)
+            .zip(self.legal_moves_san())
+            .find(|(_, legal_san)| legal_san.replace(['+', '#'], "") == normalized)
+            .map(|(m, _)| m)
             .ok_or(InvalidSanMoveError(san.to_owned()))
     }
 
+    /// Constructs a `Move` from a SAN representation, accepting only the exact SAN produced by [`Position::move_to_san`]:
+    /// minimally disambiguated, with letters rather than figurines, and with the correct check or checkmate suffix.
+    /// This makes `san -> Move -> san` conversion round-trip exactly, returning an error for any other SAN.
+    pub fn san_to_move_strict(&self, san: &str) -> Result<Move, InvalidSanMoveError> {
+        self.gen_non_illegal_moves()
+            .into_iter()
+            .zip(self.legal_moves_san())
+            .find(|(_, legal_san)| legal_san == san)
+            .map(|(m, _)| m)
+            .ok_or(InvalidSanMoveError(san.to_owned()))
+    }
+
+    /// Rewrites a leniently written SAN move (see [`Position::san_to_move_lenient`]) as the exact SAN produced by [`Position::move_to_san`],
+    /// returning an error if it is invalid, illegal, or ambiguous. Normalizing SAN that is already normalized leaves it unchanged.
+    pub fn normalize_san(&self, san: &str) -> Result<String, InvalidSanMoveError> {
+        Ok(self.move_to_san(self.san_to_move_lenient(san)?).unwrap())
+    }
+
+    /// Constructs a `Move` from a SAN representation like [`Position::san_to_move`], but also accepts the kind of SAN found in the wild:
+    /// * trailing annotations such as `!?`, `+` and `#`, even if they are inconsistent with the move
+    /// * castling written with zeros (`0-0`)
+    /// * an `e.p.` suffix on en passant captures
+    /// * unnecessary disambiguation (such as `Ngf3` when only one knight can move to f3), and missing or superfluous capture markers
+    /// * promotions written without '=' (such as `e8Q`)
+    ///
+    /// An error is returned if the move is invalid, illegal, or ambiguous.
+    pub fn san_to_move_lenient(&self, san: &str) -> Result<Move, InvalidSanMoveError> {
+        let err = || InvalidSanMoveError(san.to_owned());
+        let letters = helpers::figurines_to_letters(san);
+        let mut cleaned = letters.trim().trim_end_matches(['!', '?', '+', '#']).trim_end();
+        for suffix in ["e.p.", "ep"] {
+            cleaned = cleaned.strip_suffix(suffix).unwrap_or(cleaned).trim_end();
+        }
+        if let Ok(m) = self.san_to_move(cleaned) {
+            return Ok(m);
+        }
+        let components = helpers::parse_san_components(cleaned).ok_or_else(err)?;
+        let mut candidates = self.gen_non_illegal_moves().into_iter().filter(|m| components.matches(m, &self.content));
+        match (candidates.next(), candidates.next()) {
+            (Some(m), None) => Ok(m),
+            _ => Err(err()),
+        }
+    }
+
     /// Returns the position which would occur if the given move were played, returning an error if the move is illegal.
     pub fn with_move_made(&self, move_: Move) -> Result<Self, IllegalMoveError> {
         let move_ = match helpers::as_legal(move_, &self.gen_non_illegal_moves()) {
             Some(m) => m,
             _ => return Err(IllegalMoveError(move_)),
         };
+        Ok(self.with_legal_move_made(move_))
+    }
+
+    /// Returns the position which would occur if the given move were played, assuming it is legal (with its special move type resolved).
+    pub(crate) fn with_legal_move_made(&self, move_: Move) -> Self {
         let castling_rights_idx_offset = if self.side.is_white() { 0 } else { 2 };
         let Self {
             content,
@@ -258,27 +438,36 @@ impl Position {
         let moved_piece = content[move_src];
         match moved_piece {
             Some(Piece(PieceType::K, _)) => (castling_rights[castling_rights_idx_offset], castling_rights[castling_rights_idx_offset + 1]) = (None, None),
-            Some(Piece(PieceType::P, _)) => {
-                if (std::cmp::max(move_src, move_dest) - std::cmp::min(move_src, move_dest)) == 16 {
-                    ep_target = Some(if side.is_white() { move_src + 8 } else { move_src - 8 });
-                }
+            Some(Piece(PieceType::P, _)) if (std::cmp::max(move_src, move_dest) - std::cmp::min(move_src, move_dest)) == 16 => {
+                ep_target = Some(if side.is_white() { move_src + 8 } else { move_src - 8 });
             }
             _ => (),
         }
         for maybe_rook in [move_src, move_dest] {
-            let maybe_right = castling_rights.iter().enumerate().find(|(_, right)| right.is_some() && right.unwrap() == maybe_rook);
-            if maybe_right.is_some() {
-                castling_rights[maybe_right.unwrap().0] = None;
+            let maybe_right = castling_rights.iter().position(|&right| right == Some(maybe_rook));
+            if let Some(i) = maybe_right {
+                castling_rights[i] = None;
             }
         }
         side = !side;
         let new_content = helpers::change_content(content, &move_, &self.castling_rights);
-        Ok(Self {
+        Self {
             content: new_content,
             side,
             castling_rights,
             ep_target,
-        })
+        }
+    }
+
+    /// Returns the successor position after the given move, returning an error if the move is illegal.
+    /// This is a pure transition function: the position itself is untouched, and no history is involved (see [`Position::with_move_made`]).
+    pub fn with_move(&self, move_: &Move) -> Result<Self, IllegalMoveError> {
+        self.with_move_made(*move_)
+    }
+
+    /// Returns a lazy iterator over the legal moves in the position paired with the positions they lead to, assuming the game is ongoing.
+    pub fn successors(&self) -> impl Iterator<Item = (Move, Self)> + '_ {
+        self.legal_moves().map(|move_| (move_, self.with_legal_move_made(move_)))
     }
 
     /// Pretty-prints the position to a string, from the perspective of the side `perspective`.
@@ -327,38 +516,168 @@ impl Position {
     }
 
     /// Generates the legal moves in the position, assuming the game is ongoing.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     pub fn gen_non_illegal_moves(&self) -> Vec<Move> {
         if let Some(v) = legal_move_cache().lock().unwrap().get(self) {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(moves = v.len(), "legal move cache hit");
             return v.clone();
         }
-        let v = (0..64).fold(Vec::new(), |v, i| [v, self.gen_non_illegal_moves_sq(i)].concat());
+        let v = self.gen_non_illegal_moves_uncached();
         legal_move_cache().lock().unwrap().insert(self.clone(), v.clone());
         v
     }
 
+    /// Returns a lazy iterator over the legal moves in the position, assuming the game is ongoing.
+    pub fn legal_moves(&self) -> LegalMoves<'_> {
+        LegalMoves::new(self)
+    }
+
+    /// Checks whether the side to move has any legal move, stopping at the first one found.
+    pub fn has_legal_move(&self) -> bool {
+        self.legal_moves().next().is_some()
+    }
+
+    /// Generates the legal moves in the position without consulting or filling the legal move cache.
+    pub(crate) fn gen_non_illegal_moves_uncached(&self) -> Vec<Move> {
+        self.gen_move_list().into()
+    }
+
+    /// Generates the legal moves in the position into a [`MoveList`], assuming the game is ongoing. This neither allocates
+    /// nor consults the legal move cache, so it is the fastest way to generate moves in hot loops such as searches.
+    pub fn gen_move_list(&self) -> MoveList {
+        let mut moves = self.gen_pseudolegal_move_list();
+        moves.retain(|move_| self.is_pseudolegal_move_legal(move_));
+        moves
+    }
+
     /// Generates the legal moves **from** a specific square, assuming the game is ongoing.
     /// The square index `i` can be converted from a square name using the [`sq_to_idx`](super::sq_to_idx) function.
     pub fn gen_non_illegal_moves_sq(&self, i: usize) -> Vec<Move> {
+        let mut moves = MoveList::new();
+        self.push_pseudolegal_moves_sq(i, tables::occupancy(&self.content), &mut moves);
+        moves.retain(|move_| self.is_pseudolegal_move_legal(move_));
+        moves.into()
+    }
+
+    /// Generates the legal moves of the pieces of the given type belonging to the side to move, assuming the game is ongoing.
+    pub fn gen_non_illegal_moves_of(&self, piece_type: PieceType) -> Vec<Move> {
+        (0..64)
+            .filter(|&i| self.content[i] == Some(Piece(piece_type, self.side)))
+            .flat_map(|i| self.gen_non_illegal_moves_sq(i))
+            .collect()
+    }
+
+    /// Maps the square of every piece of the side to move to the destination squares of its legal moves (in ascending order),
+    /// assuming the game is ongoing. Pieces without legal moves map to no squares, and castling moves lead to the king's destination square.
+    /// The legal moves are generated once for all pieces (see [`Position::gen_non_illegal_moves`]), rather than square by square.
+    pub fn mobility_map(&self) -> BTreeMap<usize, Vec<usize>> {
+        let mut map: BTreeMap<usize, Vec<usize>> = (0..64)
+            .filter(|&i| matches!(self.content[i], Some(Piece(_, color)) if color == self.side))
+            .map(|i| (i, Vec::new()))
+            .collect();
+        for Move(src, dest, _) in self.gen_non_illegal_moves() {
+            map.entry(src).or_default().push(dest);
+        }
+        for dests in map.values_mut() {
+            // promotions have a move for every piece type with the same destination
+            dests.sort_unstable();
+            dests.dedup();
+        }
+        map
+    }
+
+    /// Checks whether a pseudolegal move is legal, i.e. it does not leave the king in check (or castle through check).
+    fn is_pseudolegal_move_legal(&self, move_: &Move) -> bool {
         let Self { content, side, castling_rights, .. } = self;
-        self.gen_pseudolegal_moves_sq(i)
-            .into_iter()
-            .filter(|move_| {
-                if let Move(src, dest, Some(SpecialMoveType::CastlingKingside | SpecialMoveType::CastlingQueenside)) = move_ {
-                    for sq in *std::cmp::min(src, dest)..=*std::cmp::max(src, dest) {
-                        if self.controls_square(sq, !*side) {
-                            return false;
-                        }
-                    }
-                    return true;
+        if let Move(src, dest, Some(SpecialMoveType::CastlingKingside | SpecialMoveType::CastlingQueenside)) = move_ {
+            for sq in *std::cmp::min(src, dest)..=*std::cmp::max(src, dest) {
+                if self.controls_square(sq, !*side) {
+                    return false;
                 }
-                !helpers::king_capture_pseudolegal(&helpers::change_content(content, move_, castling_rights), !*side)
-            })
-            .collect()
+            }
+            return true;
+        }
+        !helpers::king_capture_pseudolegal(&helpers::change_content(content, move_, castling_rights), !*side)
+    }
+
+    /// Generates the legal moves of the side to move which satisfy a filter, applying the filter before the (more expensive) legality check.
+    fn gen_filtered_moves(&self, filter: impl Fn(&Move) -> bool) -> Vec<Move> {
+        let mut moves = self.gen_pseudolegal_move_list();
+        moves.retain(|move_| filter(move_) && self.is_pseudolegal_move_legal(move_));
+        moves.into()
+    }
+
+    /// Checks whether a move (assumed to be pseudolegal) captures a piece.
+    pub(crate) fn captures(&self, move_: &Move) -> bool {
+        match move_.2 {
+            Some(SpecialMoveType::EnPassant) => true,
+            Some(SpecialMoveType::CastlingKingside | SpecialMoveType::CastlingQueenside) => false,
+            _ => self.content[move_.1].is_some(),
+        }
+    }
+
+    /// Generates the legal captures (including en passant captures and capturing promotions) in the position, assuming the game is ongoing.
+    pub fn gen_captures(&self) -> Vec<Move> {
+        self.gen_filtered_moves(|move_| self.captures(move_))
+    }
+
+    /// Generates the legal promotions (including capturing promotions) in the position, assuming the game is ongoing.
+    pub fn gen_promotions(&self) -> Vec<Move> {
+        self.gen_filtered_moves(|move_| matches!(move_.2, Some(SpecialMoveType::Promotion(_))))
+    }
+
+    /// Generates the legal quiet moves (moves which are neither captures nor promotions) in the position, assuming the game is ongoing.
+    pub fn gen_quiet_moves(&self) -> Vec<Move> {
+        self.gen_filtered_moves(|move_| !self.captures(move_) && !matches!(move_.2, Some(SpecialMoveType::Promotion(_))))
+    }
+
+    /// Generates the legal moves which give check in the position, assuming the game is ongoing.
+    pub fn gen_checks(&self) -> Vec<Move> {
+        self.legal_moves().filter(|&move_| self.with_legal_move_made(move_).is_check()).collect()
+    }
+
+    /// Generates the legal moves which get the side to move out of check, assuming the game is ongoing; this is empty if the side to move is not in check.
+    /// Only king moves, captures of the checking piece, and interpositions are considered, so this is much faster than filtering every legal move.
+    pub fn gen_check_evasions(&self) -> Vec<Move> {
+        let checkers = self.checkers();
+        if checkers.is_empty() {
+            return Vec::new();
+        }
+        let Some(king) = helpers::find_king(self.side, &self.content) else {
+            return Vec::new();
+        };
+        let mut king_moves = MoveList::new();
+        self.push_pseudolegal_moves_sq(king, tables::occupancy(&self.content), &mut king_moves);
+        king_moves.retain(|move_| move_.2.is_none() && self.is_pseudolegal_move_legal(move_));
+        let mut king_moves = Vec::from(king_moves);
+        if checkers.len() > 1 {
+            return king_moves;
+        }
+        let checker = checkers[0];
+        let mut targets = vec![checker];
+        for axis_direction in [-9, -8, -7, -1, 1, 7, 8, 9] {
+            let mut ray = Vec::new();
+            for current_sq in tables::ray(king, axis_direction) {
+                if current_sq == checker {
+                    targets.extend(ray);
+                    break;
+                }
+                if self.content[current_sq].is_some() {
+                    break;
+                }
+                ray.push(current_sq);
+            }
+        }
+        let ep_captured_sq = self.ep_target.map(|ep| if self.side.is_white() { ep - 8 } else { ep + 8 });
+        let mut evasions = self.gen_filtered_moves(|move_| move_.0 != king && (targets.contains(&move_.1) || (move_.2 == Some(SpecialMoveType::EnPassant) && ep_captured_sq == Some(checker))));
+        king_moves.append(&mut evasions);
+        king_moves
     }
 
     /// Checks whether the game is drawn by stalemate. Use [`Position::stalemated_side`] to know which side is in stalemate.
     pub fn is_stalemate(&self) -> bool {
-        !self.is_check() && self.gen_non_illegal_moves().is_empty()
+        !self.is_check() && !self.has_legal_move()
     }
 
     /// Checks whether any side is in check (a checkmate is also considered a check). Use [`Position::checked_side`] to know which side is in check.
@@ -368,7 +687,7 @@ impl Position {
 
     /// Checks whether any side is in checkmate. Use [`Position::checkmated_side`] to know which side is in checkmate.
     pub fn is_checkmate(&self) -> bool {
-        self.is_check() && self.gen_non_illegal_moves().is_empty()
+        self.is_check() && !self.has_legal_move()
     }
 
     /// Returns an optional boolean representing the side in stalemate (`None` if neither side is in stalemate).
@@ -401,10 +720,17 @@ impl Position {
     }
 
     /// Generates the pseudolegal moves in the position.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     pub fn gen_pseudolegal_moves(&self) -> Vec<Move> {
-        let mut pseudolegal_moves = Vec::new();
+        self.gen_pseudolegal_move_list().into()
+    }
+
+    /// Generates the pseudolegal moves in the position into a [`MoveList`].
+    pub fn gen_pseudolegal_move_list(&self) -> MoveList {
+        let mut pseudolegal_moves = MoveList::new();
+        let occupied = tables::occupancy(&self.content);
         for i in 0..64 {
-            pseudolegal_moves.append(&mut self.gen_pseudolegal_moves_sq(i));
+            self.push_pseudolegal_moves_sq(i, occupied, &mut pseudolegal_moves);
         }
         pseudolegal_moves
     }
@@ -412,212 +738,221 @@ impl Position {
     /// Generates the pseudolegal moves **from** a specific square.
     /// The square index `i` can be converted from a square name using the [`sq_to_idx`](super::sq_to_idx) function.
     pub fn gen_pseudolegal_moves_sq(&self, i: usize) -> Vec<Move> {
+        let mut pseudolegal_moves = MoveList::new();
+        self.push_pseudolegal_moves_sq(i, tables::occupancy(&self.content), &mut pseudolegal_moves);
+        pseudolegal_moves.into()
+    }
+
+    /// Appends the pseudolegal moves **from** a specific square to a move list.
+    /// The occupied squares of the position are passed in, so that they are computed once per generation pass rather than once per piece.
+    fn push_pseudolegal_moves_sq(&self, i: usize, occupied: Bitboard, pseudolegal_moves: &mut MoveList) {
         let Self {
             content,
             castling_rights,
             ep_target,
             side,
         } = self;
-        let mut pseudolegal_moves = Vec::new();
         if let Some(piece) = self.content[i] {
             if piece.1 != *side {
-                return pseudolegal_moves;
+                return;
             }
             match piece.0 {
                 PieceType::K => {
-                    let mut possible_dests = Vec::new();
-                    for axis in [1, 8, 7, 9] {
-                        if helpers::long_range_can_move(i, axis as isize) {
-                            possible_dests.push(i + axis);
-                        }
-                        if helpers::long_range_can_move(i, -(axis as isize)) {
-                            possible_dests.push(i - axis);
-                        }
-                    }
-                    possible_dests.retain(|&dest| match content[dest] {
-                        Some(Piece(_, color)) => color != *side,
-                        _ => true,
-                    });
-                    pseudolegal_moves.extend(possible_dests.into_iter().map(|d| Move(i, d, None)));
+                    pseudolegal_moves.extend(
+                        tables::king_targets(i)
+                            .filter(|&dest| match content[dest] {
+                                Some(Piece(_, color)) => color != *side,
+                                _ => true,
+                            })
+                            .map(|dest| Move(i, dest, None)),
+                    );
                     let castling_rights_idx_offset = if side.is_white() { 0 } else { 2 };
                     let (oo_sq, ooo_sq) = if side.is_white() { (6, 2) } else { (62, 58) };
                     let (kingside, queenside) = (castling_rights[castling_rights_idx_offset], castling_rights[castling_rights_idx_offset + 1]);
+                    // every square between the king and its destination and between the rook and its destination must be empty,
+                    // except for the castling king and rook themselves (which matters in Chess960)
+                    let path_clear = |r: usize, king_dest: usize, rook_dest: usize| {
+                        let squares = [i, r, king_dest, rook_dest];
+                        (*squares.iter().min().unwrap()..=*squares.iter().max().unwrap()).all(|sq| sq == i || sq == r || content[sq].is_none())
+                    };
                     if let Some(r) = kingside {
-                        match helpers::count_pieces(i + 1..=oo_sq, content) {
-                            0 => pseudolegal_moves.push(Move(i, oo_sq, Some(SpecialMoveType::CastlingKingside))),
-                            1 => {
-                                if helpers::find_all_pieces(i + 1..=oo_sq, content)[0] == r {
-                                    pseudolegal_moves.push(Move(i, oo_sq, Some(SpecialMoveType::CastlingKingside)))
-                                }
-                            }
-                            _ => (),
+                        if path_clear(r, oo_sq, oo_sq - 1) {
+                            pseudolegal_moves.push(Move(i, oo_sq, Some(SpecialMoveType::CastlingKingside)));
                         }
                     }
                     if let Some(r) = queenside {
-                        match helpers::count_pieces(ooo_sq..i, content) {
-                            0 => pseudolegal_moves.push(Move(i, ooo_sq, Some(SpecialMoveType::CastlingQueenside))),
-                            1 => {
-                                if helpers::find_all_pieces(ooo_sq..i, content)[0] == r {
-                                    pseudolegal_moves.push(Move(i, ooo_sq, Some(SpecialMoveType::CastlingQueenside)))
-                                }
-                            }
-                            _ => (),
+                        if path_clear(r, ooo_sq, ooo_sq + 1) {
+                            pseudolegal_moves.push(Move(i, ooo_sq, Some(SpecialMoveType::CastlingQueenside)));
                         }
                     }
-                    pseudolegal_moves
                 }
                 PieceType::N => {
-                    let b_r_axes = [(7, [-1, 8]), (9, [8, 1]), (-7, [1, -8]), (-9, [-8, -1])];
-                    let mut dest_squares = Vec::new();
-                    for (b_axis, r_axes) in b_r_axes {
-                        if !helpers::long_range_can_move(i, b_axis) {
-                            continue;
-                        }
-                        let b_dest = i as isize + b_axis;
-                        for r_axis in r_axes {
-                            if !helpers::long_range_can_move(b_dest as usize, r_axis) {
-                                continue;
-                            }
-                            dest_squares.push((b_dest + r_axis) as usize);
-                        }
-                    }
                     pseudolegal_moves.extend(
-                        dest_squares
-                            .into_iter()
+                        tables::knight_targets(i)
                             .filter(|&dest| match content[dest] {
                                 Some(Piece(_, color)) => color != *side,
                                 _ => true,
                             })
                             .map(|dest| Move(i, dest, None)),
                     );
-                    pseudolegal_moves
                 }
                 PieceType::P => {
-                    let mut possible_dests = Vec::new();
+                    let mut push_pawn_move = |dest: usize, ep: bool| {
+                        if (0..8).contains(&dest) || (56..64).contains(&dest) {
+                            pseudolegal_moves.extend(PieceType::PROMOTION_TYPES.into_iter().map(|p| Move(i, dest, Some(SpecialMoveType::Promotion(p)))));
+                        } else {
+                            pseudolegal_moves.push(Move(i, dest, if ep { Some(SpecialMoveType::EnPassant) } else { None }));
+                        }
+                    };
                     if side.is_white() {
                         if content[i + 8].is_none() {
-                            possible_dests.push((i + 8, false));
+                            push_pawn_move(i + 8, false);
                             if (8..16).contains(&i) && content[i + 16].is_none() {
-                                possible_dests.push((i + 16, false))
+                                push_pawn_move(i + 16, false);
                             }
                         }
-                        if helpers::long_range_can_move(i, 7) {
-                            if let Some(Piece(_, color)) = content[i + 7] {
-                                if color.is_black() {
-                                    possible_dests.push((i + 7, false));
-                                }
-                            } else if ep_target.is_some() && ep_target.unwrap() == i + 7 {
-                                possible_dests.push((i + 7, true));
-                            }
-                        }
-                        if helpers::long_range_can_move(i, 9) {
-                            if let Some(Piece(_, color)) = content[i + 9] {
-                                if color.is_black() {
-                                    possible_dests.push((i + 9, false));
-                                }
-                            } else if ep_target.is_some() && ep_target.unwrap() == i + 9 {
-                                possible_dests.push((i + 9, true));
-                            }
-                        }
-                    } else {
-                        if content[i - 8].is_none() {
-                            possible_dests.push((i - 8, false));
-                            if (48..56).contains(&i) && content[i - 16].is_none() {
-                                possible_dests.push((i - 16, false))
-                            }
-                        }
-                        if helpers::long_range_can_move(i, -9) {
-                            if let Some(Piece(_, color)) = content[i - 9] {
-                                if color.is_white() {
-                                    possible_dests.push((i - 9, false));
-                                }
-                            } else if ep_target.is_some() && ep_target.unwrap() == i - 9 {
-                                possible_dests.push((i - 9, true));
-                            }
-                        }
-                        if helpers::long_range_can_move(i, -7) {
-                            if let Some(Piece(_, color)) = content[i - 7] {
-                                if color.is_white() {
-                                    possible_dests.push((i - 7, false));
-                                }
-                            } else if ep_target.is_some() && ep_target.unwrap() == i - 7 {
-                                possible_dests.push((i - 7, true));
-                            }
+                    } else if content[i - 8].is_none() {
+                        push_pawn_move(i - 8, false);
+                        if (48..56).contains(&i) && content[i - 16].is_none() {
+                            push_pawn_move(i - 16, false);
                         }
                     }
-                    pseudolegal_moves.extend(possible_dests.into_iter().flat_map(|(dest, ep)| {
-                        if (0..8).contains(&dest) || (56..64).contains(&dest) {
-                            [PieceType::Q, PieceType::R, PieceType::B, PieceType::N]
-                                .into_iter()
-                                .map(|p| Move(i, dest, Some(SpecialMoveType::Promotion(p))))
-                                .collect()
-                        } else {
-                            vec![Move(i, dest, if ep { Some(SpecialMoveType::EnPassant) } else { None })]
+                    for dest in tables::squares(tables::pawn_attacks(*side, i)) {
+                        match content[dest] {
+                            Some(Piece(_, color)) if color != *side => push_pawn_move(dest, false),
+                            None if *ep_target == Some(dest) => push_pawn_move(dest, true),
+                            _ => (),
                         }
-                    }));
-                    pseudolegal_moves
-                }
-                long_range_type => {
-                    pseudolegal_moves.append(&mut self.gen_long_range_piece_pseudolegal_moves(i, long_range_type));
-                    pseudolegal_moves
+                    }
                 }
+                long_range_type => self.push_long_range_piece_pseudolegal_moves(i, long_range_type, occupied, pseudolegal_moves),
             }
-        } else {
-            pseudolegal_moves
         }
     }
 
-    /// Generates pseudolegal moves for a long-range piece.
-    pub(crate) fn gen_long_range_piece_pseudolegal_moves(&self, sq: usize, piece_type: PieceType) -> Vec<Move> {
+    /// Appends the pseudolegal moves of a long-range piece to a move list.
+    fn push_long_range_piece_pseudolegal_moves(&self, sq: usize, piece_type: PieceType, occupied: Bitboard, pseudolegal_moves: &mut MoveList) {
         let Self { content, side, .. } = self;
-        let axes = match piece_type {
-            PieceType::Q => vec![1, 8, 7, 9],
-            PieceType::R => vec![1, 8],
-            PieceType::B => vec![7, 9],
+        let (axes, attacks): (&[isize], _) = match piece_type {
+            PieceType::Q => (&[1, 8, 7, 9], tables::queen_attacks(sq, occupied)),
+            PieceType::R => (&[1, 8], tables::rook_attacks(sq, occupied)),
+            PieceType::B => (&[7, 9], tables::bishop_attacks(sq, occupied)),
             _ => panic!("not a long-range piece"),
         };
-        let mut dest_squares = Vec::new();
-        for axis in axes {
-            'axis: for axis_direction in [-axis, axis] {
-                let mut current_sq = sq as isize;
-                while helpers::long_range_can_move(current_sq as usize, axis_direction) {
-                    let mut skip = false;
-                    current_sq += axis_direction;
-                    if let Some(Piece(_, color)) = content[current_sq as usize] {
-                        if color == *side {
-                            continue 'axis;
-                        } else {
-                            skip = true;
+        for &axis in axes {
+            for axis_direction in [-axis, axis] {
+                pseudolegal_moves.extend(
+                    tables::ray_within(sq, axis_direction, attacks)
+                        .filter(|&dest| !matches!(content[dest], Some(Piece(_, color)) if color == *side))
+                        .map(|dest| Move(sq, dest, None)),
+                );
+            }
+        }
+    }
+
+    /// Returns the indices of the squares of all the pieces of color `side` that attack the square `sq`, in ascending order.
+    /// A piece attacks a square if it could capture an enemy piece on that square, so passing the color of the piece
+    /// on `sq` as `side` returns the pieces defending it.
+    /// The square index `sq` can be converted from a square name using the [`sq_to_idx`](super::sq_to_idx) function.
+    pub fn attackers_of(&self, sq: usize, side: Color) -> Vec<usize> {
+        tables::squares(attacks::attackers(&self.content, sq, side, tables::occupancy(&self.content))).collect()
+    }
+
+    /// Counts the pieces of color `side` that attack the square `sq`. See [`Position::attackers_of`] for details.
+    pub fn count_attackers(&self, sq: usize, side: Color) -> usize {
+        attacks::attackers(&self.content, sq, side, tables::occupancy(&self.content)).count_ones() as usize
+    }
+
+    /// Returns the squares attacked by the piece on the square `sq`, or no squares if it is empty (see [`Position::attackers_of`] for what counts as an attack).
+    /// Long-range pieces attack along their lines up to and including the first occupied square.
+    pub fn attacks_from(&self, sq: usize) -> Bitboard {
+        attacks::attacks_from(&self.content, sq)
+    }
+
+    /// Returns the squares attacked by at least one piece of color `side`, including squares occupied by its own pieces (which it defends).
+    pub fn attacked_squares(&self, side: Color) -> Bitboard {
+        attacks::attacked_squares(&self.content, side)
+    }
+
+    /// Returns the attack map of the position, giving the attackers of every square for both sides, such as for highlighting
+    /// the squares each side controls (see [`AttackMap`]).
+    pub fn attack_map(&self) -> AttackMap {
+        AttackMap::of(&self.content)
+    }
+
+    /// Returns the indices of the squares of the pieces giving check to the side to move, in ascending order
+    /// (none if the side to move has no king, as in Horde).
+    pub fn checkers(&self) -> Vec<usize> {
+        helpers::find_king(self.side, &self.content).map_or_else(Vec::new, |king| self.attackers_of(king, !self.side))
+    }
+
+    /// Returns a bit mask of the squares holding pieces that match the given matcher, where bit `i` stands for square `i`.
+    /// For example, `position.piece_mask(PieceMatcher::new(PieceType::P, Color::White))` gives the squares of the white pawns.
+    pub fn piece_mask(&self, matcher: PieceMatcher) -> Bitboard {
+        self.content
+            .iter()
+            .enumerate()
+            .filter(|(_, piece)| piece.is_some_and(|p| matcher.matches(p)))
+            .fold(0, |mask, (sq, _)| mask | 1 << sq)
+    }
+
+    /// Returns the pieces on the squares of the position, indexed by the given mapping (see [`SquareMapping`]).
+    pub fn to_grid(&self, mapping: SquareMapping) -> [Option<Piece>; 64] {
+        mapping.grid_from_rschess(&self.content)
+    }
+
+    /// Checks whether the placement of pieces in the position matches the given pattern (see [`PiecePattern`]).
+    pub fn matches_pattern(&self, pattern: &PiecePattern) -> bool {
+        pattern.matches(self)
+    }
+
+    /// Returns the absolutely pinned pieces of color `side`, in the format (_pinned piece square_, _pin ray_).
+    /// The pin ray consists of the squares from the king (exclusive) to the pinning piece (inclusive), which are
+    /// the only squares the pinned piece may move to. There are no pins if the side has no king (as in Horde).
+    pub fn pinned(&self, side: Color) -> Vec<(usize, Vec<usize>)> {
+        let content = &self.content;
+        let Some(king) = helpers::find_king(side, content) else {
+            return Vec::new();
+        };
+        let mut pins = Vec::new();
+        for (axis, piece_types) in [
+            (1, [PieceType::R, PieceType::Q]),
+            (8, [PieceType::R, PieceType::Q]),
+            (7, [PieceType::B, PieceType::Q]),
+            (9, [PieceType::B, PieceType::Q]),
+        ] {
+            for axis_direction in [-axis, axis] {
+                let mut ray = Vec::new();
+                let mut pinned = None;
+                for current_sq in tables::ray(king, axis_direction) {
+                    ray.push(current_sq);
+                    match content[current_sq] {
+                        Some(Piece(_, color)) if color == side => {
+                            if pinned.is_some() {
+                                break;
+                            }
+                            pinned = Some(current_sq);
                         }
-                    }
-                    dest_squares.push(current_sq as usize);
-                    if skip {
-                        continue 'axis;
+                        Some(Piece(pt, _)) => {
+                            if let Some(pinned) = pinned {
+                                if piece_types.contains(&pt) {
+                                    pins.push((pinned, ray));
+                                }
+                            }
+                            break;
+                        }
+                        None => (),
                     }
                 }
             }
         }
-        dest_squares.into_iter().map(|dest| Move(sq, dest, None)).collect()
+        pins.sort();
+        pins
     }
 
     /// Checks whether the given side controls a specified square in this position.
     pub(crate) fn controls_square(&self, sq: usize, side: Color) -> bool {
-        let Self {
-            mut content,
-            castling_rights,
-            ep_target,
-            ..
-        } = self.clone();
-        content[sq] = Some(Piece(PieceType::P, !side));
-        Self {
-            content,
-            side,
-            castling_rights,
-            ep_target,
-        }
-        .gen_pseudolegal_moves()
-        .into_iter()
-        .any(|Move(_, dest, _)| dest == sq)
+        attacks::is_attacked(&self.content, sq, side)
     }
 
     /// Counts the material on the board. This function is used by [`Position::is_insufficient_material`] to determine whether there is insufficient checkmating material.
@@ -636,6 +971,44 @@ impl Position {
         material
     }
 
+    /// Returns the difference between white's and black's material in standard point values (see [`PieceType::value`]),
+    /// which is positive if white is ahead and negative if black is ahead.
+    pub fn material_balance(&self) -> i32 {
+        self.content.iter().flatten().map(|Piece(pt, c)| pt.value() as i32 * c.sign()).sum()
+    }
+
+    /// Analyzes the pawn structure of the given side: its doubled, isolated, backward, and passed pawns, its pawn islands,
+    /// and the open and half-open files (see [`PawnStructure`]).
+    pub fn pawn_structure(&self, side: Color) -> PawnStructure {
+        PawnStructure::of(&self.content, side)
+    }
+
+    /// Determines the phase of the game (opening, middlegame, or endgame) from the material on the board (see [`GamePhase`]).
+    pub fn phase(&self) -> GamePhase {
+        GamePhase::of(&self.content)
+    }
+
+    /// Returns the material signature of the position: the pieces of white and then of black, each from the king to the pawns,
+    /// separated by `v`, as in `KRPPvKRP` (the naming of endgame tablebases, with white first).
+    pub fn material_signature(&self) -> String {
+        let order = [PieceType::K, PieceType::Q, PieceType::R, PieceType::B, PieceType::N, PieceType::P];
+        let side = |color: Color| -> String {
+            order
+                .iter()
+                .flat_map(|&piece_type| {
+                    let count = self.content.iter().filter(|&&p| p == Some(Piece(piece_type, color))).count();
+                    std::iter::repeat_n(char::from(piece_type), count)
+                })
+                .collect()
+        };
+        format!("{}v{}", side(Color::White), side(Color::Black))
+    }
+
+    /// Classifies the position as an endgame by its material signature, such as a rook endgame or opposite-colored bishops (see [`EndgameClass`] for the taxonomy).
+    pub fn endgame_class(&self) -> EndgameClass {
+        EndgameClass::of(&self.content)
+    }
+
     /// Checks whether the game is drawn by insufficient material.
     pub fn is_insufficient_material(&self) -> bool {
         let copy1 = self.count_material();
@@ -668,18 +1041,311 @@ impl Position {
         false
     }
 
+    /// Checks whether the position is dead (FIDE Article 5.2.2), meaning no sequence of legal moves can lead to checkmate.
+    /// This covers insufficient material as well as positions such as blocked pawn walls, by exploring every position reachable from this one.
+    /// The exploration is bounded, so positions with too many reachable positions are conservatively considered not dead.
+    pub fn is_dead_position(&self) -> bool {
+        const MAX_POSITIONS: usize = 5_000;
+        if self.is_insufficient_material() {
+            return true;
+        }
+        let mut seen = HashSet::from([self.clone()]);
+        let mut queue = VecDeque::from([self.clone()]);
+        while let Some(position) = queue.pop_front() {
+            let moves = position.gen_non_illegal_moves_uncached();
+            if moves.is_empty() && position.is_check() {
+                return false;
+            }
+            for move_ in moves {
+                let next = position.with_legal_move_made(move_);
+                if !seen.contains(&next) {
+                    if seen.len() == MAX_POSITIONS {
+                        return false;
+                    }
+                    seen.insert(next.clone());
+                    queue.push_back(next);
+                }
+            }
+        }
+        true
+    }
+
     /// Returns which side's turn it is to move.
     pub fn side_to_move(&self) -> Color {
         self.side
     }
 
+    /// Returns the en passant target square, but only if an en passant capture is actually legal in the position.
+    pub fn legal_ep_target(&self) -> Option<usize> {
+        self.ep_target.filter(|_| self.gen_non_illegal_moves().iter().any(|m| m.2 == Some(SpecialMoveType::EnPassant)))
+    }
+
+    /// Checks whether the en passant target square, if any, is behind a pawn of the side which has just moved that can have just advanced two squares.
+    pub(crate) fn has_valid_ep_target(&self) -> bool {
+        let Some(target) = self.ep_target else {
+            return true;
+        };
+        let side = self.side;
+        target / 8 == if side.is_white() { 5 } else { 2 } && {
+            let (pawn_sq, start_sq) = if side.is_white() { (target - 8, target + 8) } else { (target + 8, target - 8) };
+            self.content[target].is_none() && self.content[start_sq].is_none() && self.content[pawn_sq] == Some(Piece(PieceType::P, !side))
+        }
+    }
+
+    /// Checks a position for signs that it cannot arise in a game from the standard starting position, returning `false` if any are found.
+    /// This is a heuristic in the style of retrograde analysis: a position which passes may still be unreachable. The checks are that
+    /// * each side has exactly one king, at most eight pawns, and no pawns on the first or eighth rank;
+    /// * each side has no more promoted pieces (those beyond the starting material, with bishops counted per square color) than missing pawns;
+    /// * each side's pawns can have reached their files from distinct starting files with no more captures than the opponent has lost pieces;
+    /// * the side not to move is not in check, and the side to move is checked by at most two pieces;
+    /// * the castling rights and the en passant target square are consistent with the board.
+    pub fn is_reachable_from_startpos(&self) -> bool {
+        retro::is_reachable(self)
+    }
+
+    /// Counts the leaf nodes of the legal move tree to the given depth (see [perft](https://www.chessprogramming.org/Perft)).
+    pub fn perft(&self, depth: usize) -> u64 {
+        self.perft_cancellable(depth, &CancelToken::new()).into_inner()
+    }
+
+    /// Counts the leaf nodes of the legal move tree to the given depth like [`Position::perft`], stopping early if the token is cancelled.
+    /// If cancelled, the number of leaf nodes counted so far is returned.
+    pub fn perft_cancellable(&self, depth: usize, cancel: &CancelToken) -> Cancellable<u64> {
+        fn count(position: &Position, depth: usize, cancel: &CancelToken) -> u64 {
+            if depth == 0 {
+                return 1;
+            }
+            let moves = position.gen_move_list();
+            if depth == 1 {
+                return moves.len() as u64;
+            }
+            let mut nodes = 0;
+            for &move_ in &moves {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                nodes += count(&position.with_legal_move_made(move_), depth - 1, cancel);
+            }
+            nodes
+        }
+        let nodes = count(self, depth, cancel);
+        if cancel.is_cancelled() {
+            Cancellable::Cancelled(nodes)
+        } else {
+            Cancellable::Completed(nodes)
+        }
+    }
+
+    /// Returns the parts of the position that are compared for repetition (FIDE Article 9.2.3). Two positions are repetitions of each other
+    /// if and only if their repetition keys are equal, so the key can be used to maintain repetition counts in a map.
+    pub fn repetition_key(&self) -> RepetitionKey {
+        RepetitionKey {
+            content: self.content,
+            side: self.side,
+            castling_rights: self.castling_rights,
+            ep_target: self.legal_ep_target(),
+        }
+    }
+
+    /// Returns a hash of the pawns and kings in the position (its pawn skeleton), for pawn-structure caches and database queries.
+    /// Positions with the same pawns and kings have the same pawn hash, regardless of the other pieces and the side to move.
+    /// The hash can be maintained incrementally with [`Position::next_pawn_hash`].
+    pub fn pawn_hash(&self) -> u64 {
+        self.content.iter().enumerate().fold(0, |hash, (sq, &piece)| hash ^ pawn_hash_key(piece, sq))
+    }
+
+    /// Returns the castling rights in the position.
+    pub fn castling_rights(&self) -> CastlingRights {
+        CastlingRights::validated(self.castling_rights, &self.content)
+    }
+
+    /// Returns the Zobrist hash of the position, for transposition tables and opening books. Positions which are the same
+    /// for the purposes of repetition (see [`Position::same_position`]) have the same hash, so an en passant target square
+    /// only affects the hash if an en passant capture is legal.
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (sq, piece) in self.content.iter().enumerate() {
+            if let Some(Piece(piece_type, color)) = piece {
+                let index = [PieceType::P, PieceType::N, PieceType::B, PieceType::R, PieceType::Q, PieceType::K]
+                    .iter()
+                    .position(|p| p == piece_type)
+                    .unwrap();
+                hash ^= ZOBRIST_KEYS[index + if color.is_white() { 0 } else { 6 }][sq];
+            }
+        }
+        for (i, rook_sq) in self.castling_rights.iter().enumerate() {
+            if let Some(rook_sq) = rook_sq {
+                hash ^= ZOBRIST_KEYS[12 + i][*rook_sq];
+            }
+        }
+        if let Some(ep_target) = self.legal_ep_target() {
+            hash ^= ZOBRIST_KEYS[16][ep_target];
+        }
+        if self.side.is_black() {
+            hash ^= ZOBRIST_KEYS[17][0];
+        }
+        hash
+    }
+
+    /// Returns the pawn hash (see [`Position::pawn_hash`]) of the position after the given move, given the pawn hash of this position,
+    /// without recomputing it from scratch. Returns an error if the move is illegal.
+    pub fn next_pawn_hash(&self, pawn_hash: u64, move_: Move) -> Result<u64, IllegalMoveError> {
+        let Move(src, dest, spec) = helpers::as_legal(move_, &self.gen_non_illegal_moves()).ok_or(IllegalMoveError(move_))?;
+        let moved = self.content[src];
+        let mut hash = pawn_hash ^ pawn_hash_key(moved, src);
+        match spec {
+            Some(SpecialMoveType::CastlingKingside | SpecialMoveType::CastlingQueenside) => (),
+            Some(SpecialMoveType::EnPassant) => {
+                let captured_sq = if self.side.is_white() { dest - 8 } else { dest + 8 };
+                hash ^= pawn_hash_key(self.content[captured_sq], captured_sq);
+            }
+            _ => hash ^= pawn_hash_key(self.content[dest], dest),
+        }
+        if !matches!(spec, Some(SpecialMoveType::Promotion(_))) {
+            hash ^= pawn_hash_key(moved, dest);
+        }
+        Ok(hash)
+    }
+
+    /// Checks whether this position and another are the same for the purposes of repetition (FIDE Article 9.2.3):
+    /// the same placement of pieces, the same side to move, the same castling rights, and the same legal en passant capture.
+    /// Unlike `==`, an en passant target square is ignored if no en passant capture is legal.
+    pub fn same_position(&self, other: &Self) -> bool {
+        self.repetition_key() == other.repetition_key()
+    }
+
+    /// Returns the color-swapped mirror image of the position: the board is flipped vertically, the colors of the pieces are swapped,
+    /// and the side to move, castling rights, and en passant target square are swapped accordingly. The mirror image of a position
+    /// is equally good for the opposite side, so evaluations of the two should be equal and opposite.
+    pub fn color_swapped(&self) -> Self {
+        let mut content = [None; 64];
+        for (sq, piece) in self.content.iter().enumerate() {
+            content[sq ^ 56] = piece.map(|Piece(pt, color)| Piece(pt, !color));
+        }
+        let [k, q, bk, bq] = self.castling_rights.map(|right| right.map(|sq| sq ^ 56));
+        Self {
+            content,
+            side: !self.side,
+            castling_rights: [bk, bq, k, q],
+            ep_target: self.ep_target.map(|sq| sq ^ 56),
+        }
+    }
+
     /// Checks whether the given move is a capture, returning an error if it is illegal in this position.
     pub fn is_capture(&self, move_: Move) -> Result<bool, IllegalMoveError> {
         let move_ = match helpers::as_legal(move_, &self.gen_non_illegal_moves()) {
             Some(m) => m,
             _ => return Err(IllegalMoveError(move_)),
         };
-        Ok(move_.2 == Some(SpecialMoveType::EnPassant) || self.content[move_.1].is_some())
+        Ok(self.captures(&move_))
+    }
+
+    /// Returns the piece captured by the given move (`None` if it is not a capture), returning an error if the move is illegal.
+    pub fn captured_piece(&self, move_: Move) -> Result<Option<Piece>, IllegalMoveError> {
+        let move_ = helpers::as_legal(move_, &self.gen_non_illegal_moves()).ok_or(IllegalMoveError(move_))?;
+        Ok(match move_.2 {
+            Some(SpecialMoveType::EnPassant) => Some(Piece(PieceType::P, !self.side)),
+            _ if self.captures(&move_) => self.content[move_.1],
+            _ => None,
+        })
+    }
+
+    /// Checks whether the given move gives check, returning an error if the move is illegal.
+    pub fn gives_check(&self, move_: Move) -> Result<bool, IllegalMoveError> {
+        Ok(self.with_move_made(move_)?.is_check())
+    }
+
+    /// Checks whether the given move is castling, returning an error if the move is illegal.
+    pub fn is_castling(&self, move_: Move) -> Result<bool, IllegalMoveError> {
+        let move_ = helpers::as_legal(move_, &self.gen_non_illegal_moves()).ok_or(IllegalMoveError(move_))?;
+        Ok(matches!(move_.2, Some(SpecialMoveType::CastlingKingside | SpecialMoveType::CastlingQueenside)))
+    }
+
+    /// Explains why the given move is illegal in this position, returning `None` if it is legal.
+    pub fn why_illegal(&self, move_: Move) -> Option<IllegalMoveReason> {
+        let Move(src, dest, spec) = move_;
+        for sq in [src, dest] {
+            if sq >= 64 {
+                return Some(IllegalMoveReason::InvalidSquare(sq));
+            }
+        }
+        if helpers::as_legal(move_, &self.gen_non_illegal_moves()).is_some() {
+            return None;
+        }
+        let Piece(piece_type, color) = match self.content[src] {
+            Some(piece) => piece,
+            None => return Some(IllegalMoveReason::NoPieceOnSource),
+        };
+        if color != self.side {
+            return Some(IllegalMoveReason::WrongColor);
+        }
+        let pseudolegal: Vec<_> = self.gen_pseudolegal_moves_sq(src).into_iter().filter(|m| m.1 == dest).collect();
+        let (src_file, src_rank, dest_file, dest_rank) = ((src % 8) as isize, (src / 8) as isize, (dest % 8) as isize, (dest / 8) as isize);
+        let castling = match spec {
+            Some(SpecialMoveType::CastlingKingside) => Some(true),
+            Some(SpecialMoveType::CastlingQueenside) => Some(false),
+            _ if piece_type == PieceType::K && src_rank == dest_rank && src_rank == if self.side.is_white() { 0 } else { 7 } && (dest_file - src_file).abs() == 2 => Some(dest_file > src_file),
+            _ => None,
+        };
+        if let (PieceType::K, Some(kingside)) = (piece_type, castling) {
+            let offset = if self.side.is_white() { 0 } else { 2 };
+            let Some(rook) = self.castling_rights[offset + if kingside { 0 } else { 1 }] else {
+                return Some(IllegalMoveReason::NoCastlingRights);
+            };
+            if pseudolegal.is_empty() {
+                let king_dest = if kingside { src / 8 * 8 + 6 } else { src / 8 * 8 + 2 };
+                let squares = [src, rook, king_dest, if kingside { king_dest - 1 } else { king_dest + 1 }];
+                let blocker = (*squares.iter().min().unwrap()..=*squares.iter().max().unwrap()).find(|&sq| sq != src && sq != rook && self.content[sq].is_some());
+                return Some(blocker.map_or(IllegalMoveReason::InvalidPieceMovement(PieceType::K), IllegalMoveReason::Blocked));
+            }
+            if self.is_check() {
+                return Some(IllegalMoveReason::CastlingOutOfCheck);
+            }
+            let attacked = (src.min(dest)..=src.max(dest)).find(|&sq| self.controls_square(sq, !self.side));
+            return Some(attacked.map_or(IllegalMoveReason::LeavesKingInCheck, IllegalMoveReason::CastlingThroughCheck));
+        }
+        if self.content[dest].is_some_and(|p| p.1 == self.side) {
+            return Some(IllegalMoveReason::OwnPieceOnDestination);
+        }
+        if !pseudolegal.is_empty() {
+            let promotes = matches!(pseudolegal[0].2, Some(SpecialMoveType::Promotion(_)));
+            let promotion_given = matches!(spec, Some(SpecialMoveType::Promotion(_)));
+            if promotes != promotion_given || helpers::as_legal(move_, &pseudolegal).is_none() && !matches!(spec, Some(SpecialMoveType::Unclear) | None) {
+                return Some(IllegalMoveReason::InvalidPromotion);
+            }
+            return Some(IllegalMoveReason::LeavesKingInCheck);
+        }
+        let (file_diff, rank_diff) = (dest_file - src_file, dest_rank - src_rank);
+        let direction = self.side.sign() as isize;
+        // the squares passed through by a sliding move (or a pawn push), or `None` if the piece cannot move that way
+        let path = |step: isize| -> Vec<usize> { (1..).map(|n| (src as isize + step * n) as usize).take_while(|&sq| sq != dest).collect() };
+        let passed = match piece_type {
+            PieceType::N | PieceType::K => None,
+            PieceType::P if file_diff == 0 && rank_diff == direction => Some(Vec::new()),
+            PieceType::P if file_diff == 0 && rank_diff == 2 * direction && src_rank == if self.side.is_white() { 1 } else { 6 } => Some(path(8 * direction)),
+            PieceType::P if file_diff.abs() == 1 && rank_diff == direction => return Some(IllegalMoveReason::NothingToCapture),
+            PieceType::P => None,
+            _ => {
+                let straight = file_diff == 0 || rank_diff == 0;
+                let diagonal = file_diff.abs() == rank_diff.abs();
+                let moves_that_way = match piece_type {
+                    PieceType::R => straight,
+                    PieceType::B => diagonal,
+                    _ => straight || diagonal,
+                };
+                (moves_that_way && src != dest).then(|| path(file_diff.signum() + 8 * rank_diff.signum()))
+            }
+        };
+        let Some(passed) = passed else {
+            return Some(IllegalMoveReason::InvalidPieceMovement(piece_type));
+        };
+        if let Some(&blocker) = passed.iter().find(|&&sq| self.content[sq].is_some()) {
+            return Some(IllegalMoveReason::Blocked(blocker));
+        }
+        if piece_type == PieceType::P && self.content[dest].is_some() {
+            return Some(IllegalMoveReason::Blocked(dest));
+        }
+        Some(IllegalMoveReason::InvalidPieceMovement(piece_type))
     }
 }
 
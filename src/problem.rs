@@ -0,0 +1,104 @@
+use super::{Color, Move, Position};
+use std::collections::HashMap;
+
+/// Represents the stipulation of a mate problem (see [`Board::solve_mate`](crate::Board::solve_mate)).
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum MateKind {
+    /// The side to move forces checkmate against any defense (a direct mate, `#n`).
+    Direct,
+    /// Both sides cooperate so that the side to move is checkmated (a helpmate, `h#n`).
+    Helpmate,
+    /// The side to move forces the opponent to checkmate it, while the opponent tries not to (a selfmate, `s#n`).
+    Selfmate,
+}
+
+impl MateKind {
+    /// Returns the side which must be checkmated and the side which chooses its moves freely against any reply,
+    /// given the side to move in the initial position (`None` if both sides choose their moves, as in helpmates).
+    fn sides(self, side_to_move: Color) -> (Color, Option<Color>) {
+        match self {
+            Self::Direct => (!side_to_move, Some(side_to_move)),
+            Self::Helpmate => (side_to_move, None),
+            Self::Selfmate => (side_to_move, Some(side_to_move)),
+        }
+    }
+}
+
+/// An and/or search for the solutions of a mate problem.
+struct Solver {
+    /// The side which must be checkmated
+    mated: Color,
+    /// The side whose moves are chosen (any move reaching the goal suffices), while every move of the other side must reach it;
+    /// `None` if the moves of both sides are chosen
+    chooser: Option<Color>,
+    /// Whether positions (by Zobrist hash) can reach the goal within a number of plies
+    table: HashMap<(u64, usize), bool>,
+}
+
+impl Solver {
+    /// Checks whether the goal is reached in a position, i.e. the side which must be checkmated is checkmated.
+    fn is_goal(&self, position: &Position, moves: &[Move]) -> bool {
+        moves.is_empty() && position.side == self.mated && position.is_check()
+    }
+
+    /// Checks whether the goal can be reached from a position within the given number of plies.
+    fn solves(&mut self, position: &Position, plies: usize) -> bool {
+        let moves = position.gen_non_illegal_moves_uncached();
+        if moves.is_empty() {
+            return self.is_goal(position, &moves);
+        }
+        // the side which must be checkmated has to be the side to move when the goal is reached, so if it is to move now, it takes two plies
+        let min_plies = if position.side == self.mated { 2 } else { 1 };
+        if plies < min_plies {
+            return false;
+        }
+        let key = (position.zobrist_hash(), plies);
+        if let Some(&solves) = self.table.get(&key) {
+            return solves;
+        }
+        let chosen = self.chooser.is_none_or(|side| side == position.side);
+        let mut reaches = moves.into_iter().map(|move_| position.with_legal_move_made(move_));
+        let solves = if chosen {
+            reaches.any(|child| self.solves(&child, plies - 1))
+        } else {
+            reaches.all(|child| self.solves(&child, plies - 1))
+        };
+        self.table.insert(key, solves);
+        solves
+    }
+
+    /// Returns the solution lines from a position within the given number of plies: at positions where the moves are chosen,
+    /// every move from which the goal can be forced, and elsewhere every legal move.
+    fn lines(&mut self, position: &Position, plies: usize) -> Vec<Vec<Move>> {
+        let moves = position.gen_non_illegal_moves_uncached();
+        if self.is_goal(position, &moves) {
+            return vec![Vec::new()];
+        }
+        if !self.solves(position, plies) {
+            return Vec::new();
+        }
+        let mut lines = Vec::new();
+        for move_ in moves {
+            let child = position.with_legal_move_made(move_);
+            for mut line in self.lines(&child, plies - 1) {
+                line.insert(0, move_);
+                lines.push(line);
+            }
+        }
+        lines
+    }
+}
+
+/// Solves a mate problem in a position, returning every solution line (see [`Board::solve_mate`](crate::Board::solve_mate)).
+pub(crate) fn solve_mate(position: &Position, max_plies: usize, kind: MateKind) -> Vec<Vec<Move>> {
+    let (mated, chooser) = kind.sides(position.side);
+    let mut solver = Solver {
+        mated,
+        chooser,
+        table: HashMap::new(),
+    };
+    if solver.is_goal(position, &position.gen_non_illegal_moves_uncached()) {
+        return Vec::new();
+    }
+    solver.lines(position, max_plies)
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+/// Represents the progress of a bulk operation, such as reading a PGN database, as reported to progress callbacks.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct Progress {
+    /// The number of items (such as games) processed so far
+    pub items: usize,
+    /// The number of bytes of input read so far
+    pub bytes: u64,
+    /// The total number of bytes of input, if known
+    pub total_bytes: Option<u64>,
+    /// The time elapsed since the operation started (always zero on `wasm32-unknown-unknown`, which has no clock)
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Returns the fraction of the input read so far (in the range 0..=1), if the total size of the input is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total_bytes {
+            Some(0) => Some(1.),
+            Some(total) => Some((self.bytes as f64 / total as f64).min(1.)),
+            None => None,
+        }
+    }
+
+    /// Returns the estimated time remaining, extrapolated from the rate at which input has been read so far,
+    /// if the total size of the input is known and some input has been read.
+    pub fn eta(&self) -> Option<Duration> {
+        let total = self.total_bytes?;
+        if self.bytes == 0 {
+            return None;
+        }
+        Some(self.elapsed.mul_f64(total.saturating_sub(self.bytes) as f64 / self.bytes as f64))
+    }
+}
+
+/// A callback which is called with the progress of a bulk operation.
+pub type ProgressCallback = Box<dyn FnMut(&Progress)>;
@@ -0,0 +1,68 @@
+use super::{helpers, Color, DrawType, Fen, GameResult, InvalidFenError, Move, Piece, PieceType, Position, Variant, VariantBoard, WinType};
+
+/// A game of Racing Kings (see [`RacingKingsRules`]).
+pub type RacingKings = VariantBoard<RacingKingsRules>;
+
+/// The rules of Racing Kings, in which the aim is to be the first to move one's king to the eighth rank.
+///
+/// The rules differ from standard chess as follows:
+/// * checks are not allowed: a move which gives check is illegal (so there is no checkmate)
+/// * a side wins by moving its king to the eighth rank, except that if white's king reaches it first, black has one more move,
+///   and the game is drawn if black's king reaches the eighth rank too
+///
+/// In FENs, neither king may be in check.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub struct RacingKingsRules;
+
+impl RacingKingsRules {
+    /// Checks whether the king of the given side is on the eighth rank in a position.
+    fn has_finished(position: &Position, side: Color) -> bool {
+        helpers::find_king(side, &position.content).is_some_and(|king| king >= 56)
+    }
+}
+
+impl Variant for RacingKingsRules {
+    type State = ();
+
+    /// Both armies on the first two ranks.
+    fn starting_fen(&self) -> &'static str {
+        "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1"
+    }
+
+    fn parse_fen(&self, fen: &str) -> Result<Fen, InvalidFenError> {
+        let fen = Fen::parse(fen)?;
+        if helpers::king_capture_pseudolegal(&fen.position.content, !fen.position.side) {
+            return Err(InvalidFenError::BoardData("there are no checks in Racing Kings".to_owned()));
+        }
+        Ok(fen)
+    }
+
+    fn legal_moves(&self, position: &Position) -> Vec<Move> {
+        let mut moves = position.gen_non_illegal_moves();
+        moves.retain(|&move_| !helpers::king_capture_pseudolegal(&position.with_legal_move_made(move_).content, position.side));
+        moves
+    }
+
+    /// A side whose king reaches the eighth rank wins with [`WinType::Variant`], and a game in which both kings reach it is drawn with [`DrawType::Variant`].
+    fn result(&self, position: &Position, _state: &(), legal_moves: &[Move]) -> Option<GameResult> {
+        let (white_finished, black_finished) = (Self::has_finished(position, Color::White), Self::has_finished(position, Color::Black));
+        // if white's king has just reached the eighth rank, black can still draw by reaching it on the next move
+        let black_can_finish = || {
+            position.side.is_black()
+                && legal_moves
+                    .iter()
+                    .any(|&Move(src, dest, _)| position.content[src] == Some(Piece(PieceType::K, Color::Black)) && dest >= 56)
+        };
+        if white_finished && black_finished {
+            Some(GameResult::Draw(DrawType::Variant))
+        } else if black_finished {
+            Some(GameResult::Wins(Color::Black, WinType::Variant))
+        } else if white_finished && !black_can_finish() {
+            Some(GameResult::Wins(Color::White, WinType::Variant))
+        } else if legal_moves.is_empty() {
+            Some(GameResult::Draw(DrawType::Stalemate(position.side)))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Rating math for engine testing: Elo expected scores and differences, performance ratings, and statistics over [`MatchResult`]s
+//! such as the likelihood of superiority and the sequential probability ratio test (SPRT).
+//!
+//! Elo differences follow the logistic model, in which a player rated 400 points higher than its opponent is expected to score 10 times as much.
+
+use super::match_play::MatchResult;
+
+/// The quantile of the standard normal distribution for a two-sided 95% confidence interval.
+const Z_95: f64 = 1.959_963_985;
+
+/// Returns the expected score (between 0 and 1) of a player against an opponent, given their ratings.
+pub fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1. / (1. + 10f64.powf((opponent - rating) / 400.))
+}
+
+/// Returns the Elo difference corresponding to an expected score (between 0 and 1, exclusive), the inverse of [`expected_score`].
+/// The difference is infinite for a score of 0 or 1.
+pub fn elo_difference(score: f64) -> f64 {
+    -400. * (1. / score - 1.).log10()
+}
+
+/// Returns the performance rating of a player from the ratings of its opponents and its scores against them (1 for a win, 0.5 for a draw,
+/// and 0 for a loss): the rating at which its expected total score equals its actual total score. Returns `None` if there are no games
+/// or the player won or lost every game, in which case the performance rating is unbounded.
+pub fn performance_rating(results: &[(f64, f64)]) -> Option<f64> {
+    let total: f64 = results.iter().map(|(_, score)| score).sum();
+    if results.is_empty() || total <= 0. || total >= results.len() as f64 {
+        return None;
+    }
+    let expected_total = |rating: f64| results.iter().map(|&(opponent, _)| expected_score(rating, opponent)).sum::<f64>();
+    let (min, max) = results
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &(opponent, _)| (min.min(opponent), max.max(opponent)));
+    // the expected total score increases with the rating, so the performance rating can be found by bisection
+    let (mut low, mut high) = (min - 4000., max + 4000.);
+    for _ in 0..100 {
+        let mid = (low + high) / 2.;
+        if expected_total(mid) < total {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some((low + high) / 2.)
+}
+
+/// Returns the mean score per game of a match and the variance of the score of a single game.
+fn score_stats(result: &MatchResult) -> Option<(f64, f64)> {
+    let n = result.total() as f64;
+    if n == 0. {
+        return None;
+    }
+    let mean = result.score() / n;
+    let variance = [(result.wins, 1.), (result.draws, 0.5), (result.losses, 0.)]
+        .iter()
+        .map(|&(count, score)| count as f64 * (score - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    Some((mean, variance))
+}
+
+/// Represents an estimate of the Elo difference between the engines of a match.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct EloEstimate {
+    /// The estimated Elo difference of engine A over engine B
+    pub difference: f64,
+    /// The margin of error of the difference, for a 95% confidence interval
+    pub error: f64,
+}
+
+/// Estimates the Elo difference of engine A over engine B from the results of a match, returning `None` if no games were played.
+/// The difference is infinite if one engine won every game.
+pub fn elo_estimate(result: &MatchResult) -> Option<EloEstimate> {
+    let (mean, variance) = score_stats(result)?;
+    let deviation = Z_95 * (variance / result.total() as f64).sqrt();
+    let (low, high) = ((mean - deviation).max(0.), (mean + deviation).min(1.));
+    Some(EloEstimate {
+        difference: elo_difference(mean),
+        error: (elo_difference(high) - elo_difference(low)) / 2.,
+    })
+}
+
+/// Returns the likelihood of superiority (LOS) of engine A over engine B: the probability that engine A is the stronger engine, given the
+/// numbers of wins and losses in a match (draws do not affect it). Returns `None` if there were no decisive games.
+pub fn likelihood_of_superiority(result: &MatchResult) -> Option<f64> {
+    let (wins, losses) = (result.wins as f64, result.losses as f64);
+    if wins + losses == 0. {
+        return None;
+    }
+    Some(0.5 * (1. + erf((wins - losses) / (2. * (wins + losses)).sqrt())))
+}
+
+/// Approximates the error function, with an absolute error below 1.5e-7 (Abramowitz and Stegun, formula 7.1.26).
+fn erf(x: f64) -> f64 {
+    let t = 1. / (1. + 0.327_591_1 * x.abs());
+    let polynomial = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    (1. - polynomial * (-x * x).exp()).copysign(x)
+}
+
+/// Represents the state of a sequential probability ratio test.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum SprtStatus {
+    /// Neither hypothesis can be accepted yet, so more games are needed.
+    Continue,
+    /// The null hypothesis (that the Elo difference is `elo0`) is accepted.
+    AcceptH0,
+    /// The alternative hypothesis (that the Elo difference is `elo1`) is accepted.
+    AcceptH1,
+}
+
+/// Represents a sequential probability ratio test (SPRT) of whether engine A is stronger than engine B, as used to test changes to engines:
+/// games are played until the test accepts either the hypothesis that the Elo difference is `elo0` or the hypothesis that it is `elo1`.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Sprt {
+    /// The Elo difference of the null hypothesis
+    pub elo0: f64,
+    /// The Elo difference of the alternative hypothesis
+    pub elo1: f64,
+    /// The probability of accepting the alternative hypothesis when the null hypothesis is true (the false positive rate)
+    pub alpha: f64,
+    /// The probability of accepting the null hypothesis when the alternative hypothesis is true (the false negative rate)
+    pub beta: f64,
+}
+
+impl Sprt {
+    /// Returns the log-likelihood ratio of the alternative hypothesis over the null hypothesis given the results of a match,
+    /// using the normal approximation of the distribution of scores (as in cutechess and fishtest). It is 0 if the scores do not vary.
+    pub fn llr(&self, result: &MatchResult) -> f64 {
+        let Some((mean, variance)) = score_stats(result).filter(|&(_, variance)| variance > 0.) else {
+            return 0.;
+        };
+        let (score0, score1) = (expected_score(self.elo0, 0.), expected_score(self.elo1, 0.));
+        (score1 - score0) * (2. * mean - score0 - score1) * result.total() as f64 / (2. * variance)
+    }
+
+    /// Returns the lower and upper bounds of the log-likelihood ratio, beyond which the null or the alternative hypothesis is accepted.
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1. - self.alpha)).ln(), ((1. - self.beta) / self.alpha).ln())
+    }
+
+    /// Returns the state of the test given the results of a match.
+    pub fn status(&self, result: &MatchResult) -> SprtStatus {
+        let (llr, (lower, upper)) = (self.llr(result), self.bounds());
+        if llr <= lower {
+            SprtStatus::AcceptH0
+        } else if llr >= upper {
+            SprtStatus::AcceptH1
+        } else {
+            SprtStatus::Continue
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use super::{helpers, Color, Piece, PieceType, Position};
+
+/// The number of pieces of each type (other than pawns and kings) that each side starts with, with bishops counted separately (see [`promoted_pieces`]).
+const STARTING_PIECES: [(PieceType, usize); 3] = [(PieceType::N, 2), (PieceType::R, 2), (PieceType::Q, 1)];
+
+/// A cost too large to be paid by captures, for pawns which cannot have come from a file.
+const UNREACHABLE: usize = usize::MAX / 2;
+
+/// Checks a position for signs that it cannot arise from the standard starting position (see [`Position::is_reachable_from_startpos`]).
+pub(crate) fn is_reachable(position: &Position) -> bool {
+    let content = &position.content;
+    for color in [Color::White, Color::Black] {
+        if helpers::count_piece(0..64, Piece(PieceType::K, color), content) != 1 {
+            return false;
+        }
+    }
+    // pawns promote on reaching the last rank, and can never go back to the first
+    if content[..8].iter().chain(&content[56..]).any(|p| matches!(p, Some(Piece(PieceType::P, _)))) {
+        return false;
+    }
+    for color in [Color::White, Color::Black] {
+        let pawns = helpers::find_pieces(Piece(PieceType::P, color), 0..64, content);
+        // every piece beyond the starting material must have been promoted from a pawn which is no longer on the board
+        if pawns.len() > 8 || promoted_pieces(content, color) > 8 - pawns.len() {
+            return false;
+        }
+        // pawns change files only by capturing, and every capture removes a piece of the opponent
+        let captured = 16 - content.iter().flatten().filter(|p| p.1 == !color).count();
+        if pawn_captures(&pawns, color) > captured {
+            return false;
+        }
+    }
+    // the side which has just moved cannot have left its king in check, and no move can give more than two checks at once
+    if helpers::king_capture_pseudolegal(content, position.side) || position.checkers().len() > 2 {
+        return false;
+    }
+    position.castling_rights == position.castling_rights().rooks() && position.has_valid_ep_target()
+}
+
+/// Counts the pieces of a side which must have been promoted, i.e. those beyond the starting material. Bishops are counted
+/// per square color, since each side starts with one bishop on each color and bishops never change the color of their squares.
+fn promoted_pieces(content: &[Option<Piece>; 64], color: Color) -> usize {
+    let extra = |count: usize, starting: usize| count.saturating_sub(starting);
+    let bishops = helpers::find_pieces(Piece(PieceType::B, color), 0..64, content);
+    let light_bishops = bishops.iter().filter(|&&sq| helpers::color_complex_of(sq)).count();
+    let pieces: usize = STARTING_PIECES
+        .iter()
+        .map(|&(piece_type, starting)| extra(helpers::count_piece(0..64, Piece(piece_type, color), content), starting))
+        .sum();
+    pieces + extra(light_bishops, 1) + extra(bishops.len() - light_bishops, 1)
+}
+
+/// Returns the fewest captures by which the pawns of a side can have reached their squares, each from a different file of the starting position,
+/// or an unpayable cost if they cannot have. A pawn changes files by one with each capture, and makes at most one capture per rank advanced.
+fn pawn_captures(pawns: &[usize], color: Color) -> usize {
+    // the fewest captures for the pawns considered so far, by the set of starting files (a bitmask) they came from
+    let mut costs = vec![UNREACHABLE; 1 << 8];
+    costs[0] = 0;
+    for &sq in pawns {
+        let (file, rank) = (sq % 8, sq / 8);
+        let max_captures = if color.is_white() { rank - 1 } else { 6 - rank };
+        let mut next = vec![UNREACHABLE; 1 << 8];
+        for (files, &cost) in costs.iter().enumerate().filter(|(_, &cost)| cost < UNREACHABLE) {
+            for start in (0..8).filter(|start| files & 1 << start == 0) {
+                let captures = file.abs_diff(start);
+                if captures <= max_captures {
+                    let entry = &mut next[files | 1 << start];
+                    *entry = (*entry).min(cost + captures);
+                }
+            }
+        }
+        costs = next;
+    }
+    costs.into_iter().min().unwrap()
+}
@@ -0,0 +1,80 @@
+use super::{helpers, Color, Piece, PieceType, Position};
+
+/// Represents how a loss on time is adjudicated when the opponent of the side that ran out of time has little material.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum TimeoutRule {
+    /// The game is drawn if the opponent cannot checkmate by any series of legal moves (FIDE Article 6.9).
+    Fide,
+    /// The game is drawn if the opponent lacks the material to force checkmate: a lone king, or a king with one minor piece or two knights.
+    Uscf,
+    /// The game is drawn only if the opponent has a lone king.
+    Lichess,
+}
+
+/// Represents a rule set, which determines how a [`Board`](crate::Board) adjudicates games.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct Rules {
+    /// How a loss on time is adjudicated.
+    pub timeout: TimeoutRule,
+    /// Whether insufficient material (see [`Position::is_insufficient_material`]) ends the game in a draw automatically.
+    pub insufficient_material_draw: bool,
+    /// Whether dead positions other than insufficient material (see [`Position::is_dead_position`]) end the game in a draw automatically.
+    /// Detecting dead positions requires exploring the reachable positions after every move, which can be slow.
+    pub dead_position_draw: bool,
+    /// The number of occurrences of a position after which the game is drawn automatically, or `None` if repetitions never end the game.
+    pub repetition_limit: Option<usize>,
+    /// The number of halfmoves without a pawn push or capture after which the game is drawn automatically, or `None` if they never end the game.
+    pub halfmove_limit: Option<usize>,
+}
+
+impl Rules {
+    /// The FIDE Laws of Chess.
+    pub const FIDE: Self = Self {
+        timeout: TimeoutRule::Fide,
+        insufficient_material_draw: true,
+        dead_position_draw: false,
+        repetition_limit: Some(5),
+        halfmove_limit: Some(150),
+    };
+
+    /// The US Chess Federation rules.
+    pub const USCF: Self = Self {
+        timeout: TimeoutRule::Uscf,
+        ..Self::FIDE
+    };
+
+    /// The rules used by Lichess.
+    pub const LICHESS: Self = Self {
+        timeout: TimeoutRule::Lichess,
+        ..Self::FIDE
+    };
+
+    /// Checks whether the given side wins in the position when its opponent runs out of time, as opposed to the game being drawn.
+    pub fn timeout_wins(&self, position: &Position, side: Color) -> bool {
+        let material_of = |color: Color| -> Vec<Piece> { position.content.iter().flatten().filter(|p| p.1 == color && p.0 != PieceType::K).copied().collect() };
+        let (own, opponent) = (material_of(side), material_of(!side));
+        if own.is_empty() {
+            return false;
+        }
+        match self.timeout {
+            TimeoutRule::Lichess => true,
+            TimeoutRule::Uscf => !(own.iter().all(|p| p.0 == PieceType::N) && own.len() <= 2 || own.len() == 1 && own[0].0 == PieceType::B),
+            TimeoutRule::Fide => {
+                let lone_minor = own.len() == 1 && matches!(own[0].0, PieceType::N | PieceType::B);
+                let bishop_complexes: Vec<_> = (0..64)
+                    .filter(|&sq| position.content[sq].is_some_and(|p| p.0 != PieceType::K))
+                    .map(|sq| (position.content[sq].unwrap().0, helpers::color_complex_of(sq)))
+                    .collect();
+                let only_same_colored_bishops = bishop_complexes.iter().all(|&(pt, complex)| pt == PieceType::B && complex == bishop_complexes[0].1);
+                !(lone_minor && opponent.is_empty() || only_same_colored_bishops)
+            }
+        }
+    }
+}
+
+impl Default for Rules {
+    /// Returns the FIDE rule set.
+    fn default() -> Self {
+        Self::FIDE
+    }
+}
@@ -0,0 +1,98 @@
+//! Decodes handwritten scoresheets: given candidate readings of each move (such as those produced by an OCR engine), finds the most
+//! probable game in which every move is legal.
+
+use super::{Board, Move, UnresolvableScoresheetError};
+
+/// Represents one possible reading of a move on a scoresheet.
+#[derive(PartialEq, Clone, Debug)]
+pub struct MoveCandidate {
+    /// The move as read, in SAN (which is parsed leniently, see [`Position::san_to_move_lenient`](crate::Position::san_to_move_lenient))
+    pub san: String,
+    /// The confidence of the reading, as a probability in the range `0..=1`
+    pub confidence: f64,
+}
+
+impl MoveCandidate {
+    /// Creates a candidate reading of a move.
+    pub fn new(san: &str, confidence: f64) -> Self {
+        Self { san: san.to_owned(), confidence }
+    }
+}
+
+/// Represents a game decoded from a scoresheet by [`resolve`].
+#[derive(Clone, Debug)]
+pub struct ResolvedGame {
+    /// The game, with every decoded move played
+    pub board: Board,
+    /// The decoded moves
+    pub moves: Vec<Move>,
+    /// For each move, the index of the candidate it was decoded from (`None` for illegible moves, whose candidate lists were empty)
+    pub choices: Vec<Option<usize>>,
+    /// The natural logarithm of the probability of the game, i.e. the sum of the logarithms of the confidences of the chosen candidates
+    pub log_probability: f64,
+}
+
+/// The smallest confidence used, so that candidates read with zero confidence can still be chosen if nothing else is legal.
+const MIN_CONFIDENCE: f64 = 1e-9;
+
+/// Finds the most probable game starting from the standard starting position, given the candidate readings of each halfmove.
+/// See [`resolve_from`].
+pub fn resolve(candidates: &[Vec<MoveCandidate>], beam_width: usize) -> Result<ResolvedGame, UnresolvableScoresheetError> {
+    resolve_from(Board::default(), candidates, beam_width)
+}
+
+/// Finds the most probable game starting from the given board, given the candidate readings of each halfmove, in which every move is legal.
+///
+/// The search is a beam search: after each halfmove, only the `beam_width` most probable partial games are kept. Candidates which are
+/// illegal (or unreadable) in a partial game are discarded, so a misread move is corrected by the legality of the moves around it.
+/// An empty candidate list marks an illegible move, which may be any legal move, each considered equally likely.
+/// Returns an error if no legal game matches the candidates.
+pub fn resolve_from(board: Board, candidates: &[Vec<MoveCandidate>], beam_width: usize) -> Result<ResolvedGame, UnresolvableScoresheetError> {
+    let mut beam = vec![ResolvedGame {
+        board,
+        moves: Vec::new(),
+        choices: Vec::new(),
+        log_probability: 0.,
+    }];
+    for (ply, readings) in candidates.iter().enumerate() {
+        let mut next = Vec::new();
+        for game in &beam {
+            let mut extensions: Vec<(Move, Option<usize>, f64)> = Vec::new();
+            if readings.is_empty() {
+                let moves = game.board.gen_legal_moves();
+                let log_probability = -(moves.len() as f64).ln();
+                extensions.extend(moves.into_iter().map(|m| (m, None, log_probability)));
+            } else {
+                for (i, reading) in readings.iter().enumerate() {
+                    let Ok(move_) = game.board.position().san_to_move_lenient(&reading.san) else {
+                        continue;
+                    };
+                    if !game.board.is_legal(move_) {
+                        continue;
+                    }
+                    let log_probability = reading.confidence.clamp(MIN_CONFIDENCE, 1.).ln();
+                    match extensions.iter_mut().find(|(m, ..)| *m == move_) {
+                        Some(extension) if extension.2 >= log_probability => (),
+                        Some(extension) => *extension = (move_, Some(i), log_probability),
+                        None => extensions.push((move_, Some(i), log_probability)),
+                    }
+                }
+            }
+            for (move_, choice, log_probability) in extensions {
+                let mut extended = game.clone();
+                extended.board.make_move(move_).unwrap();
+                extended.moves.push(move_);
+                extended.choices.push(choice);
+                extended.log_probability += log_probability;
+                next.push(extended);
+            }
+        }
+        if next.is_empty() {
+            return Err(UnresolvableScoresheetError { ply });
+        }
+        next.sort_by(|a, b| b.log_probability.total_cmp(&a.log_probability));
+        next.truncate(beam_width.max(1));
+        beam = next;
+    }
+    Ok(beam.swap_remove(0))
+}
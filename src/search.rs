@@ -0,0 +1,322 @@
+//! A basic game tree search, for bots of adjustable strength.
+//!
+//! [`search`] runs an iterative deepening alpha-beta (negamax) search, extended at the leaves by a quiescence search over captures,
+//! and scores leaves with the static evaluation of the [`eval`] module. A transposition table keyed by Zobrist hashes
+//! (see [`Position::zobrist_hash`]) remembers results between iterations, which also orders the best move of each position first.
+
+use super::{eval, helpers, Board, CancelToken, Move, Piece, PieceType, Position, SpecialMoveType};
+use std::time::{Duration, Instant};
+
+/// The score of a position in which the side to move has been checkmated, before adjusting for the distance to the checkmate.
+const MATE_SCORE: i32 = 1_000_000;
+
+/// The depth searched if no limit on the depth is given.
+const MAX_DEPTH: u32 = 64;
+
+/// The number of entries in the transposition table (a power of two).
+const TABLE_SIZE: usize = 1 << 18;
+
+/// The number of nodes searched between checks of the time limit and the cancel token.
+const CHECK_INTERVAL: u64 = 1024;
+
+/// Represents the limits of a search. The search stops at whichever limit is reached first; it always completes a search of depth 1,
+/// so that a move is found however tight the limits are. Without any limits, it searches to a depth of 64 halfmoves.
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    /// The maximum depth to search, in halfmoves
+    pub depth: Option<u32>,
+    /// The maximum number of positions to search
+    pub nodes: Option<u64>,
+    /// The maximum time to search for (ignored on `wasm32-unknown-unknown`, which has no clock)
+    pub time: Option<Duration>,
+    /// A token which stops the search when cancelled
+    pub cancel: Option<CancelToken>,
+}
+
+impl SearchLimits {
+    /// Creates limits which search to the given depth, in halfmoves.
+    pub fn depth(depth: u32) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    /// Creates limits which search for the given time.
+    pub fn time(time: Duration) -> Self {
+        Self {
+            time: Some(time),
+            ..Default::default()
+        }
+    }
+}
+
+/// Represents the score of a position, from the point of view of the side to move.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum Score {
+    /// An advantage in centipawns, which is positive if the side to move is better
+    Centipawns(i32),
+    /// A forced checkmate in the given number of moves, which is positive if the side to move delivers it and negative if it is checkmated
+    Mate(i32),
+}
+
+/// Represents the result of a search.
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub struct SearchResult {
+    /// The best move found
+    pub best_move: Move,
+    /// The score of the position after the best moves of both sides
+    pub score: Score,
+    /// The principal variation: the sequence of best moves for both sides, starting with `best_move`
+    pub pv: Vec<Move>,
+    /// The depth of the last completed iteration, in halfmoves
+    pub depth: u32,
+    /// The number of positions searched
+    pub nodes: u64,
+}
+
+/// Searches the current position of a board within the given limits, returning `None` if the game is over.
+/// Positions repeated from the game (or within the search) are scored as draws.
+pub fn search(board: &Board, limits: &SearchLimits) -> Option<SearchResult> {
+    if !board.is_ongoing() || !board.has_legal_move() {
+        return None;
+    }
+    let mut searcher = Searcher {
+        limits,
+        start: helpers::now(),
+        nodes: 0,
+        stopped: false,
+        first_iteration: true,
+        table: vec![None; TABLE_SIZE],
+        path: board.position_history().iter().map(Position::zobrist_hash).collect(),
+    };
+    let mut result = None;
+    for depth in 1..=limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH) {
+        let mut pv = Vec::new();
+        let score = searcher.negamax(board.position(), depth, 0, -MATE_SCORE, MATE_SCORE, &mut pv);
+        searcher.first_iteration = false;
+        if searcher.stopped && result.is_some() {
+            break;
+        }
+        result = Some(SearchResult {
+            best_move: pv[0],
+            score: to_score(score),
+            pv,
+            depth,
+            nodes: searcher.nodes,
+        });
+        if searcher.stopped || searcher.limit_reached() || score.abs() > MATE_SCORE - MAX_DEPTH as i32 * 2 {
+            break;
+        }
+    }
+    result.map(|result| SearchResult { nodes: searcher.nodes, ..result })
+}
+
+/// Converts an internal score to a [`Score`].
+fn to_score(score: i32) -> Score {
+    if score > MATE_SCORE - MAX_DEPTH as i32 * 2 {
+        Score::Mate((MATE_SCORE - score + 1) / 2)
+    } else if score < -MATE_SCORE + MAX_DEPTH as i32 * 2 {
+        Score::Mate(-(MATE_SCORE + score + 1) / 2)
+    } else {
+        Score::Centipawns(score)
+    }
+}
+
+/// Represents how a score in the transposition table relates to the true score of its position.
+#[derive(Copy, Clone)]
+enum Bound {
+    Exact,
+    /// The true score is at least the stored score
+    Lower,
+    /// The true score is at most the stored score
+    Upper,
+}
+
+/// An entry of the transposition table.
+#[derive(Copy, Clone)]
+struct TableEntry {
+    hash: u64,
+    depth: u32,
+    score: i32,
+    bound: Bound,
+    best_move: Option<Move>,
+}
+
+/// The state of a search.
+struct Searcher<'a> {
+    limits: &'a SearchLimits,
+    start: Option<Instant>,
+    nodes: u64,
+    /// Whether a limit has been reached, after which results are discarded
+    stopped: bool,
+    /// Whether the search of depth 1 is in progress, which is never stopped
+    first_iteration: bool,
+    table: Vec<Option<TableEntry>>,
+    /// The hashes of the positions of the game and of the current line of the search, for detecting repetitions
+    path: Vec<u64>,
+}
+
+impl Searcher<'_> {
+    /// Counts a node, checking the limits every so often (but never during the first iteration).
+    fn visit(&mut self) {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(CHECK_INTERVAL) && !self.first_iteration {
+            self.stopped |= self.limit_reached();
+        }
+    }
+
+    /// Checks whether the node limit or the time limit has been reached, or the search has been cancelled.
+    fn limit_reached(&self) -> bool {
+        let limits = self.limits;
+        limits.nodes.is_some_and(|nodes| self.nodes >= nodes)
+            || limits.time.is_some_and(|time| helpers::elapsed_since(self.start) >= time)
+            || limits.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+    }
+
+    /// Scores a position from the point of view of the side to move by searching to the given depth, filling `pv` with the principal variation.
+    fn negamax(&mut self, position: &Position, depth: u32, ply: u32, mut alpha: i32, beta: i32, pv: &mut Vec<Move>) -> i32 {
+        self.visit();
+        if self.stopped {
+            return 0;
+        }
+        let hash = position.zobrist_hash();
+        if ply > 0 && self.path.contains(&hash) {
+            return 0;
+        }
+        let entry = self.table[hash as usize % TABLE_SIZE].filter(|entry| entry.hash == hash);
+        if let Some(entry) = entry {
+            if ply > 0 && entry.depth >= depth {
+                let score = from_table(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => (),
+                }
+            }
+        }
+        if depth == 0 {
+            return self.quiescence(position, alpha, beta);
+        }
+        let mut moves = position.gen_move_list();
+        if moves.is_empty() {
+            return if position.is_check() { -MATE_SCORE + ply as i32 } else { 0 };
+        }
+        order_moves(position, &mut moves, entry.and_then(|entry| entry.best_move));
+        let original_alpha = alpha;
+        let (mut best_score, mut best_move) = (-MATE_SCORE, moves[0]);
+        self.path.push(hash);
+        for &move_ in &moves {
+            let mut child_pv = Vec::new();
+            let score = -self.negamax(&position.with_legal_move_made(move_), depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            if self.stopped {
+                break;
+            }
+            if score > best_score {
+                (best_score, best_move) = (score, move_);
+                if score > alpha {
+                    alpha = score;
+                    pv.clear();
+                    pv.push(move_);
+                    pv.extend(child_pv);
+                }
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        self.path.pop();
+        if pv.is_empty() {
+            pv.push(best_move);
+        }
+        if !self.stopped {
+            let bound = if best_score <= original_alpha {
+                Bound::Upper
+            } else if best_score >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            self.table[hash as usize % TABLE_SIZE] = Some(TableEntry {
+                hash,
+                depth,
+                score: to_table(best_score, ply),
+                bound,
+                best_move: Some(best_move),
+            });
+        }
+        best_score
+    }
+
+    /// Scores a position from the point of view of the side to move by searching captures until the position is quiet,
+    /// assuming the side to move can choose not to capture.
+    fn quiescence(&mut self, position: &Position, mut alpha: i32, beta: i32) -> i32 {
+        self.visit();
+        if self.stopped {
+            return 0;
+        }
+        let stand_pat = eval::evaluate_for(position, position.side_to_move());
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+        let mut captures = position.gen_captures();
+        order_moves(position, &mut captures, None);
+        for capture in captures {
+            let score = -self.quiescence(&position.with_legal_move_made(capture), -beta, -alpha);
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+/// Converts a score relative to the root of the search to one relative to the current position (which checkmate scores depend on),
+/// for storage in the transposition table.
+fn to_table(score: i32, ply: u32) -> i32 {
+    if score > MATE_SCORE - MAX_DEPTH as i32 * 2 {
+        score + ply as i32
+    } else if score < -MATE_SCORE + MAX_DEPTH as i32 * 2 {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// Converts a score from the transposition table back to one relative to the root of the search.
+fn from_table(score: i32, ply: u32) -> i32 {
+    if score > MATE_SCORE - MAX_DEPTH as i32 * 2 {
+        score - ply as i32
+    } else if score < -MATE_SCORE + MAX_DEPTH as i32 * 2 {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+/// Orders moves for searching: the best move from the transposition table first, then captures of the most valuable pieces
+/// by the least valuable attackers, then promotions, then quiet moves.
+fn order_moves(position: &Position, moves: &mut [Move], best_move: Option<Move>) {
+    moves.sort_by_cached_key(|&move_| {
+        if Some(move_) == best_move {
+            return i32::MIN;
+        }
+        let Some(Piece(attacker, _)) = position.content[move_.0] else {
+            return 0;
+        };
+        let victim = match move_.2 {
+            Some(SpecialMoveType::EnPassant) => Some(PieceType::P),
+            Some(SpecialMoveType::CastlingKingside | SpecialMoveType::CastlingQueenside) => None,
+            _ => position.content[move_.1].map(|Piece(victim, _)| victim),
+        };
+        let promotion = matches!(move_.2, Some(SpecialMoveType::Promotion(_)));
+        match victim {
+            Some(victim) => -(victim.value() as i32 * 10 - attacker.value() as i32) - 1000,
+            None if promotion => -500,
+            None => 0,
+        }
+    });
+}
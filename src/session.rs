@@ -0,0 +1,276 @@
+use super::{Board, Color, GameResult, Move, SessionError, TimeControl};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+/// Represents a chess clock: the time remaining for each side, counting down for the side to move.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct Clock {
+    /// The time remaining for white and black, as of the start of the current turn
+    remaining: [Duration; 2],
+    /// The time added after every move
+    increment: Duration,
+    /// The instant at which the current turn started
+    turn_started: Instant,
+}
+
+impl Clock {
+    /// Creates a clock for the given time control, with the current turn starting at `now`.
+    pub fn new(time_control: TimeControl, now: Instant) -> Self {
+        Self {
+            remaining: [time_control.base; 2],
+            increment: time_control.increment,
+            turn_started: now,
+        }
+    }
+
+    /// Returns the time remaining for the given side at `now`, given the side to move.
+    pub fn remaining_at(&self, side: Color, side_to_move: Color, now: Instant) -> Duration {
+        let remaining = self.remaining[Self::index(side)];
+        if side == side_to_move {
+            remaining.saturating_sub(now.saturating_duration_since(self.turn_started))
+        } else {
+            remaining
+        }
+    }
+
+    /// Ends the turn of the given side at `now`, charging it the time it took and adding the increment.
+    /// Returns `false` (without adding the increment) if the side ran out of time during its turn.
+    fn end_turn(&mut self, side: Color, now: Instant) -> bool {
+        let remaining = self.remaining_at(side, side, now);
+        self.turn_started = now;
+        self.remaining[Self::index(side)] = remaining;
+        if remaining.is_zero() {
+            return false;
+        }
+        self.remaining[Self::index(side)] += self.increment;
+        true
+    }
+
+    fn index(side: Color) -> usize {
+        if side.is_white() {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+/// Represents something that happened in one of the games of a [`Session`].
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub struct SessionEvent<K> {
+    /// The id of the game
+    pub id: K,
+    /// What happened
+    pub kind: SessionEventKind,
+}
+
+/// Represents the kinds of events that happen in the games of a [`Session`].
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum SessionEventKind {
+    /// A move was played.
+    MoveMade(Move),
+    /// The given side ran out of time.
+    FlagFell(Color),
+    /// The game ended with the given result.
+    GameOver(GameResult),
+}
+
+/// A game managed by a [`Session`]: a board and, if the board has a time control, its clock.
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+struct SessionGame {
+    board: Board,
+    clock: Option<Clock>,
+}
+
+/// Manages many concurrent games (such as those of a simultaneous exhibition or a bot playing on a server), keyed by ids of type `K`.
+/// Games with a time control (see [`Board::time_control`]) are timed with a [`Clock`], and everything that happens in the games is
+/// collected in a single feed of events, retrieved with [`Session::drain_events`].
+///
+/// Methods which depend on the time have variants ending in `_at` which take the current instant as an argument, for determinism.
+#[derive(Clone, Debug)]
+pub struct Session<K> {
+    games: BTreeMap<K, SessionGame>,
+    events: Vec<SessionEvent<K>>,
+}
+
+impl<K: Ord + Clone> Session<K> {
+    /// Creates an empty session.
+    pub fn new() -> Self {
+        Self {
+            games: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Adds a game to the session, replacing any game with the same id. If the board has a time control, the clock of the side to move starts now,
+    /// with the time remaining for each side restored from the clock times recorded on the board (see [`Board::clock_remaining`]), if any.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn add_game(&mut self, id: K, board: Board) {
+        self.add_game_at(id, board, Instant::now())
+    }
+
+    /// Adds a game to the session like [`Session::add_game`], with the clock of the side to move starting at `now`.
+    pub fn add_game_at(&mut self, id: K, board: Board, now: Instant) {
+        let clock = board.time_control().map(|tc| {
+            let mut clock = Clock::new(tc, now);
+            for side in [Color::White, Color::Black] {
+                if let Some(remaining) = board.clock_remaining(side) {
+                    clock.remaining[Clock::index(side)] = remaining;
+                }
+            }
+            clock
+        });
+        self.games.insert(id, SessionGame { board, clock });
+    }
+
+    /// Removes a game from the session, returning its board if it exists.
+    pub fn remove_game(&mut self, id: &K) -> Option<Board> {
+        self.games.remove(id).map(|game| game.board)
+    }
+
+    /// Returns the board of the game with the given id, if it exists.
+    pub fn board(&self, id: &K) -> Option<&Board> {
+        self.games.get(id).map(|game| &game.board)
+    }
+
+    /// Returns the clock of the game with the given id, if it exists and has a time control.
+    pub fn clock(&self, id: &K) -> Option<&Clock> {
+        self.games.get(id).and_then(|game| game.clock.as_ref())
+    }
+
+    /// Returns an iterator over the ids and boards of the games in the session, in order of id.
+    pub fn games(&self) -> impl Iterator<Item = (&K, &Board)> {
+        self.games.iter().map(|(id, game)| (id, &game.board))
+    }
+
+    /// Returns the number of games in the session.
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    /// Checks whether the session has no games.
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    /// Returns the number of games in the session which are still ongoing.
+    pub fn ongoing_count(&self) -> usize {
+        self.games.values().filter(|game| game.board.is_ongoing()).count()
+    }
+
+    /// Plays a move in the game with the given id, charging the time taken to the clock of the side to move
+    /// and recording the time remaining on the board (see [`Board::move_clocks`]).
+    /// If the side had run out of time, the move is not played, and the game ends instead.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn make_move(&mut self, id: &K, move_: Move) -> Result<(), SessionError> {
+        self.make_move_at(id, move_, Instant::now())
+    }
+
+    /// Plays a move like [`Session::make_move`], at `now`.
+    pub fn make_move_at(&mut self, id: &K, move_: Move, now: Instant) -> Result<(), SessionError> {
+        let game = self.games.get_mut(id).ok_or(SessionError::UnknownGame)?;
+        if !game.board.is_ongoing() {
+            return Err(SessionError::GameOver);
+        }
+        if !game.board.is_legal(move_) {
+            return Err(SessionError::IllegalMove(move_));
+        }
+        let side = game.board.side_to_move();
+        if let Some(clock) = &mut game.clock {
+            if !clock.end_turn(side, now) {
+                game.board.flag(side).unwrap();
+                self.push_game_end(id, Some(side));
+                return Err(SessionError::GameOver);
+            }
+        }
+        let game = self.games.get_mut(id).unwrap();
+        game.board.make_move(move_).unwrap();
+        if let Some(clock) = &game.clock {
+            game.board.record_clock(clock.remaining[Clock::index(side)]).unwrap();
+        }
+        let move_ = game.board.last_move().unwrap();
+        self.events.push(SessionEvent {
+            id: id.clone(),
+            kind: SessionEventKind::MoveMade(move_),
+        });
+        self.push_game_end(id, None);
+        Ok(())
+    }
+
+    /// Ends every ongoing game in which the side to move has run out of time, returning the number of such games.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn check_flags(&mut self) -> usize {
+        self.check_flags_at(Instant::now())
+    }
+
+    /// Ends every ongoing game in which the side to move has run out of time at `now`, returning the number of such games.
+    pub fn check_flags_at(&mut self, now: Instant) -> usize {
+        let mut flagged = Vec::new();
+        for (id, game) in &mut self.games {
+            let side = game.board.side_to_move();
+            if let Some(clock) = &mut game.clock {
+                if game.board.is_ongoing() && clock.remaining_at(side, side, now).is_zero() {
+                    clock.end_turn(side, now);
+                    game.board.flag(side).unwrap();
+                    flagged.push((id.clone(), side));
+                }
+            }
+        }
+        let count = flagged.len();
+        for (id, side) in flagged {
+            self.push_game_end(&id, Some(side));
+        }
+        count
+    }
+
+    /// Returns the events that have happened since the last call, in the order in which they happened.
+    pub fn drain_events(&mut self) -> Vec<SessionEvent<K>> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Pushes the events for a game which has ended (if it has), optionally because the given side's flag fell.
+    fn push_game_end(&mut self, id: &K, flagged_side: Option<Color>) {
+        if let Some(side) = flagged_side {
+            self.events.push(SessionEvent {
+                id: id.clone(),
+                kind: SessionEventKind::FlagFell(side),
+            });
+        }
+        if let Some(result) = self.games[id].board.game_result() {
+            self.events.push(SessionEvent {
+                id: id.clone(),
+                kind: SessionEventKind::GameOver(result),
+            });
+        }
+    }
+}
+
+#[cfg(feature = "pgn")]
+impl<K: Ord + Clone + std::fmt::Display> Session<K> {
+    /// Serializes every game in the session as a PGN database, in order of id.
+    /// The tag pairs of each game are taken from its metadata (see [`Board::metadata`]), with missing tags of the
+    /// Seven Tag Roster filled in as unknown, and the id of the game stored in the _SessionId_ tag.
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for (id, game) in &self.games {
+            let mut tag_pairs: Vec<_> = game.board.metadata().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+            for (name, unknown) in [("Event", "?"), ("Site", "?"), ("Date", "????.??.??"), ("Round", "?"), ("White", "?"), ("Black", "?")] {
+                if !game.board.metadata().contains_key(name) {
+                    tag_pairs.push((name.to_owned(), unknown.to_owned()));
+                }
+            }
+            tag_pairs.push(("SessionId".to_owned(), id.to_string()));
+            let game_pgn = super::pgn::Pgn::from_board(game.board.clone(), tag_pairs).unwrap();
+            pgn.push_str(&format!("{game_pgn}\n\n"));
+        }
+        pgn
+    }
+}
+
+impl<K: Ord + Clone> Default for Session<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
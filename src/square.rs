@@ -0,0 +1,140 @@
+use super::{InvalidFileError, InvalidRankError};
+use std::fmt;
+
+/// Represents a file (column) of the board, from the a-file to the h-file.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Debug)]
+pub enum File {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+}
+
+impl File {
+    /// All of the files, from the a-file to the h-file.
+    pub const ALL: [File; 8] = [Self::A, Self::B, Self::C, Self::D, Self::E, Self::F, Self::G, Self::H];
+
+    /// Returns the file with the given index (0 for the a-file, up to 7 for the h-file), or `None` if the index is not less than 8.
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// Returns the index of the file (0 for the a-file, up to 7 for the h-file).
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the file of a square index (`0..64`).
+    pub fn of(sq: usize) -> Self {
+        Self::ALL[sq % 8]
+    }
+
+    /// Returns the file the given number of files away (towards the h-file if positive, or the a-file if negative),
+    /// or `None` if it is off the board.
+    pub fn offset(self, files: isize) -> Option<Self> {
+        self.index().checked_add_signed(files).and_then(Self::from_index)
+    }
+
+    /// Returns an iterator over the files from this file to the h-file.
+    pub fn onwards(self) -> impl DoubleEndedIterator<Item = Self> {
+        Self::ALL.into_iter().skip(self.index())
+    }
+}
+
+impl TryFrom<char> for File {
+    type Error = InvalidFileError;
+
+    /// Attempts to convert a file letter (`'a'` to `'h'`) to a `File`.
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'a'..='h' => Ok(Self::ALL[c as usize - 'a' as usize]),
+            _ => Err(InvalidFileError(c)),
+        }
+    }
+}
+
+impl From<File> for char {
+    /// Converts a `File` to its letter (`'a'` to `'h'`).
+    fn from(file: File) -> char {
+        (b'a' + file as u8) as char
+    }
+}
+
+impl fmt::Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", char::from(*self))
+    }
+}
+
+/// Represents a rank (row) of the board, from the first rank (white's back rank) to the eighth rank.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Debug)]
+pub enum Rank {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Eighth,
+}
+
+impl Rank {
+    /// All of the ranks, from the first rank to the eighth rank.
+    pub const ALL: [Rank; 8] = [Self::First, Self::Second, Self::Third, Self::Fourth, Self::Fifth, Self::Sixth, Self::Seventh, Self::Eighth];
+
+    /// Returns the rank with the given index (0 for the first rank, up to 7 for the eighth rank), or `None` if the index is not less than 8.
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// Returns the index of the rank (0 for the first rank, up to 7 for the eighth rank).
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the rank of a square index (`0..64`).
+    pub fn of(sq: usize) -> Self {
+        Self::ALL[sq / 8 % 8]
+    }
+
+    /// Returns the rank the given number of ranks away (towards the eighth rank if positive, or the first rank if negative),
+    /// or `None` if it is off the board.
+    pub fn offset(self, ranks: isize) -> Option<Self> {
+        self.index().checked_add_signed(ranks).and_then(Self::from_index)
+    }
+
+    /// Returns an iterator over the ranks from this rank to the eighth rank.
+    pub fn onwards(self) -> impl DoubleEndedIterator<Item = Self> {
+        Self::ALL.into_iter().skip(self.index())
+    }
+}
+
+impl TryFrom<char> for Rank {
+    type Error = InvalidRankError;
+
+    /// Attempts to convert a rank digit (`'1'` to `'8'`) to a `Rank`.
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '1'..='8' => Ok(Self::ALL[c as usize - '1' as usize]),
+            _ => Err(InvalidRankError(c)),
+        }
+    }
+}
+
+impl From<Rank> for char {
+    /// Converts a `Rank` to its digit (`'1'` to `'8'`).
+    fn from(rank: Rank) -> char {
+        (b'1' + rank as u8) as char
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", char::from(*self))
+    }
+}
@@ -0,0 +1,77 @@
+use super::{Bitboard, Move};
+
+/// Represents a layout of square indices, for converting between the indexing used by rschess and that of other libraries, GUIs, and datasets.
+///
+/// rschess numbers squares from a1 (0) along each rank, then rank by rank up to h8 (63), which is [`SquareMapping::A1RankMajor`].
+/// Indices (and bits of bitboards) from elsewhere can be imported with the `to_rschess` methods of their mapping, and exported with the `from_rschess` methods.
+/// Every index must be less than 64.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub enum SquareMapping {
+    /// a1 = 0, b1 = 1, ..., h1 = 7, a2 = 8, ..., h8 = 63 (the indexing of rschess, and of little-endian rank-file bitboards)
+    #[default]
+    A1RankMajor,
+    /// a8 = 0, b8 = 1, ..., h8 = 7, a7 = 8, ..., h1 = 63 (the order of FEN piece placement and of boards printed from white's point of view)
+    A8RankMajor,
+    /// a1 = 0, a2 = 1, ..., a8 = 7, b1 = 8, ..., h8 = 63
+    A1FileMajor,
+    /// a8 = 0, a7 = 1, ..., a1 = 7, b8 = 8, ..., h1 = 63
+    A8FileMajor,
+}
+
+impl SquareMapping {
+    /// Converts the index of a square in rschess to its index in this mapping.
+    pub fn from_rschess(self, sq: usize) -> usize {
+        let (file, rank) = (sq % 8, sq / 8);
+        match self {
+            Self::A1RankMajor => sq,
+            Self::A8RankMajor => (7 - rank) * 8 + file,
+            Self::A1FileMajor => file * 8 + rank,
+            Self::A8FileMajor => file * 8 + 7 - rank,
+        }
+    }
+
+    /// Converts the index of a square in this mapping to its index in rschess.
+    pub fn to_rschess(self, index: usize) -> usize {
+        let (major, minor) = (index / 8, index % 8);
+        match self {
+            Self::A1RankMajor => index,
+            Self::A8RankMajor => (7 - major) * 8 + minor,
+            Self::A1FileMajor => minor * 8 + major,
+            Self::A8FileMajor => (7 - minor) * 8 + major,
+        }
+    }
+
+    /// Rearranges a grid indexed by rschess squares (such as the squares of a position, see [`Position::to_grid`](crate::Position::to_grid))
+    /// so that it is indexed by this mapping.
+    pub fn grid_from_rschess<T: Copy>(self, grid: &[T; 64]) -> [T; 64] {
+        std::array::from_fn(|index| grid[self.to_rschess(index)])
+    }
+
+    /// Rearranges a grid indexed by this mapping so that it is indexed by rschess squares.
+    pub fn grid_to_rschess<T: Copy>(self, grid: &[T; 64]) -> [T; 64] {
+        std::array::from_fn(|sq| grid[self.from_rschess(sq)])
+    }
+
+    /// Converts a bitboard in which bit `i` stands for rschess square `i` (such as one from [`Position::piece_mask`](crate::Position::piece_mask))
+    /// to one in which bit `i` stands for square `i` of this mapping.
+    pub fn bitboard_from_rschess(self, bitboard: Bitboard) -> Bitboard {
+        (0..64).filter(|sq| bitboard & 1 << sq != 0).fold(0, |mapped, sq| mapped | 1 << self.from_rschess(sq))
+    }
+
+    /// Converts a bitboard in which bit `i` stands for square `i` of this mapping to one in which bit `i` stands for rschess square `i`.
+    pub fn bitboard_to_rschess(self, bitboard: Bitboard) -> Bitboard {
+        (0..64).filter(|index| bitboard & 1 << index != 0).fold(0, |mapped, index| mapped | 1 << self.to_rschess(index))
+    }
+
+    /// Returns the policy index of a move in this mapping, in the common from-square/to-square encoding `source * 64 + destination`
+    /// (which ignores promotion piece types).
+    pub fn policy_index(self, move_: Move) -> usize {
+        self.from_rschess(move_.0) * 64 + self.from_rschess(move_.1)
+    }
+
+    /// Returns the rschess source and destination squares of a policy index in this mapping (see [`SquareMapping::policy_index`]),
+    /// or `None` if the index is not less than 4096.
+    pub fn policy_squares(self, index: usize) -> Option<(usize, usize)> {
+        (index < 64 * 64).then(|| (self.to_rschess(index / 64), self.to_rschess(index % 64)))
+    }
+}
@@ -0,0 +1,45 @@
+//! [`mod@proptest`] strategies generating random legal positions, games, and moves, for property-testing code against rschess
+//! (for example, checking that a SAN or FEN serializer round-trips through rschess's parsers).
+//!
+//! Positions are reached by random playouts from the standard starting position, where each ply picks a legal move by index,
+//! so failing cases shrink towards shorter games and earlier moves.
+
+use super::{Board, Move, Position};
+use proptest::{collection, prelude::*};
+
+/// Plays out a game from the standard starting position, choosing each move by an index into the legal moves (modulo their number),
+/// and stopping early if the game ends.
+fn playout(choices: &[usize]) -> Board {
+    let mut board = Board::default();
+    for &choice in choices {
+        let moves = board.gen_legal_moves();
+        if moves.is_empty() {
+            break;
+        }
+        board.make_move(moves[choice % moves.len()]).unwrap();
+    }
+    board
+}
+
+/// Generates games played out randomly from the standard starting position, with up to `max_plies` halfmoves.
+/// The games may have ended (for example, by checkmate) before reaching `max_plies`.
+pub fn boards(max_plies: usize) -> impl Strategy<Value = Board> {
+    collection::vec(any::<usize>(), 0..=max_plies).prop_map(|choices| playout(&choices))
+}
+
+/// Generates positions reached by random playouts of up to `max_plies` halfmoves (see [`boards`]).
+pub fn positions(max_plies: usize) -> impl Strategy<Value = Position> {
+    boards(max_plies).prop_map(|board| board.position().clone())
+}
+
+/// Generates games which are still ongoing, along with a legal move in the current position,
+/// reached by random playouts of up to `max_plies` halfmoves (see [`boards`]).
+pub fn boards_with_move(max_plies: usize) -> impl Strategy<Value = (Board, Move)> {
+    (boards(max_plies), any::<usize>())
+        .prop_filter("the game is over", |(board, _)| board.is_ongoing())
+        .prop_map(|(board, choice)| {
+            let moves = board.gen_legal_moves();
+            let move_ = moves[choice % moves.len()];
+            (board, move_)
+        })
+}
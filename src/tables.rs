@@ -0,0 +1,441 @@
+//! Precomputed attack tables, built at compile time, for move generation and attack queries.
+
+use super::{Bitboard, Color, Piece};
+use std::sync::OnceLock;
+
+/// The file and rank steps of the eight directions in which long-range pieces move, indexed as in [`direction_index`].
+const DIRECTION_STEPS: [(isize, isize); 8] = [(1, 0), (0, 1), (-1, 1), (1, 1), (-1, 0), (0, -1), (1, -1), (-1, -1)];
+
+/// The file and rank steps of knight moves, in the order in which knight moves are generated.
+const KNIGHT_STEPS: [(isize, isize); 8] = [(-2, 1), (-1, 2), (1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1)];
+
+/// The file and rank steps of king moves, in the order in which king moves are generated.
+const KING_STEPS: [(isize, isize); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (-1, 1), (1, -1), (1, 1), (-1, -1)];
+
+/// The squares a knight on each square attacks.
+pub(crate) const KNIGHT_ATTACKS: [Bitboard; 64] = leaper_table(&KNIGHT_STEPS);
+
+/// The squares a king on each square attacks.
+pub(crate) const KING_ATTACKS: [Bitboard; 64] = leaper_table(&KING_STEPS);
+
+/// The squares a knight on each square moves to, in order (see [`targets`]).
+const KNIGHT_TARGETS: [[u8; 8]; 64] = target_table(&KNIGHT_STEPS);
+
+/// The squares a king on each square moves to (apart from castling), in order (see [`targets`]).
+const KING_TARGETS: [[u8; 8]; 64] = target_table(&KING_STEPS);
+
+/// The squares a pawn on each square attacks, for white and then for black.
+const PAWN_ATTACKS: [[Bitboard; 64]; 2] = [leaper_table(&[(-1, 1), (1, 1)]), leaper_table(&[(-1, -1), (1, -1)])];
+
+/// The squares from each square to the edge of the board (exclusive of the square itself) in each direction, indexed as in [`direction_index`].
+const RAYS: [[Bitboard; 64]; 8] = ray_table();
+
+/// The magic multipliers of the bishop attack tables (see [`Magic`]).
+const BISHOP_MAGICS: [u64; 64] = [
+    0x8008029802002200,
+    0x4291040808802804,
+    0x0008180040800300,
+    0x00088a0202aa1050,
+    0x000410a800000000,
+    0x0009100804040009,
+    0x0801140121080011,
+    0xa040808400824000,
+    0x000008a004040048,
+    0x0600200440808114,
+    0x2020410401204403,
+    0x000404106200c001,
+    0x0100011040800026,
+    0x00080088200a0820,
+    0x0008004804642080,
+    0x4000004402981800,
+    0x0710002220020088,
+    0x2010808202020402,
+    0x8010080844002820,
+    0x800c000124028000,
+    0x0002000422010040,
+    0x6438402200422000,
+    0x0010a1004c0c2000,
+    0x000a00e109010190,
+    0x08022010400414c0,
+    0x8428022220240101,
+    0x0008088004040010,
+    0x0008080000220020,
+    0x0421010000104000,
+    0x219102082500a000,
+    0x0018008042120150,
+    0x02108020a09c0402,
+    0x301c202000890208,
+    0xa004022000080100,
+    0x100c024100881200,
+    0x8000080800460a00,
+    0x1004010804440040,
+    0x420c920080041000,
+    0x05018c0114440100,
+    0x00040100308a0080,
+    0x0020821042801000,
+    0x0202026120001c02,
+    0x0002001044000800,
+    0x20aa844200800801,
+    0x0000012011001200,
+    0x0860209008808042,
+    0x0008100080a80200,
+    0x0808020050420201,
+    0x00051c0104c00000,
+    0x0000840108820022,
+    0x000a461842080004,
+    0x2400400914880002,
+    0x00040040102481b4,
+    0x2104a14202020060,
+    0x0004081041020060,
+    0x00a0840082005100,
+    0x0000412210101482,
+    0x0108504208042210,
+    0x000020044c040405,
+    0x4140050206051401,
+    0x0122008051820200,
+    0x0082800428109100,
+    0x9104042454440401,
+    0x141e200c00820848,
+];
+
+/// The magic multipliers of the rook attack tables (see [`Magic`]).
+const ROOK_MAGICS: [u64; 64] = [
+    0x0280038860400010,
+    0x098020004000b080,
+    0x2100110008402002,
+    0x0880080081041000,
+    0x0200020020041008,
+    0x2300040008010012,
+    0x0c00283004008201,
+    0x0180010000407a80,
+    0x0168800080400020,
+    0x0010400040201000,
+    0x1001002001001048,
+    0x1001002408100100,
+    0x0801000408010012,
+    0x4001000209000400,
+    0x08a20004c8020001,
+    0x2002801145002280,
+    0x0080860021004200,
+    0x001000c009402002,
+    0x00b0002004002800,
+    0x100a808010020800,
+    0x8101010008000410,
+    0x0244008002000480,
+    0x0000040010810208,
+    0x2000020000448534,
+    0x4104400480008033,
+    0x0000810100204000,
+    0x0440430900200010,
+    0x4600240900100100,
+    0x0060080080040080,
+    0x0001000300080400,
+    0x0004084400011002,
+    0x0023040200008041,
+    0x0580050043002080,
+    0x0400804002802008,
+    0x0001002001004010,
+    0x1000200901001000,
+    0x4410800801800c00,
+    0xa012003806001004,
+    0x0020100104008802,
+    0x0004808402000041,
+    0x0010400170898000,
+    0x0080500020004004,
+    0x1040408012020020,
+    0x8010040008004040,
+    0x2001080100110004,
+    0x0000020004008080,
+    0x0021010810040002,
+    0x0800008c43020024,
+    0x0000800021005100,
+    0x0070201040008080,
+    0x0000d04282006a00,
+    0x0010014400080240,
+    0x0001080110050100,
+    0x0012000810240600,
+    0x0402000801040200,
+    0x028100108a004100,
+    0x0050800300102045,
+    0x8208210040120882,
+    0x8010600101183441,
+    0x020b000910006045,
+    0x0241001002480005,
+    0x0081000400880241,
+    0x0000009008024124,
+    0x0048122980410402,
+];
+
+/// The indices in [`RAYS`] of the directions in which bishops move.
+const BISHOP_DIRECTIONS: [usize; 4] = [2, 3, 6, 7];
+
+/// The indices in [`RAYS`] of the directions in which rooks move.
+const ROOK_DIRECTIONS: [usize; 4] = [0, 1, 4, 5];
+
+/// Returns the square reached from the square `sq` by the given file and rank steps, or `None` if it is off the board.
+const fn step(sq: usize, (file_step, rank_step): (isize, isize)) -> Option<usize> {
+    let (file, rank) = ((sq % 8) as isize + file_step, (sq / 8) as isize + rank_step);
+    if file < 0 || file > 7 || rank < 0 || rank > 7 {
+        None
+    } else {
+        Some((rank * 8 + file) as usize)
+    }
+}
+
+/// Builds the table of the squares attacked from each square by a piece which moves by the given steps.
+const fn leaper_table(steps: &[(isize, isize)]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    let mut sq = 0;
+    while sq < 64 {
+        let mut i = 0;
+        while i < steps.len() {
+            if let Some(dest) = step(sq, steps[i]) {
+                table[sq] |= 1 << dest;
+            }
+            i += 1;
+        }
+        sq += 1;
+    }
+    table
+}
+
+/// Builds the table of the squares reached from each square by the given steps, in the order of the steps,
+/// followed by 64 in the place of each step which leaves the board.
+const fn target_table(steps: &[(isize, isize); 8]) -> [[u8; 8]; 64] {
+    let mut table = [[64; 8]; 64];
+    let mut sq = 0;
+    while sq < 64 {
+        let (mut i, mut len) = (0, 0);
+        while i < 8 {
+            if let Some(dest) = step(sq, steps[i]) {
+                table[sq][len] = dest as u8;
+                len += 1;
+            }
+            i += 1;
+        }
+        sq += 1;
+    }
+    table
+}
+
+/// Builds the table of rays from each square in each direction.
+const fn ray_table() -> [[Bitboard; 64]; 8] {
+    let mut table = [[0; 64]; 8];
+    let mut direction = 0;
+    while direction < 8 {
+        let mut sq = 0;
+        while sq < 64 {
+            let mut current_sq = sq;
+            while let Some(next_sq) = step(current_sq, DIRECTION_STEPS[direction]) {
+                table[direction][sq] |= 1 << next_sq;
+                current_sq = next_sq;
+            }
+            sq += 1;
+        }
+        direction += 1;
+    }
+    table
+}
+
+/// Returns the index in the tables of a direction, given as the difference in square index made by a step in that direction
+/// (1, 8, 7, or 9, or their negations).
+fn direction_index(direction: isize) -> usize {
+    match direction {
+        1 => 0,
+        8 => 1,
+        7 => 2,
+        9 => 3,
+        -1 => 4,
+        -8 => 5,
+        -7 => 6,
+        -9 => 7,
+        _ => panic!("not a direction"),
+    }
+}
+
+/// The attack table of a long-range piece on one square, looked up by multiplying the occupied squares which could block it
+/// (its _relevant occupancy_, the squares on its lines excluding the edges of the board) by a magic number, which maps every
+/// relevant occupancy to a distinct index into the table, or to an index shared only with occupancies giving the same attacks.
+struct Magic {
+    /// The squares whose occupancy affects the attacks
+    mask: Bitboard,
+    /// The magic multiplier
+    magic: u64,
+    /// The shift giving an index from the product (64 minus the number of squares in `mask`)
+    shift: u32,
+    /// The start of the table of this square in [`SlidingAttacks::attacks`]
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, occupied: Bitboard) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+/// The magic attack tables of bishops and rooks on every square.
+struct SlidingAttacks {
+    bishops: Vec<Magic>,
+    rooks: Vec<Magic>,
+    attacks: Vec<Bitboard>,
+}
+
+/// Returns the magic attack tables, building them on first use.
+fn sliding_attacks() -> &'static SlidingAttacks {
+    static SLIDING_ATTACKS: OnceLock<SlidingAttacks> = OnceLock::new();
+    SLIDING_ATTACKS.get_or_init(|| {
+        let mut attacks = Vec::new();
+        let mut build = |magics: &[u64; 64], directions: &[usize; 4]| {
+            (0..64)
+                .map(|sq| {
+                    // the last square of a ray has nothing behind it to block
+                    let mask = directions.iter().fold(0, |mask, &direction| mask | RAYS[direction][sq]) & !edge_mask(directions, sq);
+                    let magic = Magic {
+                        mask,
+                        magic: magics[sq],
+                        shift: 64 - mask.count_ones(),
+                        offset: attacks.len(),
+                    };
+                    attacks.resize(attacks.len() + (1 << mask.count_ones()), 0);
+                    // enumerate the subsets of the mask
+                    let mut occupied: Bitboard = 0;
+                    loop {
+                        attacks[magic.index(occupied)] = slow_attacks(sq, directions, occupied);
+                        occupied = occupied.wrapping_sub(mask) & mask;
+                        if occupied == 0 {
+                            break;
+                        }
+                    }
+                    magic
+                })
+                .collect::<Vec<_>>()
+        };
+        let bishops = build(&BISHOP_MAGICS, &BISHOP_DIRECTIONS);
+        let rooks = build(&ROOK_MAGICS, &ROOK_DIRECTIONS);
+        SlidingAttacks { bishops, rooks, attacks }
+    })
+}
+
+/// Returns the last square of a nonempty ray in the direction with the given index.
+fn ray_end(ray: Bitboard, direction: usize) -> usize {
+    if direction < 4 {
+        63 - ray.leading_zeros() as usize
+    } else {
+        ray.trailing_zeros() as usize
+    }
+}
+
+/// Returns the squares at the ends of the rays from the square `sq` in the given directions.
+fn edge_mask(directions: &[usize; 4], sq: usize) -> Bitboard {
+    directions
+        .iter()
+        .filter(|&&direction| RAYS[direction][sq] != 0)
+        .fold(0, |edges, &direction| edges | 1 << ray_end(RAYS[direction][sq], direction))
+}
+
+/// Computes the attacks of a long-range piece on the square `sq` moving in the given directions by walking along its rays.
+fn slow_attacks(sq: usize, directions: &[usize; 4], occupied: Bitboard) -> Bitboard {
+    directions.iter().fold(0, |attacks, &direction| {
+        let ray = RAYS[direction][sq];
+        let blockers = ray & occupied;
+        if blockers == 0 {
+            attacks | ray
+        } else {
+            // the squares behind the nearest blocker are not attacked
+            let nearest = if direction < 4 { blockers.trailing_zeros() } else { 63 - blockers.leading_zeros() } as usize;
+            attacks | ray & !RAYS[direction][nearest]
+        }
+    })
+}
+
+/// Returns the squares attacked by a bishop on the square `sq`, given the occupied squares of the board.
+/// A bishop attacks along its diagonals up to and including the first occupied square.
+pub(crate) fn bishop_attacks(sq: usize, occupied: Bitboard) -> Bitboard {
+    let tables = sliding_attacks();
+    tables.attacks[tables.bishops[sq].index(occupied)]
+}
+
+/// Returns the squares attacked by a rook on the square `sq`, given the occupied squares of the board.
+/// A rook attacks along its rank and file up to and including the first occupied square.
+pub(crate) fn rook_attacks(sq: usize, occupied: Bitboard) -> Bitboard {
+    let tables = sliding_attacks();
+    tables.attacks[tables.rooks[sq].index(occupied)]
+}
+
+/// Returns the squares attacked by a queen on the square `sq`, given the occupied squares of the board.
+pub(crate) fn queen_attacks(sq: usize, occupied: Bitboard) -> Bitboard {
+    bishop_attacks(sq, occupied) | rook_attacks(sq, occupied)
+}
+
+/// Returns the occupied squares of a position with the given content.
+pub(crate) fn occupancy(content: &[Option<Piece>; 64]) -> Bitboard {
+    content.iter().enumerate().fold(0, |occupied, (sq, piece)| occupied | (piece.is_some() as Bitboard) << sq)
+}
+
+/// Returns the squares a pawn of color `side` on the square `sq` attacks.
+pub(crate) fn pawn_attacks(side: Color, sq: usize) -> Bitboard {
+    PAWN_ATTACKS[if side.is_white() { 0 } else { 1 }][sq]
+}
+
+/// Returns an iterator over the squares a knight on the square `sq` moves to, in the order in which knight moves are generated.
+pub(crate) fn knight_targets(sq: usize) -> impl Iterator<Item = usize> {
+    targets(&KNIGHT_TARGETS[sq])
+}
+
+/// Returns an iterator over the squares a king on the square `sq` moves to (apart from castling), in the order in which king moves are generated.
+pub(crate) fn king_targets(sq: usize) -> impl Iterator<Item = usize> {
+    targets(&KING_TARGETS[sq])
+}
+
+/// Returns an iterator over the squares of an entry of a target table, which ends at the first square of 64.
+fn targets(targets: &'static [u8; 8]) -> impl Iterator<Item = usize> {
+    targets.iter().take_while(|&&sq| sq < 64).map(|&sq| sq as usize)
+}
+
+/// Returns an iterator over the squares from the square `sq` (exclusive) to the edge of the board in the given direction,
+/// in order of distance from `sq`. The direction is the difference in square index made by a step in it (1, 8, 7, or 9, or their negations).
+pub(crate) fn ray(sq: usize, direction: isize) -> Squares {
+    Squares {
+        squares: RAYS[direction_index(direction)][sq],
+        descending: direction < 0,
+    }
+}
+
+/// Returns an iterator over the squares of a bitboard which lie on the ray from the square `sq` in the given direction (see [`ray`]),
+/// in order of distance from `sq`.
+pub(crate) fn ray_within(sq: usize, direction: isize, bitboard: Bitboard) -> Squares {
+    let mut squares = ray(sq, direction);
+    squares.squares &= bitboard;
+    squares
+}
+
+/// Returns an iterator over the squares in a bitboard, in ascending order.
+pub(crate) fn squares(bitboard: Bitboard) -> Squares {
+    Squares { squares: bitboard, descending: false }
+}
+
+/// An iterator over the squares in a bitboard.
+pub(crate) struct Squares {
+    squares: Bitboard,
+    descending: bool,
+}
+
+impl Iterator for Squares {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.squares == 0 {
+            return None;
+        }
+        let sq = if self.descending { 63 - self.squares.leading_zeros() } else { self.squares.trailing_zeros() } as usize;
+        self.squares &= !(1 << sq);
+        Some(sq)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.squares.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Squares {}
@@ -0,0 +1,36 @@
+use super::Color;
+
+/// Represents a pending offer by one side to take back the most recent moves of a game, made with [`Board::offer_takeback`](crate::Board::offer_takeback).
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct TakebackOffer {
+    /// The side that made the offer
+    side: Color,
+    /// The number of halfmoves to take back
+    plies: usize,
+}
+
+impl TakebackOffer {
+    pub(crate) fn new(side: Color, plies: usize) -> Self {
+        Self { side, plies }
+    }
+
+    /// Returns the side that made the offer.
+    pub fn side(&self) -> Color {
+        self.side
+    }
+
+    /// Returns the number of halfmoves that would be taken back if the offer were accepted.
+    pub fn plies(&self) -> usize {
+        self.plies
+    }
+
+    /// Returns the number of halfmoves to take back for the given side to take back its last move, given the side to move:
+    /// one if the opponent is to move, and two (the opponent's reply as well) if the side itself is to move.
+    pub fn plies_for_last_move(side: Color, side_to_move: Color) -> usize {
+        if side == side_to_move {
+            2
+        } else {
+            1
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{helpers, Board, Color, Fen, Move, PieceType, SpecialMoveType};
+use super::{helpers, Board, CancelToken, Cancellable, Color, DrawType, Epd, EpdOperation, Fen, GameResult, Move, Outcome, Piece, PieceType, Rules, SpecialMoveType, Termination, WinType};
 
 #[test]
 fn default_board() {
@@ -279,6 +279,32 @@ fn undo_move() {
     println!("{board}");
 }
 
+#[test]
+fn board_invariants() {
+    use super::BoardInvariantError;
+
+    let mut board = Board::from_fen(Fen::try_from("r3k2r/ppp2ppp/5q2/3pP3/8/8/PPPQ1PPP/R3K2R w KQkq d6 0 12").unwrap());
+    assert_eq!(board.verify_invariants(), Ok(()));
+    board.make_moves_san("exd6 O-O-O O-O Qf4 dxc7 h6 cxd8=N").unwrap();
+    assert!(board.verify_invariants().is_ok());
+    board.undo_moves(3).unwrap();
+    board.back().unwrap();
+    assert!(board.verify_invariants().is_ok());
+    board.forward().unwrap();
+    board.offer_takeback(Color::Black, 2).unwrap();
+    board.resign(Color::White).unwrap();
+    assert!(board.verify_invariants().is_ok());
+    board.undo_moves(3).unwrap();
+    assert!(board.verify_invariants().is_ok());
+    // a target square on the wrong rank for the side to move is accepted in FENs, but has no pawn which could have advanced past it
+    let board = Board::from_fen(Fen::try_from("4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1").unwrap());
+    assert_eq!(board.verify_invariants(), Err(BoardInvariantError::EnPassantTarget(0)));
+    assert_eq!(
+        board.verify_invariants().unwrap_err().to_string(),
+        "Board invariant violated: the en passant target square at ply 0 is inconsistent with the board"
+    );
+}
+
 #[test]
 fn to_san() {
     let mut board = Board::from_fen(Fen::try_from("7k/4Q3/6Q1/3Q4/6Q1/8/2Q3Q1/K3Q3 w - - 0 1").unwrap());
@@ -295,6 +321,60 @@ fn to_san() {
     assert_eq!(board.checkmated_side(), Some(Color::Black));
 }
 
+#[test]
+fn legal_moves_san() {
+    let mut board = Board::from_fen(Fen::try_from("6B1/2N1N3/1N3N2/8/1N3N2/2N1N1K1/8/7k w - - 0 1").unwrap());
+    let sans = board.legal_moves_san();
+    assert!(sans.contains(&"Nc7d5".to_owned()) && sans.contains(&"Bd5+".to_owned()));
+    for move_ in ["g8d5", "h1g1"] {
+        let expected: Vec<_> = board.gen_legal_moves().into_iter().map(|m| board.move_to_san(m).unwrap()).collect();
+        assert_eq!(board.legal_moves_san(), expected);
+        board.make_move_uci(move_).unwrap();
+    }
+    let mut board = Board::default();
+    assert_eq!(board.legal_moves_san().len(), 20);
+    board.make_moves_san("f3 e5 g4 Qh4#").unwrap();
+    assert!(board.legal_moves_san().is_empty());
+}
+
+#[test]
+fn minimal_disambiguation() {
+    let san = |fen, uci| Board::from_fen(Fen::try_from(fen).unwrap()).move_to_san(Move::from_uci(uci).unwrap()).unwrap();
+    // by file when the file suffices, by rank when only the rank suffices, and by both when neither suffices
+    assert_eq!(san("4k3/8/8/8/8/8/8/2N1K1N1 w - - 0 1", "g1e2"), "Nge2");
+    assert_eq!(san("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1a3"), "R1a3");
+    assert_eq!(san("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a3"), "Ra3");
+    assert_eq!(san("4k3/8/8/Q1Q5/8/Q7/8/4K3 w - - 0 1", "a5b4"), "Qa5b4");
+    // only legal moves are considered, so a pinned knight does not force disambiguation
+    assert_eq!(san("4k3/8/8/8/1b6/2N5/8/4K1N1 w - - 0 1", "g1e2"), "Ne2");
+}
+
+#[test]
+fn strict_san_round_trip() {
+    for fen in [
+        "r1bq1rk1/2p1bppp/p1np1n2/1p2p3/4P3/1BP2N1P/PP1P1PP1/RNBQR1K1 b - - 0 9",
+        "4k3/8/8/Q1Q5/8/Q7/8/4K3 w - - 0 1",
+        "6B1/2N1N3/1N3N2/8/1N3N2/2N1N1K1/8/7k w - - 0 1",
+    ] {
+        let board = Board::from_fen(Fen::try_from(fen).unwrap());
+        for move_ in board.gen_legal_moves() {
+            let san = board.move_to_san(move_).unwrap();
+            assert_eq!(board.san_to_move_strict(&san).unwrap(), move_);
+            assert_eq!(board.normalize_san(&san).unwrap(), san);
+        }
+    }
+    let mut board = Board::default();
+    board.make_moves_san("e4 e5 Nf3 Nc6 Bc4 Nf6").unwrap();
+    assert_eq!(board.normalize_san("0-0").unwrap(), "O-O");
+    assert!(board.san_to_move_strict("O-O").is_ok());
+    assert!(board.san_to_move_strict("0-0").is_err());
+    assert!(board.san_to_move_strict("Ng5").is_ok());
+    assert!(board.san_to_move_strict("Nfg5").is_err());
+    assert!(board.san_to_move_strict("Bxf7").is_err());
+    assert_eq!(board.normalize_san("Bxf7").unwrap(), "Bxf7+");
+    assert_eq!(board.normalize_san("Nf3g5!?").unwrap(), "Ng5");
+}
+
 #[test]
 fn insufficient_material() {
     assert!(Board::from_fen(Fen::try_from("k1b1b1b1/1b1b1b1B/b1b1b1B1/1b1b1B1B/b1b1B1B1/1b1B1B1B/b1B3B1/1B1B1B1K w - - 0 1").unwrap()).is_insufficient_material());
@@ -329,6 +409,835 @@ fn valid_make_move_san() {
     println!("\n{}", board.pretty_print(Color::White, true));
 }
 
+#[test]
+fn uci_commands() {
+    use super::{
+        errors::{InvalidUciLineError, UciCommandError},
+        uci::{parse_go_command, parse_position_command, GoParams},
+    };
+    use std::time::Duration;
+
+    let board = parse_position_command("position startpos moves e2e4 e7e5 g1f3").unwrap();
+    assert_eq!(board.current_ply(), 3);
+    assert_eq!(board.to_fen().to_string(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+    assert_eq!(board.to_uci_moves(), "e2e4 e7e5 g1f3");
+    assert_eq!(parse_position_command(&format!("position startpos moves {}", board.to_uci_moves())).unwrap(), board);
+    assert_eq!(Board::default().to_uci_moves(), "");
+    let mut board = Board::from_fen(Fen::try_from("4k3/1P6/8/8/8/8/8/R3K3 w Q - 0 1").unwrap());
+    board.make_moves_san("O-O-O Kf7 b8=N").unwrap();
+    assert_eq!(board.to_uci_moves(), "e1c1 e8f7 b7b8n");
+    assert_eq!(parse_position_command("position startpos").unwrap(), Board::default());
+    let fen = "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1";
+    let board = parse_position_command(&format!("position fen {fen} moves e2e4")).unwrap();
+    assert_eq!(board.initial_fen().to_string(), fen);
+    assert_eq!(board.current_ply(), 1);
+    assert_eq!(parse_position_command(&format!("position fen {fen}")).unwrap().to_fen().to_string(), fen);
+    assert!(matches!(parse_position_command("position"), Err(UciCommandError::MissingPosition)));
+    assert!(matches!(parse_position_command("go depth 3"), Err(UciCommandError::UnexpectedCommand(_))));
+    assert!(matches!(parse_position_command("position fen 8/8 w"), Err(UciCommandError::InvalidFen(_))));
+    assert!(matches!(
+        parse_position_command("position startpos moves e2e4 e2e4"),
+        Err(UciCommandError::InvalidMoves(InvalidUciLineError { index: 1, .. }))
+    ));
+
+    let params = parse_go_command("go wtime 300000 btime -50 winc 2000 binc 2000 movestogo 40").unwrap();
+    assert_eq!(params.time(Color::White), Some(Duration::from_secs(300)));
+    assert_eq!(params.time(Color::Black), Some(Duration::ZERO));
+    assert_eq!(params.increment(Color::Black), Some(Duration::from_secs(2)));
+    assert_eq!(params.movestogo, Some(40));
+    let params = parse_go_command("go searchmoves e2e4 d2d4 depth 12 infinite").unwrap();
+    assert_eq!(params.searchmoves, vec![Move::from_uci("e2e4").unwrap(), Move::from_uci("d2d4").unwrap()]);
+    assert_eq!(params.depth, Some(12));
+    assert!(params.infinite);
+    assert_eq!(parse_go_command("go").unwrap(), GoParams::default());
+    assert!(matches!(parse_go_command("go depth"), Err(UciCommandError::MissingValue(_))));
+    assert!(matches!(parse_go_command("go nodes many"), Err(UciCommandError::InvalidValue(..))));
+    assert!(matches!(parse_go_command("go fast"), Err(UciCommandError::UnexpectedToken(_))));
+}
+
+#[test]
+fn legal_move_iterator() {
+    let board = Board::from_fen(Fen::try_from("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4").unwrap());
+    assert_eq!(board.legal_moves().collect::<Vec<_>>(), board.gen_legal_moves());
+    assert!(board.has_legal_move());
+    let fen = Fen::try_from("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+    let fresh = fen.position();
+    assert_eq!(fresh.legal_moves().collect::<Vec<_>>(), fresh.gen_non_illegal_moves());
+    let mut board = board;
+    board.make_move_san("Qxf7#").unwrap();
+    assert!(!board.has_legal_move());
+    assert_eq!(board.legal_moves().count(), 0);
+    assert!(!board.position().has_legal_move());
+    let stalemate = Board::from_fen(Fen::try_from("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1").unwrap());
+    assert!(stalemate.position().is_stalemate());
+    assert!(!stalemate.position().has_legal_move());
+}
+
+#[test]
+fn successors() {
+    let position = Board::default().position().clone();
+    let successors: Vec<_> = position.successors().collect();
+    assert_eq!(successors.len(), 20);
+    for (move_, successor) in &successors {
+        assert_eq!(*successor, position.with_move(move_).unwrap());
+    }
+    let leaves: usize = successors.iter().map(|(_, p)| p.successors().count()).sum();
+    assert_eq!(leaves as u64, position.perft(2));
+}
+
+#[test]
+fn filtered_movegen() {
+    use std::collections::HashSet;
+
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "4k3/8/8/2pP4/1K6/8/8/8 w - c6 0 2",
+        "4k3/8/8/8/8/8/4q3/R3K2R w KQ - 0 1",
+        "4k3/8/8/8/1b6/8/3r4/R3K1N1 w Q - 0 1",
+    ];
+    for fen in fens {
+        let fen = Fen::try_from(fen).unwrap();
+        let position = fen.position();
+        let legal: HashSet<_> = position.gen_non_illegal_moves().into_iter().collect();
+        let set = |moves: Vec<Move>| moves.into_iter().collect::<HashSet<_>>();
+        let captures = set(position.gen_captures());
+        let quiet = set(position.gen_quiet_moves());
+        let promotions = set(position.gen_promotions());
+        assert!(captures.iter().all(|&m| position.is_capture(m).unwrap()));
+        assert!(promotions.iter().all(|m| matches!(m.special_move_type(), Some(SpecialMoveType::Promotion(_)))));
+        assert_eq!(&(&(&captures | &quiet) | &promotions), &legal);
+        assert!(captures.is_disjoint(&quiet) && promotions.is_disjoint(&quiet));
+        let checks = set(position.gen_checks());
+        assert_eq!(checks, legal.iter().copied().filter(|m| position.with_move(m).unwrap().is_check()).collect());
+        let evasions = set(position.gen_check_evasions());
+        if position.is_check() {
+            assert_eq!(evasions, legal);
+        } else {
+            assert!(evasions.is_empty());
+        }
+    }
+}
+
+#[test]
+fn piece_patterns() {
+    use super::{PieceMatcher, PiecePattern};
+
+    let fen = Fen::try_from("6k1/5ppp/8/4P3/8/8/5PPP/6KR w - - 0 1").unwrap();
+    let position = fen.position();
+    assert_eq!(position.piece_mask(PieceMatcher::new(PieceType::K, Color::Black)), 1 << 62);
+    assert_eq!(position.piece_mask(PieceMatcher::of_type(PieceType::P)).count_ones(), 7);
+    assert_eq!(position.piece_mask(PieceMatcher::of_color(Color::White)).count_ones(), 6);
+    assert_eq!(position.piece_mask(PieceMatcher::any()).count_ones(), 10);
+    let pattern = PiecePattern::new()
+        .require([helpers::sq_to_idx('e', '5')], PieceMatcher::new(PieceType::P, Color::White))
+        .require([helpers::sq_to_idx('g', '8')], PieceMatcher::new(PieceType::K, Color::Black))
+        .require(helpers::squares_in_file('h'), PieceMatcher::of_type(PieceType::R));
+    assert!(position.matches_pattern(&pattern));
+    assert!(!Board::default().position().matches_pattern(&pattern));
+    assert!(position.matches_pattern(&PiecePattern::new()));
+    let pattern = pattern.require_empty(helpers::squares_in_rank('4'));
+    assert!(position.matches_pattern(&pattern));
+    assert!(!position.matches_pattern(&pattern.forbid(helpers::squares_in_rank('7'), PieceMatcher::new(PieceType::P, Color::Black))));
+}
+
+#[test]
+fn square_mappings() {
+    use super::SquareMapping;
+
+    let sq = helpers::sq_to_idx;
+    let mappings = [SquareMapping::A1RankMajor, SquareMapping::A8RankMajor, SquareMapping::A1FileMajor, SquareMapping::A8FileMajor];
+    for mapping in mappings {
+        assert!((0..64).all(|square| mapping.to_rschess(mapping.from_rschess(square)) == square));
+    }
+    assert_eq!(SquareMapping::A1RankMajor.from_rschess(sq('b', '1')), 1);
+    assert_eq!(SquareMapping::A8RankMajor.from_rschess(sq('a', '8')), 0);
+    assert_eq!(SquareMapping::A8RankMajor.from_rschess(sq('h', '1')), 63);
+    assert_eq!(SquareMapping::A1FileMajor.from_rschess(sq('a', '2')), 1);
+    assert_eq!(SquareMapping::A8FileMajor.from_rschess(sq('a', '7')), 1);
+    assert_eq!(SquareMapping::A8FileMajor.to_rschess(63), sq('h', '1'));
+    let position = Board::default().position().clone();
+    let grid = position.to_grid(SquareMapping::A8RankMajor);
+    assert_eq!((grid[0], grid[60]), (Some(Piece(PieceType::R, Color::Black)), Some(Piece(PieceType::K, Color::White))));
+    assert_eq!(SquareMapping::A8RankMajor.grid_to_rschess(&grid), position.to_grid(SquareMapping::A1RankMajor));
+    let king = position.piece_mask(super::PieceMatcher::new(PieceType::K, Color::White));
+    assert_eq!(SquareMapping::A8RankMajor.bitboard_from_rschess(king), 1 << 60);
+    assert_eq!(SquareMapping::A1FileMajor.bitboard_from_rschess(king), 1 << 32);
+    for mapping in mappings {
+        assert_eq!(mapping.bitboard_to_rschess(mapping.bitboard_from_rschess(0xffff)), 0xffff);
+        let move_ = Move::from_uci("e2e4").unwrap();
+        assert_eq!(mapping.policy_squares(mapping.policy_index(move_)), Some((move_.0, move_.1)));
+    }
+    assert_eq!(SquareMapping::A1RankMajor.policy_index(Move::from_uci("e2e4").unwrap()), 12 * 64 + 28);
+    assert_eq!(SquareMapping::A8RankMajor.policy_index(Move::from_uci("e2e4").unwrap()), 52 * 64 + 36);
+    assert_eq!(SquareMapping::A8RankMajor.policy_squares(64 * 64), None);
+}
+
+#[test]
+fn files_and_ranks() {
+    use super::{file_rank_to_idx, idx_to_file_rank, idx_to_sq, sq_to_idx, File, Rank};
+
+    assert_eq!(File::ALL.map(char::from), ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h']);
+    assert_eq!(Rank::ALL.iter().map(Rank::to_string).collect::<String>(), "12345678");
+    assert_eq!(File::try_from('h').unwrap(), File::H);
+    assert_eq!(Rank::try_from('8').unwrap(), Rank::Eighth);
+    assert!(File::try_from('i').is_err() && Rank::try_from('0').is_err() && Rank::try_from('9').is_err());
+    assert_eq!(File::C.offset(-2), Some(File::A));
+    assert_eq!(File::C.offset(-3), None);
+    assert_eq!(Rank::Seventh.offset(1), Some(Rank::Eighth));
+    assert_eq!(Rank::Seventh.offset(2), None);
+    assert_eq!(File::F.onwards().collect::<Vec<_>>(), [File::F, File::G, File::H]);
+    assert_eq!(Rank::Sixth.onwards().rev().collect::<Vec<_>>(), [Rank::Eighth, Rank::Seventh, Rank::Sixth]);
+    for idx in 0..64 {
+        let (file, rank) = idx_to_file_rank(idx).unwrap();
+        assert_eq!(file_rank_to_idx(file, rank), idx);
+        assert_eq!((File::of(idx), Rank::of(idx)), (file, rank));
+        let (file, rank) = idx_to_sq(idx).unwrap();
+        assert_eq!(sq_to_idx(file, rank).unwrap(), idx);
+    }
+    assert_eq!(sq_to_idx('h', '8').unwrap(), 63);
+    assert_eq!(idx_to_sq(63).unwrap(), ('h', '8'));
+    assert!(idx_to_file_rank(64).is_err() && idx_to_sq(64).is_err());
+    assert!(sq_to_idx('i', '1').is_err() && sq_to_idx('a', '9').is_err());
+}
+
+#[test]
+fn legal_moves_from_and_of() {
+    let mut board = Board::default();
+    let sq = helpers::sq_to_idx;
+    let knight_moves = board.legal_moves_from(sq('g', '1'));
+    assert_eq!(knight_moves, vec![Move(sq('g', '1'), sq('f', '3'), None), Move(sq('g', '1'), sq('h', '3'), None)]);
+    assert!(board.legal_moves_from(sq('e', '7')).is_empty());
+    assert!(board.legal_moves_from(sq('e', '4')).is_empty());
+    assert!(board.legal_moves_from(64).is_empty());
+    assert_eq!(board.legal_moves_of(PieceType::N).len(), 4);
+    assert_eq!(board.legal_moves_of(PieceType::P).len(), 16);
+    assert!(board.legal_moves_of(PieceType::K).is_empty());
+    board.make_moves_san("f3 e5 g4 Qh4#").unwrap();
+    assert!(board.legal_moves_from(sq('e', '1')).is_empty());
+    assert!(board.legal_moves_of(PieceType::P).is_empty());
+}
+
+#[test]
+fn mobility_map() {
+    let sq = helpers::sq_to_idx;
+    let map = Board::default().mobility_map();
+    assert_eq!(map.len(), 16);
+    assert_eq!(map[&sq('g', '1')], [sq('f', '3'), sq('h', '3')]);
+    assert_eq!(map[&sq('e', '2')], [sq('e', '3'), sq('e', '4')]);
+    assert!(map[&sq('a', '1')].is_empty());
+    // promotions are a single destination, and castling leads to the king's destination
+    let mut board = Board::from_fen(Fen::try_from("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap());
+    let map = board.mobility_map();
+    assert_eq!(map[&sq('b', '7')], [sq('a', '8'), sq('b', '8')]);
+    assert!(map[&sq('e', '1')].contains(&sq('c', '1')) && map[&sq('e', '1')].contains(&sq('g', '1')));
+    for (&square, dests) in &map {
+        let mut expected: Vec<_> = board.legal_moves_from(square).into_iter().map(|move_| move_.1).collect();
+        expected.sort_unstable();
+        expected.dedup();
+        assert_eq!(*dests, expected);
+    }
+    board.make_moves_san("Rxa8+").unwrap();
+    assert!(board.mobility_map().keys().all(|&square| board.position().content[square].unwrap().1 == Color::Black));
+    let mut board = Board::default();
+    board.make_moves_san("f3 e5 g4 Qh4#").unwrap();
+    assert!(board.mobility_map().is_empty());
+}
+
+#[test]
+fn proof_games() {
+    use super::{errors::ProofGameError, verify::verify_proof_game};
+    let moves = |uci: &str| uci.split(' ').map(|m| Move::from_uci(m).unwrap()).collect::<Vec<_>>();
+    let board = verify_proof_game(&moves("f2f3 e7e5 g2g4 d8h4")).unwrap();
+    assert!(board.is_game_over());
+    assert!(board.position().is_reachable_from_startpos());
+    assert_eq!(verify_proof_game(&moves("e2e4 e7e5 e4e5")), Err(ProofGameError::IllegalMove(2, Move::from_uci("e4e5").unwrap())));
+    assert_eq!(verify_proof_game(&moves("f2f3 e7e5 g2g4 d8h4 e1f2")), Err(ProofGameError::GameOver(4)));
+    let reachable = |fen: &str| Fen::try_from(fen).unwrap().position().is_reachable_from_startpos();
+    assert!(reachable("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"));
+    assert!(reachable("4k3/8/8/8/8/3B4/PPPPPP2/2B1KB1B w - - 0 1"));
+    assert!(reachable("r1bqkbnr/pppppppp/8/8/8/4P3/PPPPP1PP/RNBQKBNR b KQkq - 0 1"));
+    assert!(reachable("4k3/8/8/8/8/8/4Q3/4K3 b - - 0 1"));
+    // too many pawns, or more promoted pieces (here light-squared bishops) than missing pawns
+    assert!(!reachable("4k3/8/8/8/P7/8/PPPPPPPP/4K3 w - - 0 1"));
+    assert!(!reachable("4k3/8/8/8/8/3B4/PPPPPPP1/2B1KB1B w - - 0 1"));
+    // doubled pawns need a capture, but black has lost no pieces
+    assert!(!reachable("rnbqkbnr/pppppppp/8/8/8/4P3/PPPPP1PP/RNBQKBNR b KQkq - 0 1"));
+    // the pawn on a3 must have come from the c-file, which takes two captures, but it has advanced only one rank
+    assert!(!reachable("4k3/8/8/8/8/P7/PP6/4K3 w - - 0 1"));
+}
+
+#[test]
+fn pawn_hash() {
+    let mut board = Board::default();
+    let start = board.pawn_hash();
+    board.make_moves_san("Nf3 Nf6 Ng1 Ng8").unwrap();
+    assert_eq!(board.pawn_hash(), start);
+    let mut hash = start;
+    let mut board = Board::default();
+    for san in ["e4", "d5", "exd5", "c5", "dxc6", "Qb6", "cxb7", "Kd8", "bxa8=Q", "e5", "Nf3", "e4", "d4", "exd3", "Bxd3", "Nf6", "O-O"] {
+        let move_ = board.san_to_move(san).unwrap();
+        hash = board.position().next_pawn_hash(hash, move_).unwrap();
+        board.make_move(move_).unwrap();
+        assert_eq!(hash, board.pawn_hash(), "after {san}");
+    }
+    let other = Fen::try_from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQK2R b KQkq - 0 1").unwrap();
+    assert_eq!(other.position().pawn_hash(), start);
+    let other = Fen::try_from("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert_ne!(other.position().pawn_hash(), start);
+    assert!(Board::default().position().next_pawn_hash(start, Move::from_uci("e2e5").unwrap()).is_err());
+}
+
+#[test]
+fn zobrist_hash() {
+    let mut a = Board::default();
+    let mut b = Board::default();
+    a.make_moves_san("Nf3 Nf6 d4 d5").unwrap();
+    b.make_moves_san("d4 d5 Nf3 Nf6").unwrap();
+    assert_eq!(a.zobrist_hash(), b.zobrist_hash());
+    a.make_moves_san("Nbd2 Nbd7 Nb1 Nb8").unwrap();
+    assert_eq!(a.zobrist_hash(), b.zobrist_hash());
+    let hash = |fen: &str| Fen::try_from(fen).unwrap().position().zobrist_hash();
+    let start = hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    assert_eq!(start, Board::default().zobrist_hash());
+    assert_ne!(start, hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1"));
+    assert_ne!(start, hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w Kkq - 0 1"));
+    // an en passant target square only counts if the capture is legal
+    assert_eq!(
+        hash("rnbqkbnr/pppp1ppp/8/4p3/8/8/PPPPPPPP/RNBQKBNR w KQkq e6 0 2"),
+        hash("rnbqkbnr/pppp1ppp/8/4p3/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 2")
+    );
+    assert_ne!(
+        hash("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3"),
+        hash("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3")
+    );
+}
+
+#[test]
+fn castling_rights_fen() {
+    use super::{CastlingRights, GameBuilder};
+
+    // every Chess960 starting position (and every subset of its castling rights) is written with KQkq and parsed back identically
+    for number in 0..960 {
+        let position = GameBuilder::new().chess960(number).build().unwrap().position().clone();
+        let rights = position.castling_rights();
+        for side in [Color::White, Color::Black] {
+            let (kingside, queenside) = (rights.kingside(side).unwrap(), rights.queenside(side).unwrap());
+            let king = position.content.iter().position(|&p| p == Some(Piece(PieceType::K, side))).unwrap();
+            assert!(queenside < king && king < kingside);
+        }
+        for subset in 0..16 {
+            let mut position = position.clone();
+            for (i, right) in position.castling_rights.iter_mut().enumerate() {
+                if subset & (1 << i) == 0 {
+                    *right = None;
+                }
+            }
+            let fen = format!("{} 0 1", position.to_fen());
+            let expected: String = "KQkq".chars().enumerate().filter(|(i, _)| subset & (1 << i) != 0).map(|(_, c)| c).collect();
+            assert_eq!(fen.split(' ').nth(2).unwrap(), if expected.is_empty() { "-" } else { &expected });
+            assert_eq!(Fen::try_from(fen.as_str()).unwrap().position(), &position, "{fen}");
+        }
+    }
+    // a second rook on the side of the king's castling rook makes the right ambiguous, so its file is written instead
+    let fen = |fen: &str, moves: &str| {
+        let mut board = Board::from_fen(Fen::try_from(fen).unwrap());
+        board.make_moves_san(moves).unwrap();
+        board.position().to_fen()
+    };
+    assert_eq!(fen("r3k3/1r6/8/8/8/8/8/4K3 b q - 0 1", "Rbb8"), "rr2k3/8/8/8/8/8/8/4K3 w a -");
+    assert_eq!(fen("r3k3/1r6/8/8/8/8/8/R3K2R w KQq - 0 1", "Kf1 Rbb8"), "rr2k3/8/8/8/8/8/8/R4K1R w a -");
+    assert_eq!(fen("4k2r/8/8/8/8/8/6R1/4K2R w Kk - 0 1", "Rgg1"), "4k2r/8/8/8/8/8/8/4K1RR b Hk -");
+    assert_eq!(fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "Rd1 Rd8"), "3rk2r/8/8/8/8/8/8/3RK2R w Kk -");
+    // rights which are inconsistent with the board are dropped rather than written
+    let mut position = Fen::try_from("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap().position().clone();
+    position.castling_rights = [Some(7), Some(0), Some(63), None];
+    assert_eq!(position.castling_rights(), CastlingRights::validated([Some(7), None, None, None], &position.content));
+    assert_eq!(position.to_fen().split(' ').nth(2).unwrap(), "K");
+    position.content[4] = None;
+    assert!(position.castling_rights().is_empty());
+    assert_eq!(position.to_fen().split(' ').nth(2).unwrap(), "-");
+}
+
+#[test]
+fn evaluation() {
+    use super::eval::{evaluate, evaluate_for};
+
+    let eval = |fen: &str| evaluate(Fen::try_from(fen).unwrap().position());
+    assert_eq!(evaluate(Board::default().position()), 0);
+    // the evaluation of the mirror image of a position is equal and opposite
+    for fen in ["r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4", "8/5k2/8/3P4/8/8/1K6/8 b - - 0 50"] {
+        let position = Fen::try_from(fen).unwrap().position().clone();
+        assert_eq!(evaluate(&position), -evaluate(&position.color_swapped()));
+        assert_eq!(evaluate_for(&position, Color::Black), -evaluate(&position));
+    }
+    assert!(eval("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1") > 800);
+    assert!(eval("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1") > 0);
+    // without pieces, only the endgame tables are used: here a pawn about to promote, and kings on squares worth the same
+    assert_eq!(eval("k7/P7/8/8/8/8/8/K7 w - - 0 1"), 94 + 178);
+    // with all of the pieces, only the middlegame tables are used: here developing the knights gains 33 and 36 centipawns
+    assert_eq!(eval("rnbqkbnr/pppppppp/8/8/8/2N2N2/PPPPPPPP/R1BQKB1R b KQkq - 2 2"), 69);
+}
+
+#[cfg(feature = "search")]
+#[test]
+fn alpha_beta_search() {
+    use super::{
+        search::{search, Score, SearchLimits},
+        CancelToken,
+    };
+    use std::time::Duration;
+
+    let mut board = Board::default();
+    board.make_moves_san("f3 e5 g4").unwrap();
+    let result = search(&board, &SearchLimits::depth(3)).unwrap();
+    assert_eq!(result.best_move, board.san_to_move("Qh4#").unwrap());
+    assert_eq!((result.score, result.pv.len()), (Score::Mate(1), 1));
+    // mate in 2 with a rook and king
+    let board = Board::from_fen(Fen::try_from("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap());
+    let result = search(&board, &SearchLimits::depth(4)).unwrap();
+    assert_eq!(result.score, Score::Mate(2));
+    let mut line = board.clone();
+    for &move_ in &result.pv {
+        line.make_move(move_).unwrap();
+    }
+    assert!(line.position().is_checkmate());
+    // the side to move is mated next move whatever it does
+    let board = Board::from_fen(Fen::try_from("k7/8/1K6/8/8/8/8/7R b - - 0 1").unwrap());
+    assert_eq!(search(&board, &SearchLimits::depth(3)).unwrap().score, Score::Mate(-1));
+    // a hanging queen is taken, and quiescence sees that the queen on d5 is defended
+    let board = Board::from_fen(Fen::try_from("4k3/8/8/3q4/8/8/3Q4/4K3 w - - 0 1").unwrap());
+    assert_eq!(search(&board, &SearchLimits::depth(1)).unwrap().best_move, board.san_to_move("Qxd5").unwrap());
+    let board = Board::from_fen(Fen::try_from("4k3/8/2p5/3q4/8/8/3Q4/4K3 w - - 0 1").unwrap());
+    let result = search(&board, &SearchLimits::depth(1)).unwrap();
+    assert!(matches!(result.score, Score::Centipawns(cp) if (-300..0).contains(&cp)), "{:?}", result.score);
+    // other limits stop the search after depth 1
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    let limits = SearchLimits {
+        cancel: Some(cancel),
+        ..Default::default()
+    };
+    assert_eq!(search(&Board::default(), &limits).unwrap().depth, 1);
+    assert!(search(&Board::default(), &SearchLimits::time(Duration::from_millis(50))).is_some());
+    let mut board = Board::default();
+    board.make_moves_san("f3 e5 g4 Qh4#").unwrap();
+    assert!(search(&board, &SearchLimits::depth(2)).is_none());
+}
+
+#[test]
+fn endgame_classes() {
+    use super::EndgameClass;
+
+    let class = |fen: &str| Fen::try_from(fen).unwrap().position().endgame_class();
+    assert_eq!(class("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"), EndgameClass::NotEndgame);
+    assert_eq!(class("8/5pk1/8/8/8/8/5PK1/8 w - - 0 1"), EndgameClass::Pawn);
+    assert_eq!(class("8/5pk1/8/3n4/8/2N5/5PK1/8 w - - 0 1"), EndgameClass::Knight);
+    assert_eq!(class("8/5pk1/8/3b4/8/4B3/5PK1/8 w - - 0 1"), EndgameClass::OppositeColoredBishops);
+    assert_eq!(class("8/5pk1/8/3b4/8/3B4/5PK1/8 w - - 0 1"), EndgameClass::Bishop);
+    assert_eq!(class("8/5pk1/8/3b4/8/3N4/5PK1/8 w - - 0 1"), EndgameClass::BishopVsKnight);
+    assert_eq!(class("8/5pk1/8/3bn3/8/3N4/5PK1/8 w - - 0 1"), EndgameClass::MinorPiece);
+    assert_eq!(class("8/5pk1/8/3r4/8/3R4/5PK1/8 w - - 0 1"), EndgameClass::Rook);
+    assert_eq!(class("8/5pk1/8/3r4/8/3R4/5PK1/8 w - - 0 1").to_string(), "rook endgame");
+    assert_eq!(class("8/5pk1/8/3rb3/8/3RN3/5PK1/8 w - - 0 1"), EndgameClass::RookAndMinorPiece);
+    assert_eq!(class("8/5pk1/8/3b4/8/3R4/5PK1/8 w - - 0 1"), EndgameClass::RookVsMinorPiece);
+    assert_eq!(class("8/5pk1/8/3q4/8/3Q4/5PK1/8 w - - 0 1"), EndgameClass::Queen);
+    assert_eq!(class("8/6k1/8/3r4/8/3Q4/6K1/8 w - - 0 1"), EndgameClass::QueenVsRook);
+    assert_eq!(class("8/6k1/8/3r4/8/3Q4/6K1/8 w - - 0 1").to_string(), "queen vs rook");
+    assert_eq!(class("8/6k1/8/3rn3/8/3Q4/6K1/8 w - - 0 1"), EndgameClass::Mixed);
+    assert_eq!(class("8/6k1/8/8/8/3R4/6K1/8 w - - 0 1"), EndgameClass::Rook);
+}
+
+#[test]
+fn pawn_structure() {
+    let position = Fen::try_from("4k3/p7/8/3P4/P4p2/2P3P1/2P1P2P/4K3 w - - 0 1").unwrap().position().clone();
+    let sq = helpers::sq_to_idx;
+    let white = position.pawn_structure(Color::White);
+    assert_eq!(white.doubled, [sq('c', '2'), sq('c', '3')]);
+    assert_eq!(white.isolated, [sq('a', '4')]);
+    assert_eq!(white.backward, [sq('e', '2')]);
+    assert_eq!(white.passed, [sq('c', '2'), sq('h', '2'), sq('c', '3'), sq('d', '5')]);
+    assert_eq!(white.islands, [vec!['a'], vec!['c', 'd', 'e'], vec!['g', 'h']]);
+    assert_eq!((white.open_files, white.half_open_files), (vec!['b'], vec!['f']));
+    let black = position.pawn_structure(Color::Black);
+    assert!(black.doubled.is_empty() && black.backward.is_empty() && black.passed.is_empty());
+    assert_eq!(black.isolated, [sq('f', '4'), sq('a', '7')]);
+    assert_eq!(black.islands, [vec!['a'], vec!['f']]);
+    assert_eq!(black.half_open_files, ['c', 'd', 'e', 'g', 'h']);
+    let start = Board::default().position().pawn_structure(Color::Black);
+    assert_eq!(start.islands.len(), 1);
+    assert!(start.passed.is_empty() && start.open_files.is_empty() && start.half_open_files.is_empty());
+}
+
+#[test]
+fn phase_and_material_signature() {
+    use super::GamePhase;
+
+    let position = |fen: &str| Fen::try_from(fen).unwrap().position().clone();
+    let start = Board::default().position().clone();
+    assert_eq!(start.phase(), GamePhase::Opening);
+    assert_eq!(start.material_signature(), "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP");
+    let middlegame = position("r2qr1k1/ppp2ppp/8/8/8/8/PPP2PPP/R2QR1K1 w - - 0 1");
+    assert_eq!(middlegame.phase(), GamePhase::Middlegame);
+    let endgame = position("8/5k2/3r2p1/8/5P2/3R2PP/6K1/8 w - - 0 1");
+    assert_eq!(endgame.phase(), GamePhase::Endgame);
+    assert_eq!(endgame.material_signature(), "KRPPPvKRP");
+    assert_eq!(position("8/8/8/4k3/8/8/8/4K3 w - - 0 1").material_signature(), "KvK");
+}
+
+#[test]
+fn move_classification() {
+    let board = Board::from_fen(Fen::try_from("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 2").unwrap());
+    let uci = |s| Move::from_uci(s).unwrap();
+    assert!(board.is_capture(uci("e5d6")).unwrap());
+    assert_eq!(board.captured_piece(uci("e5d6")).unwrap(), Some(Piece::try_from('p').unwrap()));
+    assert_eq!(board.captured_piece(uci("a1a8")).unwrap(), Some(Piece::try_from('r').unwrap()));
+    assert_eq!(board.captured_piece(uci("a1a7")).unwrap(), None);
+    assert!(!board.is_capture(uci("e1g1")).unwrap());
+    assert!(board.is_castling(uci("e1g1")).unwrap());
+    assert!(board.is_castling(uci("e1c1")).unwrap());
+    assert!(!board.is_castling(uci("e1f1")).unwrap());
+    assert!(board.gives_check(uci("a1a8")).unwrap());
+    assert!(board.gives_check(uci("h1h8")).unwrap());
+    assert!(!board.gives_check(uci("a1a7")).unwrap());
+    assert!(board.gives_check(uci("e1e3")).is_err());
+    assert!(board.is_castling(uci("e1d3")).is_err());
+    assert!(board.captured_piece(uci("a1b3")).is_err());
+}
+
+#[test]
+fn eval_symmetry() {
+    use super::verify::{eval_asymmetries, EvalPerspective};
+
+    let fen = Fen::try_from("r3k2r/ppp2ppp/2n5/3pP3/8/5N2/PPP2PPP/R3K2R w KQkq d6 0 9").unwrap();
+    let position = fen.position();
+    let mirrored = position.color_swapped();
+    assert_eq!(mirrored.to_fen(), "r3k2r/ppp2ppp/5n2/8/3Pp3/2N5/PPP2PPP/R3K2R b KQkq d3");
+    assert_eq!(mirrored.color_swapped(), *position);
+    assert_eq!(position.gen_non_illegal_moves().len(), mirrored.gen_non_illegal_moves().len());
+    let corpus = [Board::default().position().clone(), position.clone()];
+    assert!(eval_asymmetries(&corpus, EvalPerspective::White, |p| p.material_balance()).is_empty());
+    let relative = |p: &super::Position| p.material_balance() * p.side_to_move().sign();
+    assert!(eval_asymmetries(&corpus, EvalPerspective::SideToMove, relative).is_empty());
+    let biased = |p: &super::Position| p.material_balance() + p.piece_mask(super::PieceMatcher::new(PieceType::N, Color::White)).count_ones() as i32;
+    let asymmetries = eval_asymmetries(&corpus, EvalPerspective::White, biased);
+    assert_eq!(asymmetries.len(), 2);
+    assert_eq!(asymmetries[1].fen, position.to_fen());
+    assert_eq!(asymmetries[1].mirrored_fen, mirrored.to_fen());
+    assert_eq!((asymmetries[1].score, asymmetries[1].mirrored_score), (1, 1));
+}
+
+#[test]
+fn scoresheet_resolution() {
+    use super::scoresheet::{resolve, MoveCandidate};
+
+    let c = MoveCandidate::new;
+    let candidates = vec![
+        vec![c("e4", 0.9), c("c4", 0.1)],
+        vec![c("e5", 0.6), c("c5", 0.4)],
+        vec![c("Nf3", 0.5), c("Nf6", 0.5)],
+        vec![c("Nc6", 0.8)],
+        vec![c("Bb5", 0.3), c("Bb6", 0.7)],
+        vec![],
+        vec![c("Ba4", 0.9)],
+    ];
+    let game = resolve(&candidates, 8).unwrap();
+    let sans: Vec<_> = game.board.history().into_iter().map(|(_, san)| san).collect();
+    assert_eq!(sans[..5], ["e4", "e5", "Nf3", "Nc6", "Bb5"]);
+    assert_eq!(sans[6], "Ba4");
+    assert_eq!(game.choices[..5], [Some(0), Some(0), Some(0), Some(0), Some(0)]);
+    assert_eq!(game.choices[5], None);
+    assert_eq!(game.moves.len(), 7);
+    assert!(game.log_probability < 0.);
+    let err = resolve(&[vec![c("e4", 1.)], vec![c("Ke7", 1.)], vec![c("Qh5", 1.)]], 4).unwrap_err();
+    assert_eq!(err.ply, 1);
+    assert_eq!(resolve(&[vec![c("e4", 0.)], vec![c("e5", 0.)]], 1).unwrap().moves.len(), 2);
+}
+
+#[test]
+fn antichess() {
+    use super::Antichess;
+
+    let mut game = Antichess::new();
+    assert_eq!(game.gen_legal_moves().len(), 20);
+    game.make_move_uci("e2e3").unwrap();
+    game.make_move_uci("b7b5").unwrap();
+    assert_eq!(game.gen_legal_moves(), vec![Move(helpers::sq_to_idx('f', '1'), helpers::sq_to_idx('b', '5'), None)]);
+    assert!(game.make_move_uci("a2a3").is_err());
+    game.make_move_uci("f1b5").unwrap();
+    game.undo_move().unwrap();
+    assert_eq!(game.to_fen().to_string(), "rnbqkbnr/p1pppppp/8/1p6/8/4P3/PPPP1PPP/RNBQKBNR w - b6 0 2");
+
+    let mut game = Antichess::from_fen("8/8/8/8/8/8/1p6/R7 b - - 0 1").unwrap();
+    assert_eq!(game.gen_legal_moves().len(), 5);
+    game.make_move_uci("b2a1k").unwrap();
+    assert_eq!(game.game_result(), Some(GameResult::Wins(Color::White, WinType::Variant)));
+    assert_eq!(game.to_fen().to_string(), "8/8/8/8/8/8/8/k7 w - - 0 2");
+    assert!(game.gen_legal_moves().is_empty());
+
+    let game = Antichess::from_fen("8/8/8/8/8/p7/P7/8 w - - 0 1").unwrap();
+    assert_eq!(game.game_result(), Some(GameResult::Wins(Color::White, WinType::Variant)));
+    let mut game = Antichess::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    game.make_move_uci("e1e2").unwrap();
+    game.make_move_uci("e8e7").unwrap();
+    assert!(game.is_ongoing());
+    assert!(Antichess::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").is_err());
+}
+
+#[test]
+fn variant_trait() {
+    use super::{Position, Standard, Variant, VariantBoard};
+
+    let mut game = VariantBoard::<Standard>::new();
+    for uci in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+        game.make_move_uci(uci).unwrap();
+    }
+    assert_eq!(game.game_result(), Some(GameResult::Wins(Color::Black, WinType::Checkmate)));
+
+    /// Three-check: a side also wins by giving a third check.
+    #[derive(Eq, PartialEq, Hash, Clone, Debug, Default)]
+    struct ThreeCheck;
+
+    impl Variant for ThreeCheck {
+        type State = [u8; 2];
+
+        fn starting_fen(&self) -> &'static str {
+            Standard.starting_fen()
+        }
+
+        fn update_state(&self, checks: &mut [u8; 2], position: &Position, move_: Move) {
+            if position.gives_check(move_).unwrap() {
+                checks[position.side_to_move().is_black() as usize] += 1;
+            }
+        }
+
+        fn result(&self, position: &Position, checks: &[u8; 2], legal_moves: &[Move]) -> Option<GameResult> {
+            match checks.iter().position(|&n| n >= 3) {
+                Some(i) => Some(GameResult::Wins(if i == 0 { Color::White } else { Color::Black }, WinType::Variant)),
+                None => Standard.result(position, &(), legal_moves),
+            }
+        }
+    }
+
+    let mut game = VariantBoard::<ThreeCheck>::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    for uci in ["a1a8", "e8e7", "a8a7", "e7e6"] {
+        game.make_move_uci(uci).unwrap();
+    }
+    assert_eq!(game.state(), &[2, 0]);
+    game.make_move_uci("a7a6").unwrap();
+    assert_eq!(game.game_result(), Some(GameResult::Wins(Color::White, WinType::Variant)));
+    game.undo_move().unwrap();
+    assert_eq!(game.state(), &[2, 0]);
+    assert!(game.is_ongoing());
+}
+
+#[test]
+fn horde() {
+    use super::Horde;
+
+    let mut game = Horde::new();
+    assert_eq!(game.gen_legal_moves().len(), 8);
+    assert_eq!(game.to_fen().to_string(), "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1");
+    game.make_move_uci("b5b6").unwrap();
+    game.make_move_uci("a7b6").unwrap();
+    game.undo_move().unwrap();
+    assert_eq!(game.side_to_move(), Color::Black);
+
+    // pawns on the first rank can move two squares, without allowing en passant
+    let mut game = Horde::from_fen("4k3/8/8/8/8/8/8/P7 w - - 0 1").unwrap();
+    assert_eq!(game.gen_legal_moves().len(), 2);
+    game.make_move_uci("a1a3").unwrap();
+    assert_eq!(game.to_fen().to_string(), "4k3/8/8/8/8/P7/8/8 b - - 0 1");
+
+    let mut game = Horde::from_fen("8/8/8/8/8/8/3Pk3/8 b - - 0 1").unwrap();
+    game.make_move_uci("e2d2").unwrap();
+    assert_eq!(game.game_result(), Some(GameResult::Wins(Color::Black, WinType::Variant)));
+    let game = Horde::from_fen("k7/1Q6/P7/8/8/8/8/8 b - - 0 1").unwrap();
+    assert!(game.is_check());
+    assert_eq!(game.game_result(), Some(GameResult::Wins(Color::White, WinType::Checkmate)));
+    let game = Horde::from_fen("k7/P7/PP6/8/8/8/8/8 b - - 0 1").unwrap();
+    assert_eq!(game.game_result(), Some(GameResult::Draw(DrawType::Stalemate(Color::Black))));
+    assert!(Horde::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+    assert!(Horde::from_fen("4k3/8/8/8/8/8/8/R3P3 w Q - 0 1").is_err());
+    assert!(Horde::from_fen("8/8/8/8/8/8/8/P7 w - - 0 1").is_err());
+}
+
+#[test]
+fn kingless_position() {
+    use super::Horde;
+
+    // white has no king in Horde, so nothing concerning white's king may panic
+    let game = Horde::new();
+    let position = game.position();
+    assert!(!position.is_check() && !position.is_checkmate() && !position.is_stalemate());
+    assert_eq!(position.checked_side(), None);
+    assert!(position.checkers().is_empty());
+    assert!(position.pinned(Color::White).is_empty());
+    assert!(position.gen_check_evasions().is_empty());
+    assert_eq!(position.gen_non_illegal_moves().len(), 8);
+    assert_eq!(position.legal_moves_san().len(), 8);
+    let mut game = Horde::from_fen("4k3/8/8/8/8/8/4Q3/8 b - - 0 1").unwrap();
+    let position = game.position();
+    assert_eq!(position.checkers(), [12]);
+    assert!(position.pinned(Color::White).is_empty());
+    assert!(position.is_check() && !position.is_checkmate());
+    game.make_move_uci("e8d7").unwrap();
+    assert!(game.position().checkers().is_empty());
+}
+
+#[test]
+fn racing_kings() {
+    use super::RacingKings;
+
+    let game = RacingKings::new();
+    assert_eq!(game.gen_legal_moves().len(), 21);
+    // moves which give check are illegal
+    let game = RacingKings::from_fen("8/8/8/8/8/8/k7/6RK w - - 0 1").unwrap();
+    assert_eq!(game.gen_legal_moves().len(), 13);
+    assert!(!game.is_legal(Move::from_uci("g1g2").unwrap()));
+    assert!(RacingKings::from_fen("8/8/8/8/8/8/k7/R6K b - - 0 1").is_err());
+
+    let mut game = RacingKings::from_fen("8/k7/8/8/8/8/8/7K b - - 0 1").unwrap();
+    game.make_move_uci("a7a8").unwrap();
+    assert_eq!(game.game_result(), Some(GameResult::Wins(Color::Black, WinType::Variant)));
+    // black gets one more move to equalize after white's king reaches the eighth rank
+    let mut game = RacingKings::from_fen("8/k6K/8/8/8/8/8/8 w - - 0 1").unwrap();
+    game.make_move_uci("h7h8").unwrap();
+    assert!(game.is_ongoing());
+    game.make_move_uci("a7b8").unwrap();
+    assert_eq!(game.game_result(), Some(GameResult::Draw(DrawType::Variant)));
+    game.undo_move().unwrap();
+    game.make_move_uci("a7a6").unwrap();
+    assert_eq!(game.game_result(), Some(GameResult::Wins(Color::White, WinType::Variant)));
+    let mut game = RacingKings::from_fen("8/6K1/8/k7/8/8/8/8 w - - 0 1").unwrap();
+    game.make_move_uci("g7g8").unwrap();
+    assert_eq!(game.game_result(), Some(GameResult::Wins(Color::White, WinType::Variant)));
+}
+
+#[test]
+fn with_move() {
+    let mut board = Board::default();
+    let position = board.position().clone();
+    let e4 = Move::from_uci("e2e4").unwrap();
+    let after = position.with_move(&e4).unwrap();
+    assert_eq!(position, *board.position());
+    board.make_move(e4).unwrap();
+    assert_eq!(after, *board.position());
+    assert!(after.with_move(&Move::from_uci("e2e4").unwrap()).is_err());
+    assert!(position.with_move(&Move::from_uci("e2e5").unwrap()).is_err());
+}
+
+#[test]
+fn make_moves_uci() {
+    use super::InvalidUciMoveError;
+
+    let mut board = Board::default();
+    board.make_moves_uci("e2e4 e7e5\tg1f3 b8c6\n").unwrap();
+    assert_eq!(board.current_ply(), 4);
+    let err = board.make_moves_uci("f1b5 a7a6 b5b5").unwrap_err();
+    assert_eq!(err.index, 2);
+    assert!(matches!(err.error, InvalidUciMoveError::IllegalMove(m) if m == "b5b5"));
+    assert!(matches!(board.make_moves_uci("f1b5 a7a6 xyz").unwrap_err().error, InvalidUciMoveError::InvalidUci(_)));
+    assert_eq!(board.current_ply(), 4);
+}
+
+#[cfg(feature = "pgn")]
+#[test]
+fn pgn_lenient_typography() {
+    use super::pgn::Pgn;
+
+    let text = "\u{feff}[Event \u{201c}Club\u{a0}Championship\u{201d}]\n[Site \"?\"]\n[Date \"?\"]\n[Round \"?\"]\n[White \u{201c}O\u{2019}Brien\u{201d}]\n[Black \"?\"]\n[Result \"\u{bd}\u{2013}\u{bd}\"]\n\n1.\u{a0}e4 e5 \u{bd}\u{2013}\u{bd}";
+    assert!(Pgn::try_from(text).is_err());
+    let pgn = Pgn::parse_lenient(text).unwrap();
+    assert_eq!(pgn.tag_pairs()["Event"], "Club Championship");
+    assert_eq!(pgn.tag_pairs()["White"], "O'Brien");
+    assert_eq!(pgn.tag_pairs()["Result"], "1/2-1/2");
+    assert_eq!(pgn.board().current_ply(), 2);
+    assert!(matches!(pgn.board().game_result(), Some(GameResult::Draw(_))));
+}
+
+#[cfg(feature = "pgn")]
+#[test]
+fn pgn_write_options() {
+    use super::{
+        errors::PgnWriteError,
+        pgn::{Latin1Fallback, Newline, Pgn, PgnEncoding, PgnWriteOptions},
+    };
+
+    let mut board = Board::default();
+    board.make_moves_san("e4 e5").unwrap();
+    let tag_pairs = [
+        ("Event", "Café Open"),
+        ("Site", "Zürich"),
+        ("Date", "????.??.??"),
+        ("Round", "1"),
+        ("White", "Ivanov – Иванов"),
+        ("Black", "“?”"),
+    ];
+    let pgn = Pgn::from_board(board, tag_pairs.iter().map(|&(k, v)| (k.to_owned(), v.to_owned())).collect()).unwrap();
+    assert_eq!(pgn.to_bytes(&PgnWriteOptions::default()).unwrap(), pgn.to_string().into_bytes());
+    let options = PgnWriteOptions {
+        newline: Newline::CrLf,
+        encoding: PgnEncoding::Latin1(Latin1Fallback::Replace),
+        ..Default::default()
+    };
+    let bytes = pgn.to_bytes(&options).unwrap();
+    let text: String = bytes.iter().map(|&b| b as char).collect();
+    assert!(text.starts_with("[Event \"Café Open\"]\r\n[Site \"Zürich\"]\r\n"));
+    assert!(bytes.windows(4).any(|w| w == b"Caf\xe9"));
+    assert!(text.contains("[White \"Ivanov - ??????\"]\r\n"));
+    assert!(text.contains("[Black \"\"?\"\"]\r\n"));
+    assert!(!text.replace("\r\n", "").contains('\n'));
+    let options = PgnWriteOptions {
+        encoding: PgnEncoding::Latin1(Latin1Fallback::Error),
+        ..options
+    };
+    assert!(matches!(pgn.to_bytes(&options), Err(PgnWriteError::Unrepresentable('–'))));
+    let mut out = Vec::new();
+    pgn.write_to(&mut out, &PgnWriteOptions::default()).unwrap();
+    assert_eq!(out, pgn.to_string().into_bytes());
+}
+
+#[cfg(feature = "pgn")]
+#[test]
+fn pgn_broadcast_updates() {
+    use super::pgn::Pgn;
+
+    let tags = |result: &str| format!("[Event \"Live\"]\n[Site \"?\"]\n[Date \"?\"]\n[Round \"1\"]\n[White \"A\"]\n[Black \"B\"]\n[Result \"{result}\"]\n\n");
+    let mut pgn = Pgn::try_from(format!("{}1. e4 e5 2. Nf3", tags("*")).as_str()).unwrap();
+    let diff = pgn.update(&format!("{}1. e4 e5 2. Nf3 Nc6 3. Bb5", tags("*"))).unwrap();
+    assert_eq!(diff.kept, 3);
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.added.len(), 2);
+    assert_eq!(pgn.board().current_ply(), 5);
+    let diff = pgn.update(&format!("{}1. e4 e5 2. Nf3 Nf6 3. Nxe5", tags("*"))).unwrap();
+    assert_eq!(diff.kept, 3);
+    assert_eq!(diff.removed.len(), 2);
+    assert_eq!(diff.added.len(), 2);
+    assert_eq!(pgn.board().history().last().unwrap().1, "Nxe5");
+    assert!(pgn.update(&format!("{}1. e4 e5 2. Nf3 Nf6 3. Nxe5", tags("*"))).unwrap().is_empty());
+    let diff = pgn.update(&format!("{}1. e4 e5 2. Nf3 Nf6 3. Nxe5 1-0", tags("1-0"))).unwrap();
+    assert!(diff.is_empty());
+    assert_eq!(pgn.board().resigned_side(), Some(Color::Black));
+    assert_eq!(pgn.tag_pairs()["Result"], "1-0");
+    let before = pgn.clone();
+    assert!(pgn.update(&format!("{}1. e4 e5 2. Ke3", tags("*"))).is_err());
+    assert_eq!(pgn, before);
+}
+
+#[cfg(feature = "pgn")]
+#[test]
+fn pgn_figurines() {
+    use super::pgn::Pgn;
+
+    let text = "[Event \"♔ Open\"]\n[Site \"?\"]\n[Date \"?\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"*\"]\n\n1. ♘f3 ♞f6 2. ♙e4 ♞xe4 *";
+    let pgn = Pgn::try_from(text).unwrap();
+    assert_eq!(pgn.tag_pairs()["Event"], "♔ Open");
+    assert_eq!(pgn.board().history().iter().map(|(_, san)| san.as_str()).collect::<Vec<_>>(), ["Nf3", "Nf6", "e4", "Nxe4"]);
+}
+
 #[cfg(feature = "pgn")]
 #[test]
 #[ignore]
@@ -359,14 +1268,33 @@ fn position_to_image() {
             dark_square_color: img::Rgb::from_hex("#2d313d").unwrap(),
             piece_set: img::PieceSet::Builtin("merida".to_owned()),
             size: 1024,
+            arrows: vec![img::Arrow {
+                from: 15,
+                to: 38,
+                color: img::Rgb::from_hex("#15781b").unwrap(),
+            }],
+            highlighted_squares: vec![img::HighlightedSquare {
+                square: 29,
+                color: img::Rgb::from_hex("#eb6150").unwrap(),
+            }],
+            radial_highlights: Vec::new(),
+            material_tray: None,
+            square_textures: None,
+            evaluation_bar: Some(img::EvaluationBar::new(img::Evaluation::Centipawns(-250))),
+            coordinates: Some(img::Coordinates {
+                placement: img::CoordinatePlacement::Outside,
+                ..Default::default()
+            }),
         },
         Color::White,
     )
     .unwrap()
     .save("test1.png")
     .unwrap();
-    let mut pip = img::PositionImageProperties::default();
-    pip.piece_set = img::PieceSet::Builtin("horsey".to_owned());
+    let pip = img::PositionImageProperties {
+        piece_set: img::PieceSet::Builtin("horsey".to_owned()),
+        ..Default::default()
+    };
     img::position_to_image(board.position(), pip, Color::Black).unwrap().save("test2.png").unwrap();
 }
 
@@ -375,29 +1303,1968 @@ fn position_to_image() {
 #[ignore]
 fn custom_piece_set() {
     use super::img;
-    use image;
-    use nsvg;
-    use std::collections::HashMap;
     use std::path::PathBuf;
 
     let board = Board::from_fen(Fen::try_from("8/1r6/8/6n1/5k2/1b6/3K3N/7Q b - - 0 1").unwrap());
-    let mut pip = img::PositionImageProperties {
+    let dir = PathBuf::from("assets").join("pieces").join("kiwen-suwi");
+    let pip = img::PositionImageProperties {
         light_square_color: img::Rgb::from_hex("#687381").unwrap(),
         dark_square_color: img::Rgb::from_hex("#2d313d").unwrap(),
-        piece_set: img::PieceSet::default(),
+        piece_set: img::PieceSet::from_dir(dir).unwrap(),
         size: 1024,
+        ..Default::default()
     };
-    let mut hm = HashMap::new();
-    let set = "kiwen-suwi";
-    let dir = PathBuf::from("assets").join("pieces").join(set);
-    for fname in std::fs::read_dir(dir).unwrap() {
-        let fname = fname.unwrap();
-        let name = String::from_utf8_lossy(fname.file_name().to_string_lossy().as_bytes()).split('.').next().unwrap().to_owned();
-        let svg = nsvg::parse_str(std::fs::read_to_string(fname.path()).unwrap().as_str(), nsvg::Units::Pixel, 96.0).unwrap();
-        let piece_img = svg.rasterize(318. / svg.width()).unwrap();
-        let piece_img = image::RgbaImage::from_vec(318, 318, piece_img.to_vec()).unwrap();
-        hm.insert(name, piece_img);
-    }
-    pip.piece_set = img::PieceSet::Custom(hm);
     img::position_to_image(board.position(), pip, Color::White).unwrap().save("test1.png").unwrap();
 }
+
+#[test]
+fn move_indices() {
+    let move_ = Move::from_uci("e7e8n").unwrap();
+    assert_eq!((move_.from_index(), move_.to_index()), (52, 60));
+    assert_eq!(move_.promotion(), Some(PieceType::N));
+    assert_eq!(Move::from_uci("g1f3").unwrap().promotion(), None);
+}
+
+#[test]
+fn attackers() {
+    let position = Fen::try_from("4k3/8/3n4/1b6/4p3/2N2Q2/8/R3K2R w KQ - 0 1").unwrap().position().clone();
+    // e4: attacked by the knight on c3 and the queen on f3, defended by the knight on d6
+    assert_eq!(position.attackers_of(helpers::sq_to_idx('e', '4'), Color::White), [18, 21]);
+    assert_eq!(position.attackers_of(helpers::sq_to_idx('e', '4'), Color::Black), [43]);
+    // d3: attacked by the pawn on e4 and the bishop on b5
+    assert_eq!(position.attackers_of(helpers::sq_to_idx('d', '3'), Color::Black), [28, 33]);
+    assert_eq!(position.count_attackers(helpers::sq_to_idx('d', '1'), Color::White), 4);
+}
+
+#[test]
+fn attack_maps() {
+    let start = Board::default().position().clone();
+    // white attacks the second and third ranks and the first rank apart from the corners
+    assert_eq!(start.attacked_squares(Color::White), 0x00ff_ff7e);
+    assert_eq!(start.attacked_squares(Color::Black), 0x7eff_ff00_0000_0000);
+    assert_eq!(start.attacks_from(helpers::sq_to_idx('g', '1')), 1 << 12 | 1 << 21 | 1 << 23);
+    assert_eq!(start.attacks_from(helpers::sq_to_idx('e', '4')), 0);
+    assert_eq!(start.attack_map().count(helpers::sq_to_idx('f', '3'), Color::White), 3);
+    for fen in ["4k3/8/3n4/1b6/4p3/2N2Q2/8/R3K2R w KQ - 0 1", "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4"] {
+        let position = Fen::try_from(fen).unwrap().position().clone();
+        let map = position.attack_map();
+        for side in Color::ALL {
+            assert_eq!(map.attacked_squares(side), position.attacked_squares(side));
+            for sq in 0..64 {
+                let attackers: Vec<_> = (0..64).filter(|i| map.attackers(sq, side) & 1 << i != 0).collect();
+                assert_eq!(attackers, position.attackers_of(sq, side));
+            }
+        }
+    }
+}
+
+#[test]
+fn square_control() {
+    for fen in ["4k3/8/3n4/1b6/4p3/2N2Q2/8/R3K2R w KQ - 0 1", "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"] {
+        let position = Fen::try_from(fen).unwrap().position().clone();
+        for side in Color::ALL {
+            for sq in 0..64 {
+                assert_eq!(position.controls_square(sq, side), position.count_attackers(sq, side) > 0);
+            }
+        }
+    }
+    // the rook on f8 stops white from castling kingside through f1
+    let position = Fen::try_from("5rk1/8/8/8/8/8/8/4K2R w K - 0 1").unwrap().position().clone();
+    assert!(position.controls_square(helpers::sq_to_idx('f', '1'), Color::Black));
+    assert!(position.gen_non_illegal_moves().iter().all(|move_| move_.special_move_type().is_none()));
+}
+
+#[test]
+fn magic_sliding_attacks() {
+    use super::tables;
+    // walks along the rays from `sq`, stopping at the first occupied square
+    let walk = |sq: usize, directions: &[(isize, isize)], occupied: u64| {
+        let mut attacks = 0;
+        for (file_step, rank_step) in directions {
+            let (mut file, mut rank) = ((sq % 8) as isize + file_step, (sq / 8) as isize + rank_step);
+            while (0..8).contains(&file) && (0..8).contains(&rank) {
+                let current_sq = (rank * 8 + file) as usize;
+                attacks |= 1 << current_sq;
+                if occupied & 1 << current_sq != 0 {
+                    break;
+                }
+                (file, rank) = (file + file_step, rank + rank_step);
+            }
+        }
+        attacks
+    };
+    let mut occupied: u64 = 0x9e37_79b9_7f4a_7c15;
+    for _ in 0..200 {
+        occupied ^= occupied << 13;
+        occupied ^= occupied >> 7;
+        occupied ^= occupied << 17;
+        for sq in 0..64 {
+            assert_eq!(tables::bishop_attacks(sq, occupied), walk(sq, &[(1, 1), (-1, 1), (1, -1), (-1, -1)], occupied));
+            assert_eq!(tables::rook_attacks(sq, occupied), walk(sq, &[(1, 0), (-1, 0), (0, 1), (0, -1)], occupied));
+        }
+    }
+    let kiwipete = Fen::try_from("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap().position().clone();
+    assert_eq!(kiwipete.perft(3), 97862);
+}
+
+#[test]
+fn checkers_and_pins() {
+    let position = Fen::try_from("4r1k1/8/8/1b6/8/3N4/4B3/q3K2R w K - 0 1").unwrap().position().clone();
+    assert_eq!(position.checkers(), [0]);
+    // the bishop on e2 is pinned by the rook on e8, and the queen on a1 is giving check
+    assert_eq!(position.pinned(Color::White), [(12, vec![12, 20, 28, 36, 44, 52, 60])]);
+    assert!(position.pinned(Color::Black).is_empty());
+}
+
+#[test]
+fn piece_type_values() {
+    assert_eq!(PieceType::ALL.iter().map(PieceType::value).sum::<u32>(), 21);
+    assert_eq!(PieceType::ALL.iter().filter(|pt| pt.is_promotion_type()).count(), PieceType::PROMOTION_TYPES.len());
+    assert!(Move::from_uci("e7e8p").is_err());
+}
+
+#[test]
+fn color_geometry() {
+    for color in Color::ALL {
+        let pawn_sq = helpers::sq_to_idx('e', color.pawn_rank());
+        let push = (pawn_sq as isize + color.pawn_direction()) as usize;
+        assert_eq!(helpers::idx_to_sq(push).1, if color.is_white() { '3' } else { '6' });
+        assert_eq!(Board::default().position().content[helpers::sq_to_idx('e', color.backrank())].map(|p| p.color()), Some(color));
+        assert_eq!(color.sign(), -(!color).sign());
+    }
+}
+
+#[test]
+fn lenient_san() {
+    let mut board = Board::default();
+    for san in ["e4!?", "d5", "e5", "f5", "exf6 e.p.", "Nh6", "fxg7+", "Qd6??", "gxh8Q", "Nbd7", "Ng1f3", "a6", "Bc4", "dxc4", "0-0!"] {
+        board.make_move_san_lenient(san).unwrap();
+    }
+    assert_eq!(board.gen_movetext(), "1. e4 d5 2. e5 f5 3. exf6 Nh6 4. fxg7 Qd6 5. gxh8=Q Nd7 6. Nf3 a6 7. Bc4 dxc4 8. O-O");
+    assert!(board.make_move_san("Qd6d2").is_err());
+    board.make_move_san_lenient("Qd6xd2").unwrap();
+    assert!(board.make_move_san_lenient("N5").is_err());
+}
+
+#[test]
+fn game_result_tokens() {
+    for token in ["1-0", "0-1", "1/2-1/2"] {
+        assert_eq!(GameResult::from_pgn_token(token).unwrap().unwrap().to_pgn_token(), token);
+    }
+    assert_eq!(GameResult::from_pgn_token("*").unwrap(), None);
+    assert!(GameResult::from_pgn_token("2-0").is_err());
+    let result = GameResult::Draw(DrawType::Stalemate(Color::Black));
+    assert_eq!(result.score_for(Color::White) + result.score_for(Color::Black), 1.);
+    assert_eq!(GameResult::Wins(Color::Black, WinType::Checkmate).score_for(Color::Black), 1.);
+}
+
+#[test]
+fn position_san_round_trip() {
+    let position = Fen::try_from("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4").unwrap().position().clone();
+    let mate = position.san_to_move("Qxf7#").unwrap();
+    assert_eq!((mate.from_index(), mate.to_index()), (39, 53));
+    assert_eq!(position.move_to_san(mate).unwrap(), "Qxf7#");
+    assert_eq!(position.san_to_move("Qf7+").unwrap_err().0, "Qf7+");
+}
+
+#[test]
+fn lan() {
+    let mut board = Board::default();
+    for lan in ["e2-e4", "d7-d5", "e4xd5", "Qd8xd5", "Ng1-f3", "Bc8-g4", "Bf1-e2", "Nb8-c6", "O-O", "O-O-O"] {
+        let move_ = board.lan_to_move(lan).unwrap();
+        assert_eq!(board.move_to_lan(move_).unwrap(), lan);
+        board.make_move(move_).unwrap();
+    }
+    assert!(board.make_move_lan("Nf3-e5").is_ok());
+    assert!(board.make_move_lan("Rd8").is_err());
+    assert_eq!(board.move_to_lan(Move::from_uci("d5d2").unwrap()).unwrap(), "Qd5xd2");
+}
+
+#[test]
+fn outcome_and_termination() {
+    let mut board = Board::default();
+    assert_eq!(board.outcome(), Outcome::Unknown);
+    board.make_moves_san("f3 e5 g4 Qh4#").unwrap();
+    let (outcome, termination) = board.game_result().unwrap().into_parts();
+    assert_eq!(outcome, Outcome::Wins(Color::Black));
+    assert_eq!(termination, Termination::Win(WinType::Checkmate));
+    assert_eq!(GameResult::from_parts(outcome, termination), board.game_result());
+    assert_eq!(GameResult::from_parts(Outcome::Draw, termination), None);
+    assert_eq!(Outcome::from_pgn_token("*").unwrap().score_for(Color::White), None);
+}
+
+#[test]
+fn same_position() {
+    // the en passant target after 1. e4 is irrelevant since no capture is possible
+    let mut a = Board::default();
+    a.make_moves_san("e4 Nf6 Nf3 Ng8 Ng1").unwrap();
+    let b = Board::from_fen(Fen::try_from("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap());
+    assert!(a.same_position(&b));
+    assert_ne!(a, b);
+    assert_eq!(b.position().legal_ep_target(), None);
+    // here the en passant capture is legal, so the positions differ
+    let c = Board::from_fen(Fen::try_from("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap());
+    let d = Board::from_fen(Fen::try_from("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap());
+    assert_eq!(c.position().legal_ep_target(), Some(20));
+    assert!(!c.same_position(&d));
+}
+
+#[test]
+fn fan() {
+    let mut board = Board::default();
+    let nf3 = board.san_to_move("Nf3").unwrap();
+    assert_eq!(board.move_to_fan(nf3).unwrap(), "♘f3");
+    board.make_move_san("♘f3").unwrap();
+    board.make_move_san("♞f6").unwrap();
+    board.make_move_san_lenient("♘g1!?").unwrap();
+    assert_eq!(board.move_to_fan(board.san_to_move("e5").unwrap()).unwrap(), "e5");
+    let board = Board::from_fen(Fen::try_from("8/1P2k3/8/8/8/8/8/4K3 w - - 0 1").unwrap());
+    assert_eq!(board.move_to_fan(board.san_to_move("b8=Q").unwrap()).unwrap(), "b8=♕");
+    assert!(board.san_to_move("b8=♛").is_ok());
+    // pawn figurines and the variation selectors of emoji presentation are ignored
+    let mut board = Board::default();
+    board.make_moves_san("♙e4 ♞\u{fe0f}f6 ♘\u{fe0e}c3").unwrap();
+    assert_eq!(board.move_to_san(board.lan_to_move("♟e7-e5").unwrap()).unwrap(), "e5");
+    assert_eq!(PieceType::from_figurine('♟'), Some(PieceType::P));
+    assert_eq!(PieceType::from_figurine('♠'), None);
+}
+
+#[test]
+fn epd() {
+    let line = r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";"#;
+    let epd = Epd::try_from(line).unwrap();
+    assert_eq!(epd.id(), Some("WAC.001"));
+    assert_eq!(epd.best_moves().iter().map(|m| (m.from_index(), m.to_index())).collect::<Vec<_>>(), [(22, 46)]);
+    assert_eq!(epd.to_string(), line);
+    let epd = Epd::try_from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - ce 25; pv e4 e5 Nf3; c0 \"king's pawn\"; hmvc 0; fmvn 1;").unwrap();
+    assert_eq!(epd.centipawn_evaluation(), Some(25));
+    assert_eq!(epd.pv().len(), 3);
+    assert_eq!(epd.operation("c0").unwrap().operands(), ["king's pawn"]);
+    assert_eq!(epd.to_fen(), Board::default().to_fen());
+    assert!(Epd::try_from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - bm e5;").is_err());
+    assert!(Epd::try_from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - id \"x\"").is_err());
+    let board = Board::default();
+    let epd = board.to_epd(vec![EpdOperation::new("bm", ["e4", "d4"]), EpdOperation::new("id", ["start"])]).unwrap();
+    assert_eq!(epd.to_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - bm e4 d4; id \"start\";");
+    assert!(board.to_epd(vec![EpdOperation::new("am", ["Ke2"])]).is_err());
+}
+
+#[test]
+fn repetition() {
+    let mut board = Board::default();
+    let start = board.position().repetition_key();
+    board.make_moves_san("Nf3 Nf6 Ng1 Ng8").unwrap();
+    assert_eq!(board.repetition_count(), 2);
+    assert!(!board.is_threefold_repetition());
+    board.make_moves_san("Nf3 Nf6 Ng1 Ng8").unwrap();
+    assert_eq!(board.position().repetition_key(), start);
+    assert!(board.is_threefold_repetition());
+    assert!(board.is_ongoing());
+    board.make_moves_san("Nf3 Nf6 Ng1 Ng8 Nf3 Nf6 Ng1 Ng8").unwrap();
+    assert_eq!(board.repetition_count(), 5);
+    assert_eq!(board.game_result(), Some(GameResult::Draw(DrawType::FivefoldRepetition)));
+    // the en passant target is only part of the key when the capture is legal
+    let a = Fen::try_from("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    let b = Fen::try_from("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert_eq!(a.position().repetition_key(), b.position().repetition_key());
+    // the same placement repeats only once castling rights and legal en passant captures are the same again
+    let mut board = Board::from_fen(Fen::try_from("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap());
+    board.make_moves_san("Ke2 Ke7 Ke1 Ke8").unwrap();
+    assert_eq!(board.repetition_count(), 1);
+    board.make_moves_san("Ke2 Ke7 Ke1 Ke8").unwrap();
+    assert_eq!(board.repetition_count(), 2);
+    board.undo_move().unwrap();
+    board.make_move_san("Ke8").unwrap();
+    assert_eq!(board.repetition_count(), 2);
+    let mut board = Board::from_fen(Fen::try_from("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1").unwrap());
+    board.make_moves_san("e4 Kd8 Kd1 Ke8 Ke1").unwrap();
+    assert_eq!(board.repetition_count(), 1);
+    board.make_moves_san("Kd8 Kd1 Ke8 Ke1").unwrap();
+    assert_eq!(board.repetition_count(), 2);
+    board.verify_invariants().unwrap();
+}
+
+#[test]
+fn repetition_trigger_plies() {
+    // the starting position occurs for the nth time after 4 * (n - 1) plies, and the current occurrence counts
+    let mut board = Board::default();
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+    for ply in 1..=16 {
+        board.make_move_san(shuffle[(ply - 1) % 4]).unwrap();
+        assert_eq!(board.is_threefold_repetition(), ply >= 8, "ply {ply}");
+        assert_eq!(board.is_fivefold_repetition(), ply >= 16, "ply {ply}");
+        assert_eq!(board.is_ongoing(), ply < 16, "ply {ply}");
+    }
+    assert_eq!(board.repetition_count(), 5);
+    assert_eq!(board.game_result(), Some(GameResult::Draw(DrawType::FivefoldRepetition)));
+}
+
+#[test]
+fn fen_normalization() {
+    let fen = Fen::try_from("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    assert_eq!(fen.normalized().to_string(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    let other = Fen::try_from("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 12 40").unwrap();
+    assert!(fen.eq_ignore_clocks(&other));
+    assert_ne!(fen, other);
+    let fen = Fen::try_from("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+    assert_eq!(fen.normalized(), fen);
+    assert!(!fen.eq_ignore_clocks(&Fen::try_from("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap()));
+}
+
+#[test]
+fn metrics() {
+    use super::metrics::{self, Metrics, ParseKind};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static MOVES: AtomicUsize = AtomicUsize::new(0);
+    static FEN_FAILURES: AtomicUsize = AtomicUsize::new(0);
+    struct Counter;
+    impl Metrics for Counter {
+        fn move_made(&self) {
+            MOVES.fetch_add(1, Ordering::SeqCst);
+        }
+        fn parse_failed(&self, kind: ParseKind) {
+            if kind == ParseKind::Fen {
+                FEN_FAILURES.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+    metrics::set_metrics(Counter).unwrap();
+    assert!(metrics::set_metrics(Counter).is_err());
+    // other tests may run concurrently, so only lower bounds can be checked
+    let (moves, failures) = (MOVES.load(Ordering::SeqCst), FEN_FAILURES.load(Ordering::SeqCst));
+    Board::default().make_moves_san("e4 e5").unwrap();
+    assert!(Fen::try_from("not a fen").is_err());
+    assert!(MOVES.load(Ordering::SeqCst) >= moves + 2);
+    assert!(FEN_FAILURES.load(Ordering::SeqCst) > failures);
+}
+
+#[test]
+fn perft_cancellation() {
+    let position = Board::default().position().clone();
+    assert_eq!(position.perft(3), 8902);
+    let kiwipete = Fen::try_from("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(kiwipete.position().perft(2), 2039);
+    let cancel = CancelToken::new();
+    cancel.clone().cancel();
+    let res = position.perft_cancellable(3, &cancel);
+    assert!(res.is_cancelled());
+    assert!(res.into_inner() < 8902);
+    assert_eq!(position.perft_cancellable(2, &CancelToken::new()), Cancellable::Completed(400));
+}
+
+#[test]
+fn move_lists() {
+    use super::MoveList;
+    let kiwipete = Fen::try_from("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap().position().clone();
+    let moves = kiwipete.gen_move_list();
+    assert_eq!(moves.len(), 48);
+    assert_eq!(Vec::from(moves.clone()), kiwipete.gen_non_illegal_moves());
+    assert_eq!(Vec::from(kiwipete.gen_pseudolegal_move_list()), kiwipete.gen_pseudolegal_moves());
+    let mut captures = moves.clone();
+    captures.retain(|&move_| kiwipete.is_capture(move_).unwrap());
+    assert_eq!(captures.to_vec(), kiwipete.gen_captures());
+    assert_eq!(moves.iter().copied().collect::<MoveList>(), moves);
+    captures.clear();
+    assert!(captures.is_empty());
+}
+
+#[test]
+fn board_position_cache() {
+    let mut board = Board::default();
+    let fresh = board.clone();
+    assert_eq!(board.gen_legal_moves().len(), 20);
+    // the cache is not part of the game state
+    assert_eq!(board, fresh);
+    board.make_moves_san("f3 e5 g4").unwrap();
+    assert!(!board.is_check());
+    assert_eq!(board.move_to_san(Move::from_uci("d8h4").unwrap()).unwrap(), "Qh4#");
+    board.make_move_san("Qh4#").unwrap();
+    assert!(board.is_check() && board.is_checkmate());
+    assert_eq!(board.checkmated_side(), Some(Color::White));
+    assert!(board.gen_legal_moves().is_empty());
+    board.undo_move().unwrap();
+    assert!(!board.is_check() && !board.is_checkmate());
+    assert_eq!(board.checked_side(), None);
+    assert_eq!(board.legal_moves_san().len(), 30);
+    assert_eq!(board.gen_legal_moves(), board.position().gen_non_illegal_moves());
+}
+
+#[test]
+fn dead_position() {
+    // a blocked pawn wall which neither king can cross
+    let fen = Fen::try_from("k7/8/8/p1p1p1p1/P1P1P1P1/8/8/K7 w - - 0 1").unwrap();
+    assert!(!fen.position().is_insufficient_material());
+    assert!(fen.position().is_dead_position());
+    let mut board = Board::from_fen(fen);
+    assert!(board.is_ongoing());
+    board.set_dead_position_detection(true);
+    assert_eq!(board.game_result(), Some(GameResult::Draw(DrawType::DeadPosition)));
+    assert!(!Board::default().is_dead_position());
+    assert!(!Fen::try_from("k7/8/8/8/8/8/8/KR6 w - - 0 1").unwrap().position().is_dead_position());
+}
+
+#[test]
+fn rules() {
+    use super::TerminationPolicy;
+
+    // white runs out of time against a knight; checkmate is possible but cannot be forced
+    let fen = Fen::try_from("8/8/4k3/8/8/2n5/P7/K7 w - - 0 1").unwrap();
+    let results: Vec<_> = [Rules::FIDE, Rules::USCF, Rules::LICHESS]
+        .into_iter()
+        .map(|rules| {
+            let mut board = Board::from_fen(fen.clone());
+            board.set_rules(rules);
+            board.flag(Color::White).unwrap();
+            board.game_result().unwrap()
+        })
+        .collect();
+    assert_eq!(
+        results,
+        [
+            GameResult::Wins(Color::Black, WinType::Timeout),
+            GameResult::Draw(DrawType::TimeoutVsInsufficientMaterial),
+            GameResult::Wins(Color::Black, WinType::Timeout)
+        ]
+    );
+    // against a lone king, a knight can never checkmate
+    let position = Fen::try_from("8/8/4k3/8/8/2n5/8/K7 w - - 0 1").unwrap().position().clone();
+    assert!(!Rules::FIDE.timeout_wins(&position, Color::Black));
+    assert!(!Rules::USCF.timeout_wins(&position, Color::Black));
+    assert!(Rules::LICHESS.timeout_wins(&position, Color::Black));
+    let mut board = Board::default();
+    board.flag(Color::Black).unwrap();
+    assert!(board.flag(Color::White).is_err());
+    assert_eq!(board.game_result(), Some(GameResult::Wins(Color::White, WinType::Timeout)));
+    // lowering the repetition limit makes threefold repetition automatic
+    let mut board = Board::default();
+    board.set_rules(Rules {
+        repetition_limit: Some(3),
+        ..Rules::FIDE
+    });
+    board.make_moves_san("Nf3 Nf6 Ng1 Ng8 Nf3 Nf6 Ng1 Ng8").unwrap();
+    assert_eq!(board.game_result(), Some(GameResult::Draw(DrawType::RepetitionLimit(3))));
+    // so does a custom halfmove limit, which is reported with its number of halfmoves
+    let mut board = Board::default();
+    board.set_rules(Rules {
+        halfmove_limit: Some(4),
+        ..Rules::FIDE
+    });
+    board.make_moves_san("Nf3 Nf6 Nc3").unwrap();
+    assert!(board.is_ongoing());
+    board.make_move_san("Nc6").unwrap();
+    assert_eq!(board.game_result(), Some(GameResult::Draw(DrawType::HalfmoveLimit(4))));
+    // disabling the insufficient material draw keeps the game going
+    let board = Board::from_fen(Fen::try_from("8/8/4k3/8/8/2n5/8/K7 w - - 0 1").unwrap());
+    assert!(!board.is_ongoing());
+    let mut board = Board::from_fen(Fen::try_from("8/8/4k3/8/8/2n5/8/KR6 w - - 0 1").unwrap());
+    board.set_rules(Rules {
+        insufficient_material_draw: false,
+        ..Rules::FIDE
+    });
+    board.make_moves_san("Rd1 Nxd1").unwrap();
+    assert!(board.is_ongoing());
+    // a halfmove clock past the FIDE limit only ends the game under rules which have that limit
+    let fen = Fen::try_from("4k3/8/8/8/8/8/4P3/4K3 w - - 150 120").unwrap();
+    assert_eq!(Board::from_fen(fen.clone()).game_result(), Some(GameResult::Draw(DrawType::SeventyFiveMoveRule)));
+    let board = Board::builder().fen(fen.clone()).rules(Rules { halfmove_limit: None, ..Rules::FIDE }).build().unwrap();
+    assert!(board.is_ongoing() && board.game_result().is_none());
+    board.verify_invariants().unwrap();
+    let mut board = Board::from_fen(fen);
+    board.set_rules(Rules {
+        halfmove_limit: Some(200),
+        ..Rules::FIDE
+    });
+    assert!(board.is_ongoing() && board.game_result().is_none());
+    board.set_rules(Rules::FIDE);
+    assert_eq!(board.game_result(), Some(GameResult::Draw(DrawType::SeventyFiveMoveRule)));
+    board.set_termination_policy(TerminationPolicy::ByClaim);
+    assert!(board.is_ongoing());
+    // a game decided otherwise keeps its result
+    board.resign(Color::White).unwrap();
+    board.set_termination_policy(TerminationPolicy::Automatic);
+    assert_eq!(board.game_result(), Some(GameResult::Wins(Color::Black, WinType::Resignation)));
+}
+
+#[test]
+fn draw_claims() {
+    use super::{errors::DrawClaimError, TerminationPolicy};
+
+    let mut board = Board::default();
+    board.make_moves_san("Nf3 Nf6 Ng1 Ng8 Nf3 Nf6 Ng1").unwrap();
+    assert!(board.claimable_draws().is_empty());
+    assert_eq!(board.claim_draw(DrawType::ThreefoldRepetition), Err(DrawClaimError::NotClaimable(DrawType::ThreefoldRepetition)));
+    board.make_move_san("Ng8").unwrap();
+    assert_eq!(board.claimable_draws(), [DrawType::ThreefoldRepetition]);
+    assert!(board.claim_draw(DrawType::FiftyMoveRule).is_err());
+    board.claim_draw(DrawType::ThreefoldRepetition).unwrap();
+    assert_eq!(board.game_result(), Some(GameResult::Draw(DrawType::ThreefoldRepetition)));
+    assert!(board.claimable_draws().is_empty());
+    assert_eq!(board.claim_draw(DrawType::ThreefoldRepetition), Err(DrawClaimError::GameOver));
+    board.undo_move().unwrap();
+    assert!(board.is_ongoing() && board.claimed_draw().is_none());
+    // under the claim policy, repetitions and insufficient material only make draws claimable
+    let mut board = Board::default();
+    board.set_termination_policy(TerminationPolicy::ByClaim);
+    board.make_moves_san("Nf3 Nf6 Ng1 Ng8 Nf3 Nf6 Ng1 Ng8 Nf3 Nf6 Ng1 Ng8 Nf3 Nf6 Ng1 Ng8").unwrap();
+    assert_eq!(board.repetition_count(), 5);
+    assert!(board.is_ongoing());
+    assert_eq!(board.claimable_draws(), [DrawType::ThreefoldRepetition, DrawType::FivefoldRepetition]);
+    board.set_termination_policy(TerminationPolicy::Automatic);
+    assert_eq!(board.game_result(), Some(GameResult::Draw(DrawType::FivefoldRepetition)));
+    // custom repetition and halfmove limits become claimable when they are reached
+    let mut board = Board::default();
+    board.set_termination_policy(TerminationPolicy::ByClaim);
+    board.set_rules(Rules {
+        repetition_limit: Some(2),
+        halfmove_limit: Some(80),
+        ..Rules::FIDE
+    });
+    board.make_moves_san("Nf3 Nf6 Ng1 Ng8").unwrap();
+    assert!(board.is_ongoing());
+    assert_eq!(board.claimable_draws(), [DrawType::RepetitionLimit(2)]);
+    board.claim_draw(DrawType::RepetitionLimit(2)).unwrap();
+    assert_eq!(board.game_result(), Some(GameResult::Draw(DrawType::RepetitionLimit(2))));
+    let mut board = Board::from_fen(Fen::try_from("4k3/8/8/8/8/8/8/R3K3 w - - 79 60").unwrap());
+    board.set_termination_policy(TerminationPolicy::ByClaim);
+    board.set_rules(Rules {
+        halfmove_limit: Some(80),
+        ..Rules::FIDE
+    });
+    assert!(board.claimable_draws().is_empty());
+    board.make_move_san("Ra2").unwrap();
+    assert!(board.is_ongoing());
+    assert_eq!(board.claimable_draws(), [DrawType::HalfmoveLimit(80)]);
+    let fen = Fen::try_from("4k3/8/8/8/8/7n/8/4K1N1 w - - 0 1").unwrap();
+    let mut board = Board::from_fen(fen.clone());
+    board.make_move_san("Nxh3").unwrap();
+    assert_eq!(board.game_result(), Some(GameResult::Draw(DrawType::InsufficientMaterial)));
+    let mut board = Board::from_fen(fen);
+    board.set_termination_policy(TerminationPolicy::ByClaim);
+    board.make_move_san("Nxh3").unwrap();
+    assert_eq!(board.claimable_draws(), [DrawType::InsufficientMaterial]);
+    board.claim_draw(DrawType::InsufficientMaterial).unwrap();
+    assert_eq!(board.game_result(), Some(GameResult::Draw(DrawType::InsufficientMaterial)));
+    board.verify_invariants().unwrap();
+}
+
+#[test]
+fn adjudication() {
+    use super::match_play::{AdjudicationReason, Adjudicator, ResignRule, Wdl};
+
+    let rule = ResignRule {
+        move_count: 2,
+        score: 500,
+        two_sided: true,
+    };
+    let play = |adjudicator: &mut Adjudicator, moves: &[(&str, i32)]| {
+        let mut board = Board::default();
+        let mut adjudication = None;
+        for &(san, score) in moves {
+            board.make_move_san(san).unwrap();
+            adjudication = adjudicator.update(&board, score);
+        }
+        (board, adjudication)
+    };
+    let moves = [("e4", 600), ("e5", -600), ("Nf3", 100), ("Nc6", -700), ("Bb5", 700), ("a6", -700), ("Ba4", 700)];
+    let (_, adjudication) = play(&mut Adjudicator::new().resign(rule), &moves[..4]);
+    assert_eq!(adjudication, None);
+    let mut adjudicator = Adjudicator::new().resign(rule);
+    let (mut board, adjudication) = play(&mut adjudicator, &moves);
+    let adjudication = adjudication.unwrap();
+    assert_eq!(adjudication.reason, AdjudicationReason::Resignation);
+    adjudication.apply(&mut board).unwrap();
+    assert_eq!(board.game_result(), Some(GameResult::Wins(Color::White, WinType::Resignation)));
+    let (_, adjudication) = play(&mut Adjudicator::new().resign(ResignRule { two_sided: false, ..rule }), &moves[..4]);
+    assert!(adjudication.is_some());
+    // the tablebase takes precedence, and its results are from the point of view of the side to move
+    let mut adjudicator = Adjudicator::new().resign(rule).tablebase(|_: &super::Position| Some(Wdl::Loss));
+    let (_, adjudication) = play(&mut adjudicator, &moves);
+    assert_eq!(
+        adjudication.map(|a| (a.result, a.reason)),
+        Some((GameResult::Wins(Color::White, WinType::Resignation), AdjudicationReason::Tablebase))
+    );
+}
+
+#[test]
+fn engine_match() {
+    use super::{
+        match_play::{run_match, Adjudicator, DrawRule, Engine, EngineMatch, EngineMove},
+        TimeControl,
+    };
+    use std::time::Duration;
+
+    /// Plays the first legal move, or an illegal move if `illegal` is set.
+    struct TestEngine {
+        name: &'static str,
+        illegal: bool,
+    }
+    impl Engine for TestEngine {
+        fn name(&self) -> String {
+            self.name.to_owned()
+        }
+        fn go(&mut self, board: &Board, _: Option<[Duration; 2]>) -> Option<EngineMove> {
+            let move_ = if self.illegal { Move::from_uci("e2e5").unwrap() } else { board.gen_legal_moves()[0] };
+            Some(EngineMove { move_, score: Some(0) })
+        }
+    }
+    let engines = |illegal: bool| (TestEngine { name: "A", illegal: false }, TestEngine { name: "B", illegal });
+    let (mut a, mut b) = engines(true);
+    let result = run_match(&mut a, &mut b, None, &[]);
+    assert_eq!((result.wins, result.draws, result.losses), (2, 0, 0));
+    assert_eq!(result.games[0].metadata()["Termination"], "rules infraction");
+    assert_eq!((&result.games[1].metadata()["White"][..], &result.games[1].metadata()["Black"][..]), ("B", "A"));
+    // with no time on the clock, white runs out of time in every game
+    let (mut a, mut b) = engines(false);
+    let result = run_match(&mut a, &mut b, Some(TimeControl::from_secs(0, 0)), &[]);
+    assert_eq!((result.wins, result.draws, result.losses), (1, 0, 1));
+    assert_eq!(result.games[0].game_result(), Some(GameResult::Wins(Color::Black, WinType::Timeout)));
+    let opening = Board::from_fen(Fen::try_from("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap());
+    let adjudicator = Adjudicator::new().draw(DrawRule {
+        move_number: 1,
+        move_count: 1,
+        score: 0,
+    });
+    let result = EngineMatch::new(None, vec![opening]).rounds(2).adjudicator(adjudicator).run(&mut a, &mut b);
+    assert_eq!((result.total(), result.score()), (4, 2.));
+    assert!(result.games.iter().all(|game| game.current_ply() == 2 && game.metadata()["Termination"] == "adjudication"));
+    #[cfg(feature = "pgn")]
+    assert!(result.to_pgn().contains("[Round \"4\"]"));
+}
+
+#[test]
+fn rating_math() {
+    use super::{
+        match_play::MatchResult,
+        rating::{elo_difference, elo_estimate, expected_score, likelihood_of_superiority, performance_rating, Sprt, SprtStatus},
+    };
+
+    let close = |a: f64, b: f64| (a - b).abs() < 0.01;
+    assert!(close(expected_score(1600., 1600.), 0.5));
+    assert!(close(expected_score(2000., 1600.), 0.909));
+    assert!(close(elo_difference(expected_score(1750., 1500.)), 250.));
+    assert!(close(performance_rating(&[(1500., 1.), (1700., 0.)]).unwrap(), 1600.));
+    assert!(close(performance_rating(&[(1500., 0.5), (1500., 1.), (1500., 0.5)]).unwrap(), 1500. + elo_difference(2. / 3.)));
+    assert_eq!(performance_rating(&[(1500., 1.), (1700., 1.)]), None);
+    let result = |wins, draws, losses| MatchResult {
+        wins,
+        draws,
+        losses,
+        games: Vec::new(),
+    };
+    let estimate = elo_estimate(&result(600, 800, 400)).unwrap();
+    assert!(close(estimate.difference, elo_difference(1000. / 1800.)));
+    assert!(estimate.error > 0. && estimate.error < estimate.difference);
+    assert!(close(likelihood_of_superiority(&result(10, 5, 10)).unwrap(), 0.5));
+    assert!(likelihood_of_superiority(&result(30, 0, 10)).unwrap() > 0.99);
+    assert_eq!(likelihood_of_superiority(&result(0, 10, 0)), None);
+    let sprt = Sprt {
+        elo0: 0.,
+        elo1: 10.,
+        alpha: 0.05,
+        beta: 0.05,
+    };
+    assert_eq!(sprt.status(&result(600, 800, 400)), SprtStatus::AcceptH1);
+    assert_eq!(sprt.status(&result(400, 800, 600)), SprtStatus::AcceptH0);
+    assert_eq!(sprt.status(&result(10, 10, 9)), SprtStatus::Continue);
+    assert_eq!(sprt.llr(&result(0, 10, 0)), 0.);
+}
+
+#[cfg(feature = "pgn")]
+#[test]
+fn pgn_reader() {
+    use super::{
+        errors::{InvalidPgnError, PgnReadError},
+        pgn::PgnReader,
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    let tags = r#"[Event "?"]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "?"]
+[Black "?"]"#;
+    let database = format!("{tags}\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n\n{tags}\n[Result \"1-0\"]\n\n1. e4 e5 2. Ke3 1-0\n\n{tags}\n[Result \"*\"]\n\n1. e4 e5 *\n");
+    let reports = Rc::new(RefCell::new(Vec::new()));
+    let reports_ = Rc::clone(&reports);
+    let games: Vec<_> = PgnReader::new(database.as_bytes())
+        .with_progress(Some(database.len() as u64), move |progress| reports_.borrow_mut().push(*progress))
+        .collect();
+    assert_eq!(games.len(), 3);
+    assert!(games[0].as_ref().unwrap().board().is_checkmate());
+    assert!(games[1].is_err());
+    assert!(games[2].is_ok());
+    let reports = reports.borrow();
+    assert_eq!(reports.iter().map(|p| p.items).collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(reports[2].bytes, database.len() as u64);
+    assert_eq!(reports[2].fraction(), Some(1.));
+    assert_eq!(reports[2].eta(), Some(std::time::Duration::ZERO));
+    // a malformed FEN tag only invalidates its own game
+    let database = format!("{tags}\n[Result \"*\"]\n[FEN \"bogus\"]\n\n1. e4 *\n\n{tags}\n[Result \"*\"]\n\n1. e4 e5 *\n");
+    let games: Vec<_> = PgnReader::new(database.as_bytes()).collect();
+    assert_eq!(games.len(), 2);
+    assert!(matches!(games[0], Err(PgnReadError::InvalidPgn(InvalidPgnError::InvalidFen(_)))));
+    assert!(games[1].is_ok());
+}
+
+#[cfg(feature = "pgn")]
+#[test]
+fn pgn_slices() {
+    use super::pgn::{Pgn, PgnSlices};
+
+    let game = "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n";
+    let database = format!("{game}\n{game}\n\n{game}");
+    let slices: Vec<_> = PgnSlices::new(database.as_bytes()).collect::<Result<_, _>>().unwrap();
+    assert_eq!(slices.len(), 3);
+    assert_eq!(slices[0].as_ptr(), database.as_ptr());
+    assert!(slices.iter().all(|s| Pgn::try_from(*s).unwrap().board().is_checkmate()));
+    // the buffered reader splits the database into the same games
+    let mut reader = super::pgn::PgnReader::new(database.as_bytes());
+    let texts: Vec<_> = std::iter::from_fn(|| reader.next_game_text()).map(Result::unwrap).collect();
+    assert_eq!(texts, slices);
+    // a comment line starting with a bracket does not start a new game
+    let commented = "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"*\"]\n\n1. e4 { a long comment\n[%clk 0:01:00] } e5 *\n";
+    let database = format!("{commented}\n{game}");
+    let slices: Vec<_> = PgnSlices::new(database.as_bytes()).collect::<Result<_, _>>().unwrap();
+    assert_eq!(slices.len(), 2);
+    assert_eq!(Pgn::try_from(slices[0]).unwrap().board().current_ply(), 2);
+    assert!(Pgn::try_from(slices[1]).unwrap().board().is_checkmate());
+    let mut reader = super::pgn::PgnReader::new(database.as_bytes());
+    let texts: Vec<_> = std::iter::from_fn(|| reader.next_game_text()).map(Result::unwrap).collect();
+    assert_eq!(texts, slices);
+    // each game is validated as UTF-8 separately, and splitting continues after an invalid one
+    let invalid = [game.as_bytes(), b"\n", &game.as_bytes()[..game.len() - 5], b"{ \xff } 0-1\n\n", game.as_bytes()].concat();
+    let games: Vec<_> = PgnSlices::new(&invalid).collect();
+    assert_eq!(games.len(), 3);
+    assert!(games[0].is_ok() && games[1].is_err() && games[2].is_ok());
+    #[cfg(feature = "mmap")]
+    {
+        use super::pgn::MappedPgnDatabase;
+        let path = std::env::temp_dir().join(format!("rschess-mmap-{}.pgn", std::process::id()));
+        std::fs::write(&path, &database).unwrap();
+        let db = MappedPgnDatabase::open(&path).unwrap();
+        assert_eq!(db.games().collect::<Result<Vec<_>, _>>().unwrap(), slices);
+        drop(db);
+        // invalid UTF-8 is only detected in the game which contains it
+        std::fs::write(&path, &invalid).unwrap();
+        let db = MappedPgnDatabase::open(&path).unwrap();
+        assert_eq!(db.games().map(|game| game.is_ok()).collect::<Vec<_>>(), [true, false, true]);
+        drop(db);
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn position_to_svg() {
+    use super::img;
+
+    let board = Board::from_fen(Fen::try_from("8/1r6/8/6n1/5k2/1b6/3K3N/7Q b - - 0 1").unwrap());
+    let svg = img::position_to_svg(board.position(), img::PositionImageProperties::default(), Color::White).unwrap();
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="512" height="512""#));
+    assert_eq!(svg.matches("<rect ").count(), 64);
+    assert_eq!(svg.matches("<use ").count(), 7);
+    // each distinct piece is embedded once
+    assert_eq!(svg.matches("<image ").count(), 7);
+    assert!(svg.contains(r##"<use href="#wQ" x="448" y="448"/>"##));
+    let flipped = img::position_to_svg(board.position(), img::PositionImageProperties::default(), Color::Black).unwrap();
+    assert!(flipped.contains(r##"<use href="#wQ" x="0" y="0"/>"##));
+    let props = img::PositionImageProperties {
+        piece_set: img::PieceSet::Builtin("nonexistent".to_owned()),
+        ..Default::default()
+    };
+    assert!(img::position_to_svg(board.position(), props, Color::White).is_err());
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn diff_image() {
+    use super::img;
+
+    let before = Fen::try_from("k7/4P3/8/4p3/8/5N2/8/4K2R w - - 0 1").unwrap().position().clone();
+    let after = Fen::try_from("k3Q3/8/8/4N3/8/8/8/4K3 b - - 0 1").unwrap().position().clone();
+    let props = img::PositionImageProperties {
+        light_square_color: img::Rgb::new(255, 255, 255),
+        dark_square_color: img::Rgb::new(0, 0, 0),
+        size: 64,
+        ..Default::default()
+    };
+    let image = img::diff_image(&before, &after, props, Color::White).unwrap();
+    let corner = |file: u32, rank: u32| image.get_pixel(file * 8, (7 - rank) * 8).0;
+    // the knight moved from f3 (light) to e5 (dark), capturing a pawn
+    assert_eq!(corner(5, 2), [128, 178, 255, 255]);
+    assert_eq!(corner(4, 4), [0, 50, 128, 255]);
+    // the pawn on e7 (dark) disappeared, and a queen appeared on e8 (light)
+    assert_eq!(corner(4, 6), [110, 0, 0, 255]);
+    assert_eq!(corner(4, 7), [128, 228, 128, 255]);
+    // the rook on h1 (light) disappeared
+    assert_eq!(corner(7, 0), [238, 128, 128, 255]);
+    // the kings did not move
+    assert_eq!(corner(4, 0), [0, 0, 0, 255]);
+    assert_eq!(corner(0, 7), [255, 255, 255, 255]);
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn render_batch() {
+    use super::img::{self, RenderJob, RenderSubject};
+
+    let props = img::PositionImageProperties { size: 64, ..Default::default() };
+    let mut board = Board::default();
+    board.make_move_san("e4").unwrap();
+    let jobs = vec![
+        RenderJob {
+            subject: RenderSubject::Position(Box::new(board.position().clone())),
+            props: props.clone(),
+            perspective: Color::White,
+        },
+        RenderJob {
+            subject: RenderSubject::Board(Box::new(board.clone())),
+            props: props.clone(),
+            perspective: Color::Black,
+        },
+        RenderJob {
+            subject: RenderSubject::Position(Box::new(board.position().clone())),
+            props: img::PositionImageProperties { size: 4, ..Default::default() },
+            perspective: Color::White,
+        },
+    ];
+    let results = img::render_batch(&jobs);
+    assert_eq!(results.len(), 3);
+    let decode = |bytes: &Vec<u8>| image::load_from_memory_with_format(bytes, image::ImageFormat::Png).unwrap().to_rgba8();
+    assert_eq!(decode(results[0].as_ref().unwrap()), img::position_to_image(board.position(), props.clone(), Color::White).unwrap());
+    assert_eq!(decode(results[1].as_ref().unwrap()), img::board_to_image(&board, props, Color::Black).unwrap());
+    assert!(results[2].is_err());
+}
+
+#[cfg(feature = "pgn")]
+#[test]
+fn pgn_reader_open() {
+    use super::pgn::PgnReader;
+    use std::{cell::Cell, rc::Rc};
+
+    let game = "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n\n";
+    let database = game.repeat(3);
+    #[allow(unused_mut)]
+    let mut files = vec![("pgn", database.clone().into_bytes())];
+    #[cfg(feature = "zstd")]
+    files.push(("pgn.zst", zstd::encode_all(database.as_bytes(), 0).unwrap()));
+    #[cfg(feature = "gzip")]
+    {
+        use std::io::Write;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(database.as_bytes()).unwrap();
+        files.push(("pgn.gz", encoder.finish().unwrap()));
+    }
+    for (ext, contents) in files {
+        let path = std::env::temp_dir().join(format!("rschess-open-{}.{ext}", std::process::id()));
+        std::fs::write(&path, &contents).unwrap();
+        let last_bytes = Rc::new(Cell::new(0));
+        let last_bytes_ = Rc::clone(&last_bytes);
+        let reader = PgnReader::open(&path).unwrap().with_progress(None, move |progress| {
+            assert_eq!(progress.total_bytes, Some(contents.len() as u64));
+            last_bytes_.set(progress.bytes);
+        });
+        let games: Vec<_> = reader.map(|g| g.unwrap()).collect();
+        assert_eq!(games.len(), 3, "{ext}");
+        assert!(last_bytes.get() > 0);
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn image_overlays() {
+    use super::img;
+
+    let position = Fen::try_from("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap().position().clone();
+    let red = img::Rgb::new(255, 0, 0);
+    let props = img::PositionImageProperties {
+        light_square_color: img::Rgb::new(255, 255, 255),
+        dark_square_color: img::Rgb::new(0, 0, 0),
+        size: 64,
+        arrows: vec![img::Arrow { from: 0, to: 63, color: red }],
+        highlighted_squares: vec![img::HighlightedSquare { square: 7, color: red }],
+        ..Default::default()
+    };
+    let image = img::position_to_image(&position, props.clone(), Color::White).unwrap();
+    // h1 is light, so blending it with red halfway gives pink
+    assert_eq!(image.get_pixel(60, 60).0, [255, 128, 128, 255]);
+    // the arrow passes through the center of the board, where d4 (dark) and e5 (dark) meet
+    assert_eq!(image.get_pixel(31, 32).0, [204, 0, 0, 255]);
+    // the corner of b1 is far from the arrow
+    assert_eq!(image.get_pixel(8, 56).0, [255, 255, 255, 255]);
+    let svg = img::position_to_svg(&position, props.clone(), Color::White).unwrap();
+    assert_eq!(svg.matches("<polygon ").count(), 1);
+    assert!(svg.contains(r##"fill="#ff8080""##));
+    let invalid = img::PositionImageProperties {
+        arrows: vec![img::Arrow { from: 0, to: 64, color: red }],
+        ..props
+    };
+    assert!(img::position_to_image(&position, invalid, Color::White).is_err());
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn board_image_highlights() {
+    use super::img;
+
+    let mut board = Board::default();
+    board.make_moves_san("e4 f5 Qh5+").unwrap();
+    let props = img::PositionImageProperties {
+        light_square_color: img::Rgb::new(255, 255, 255),
+        dark_square_color: img::Rgb::new(0, 0, 0),
+        size: 256,
+        ..Default::default()
+    };
+    let image = img::board_to_image(&board, props.clone(), Color::White).unwrap();
+    // d1 (light) and h5 (light) are tinted, while e1 is not
+    assert_ne!(image.get_pixel(96, 255).0, [255, 255, 255, 255]);
+    assert_eq!(image.get_pixel(96, 255), image.get_pixel(255, 96));
+    assert_eq!(image.get_pixel(159, 255).0, [0, 0, 0, 255]);
+    // the highlight under the black king on e8 (light) is red at the center and fades out towards the corners
+    let plain = img::position_to_image(board.position(), props.clone(), Color::White).unwrap();
+    let background: Vec<_> = (128..160)
+        .flat_map(|x| (0..32).map(move |y| (x, y)))
+        .filter(|&(x, y)| plain.get_pixel(x, y).0 == [255, 255, 255, 255])
+        .collect();
+    assert!(background.iter().any(|&(x, y)| image.get_pixel(x, y).0[1] < 100));
+    assert!(image.get_pixel(128, 0).0[1] > 200);
+    let svg = img::board_to_svg(&board, props, Color::White).unwrap();
+    assert!(svg.contains("<radialGradient"));
+    board.make_move_san("g6").unwrap();
+    assert!(!img::board_to_svg(&board, Default::default(), Color::White).unwrap().contains("<radialGradient"));
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn game_animation() {
+    use super::img;
+    use image::AnimationDecoder;
+    use std::time::Duration;
+
+    let mut board = Board::default();
+    board.make_moves_san("f3 e5 g4 Qh4#").unwrap();
+    let props = img::PositionImageProperties { size: 64, ..Default::default() };
+    let gif = img::game_to_gif(&board, props.clone(), Color::White, Duration::from_millis(500)).unwrap();
+    let frames = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(gif)).unwrap().into_frames().collect_frames().unwrap();
+    assert_eq!(frames.len(), 5);
+    assert_eq!(frames[0].delay().numer_denom_ms(), (500, 1));
+    assert_eq!(frames[4].buffer().dimensions(), (64, 64));
+    let props_clone = props.clone();
+    let apng = img::game_to_apng(&board, props, Color::White, Duration::from_millis(500)).unwrap();
+    let decoder = image::codecs::png::PngDecoder::new(std::io::Cursor::new(apng)).unwrap();
+    assert!(decoder.is_apng().unwrap());
+    let frames = decoder.apng().unwrap().into_frames().collect_frames().unwrap();
+    assert_eq!(frames.len(), 5);
+    let mut partial = Board::default();
+    partial.make_moves_san("f3 e5").unwrap();
+    assert_eq!(frames[2].buffer(), &img::board_to_image(&partial, props_clone, Color::White).unwrap());
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn material_tray() {
+    use super::img;
+
+    let mut board = Board::default();
+    board.make_moves_san("e4 d5 exd5 Qxd5 Nc3 Qxa2 Rxa2").unwrap();
+    assert_eq!(board.captured_pieces(Color::White), [PieceType::P, PieceType::Q]);
+    assert_eq!(board.captured_pieces(Color::Black), [PieceType::P, PieceType::P]);
+    assert_eq!(board.position().material_balance(), 8);
+    let tray = img::MaterialTray {
+        background: img::Rgb::new(0, 0, 255),
+        text_color: img::Rgb::new(255, 0, 0),
+    };
+    let props = img::PositionImageProperties {
+        size: 256,
+        material_tray: Some(tray),
+        ..Default::default()
+    };
+    let image = img::board_to_image(&board, props.clone(), Color::White).unwrap();
+    assert_eq!(image.dimensions(), (256, 294));
+    let is_text = |x, y: u32| image.get_pixel(x, y).0 == [255, 0, 0, 255];
+    // white is ahead, so "+8" is drawn in the bottom tray (after the captured pieces), and not in the top tray
+    assert!((0..256).any(|x| (275..294).any(|y| is_text(x, y))));
+    assert!(!(0..256).any(|x| (0..19).any(|y| is_text(x, y))));
+    assert_eq!(image.get_pixel(255, 0).0, [0, 0, 255, 255]);
+    assert_eq!(img::position_to_image(board.position(), props.clone(), Color::White).unwrap().dimensions(), (256, 256));
+    let svg = img::board_to_svg(&board, props, Color::Black).unwrap();
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="256" height="294""#));
+    assert_eq!(svg.matches("<svg ").count(), 2);
+    // four captured pieces and two characters
+    assert_eq!(svg.matches("<image x=").count(), 4);
+    assert_eq!(svg.matches(r##"fill="#ff0000""##).count(), 2);
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn evaluation_bar() {
+    use super::img::{self, Evaluation, EvaluationBar};
+
+    assert_eq!(Evaluation::Centipawns(0).white_share(), 0.5);
+    assert!(Evaluation::Centipawns(300).white_share() > 0.7);
+    assert_eq!(Evaluation::Mate(Color::Black, 3).white_share(), 0.);
+    assert_eq!(Evaluation::Centipawns(-153).label(), "1.5");
+    assert_eq!(Evaluation::Centipawns(1234).label(), "12");
+    assert_eq!(Evaluation::Mate(Color::White, 3).label(), "M3");
+    let (white, black) = (img::Rgb::new(255, 255, 255), img::Rgb::new(0, 0, 0));
+    let props = img::PositionImageProperties {
+        size: 200,
+        evaluation_bar: Some(EvaluationBar {
+            evaluation: Evaluation::Mate(Color::White, 2),
+            white_color: white,
+            black_color: black,
+        }),
+        ..Default::default()
+    };
+    let position = Board::default().position().clone();
+    let image = img::position_to_image(&position, props.clone(), Color::White).unwrap();
+    assert_eq!(image.dimensions(), (210, 200));
+    // the bar is filled with white, with the label in black at the bottom
+    assert_eq!(image.get_pixel(0, 0).0, [255, 255, 255, 255]);
+    assert!((0..10).any(|x| (185..200).any(|y| image.get_pixel(x, y).0 == [0, 0, 0, 255])));
+    let props = img::PositionImageProperties {
+        evaluation_bar: Some(EvaluationBar::new(Evaluation::Centipawns(0))),
+        ..props
+    };
+    let image = img::position_to_image(&position, props.clone(), Color::Black).unwrap();
+    // from black's perspective, white's half of the bar is at the top
+    assert_eq!(image.get_pixel(0, 90).0, [255, 255, 255, 255]);
+    assert_eq!(image.get_pixel(0, 110).0, [64, 61, 57, 255]);
+    let svg = img::position_to_svg(&position, props, Color::White).unwrap();
+    assert!(svg.contains(r#"width="210" height="200""#));
+    assert!(svg.contains(r#"<g transform="translate(10 0)">"#));
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn image_coordinates() {
+    use super::img;
+
+    let position = Fen::try_from("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap().position().clone();
+    let red = img::Rgb::new(255, 0, 0);
+    let coordinates = img::Coordinates {
+        color: red,
+        border_color: img::Rgb::new(0, 0, 255),
+        ..Default::default()
+    };
+    let props = img::PositionImageProperties {
+        light_square_color: img::Rgb::new(255, 255, 255),
+        dark_square_color: img::Rgb::new(0, 0, 0),
+        size: 256,
+        coordinates: Some(coordinates.clone()),
+        ..Default::default()
+    };
+    let image = img::position_to_image(&position, props.clone(), Color::White).unwrap();
+    assert_eq!(image.dimensions(), (256, 256));
+    let is_label = |x, y| image.get_pixel(x, y).0 == [255, 0, 0, 255];
+    // the file letter is in the bottom-right corner of a1 and the rank number is in the top-left corner of a8
+    assert!((16..32).any(|x| (240..256).any(|y| is_label(x, y))));
+    assert!((0..16).any(|x| (0..16).any(|y| is_label(x, y))));
+    assert!(!(16..32).any(|x| (0..16).any(|y| is_label(x, y))));
+    let outside = img::PositionImageProperties {
+        coordinates: Some(img::Coordinates {
+            placement: img::CoordinatePlacement::Outside,
+            ..coordinates
+        }),
+        ..props.clone()
+    };
+    let image = img::position_to_image(&position, outside.clone(), Color::Black).unwrap();
+    assert_eq!(image.dimensions(), (288, 288));
+    assert_eq!(image.get_pixel(0, 0).0, [0, 0, 255, 255]);
+    // the border surrounds the board: h8 (dark) is in the bottom-left corner from black's perspective
+    assert_eq!(image.get_pixel(16, 271).0, [0, 0, 0, 255]);
+    let svg = img::position_to_svg(&position, outside, Color::Black).unwrap();
+    assert!(svg.contains(r#"width="288" height="288""#));
+    assert_eq!(svg.matches(r##"<path d="M"##).count(), 16);
+    let svg = img::position_to_svg(&position, props, Color::White).unwrap();
+    assert!(svg.contains(r#"width="256" height="256""#));
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn image_themes() {
+    use super::img;
+
+    for name in img::BUILTIN_THEMES {
+        img::Theme::builtin(name).unwrap();
+    }
+    assert!(img::Theme::builtin("neon").is_err());
+    let position = Fen::try_from("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap().position().clone();
+    let props = img::PositionImageProperties { size: 256, ..Default::default() }.with_theme(img::Theme::builtin("lichess-brown").unwrap());
+    let image = img::position_to_image(&position, props, Color::White).unwrap();
+    // a1 is dark and h1 is light
+    assert_eq!(image.get_pixel(2, 253).0, [0xb5, 0x88, 0x63, 255]);
+    assert_eq!(image.get_pixel(253, 253).0, [0xf0, 0xd9, 0xb5, 255]);
+    let textures = img::SquareTextures {
+        light: image::RgbaImage::from_fn(2, 1, |x, _| image::Rgba([255 * x as u8, 0, 0, 255])),
+        dark: image::RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 255, 255])),
+    };
+    let props = img::PositionImageProperties {
+        size: 256,
+        highlighted_squares: vec![img::HighlightedSquare {
+            square: 0,
+            color: img::Rgb::new(255, 0, 0),
+        }],
+        ..Default::default()
+    }
+    .with_theme(img::Theme {
+        square_textures: Some(textures),
+        ..img::Theme::builtin("default").unwrap()
+    });
+    let image = img::position_to_image(&position, props.clone(), Color::White).unwrap();
+    // the light texture is stretched across the board, so the light squares on the left are black and those on the right are red
+    assert_eq!(image.get_pixel(34, 253).0, [0, 0, 0, 255]);
+    assert_eq!(image.get_pixel(253, 253).0, [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(2, 253).0, [128, 0, 128, 255]);
+    let svg = img::position_to_svg(&position, props, Color::White).unwrap();
+    assert_eq!(svg.matches("<pattern").count(), 2);
+    assert_eq!(svg.matches("url(#texture-light)").count(), 32);
+    let marble = img::Theme::builtin("blue-marble").unwrap();
+    let image = img::position_to_image(&position, img::PositionImageProperties::default().with_theme(marble), Color::White).unwrap();
+    assert_ne!(image.get_pixel(40, 40), image.get_pixel(56, 56));
+}
+
+#[test]
+fn game_builder() {
+    use super::{GameBuilder, Odds, TimeControl};
+
+    let board = Board::builder().chess960(518).build().unwrap();
+    assert_eq!(board.position(), Board::default().position());
+    let board = Board::builder()
+        .chess960(0)
+        .time_control(TimeControl::from_secs(180, 2))
+        .rules(Rules::LICHESS)
+        .metadata("White", "Alice")
+        .metadata("Black", "Bob")
+        .build()
+        .unwrap();
+    assert_eq!(board.position().to_fen().split(' ').next().unwrap(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR");
+    assert_eq!(board.gen_legal_moves().len(), 20);
+    assert_eq!(board.time_control().unwrap().to_string(), "180+2");
+    assert_eq!(board.rules(), Rules::LICHESS);
+    assert_eq!(board.metadata().get("White").map(String::as_str), Some("Alice"));
+    let board = GameBuilder::new().odds(Color::White, Odds::Rook).odds(Color::Black, Odds::Pawn).build().unwrap();
+    assert_eq!(board.to_fen().to_string(), "rnbqkbnr/ppppp1pp/8/8/8/8/PPPPPPPP/1NBQKBNR w Kkq - 0 1");
+    assert!(GameBuilder::new().chess960(960).build().is_err());
+    assert!(GameBuilder::new().odds(Color::White, Odds::Queen).odds(Color::White, Odds::Queen).build().is_err());
+    // castling requires the squares between the rook and its destination to be empty too
+    let board = Board::from_fen(Fen::try_from("r3k2r/8/8/8/8/8/8/RN2K2R w KQkq - 0 1").unwrap());
+    assert!(board.san_to_move("O-O-O").is_err());
+    assert!(board.san_to_move("O-O").is_ok());
+}
+
+#[test]
+fn takeback_negotiation() {
+    use super::TakebackOffer;
+
+    let mut board = Board::default();
+    board.make_moves_san("e4 e5 Nf3").unwrap();
+    assert!(board.offer_takeback(Color::White, 4).is_err());
+    let plies = TakebackOffer::plies_for_last_move(Color::White, board.side_to_move());
+    assert_eq!(plies, 1);
+    board.offer_takeback(Color::White, plies).unwrap();
+    assert!(board.offer_takeback(Color::Black, 1).is_err());
+    assert!(board.accept_takeback(Color::White).is_err());
+    board.accept_takeback(Color::Black).unwrap();
+    assert_eq!(board.takeback_offer(), None);
+    assert_eq!(board.gen_movetext(), "1. e4 e5");
+    // black asks to take back its own move while it is to move, which also takes back white's reply
+    board.make_move_san("Nc3").unwrap();
+    board.offer_takeback(Color::Black, TakebackOffer::plies_for_last_move(Color::Black, board.side_to_move())).unwrap();
+    board.accept_takeback(Color::White).unwrap();
+    assert_eq!(board.gen_movetext(), "1. e4");
+    // the opponent moving declines the offer
+    board.make_move_san("c5").unwrap();
+    board.offer_takeback(Color::Black, 1).unwrap();
+    board.make_move_san("Nf3").unwrap();
+    assert_eq!(board.takeback_offer(), None);
+    assert!(board.accept_takeback(Color::White).is_err());
+    board.offer_takeback(Color::White, 3).unwrap();
+    board.decline_takeback(Color::Black).unwrap();
+    board.offer_takeback(Color::White, 3).unwrap();
+    assert!(board.withdraw_takeback(Color::Black).is_err());
+    board.withdraw_takeback(Color::White).unwrap();
+    assert!(board.undo_moves(4).is_err());
+    board.undo_moves(3).unwrap();
+    assert_eq!(board, Board::default());
+}
+
+#[test]
+fn rematch_and_continuation() {
+    use super::TimeControl;
+
+    let mut board = Board::builder()
+        .chess960(0)
+        .time_control(TimeControl::from_secs(60, 0))
+        .metadata("White", "Alice")
+        .metadata("WhiteElo", "2100")
+        .metadata("Black", "Bob")
+        .metadata("Event", "Casual")
+        .build()
+        .unwrap();
+    board.make_moves_san("e4 e5").unwrap();
+    board.resign(Color::Black).unwrap();
+    board.set_metadata("Result", "1-0");
+    let rematch = board.rematch();
+    assert!(rematch.is_ongoing());
+    assert_eq!(rematch.last_move(), None);
+    assert_eq!(rematch.initial_fen(), board.initial_fen());
+    assert_eq!(rematch.time_control(), Some(TimeControl::from_secs(60, 0)));
+    let metadata: Vec<_> = rematch.metadata().iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    assert_eq!(metadata, [("Black", "Alice"), ("BlackElo", "2100"), ("Event", "Casual"), ("White", "Bob")]);
+    let continuation = board.continue_from_current();
+    assert!(continuation.is_ongoing());
+    assert!(continuation.same_position(&board));
+    assert_eq!(continuation.initial_fen(), &board.to_fen());
+    assert_eq!(continuation.metadata()["SetUp"], "1");
+    assert_eq!(continuation.metadata()["FEN"], board.to_fen().to_string());
+    assert_eq!(continuation.metadata()["White"], "Alice");
+    assert!(!continuation.metadata().contains_key("Result"));
+}
+
+#[test]
+fn session() {
+    use super::{Session, SessionEvent, SessionEventKind, TimeControl};
+    use std::time::{Duration, Instant};
+
+    let start = Instant::now();
+    let secs = |n| start + Duration::from_secs(n);
+    let mut session = Session::new();
+    session.add_game_at(1, Board::builder().time_control(TimeControl::from_secs(60, 2)).build().unwrap(), start);
+    session.add_game_at(2, Board::default(), start);
+    let e4 = Move::from_uci("e2e4").unwrap();
+    session.make_move_at(&1, e4, secs(10)).unwrap();
+    session.make_move_at(&2, e4, secs(10)).unwrap();
+    assert!(session.make_move_at(&3, e4, secs(10)).is_err());
+    assert!(session.make_move_at(&2, e4, secs(10)).is_err());
+    let clock = session.clock(&1).unwrap();
+    assert_eq!(clock.remaining_at(Color::White, Color::Black, secs(20)), Duration::from_secs(52));
+    assert_eq!(clock.remaining_at(Color::Black, Color::Black, secs(20)), Duration::from_secs(50));
+    assert!(session.clock(&2).is_none());
+    assert_eq!(session.check_flags_at(secs(69)), 0);
+    assert_eq!(session.check_flags_at(secs(70)), 1);
+    assert_eq!(session.ongoing_count(), 1);
+    assert!(session.make_move_at(&1, Move::from_uci("e7e5").unwrap(), secs(71)).is_err());
+    let events = session.drain_events();
+    let kinds: Vec<_> = events.iter().map(|SessionEvent { id, kind }| (*id, *kind)).collect();
+    assert!(matches!(
+        kinds[..],
+        [
+            (1, SessionEventKind::MoveMade(_)),
+            (2, SessionEventKind::MoveMade(_)),
+            (1, SessionEventKind::FlagFell(Color::Black)),
+            (1, SessionEventKind::GameOver(GameResult::Wins(Color::White, WinType::Timeout)))
+        ]
+    ));
+    assert!(session.drain_events().is_empty());
+    // a move made after the flag has fallen ends the game instead
+    let mut board = Board::builder().time_control(TimeControl::from_secs(5, 0)).build().unwrap();
+    board.make_moves_san("e4 e5").unwrap();
+    session.add_game_at(3, board, start);
+    assert!(session.make_move_at(&3, Move::from_uci("g1f3").unwrap(), secs(6)).is_err());
+    assert_eq!(session.board(&3).unwrap().flagged_side(), Some(Color::White));
+    assert_eq!(session.drain_events().len(), 2);
+    #[cfg(feature = "pgn")]
+    {
+        let pgn = session.to_pgn();
+        assert_eq!(pgn.matches("[SessionId ").count(), 3);
+        assert!(pgn.contains(r#"[TimeControl "60+2"]"#));
+    }
+}
+
+#[cfg(feature = "pgn")]
+#[test]
+fn opening_tree() {
+    use super::{explorer::OpeningTree, pgn::PgnReader};
+
+    let tags = "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]";
+    let database = [
+        ("[WhiteElo \"2000\"]\n[BlackElo \"1800\"]\n", "1. e4 e5 2. Nf3 Nc6", "1-0"),
+        ("[WhiteElo \"2200\"]\n", "1. e4 c5 2. Nf3 d6", "0-1"),
+        ("", "1. Nf3 e5 2. e4 Nc6", "1/2-1/2"),
+        ("", "1. e4 e5 2. Bc4 Nc6", "1-0"),
+        ("", "1. e4 e5 2. Ke2 Ke7 3. Ke3 Ke6 4. Kf3 Kf6", "1-0"),
+        ("", "1. e4 e5 2. Nf3 Nf3", "1-0"),
+    ]
+    .map(|(ratings, movetext, result)| format!("{tags}\n{ratings}[Result \"{result}\"]\n\n{movetext} {result}\n\n"))
+    .concat();
+    let mut tree = OpeningTree::new().with_max_plies(6);
+    assert!(tree.is_empty());
+    // the last game is invalid, as 2... Nf3 is illegal
+    assert_eq!(tree.add_games(PgnReader::new(database.as_bytes())), 5);
+    assert_eq!(tree.games(), 5);
+    let mut board = Board::default();
+    let moves = tree.moves_after(&board);
+    assert_eq!(moves.len(), 2);
+    assert_eq!((moves[0].0, moves[0].1.games), (board.san_to_move("e4").unwrap(), 4));
+    let e4 = moves[0].1;
+    assert_eq!((e4.white_wins, e4.draws, e4.black_wins), (3, 0, 1));
+    assert_eq!(e4.average_rating(), Some(2050.));
+    assert_eq!(e4.score(Color::White), Some(0.75));
+    board.make_moves_san("e4 e5 Nf3").unwrap();
+    // the third game transposes into the first
+    let moves = tree.moves_after(&board);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].0, board.san_to_move("Nc6").unwrap());
+    assert_eq!((moves[0].1.white_wins, moves[0].1.draws), (1, 1));
+    assert_eq!(tree.stats(&board), Some(moves[0].1));
+    // moves beyond the ply limit are not added
+    board = Board::default();
+    board.make_moves_san("e4 e5 Ke2 Ke7 Ke3 Ke6").unwrap();
+    assert!(tree.moves_after(&board).is_empty());
+    assert_eq!(tree.stats(&board), None);
+}
+
+#[cfg(feature = "lichess")]
+#[test]
+fn lichess_import() {
+    use super::lichess::{LichessEval, LichessGame, LichessReader, MoveAnalysis};
+    use std::time::Duration;
+
+    let ndjson = r#"{"id":"abcd1234","rated":true,"variant":"standard","speed":"blitz","createdAt":1700000000000,"status":"mate","players":{"white":{"user":{"name":"Alice","title":"FM"},"rating":2300,"ratingDiff":6},"black":{"user":{"name":"Bob"},"rating":1500,"ratingDiff":-2}},"winner":"white","opening":{"eco":"C23","name":"Bishop's Opening"},"moves":"e4 e5 Bc4 Nc6 Qh5 Nf6 Qxf7#","clocks":[18003,18003,17803,17750,17600,17500,17400],"clock":{"initial":180,"increment":2},"analysis":[{"eval":30},{"eval":25},{"eval":20},{"eval":15},{"eval":10},{"mate":1,"best":"g7g6","variation":"g6 Qf3 Nf6","judgment":{"name":"Blunder","comment":"Checkmate is now unavoidable. g6 was best."}}]}
+
+{"id":"efgh5678","rated":false,"variant":"fromPosition","speed":"correspondence","status":"resign","initialFen":"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1","players":{"white":{"aiLevel":3},"black":{"user":{"name":"Carol"}}},"winner":"black","moves":"e4 Kd7"}
+{"id":"ijkl9012","variant":"atomic","moves":"e4"}"#;
+    let mut games = LichessReader::new(ndjson.as_bytes());
+    let game = games.next().unwrap().unwrap();
+    assert_eq!(game.id, "abcd1234");
+    let tags = game.pgn.tag_pairs();
+    for (tag, value) in [
+        ("Event", "Rated Blitz game"),
+        ("Site", "https://lichess.org/abcd1234"),
+        ("Date", "2023.11.14"),
+        ("UTCTime", "22:13:20"),
+        ("White", "Alice"),
+        ("WhiteTitle", "FM"),
+        ("WhiteElo", "2300"),
+        ("WhiteRatingDiff", "+6"),
+        ("BlackRatingDiff", "-2"),
+        ("ECO", "C23"),
+        ("Termination", "Normal"),
+        ("TimeControl", "180+2"),
+        ("Result", "1-0"),
+    ] {
+        assert_eq!(tags[tag], value);
+    }
+    let board = game.pgn.board();
+    assert!(board.is_checkmate());
+    assert_eq!(board.move_clocks()[2], Some(Duration::from_millis(178_030)));
+    assert_eq!(board.move_clocks().len(), 7);
+    assert_eq!(game.analysis.len(), 6);
+    assert_eq!(game.analysis[0].eval, Some(LichessEval::Centipawns(30)));
+    assert_eq!(
+        game.analysis[5],
+        MoveAnalysis {
+            eval: Some(LichessEval::Mate(1)),
+            best: Some(board.position_at_ply(5).unwrap().lan_to_move("g7g6").unwrap()),
+            variation: Some("g6 Qf3 Nf6".to_owned()),
+            judgment: Some("Blunder".to_owned()),
+        }
+    );
+    let game = games.next().unwrap().unwrap();
+    let tags = game.pgn.tag_pairs();
+    for (tag, value) in [
+        ("Event", "Casual Correspondence game"),
+        ("White", "lichess AI level 3"),
+        ("Variant", "From Position"),
+        ("Result", "0-1"),
+        ("SetUp", "1"),
+    ] {
+        assert_eq!(tags[tag], value);
+    }
+    assert_eq!(game.pgn.board().resigned_side(), Some(Color::White));
+    assert!(game.analysis.is_empty());
+    assert!(matches!(games.next(), Some(Err(super::LichessImportError::UnsupportedVariant(v))) if v == "atomic"));
+    assert!(games.next().is_none());
+    assert!(LichessGame::from_json(r#"{"id":"x","moves":"e4 e4"}"#).is_err());
+    // a malformed speed does not stop the import
+    for (speed, event) in [(r#""""#, "Casual Unknown game"), (r#""ébullition""#, "Casual Ébullition game"), ("null", "Casual Unknown game")] {
+        let game = LichessGame::from_json(&format!(r#"{{"id":"x","speed":{speed},"moves":"e4"}}"#)).unwrap();
+        assert_eq!(game.pgn.tag_pairs()["Event"], event);
+    }
+}
+
+#[cfg(feature = "chesscom")]
+#[test]
+fn chesscom_import() {
+    use super::chesscom::{parse_archive, ChessComGame};
+    use std::time::Duration;
+
+    let pgn = r#"[Event "Live Chess"]
+[Site "Chess.com"]
+[Date "2024.01.05"]
+[Round "-"]
+[White "alice"]
+[Black "bob"]
+[Result "1-0"]
+[Timezone "UTC"]
+[ECO "C23"]
+[TimeControl "180+2"]
+[Termination "alice won by checkmate"]
+[Link "https://www.chess.com/game/live/123"]
+
+1. e4 {[%clk 0:03:01.9]} 1... e5 {[%clk 0:03:01.5]} 2. Bc4 {[%clk 0:03:03.1]} 2... Nc6 {[%clk 0:03:02.8]} 3. Qh5 {[%clk 0:03:04]} 3... Nf6 {[%clk 0:03:03.7]} 4. Qxf7# {[%clk 0:03:05.2]} 1-0
+"#;
+    let game = serde_json::json!({
+        "url": "https://www.chess.com/game/live/123",
+        "pgn": pgn,
+        "time_control": "180+2",
+        "end_time": 1704448920,
+        "rated": true,
+        "accuracies": {"white": 91.5, "black": 23.1},
+        "time_class": "blitz",
+        "rules": "chess",
+        "white": {"rating": 1508, "result": "win", "username": "alice"},
+        "black": {"rating": 1392, "result": "checkmated", "username": "bob"},
+    });
+    let variant = serde_json::json!({"url": "", "pgn": "", "time_control": "180", "time_class": "blitz", "rules": "crazyhouse"});
+    let archive = serde_json::json!({ "games": [game, variant] }).to_string();
+    let games = parse_archive(&archive).unwrap();
+    assert_eq!(games.len(), 2);
+    let game = games[0].as_ref().unwrap();
+    assert_eq!(game.time_class, "blitz");
+    assert!(game.rated);
+    assert_eq!(game.end_time, Some(1704448920));
+    assert_eq!(game.player(Color::White).accuracy, Some(91.5));
+    assert_eq!(game.black.result, "checkmated");
+    assert_eq!(game.black.rating, Some(1392));
+    let board = game.pgn.board();
+    assert!(board.is_checkmate());
+    assert_eq!(board.move_clocks()[5], Some(Duration::from_millis(183_700)));
+    assert_eq!(game.pgn.tag_pairs()["Termination"], "alice won by checkmate");
+    assert!(matches!(&games[1], Err(super::ChessComImportError::UnsupportedVariant(rules)) if rules == "crazyhouse"));
+    assert!(parse_archive(r#"{"archives": []}"#).is_err());
+    assert!(ChessComGame::from_json(r#"{"url": "x", "pgn": "1. e4"}"#).is_err());
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn wasm_bindings() {
+    use super::wasm::{is_valid_fen, normalize_fen, WasmBoard, WasmMove};
+    use wasm_bindgen::JsError;
+
+    // JsError cannot be debug-printed (or even created outside of WebAssembly), so only successes are tested
+    fn ok<T>(res: Result<T, JsError>) -> T {
+        res.unwrap_or_else(|_| panic!("the binding failed"))
+    }
+
+    let mut board = ok(WasmBoard::new(None));
+    assert_eq!(board.legal_moves().len(), 20);
+    assert_eq!(board.side_to_move(), "w");
+    ok(board.make_move_uci("f2f3"));
+    ok(board.make_move_san("e5"));
+    ok(board.make_move(&ok(WasmMove::from_uci("g2g4"))));
+    let mate = board.legal_moves().into_iter().find(|m| m.uci() == "d8h4").unwrap();
+    assert_eq!((mate.from(), mate.to(), mate.promotion()), ("d8".to_owned(), "h4".to_owned(), None));
+    assert_eq!(ok(board.move_to_san(&mate)), "Qh4#");
+    ok(board.make_move(&mate));
+    assert!(board.is_checkmate() && board.is_game_over());
+    assert_eq!(board.result(), "0-1");
+    assert_eq!(board.uci_moves(), "f2f3 e7e5 g2g4 d8h4");
+    ok(board.undo_move());
+    assert_eq!(board.result(), "*");
+    let board = ok(WasmBoard::new(Some("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1".to_owned())));
+    assert_eq!(board.legal_moves().iter().filter_map(WasmMove::promotion).collect::<Vec<_>>(), ["q", "r", "b", "n"]);
+    assert!(board.legal_moves_san().contains(&"b8=Q+".to_owned()));
+    assert!(is_valid_fen(&board.fen()));
+    assert!(!is_valid_fen("not a fen"));
+    assert_eq!(ok(normalize_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1")), "4k3/8/8/8/4P3/8/8/4K3 b - - 0 1");
+}
+
+#[cfg(feature = "pgn")]
+#[test]
+fn pgn_clocks() {
+    use super::{pgn::Pgn, Session, TimeControl};
+    use std::time::{Duration, Instant};
+
+    let start = Instant::now();
+    let mut session = Session::new();
+    session.add_game_at(1, Board::builder().time_control(TimeControl::from_secs(300, 3)).build().unwrap(), start);
+    for (uci, secs) in [("e2e4", 5), ("e7e5", 12), ("g1f3", 3007)] {
+        session.make_move_at(&1, Move::from_uci(uci).unwrap(), start + Duration::from_millis(secs * 100)).unwrap();
+    }
+    let board = session.board(&1).unwrap();
+    let clocks = [Duration::from_millis(302_500), Duration::from_millis(302_300), Duration::from_millis(6_000)];
+    assert_eq!(board.move_clocks(), clocks.map(Some));
+    let pgn = session.to_pgn();
+    assert!(pgn.contains(r#"[TimeControl "300+3"]"#));
+    assert!(pgn.contains("1. e4 {[%clk 0:05:02.5]} 1... e5 {[%clk 0:05:02.3]} 2. Nf3 {[%clk 0:00:06]} *"));
+    // the clocks survive a round trip, and a session resumes the game with them
+    let parsed = Pgn::try_from(pgn.replace(" *", " 1-0").as_str()).unwrap();
+    assert_eq!(parsed.board().time_control(), Some(TimeControl::from_secs(300, 3)));
+    assert_eq!(parsed.board().move_clocks(), clocks.map(Some));
+    let mut board = parsed.board().clone();
+    board.undo_move().unwrap();
+    let mut resumed = Session::new();
+    resumed.add_game_at(1, board, start);
+    let clock = resumed.clock(&1).unwrap();
+    assert_eq!(clock.remaining_at(Color::White, Color::Black, start), Duration::from_millis(302_500));
+    assert_eq!(clock.remaining_at(Color::Black, Color::Black, start), Duration::from_millis(302_300));
+    // other comments are skipped, and moves without clock times have none
+    let text = "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"0-1\"]\n\n\
+        1. f3 { [%eval 0.0] [%clk 0:01:00] } 1... e5 { A comment } 2. g4 { [%clk 0:00:59] } Qh4# 0-1";
+    let parsed = Pgn::try_from(text).unwrap();
+    let (minute, almost) = (Duration::from_secs(60), Duration::from_secs(59));
+    assert_eq!(parsed.board().move_clocks(), [Some(minute), None, Some(almost), None]);
+    assert_eq!(parsed.board().clock_remaining(Color::White), Some(almost));
+    assert_eq!(parsed.board().clock_remaining(Color::Black), None);
+}
+
+#[cfg(feature = "pgn")]
+#[test]
+fn pgn_fen_checkpoints() {
+    use super::{
+        pgn::{Pgn, PgnWriteOptions},
+        FenCheckpoints, InvalidPgnError,
+    };
+
+    let mut board = Board::default();
+    board.make_moves_san("e4 d5 exd5 Qxd5 Nc3 Qa5 d4 Nf6").unwrap();
+    assert_eq!(board.gen_movetext_with_checkpoints(FenCheckpoints::default()), board.gen_movetext());
+    let captures = FenCheckpoints { captures: true, ..Default::default() };
+    assert_eq!(
+        board.gen_movetext_with_checkpoints(captures),
+        "1. e4 d5 2. exd5 {[%fen rnbqkbnr/ppp1pppp/8/3P4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2]} \
+         2... Qxd5 {[%fen rnb1kbnr/ppp1pppp/8/3q4/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3]} 3. Nc3 Qa5 4. d4 Nf6"
+    );
+    // checkpoints are verified when the PGN is parsed
+    let tag_pairs = ["Event", "Site", "Date", "Round", "White", "Black"].map(|tag| (tag.to_owned(), "?".to_owned()));
+    let pgn = Pgn::from_board(board.clone(), tag_pairs.to_vec()).unwrap();
+    let options = PgnWriteOptions {
+        checkpoints: FenCheckpoints { interval: Some(4), captures: true },
+        ..Default::default()
+    };
+    let text = String::from_utf8(pgn.to_bytes(&options).unwrap()).unwrap();
+    assert_eq!(text.matches("[%fen").count(), 3);
+    assert!(text.contains("4. d4 Nf6 {[%fen rnb1kb1r/ppp1pppp/5n2/q7/3P4/2N5/PPP2PPP/R1BQKBNR w KQkq - 1 5]} *"));
+    assert_eq!(Pgn::try_from(text.as_str()).unwrap().board(), pgn.board());
+    let corrupted = text.replace("5n2/q7/3P4", "5n2/q7/4P3");
+    assert!(matches!(Pgn::try_from(corrupted.as_str()), Err(InvalidPgnError::Checkpoint(7, _))));
+    let corrupted = text.replace("[%fen rnb1kbnr", "[%fen garbage");
+    assert!(matches!(Pgn::try_from(corrupted.as_str()), Err(InvalidPgnError::Checkpoint(3, _))));
+}
+
+#[cfg(feature = "pgn")]
+#[test]
+fn annotations() {
+    use super::pgn::{self, Annotation, AnnotationColor};
+
+    let sq = |name: &str| helpers::sq_to_idx(name.chars().next().unwrap(), name.chars().nth(1).unwrap());
+    let annotations = pgn::parse_annotations(" Ge2e4 Rd5,Yc8h3 ").unwrap();
+    assert_eq!(
+        annotations,
+        [
+            Annotation::Arrow {
+                from: sq("e2"),
+                to: sq("e4"),
+                color: AnnotationColor::Green
+            },
+            Annotation::Square {
+                square: sq("d5"),
+                color: AnnotationColor::Red
+            },
+            Annotation::Arrow {
+                from: sq("c8"),
+                to: sq("h3"),
+                color: AnnotationColor::Yellow
+            },
+        ]
+    );
+    assert!(pgn::parse_annotations("").unwrap().is_empty());
+    for invalid in ["Xe2e4", "G", "Ge2e", "Gi2", "Ge2e4e6", "ge2e4"] {
+        assert!(pgn::parse_annotations(invalid).is_err(), "{invalid}");
+    }
+    #[cfg(feature = "img")]
+    {
+        use super::img;
+
+        let props = img::PositionImageProperties::default().with_annotations("Ge2e4 Bd5").unwrap();
+        assert_eq!(
+            props.arrows,
+            [img::Arrow {
+                from: sq("e2"),
+                to: sq("e4"),
+                color: img::Rgb::from_hex("#15781b").unwrap()
+            }]
+        );
+        assert_eq!(
+            props.highlighted_squares,
+            [img::HighlightedSquare {
+                square: sq("d5"),
+                color: img::Rgb::from_hex("#003088").unwrap()
+            }]
+        );
+        assert!(img::PositionImageProperties::default().with_annotations("Gz9").is_err());
+    }
+}
+
+#[test]
+fn board_events() {
+    use super::{BoardEvent, Piece};
+
+    let mut board = Board::default();
+    board.make_move_san("e4").unwrap();
+    board.record_events(true);
+    board.make_moves_san("f5 exf5 g5").unwrap();
+    board.offer_draw(Color::Black).unwrap();
+    assert_eq!(board.draw_offer(), Some(Color::Black));
+    board.make_move_san("Qh5#").unwrap();
+    assert_eq!(board.draw_offer(), None);
+    let events = board.drain_events();
+    assert_eq!(
+        events[..5],
+        [
+            BoardEvent::MoveMade {
+                move_: Move(53, 37, None),
+                san: "f5".to_owned(),
+                uci: "f7f5".to_owned()
+            },
+            BoardEvent::MoveMade {
+                move_: Move(28, 37, None),
+                san: "exf5".to_owned(),
+                uci: "e4f5".to_owned()
+            },
+            BoardEvent::Capture(Piece::try_from('p').unwrap()),
+            BoardEvent::MoveMade {
+                move_: Move(54, 38, None),
+                san: "g5".to_owned(),
+                uci: "g7g5".to_owned()
+            },
+            BoardEvent::DrawOffered(Color::Black),
+        ]
+    );
+    assert!(matches!(
+        events[5..],
+        [
+            BoardEvent::MoveMade { .. },
+            BoardEvent::Check(Color::Black),
+            BoardEvent::GameOver(GameResult::Wins(Color::White, WinType::Checkmate))
+        ]
+    ));
+    assert!(board.drain_events().is_empty());
+    board.undo_move().unwrap();
+    assert!(matches!(board.drain_events()[..], [BoardEvent::MoveUndone(_)]));
+    board.record_events(false);
+    board.make_move_san("Qh5#").unwrap();
+    assert!(board.drain_events().is_empty());
+    let mut board = Board::from_fen(Fen::try_from("4k3/1P6/8/8/8/8/8/R3K3 w Q - 0 1").unwrap());
+    board.record_events(true);
+    board.make_moves_san("O-O-O Kf8 b8=Q+").unwrap();
+    let events = board.drain_events();
+    assert!(events.contains(&BoardEvent::Castling { side: Color::White, kingside: false }));
+    assert!(events.contains(&BoardEvent::Promotion(PieceType::Q)));
+    assert!(events.contains(&BoardEvent::Check(Color::Black)));
+}
+
+#[test]
+fn why_illegal() {
+    use super::IllegalMoveReason;
+
+    let uci = |s| Move::from_uci(s).unwrap();
+    let mut board = Board::default();
+    assert_eq!(board.why_illegal(uci("e2e4")), None);
+    assert_eq!(board.why_illegal(uci("e3e4")), Some(IllegalMoveReason::NoPieceOnSource));
+    assert_eq!(board.why_illegal(uci("e7e5")), Some(IllegalMoveReason::WrongColor));
+    assert_eq!(board.why_illegal(uci("d1d2")), Some(IllegalMoveReason::OwnPieceOnDestination));
+    assert_eq!(board.why_illegal(uci("g1g3")), Some(IllegalMoveReason::InvalidPieceMovement(PieceType::N)));
+    assert_eq!(board.why_illegal(uci("f1c4")), Some(IllegalMoveReason::Blocked(12)));
+    assert_eq!(board.why_illegal(uci("e2d3")), Some(IllegalMoveReason::NothingToCapture));
+    assert_eq!(board.why_illegal(uci("e1g1")), Some(IllegalMoveReason::Blocked(5)));
+    assert_eq!(board.why_illegal(uci("g1g3")).unwrap().to_string(), IllegalMoveReason::InvalidPieceMovement(PieceType::N).to_string());
+    board.make_moves_san("e4 e5 Ke2 Ke7 Kd3 Kd6").unwrap();
+    assert_eq!(board.why_illegal(uci("d3f3")), Some(IllegalMoveReason::InvalidPieceMovement(PieceType::K)));
+    let board = Board::from_fen(Fen::try_from("4k3/8/8/8/8/8/8/R3K2R w K - 0 1").unwrap());
+    assert_eq!(board.why_illegal(uci("e1c1")), Some(IllegalMoveReason::NoCastlingRights));
+    let board = Board::from_fen(Fen::try_from("4k3/8/8/8/8/8/5r2/4K2R w K - 0 1").unwrap());
+    assert_eq!(board.why_illegal(uci("e1g1")), Some(IllegalMoveReason::CastlingThroughCheck(5)));
+    assert_eq!(board.why_illegal(uci("e1f1")), Some(IllegalMoveReason::LeavesKingInCheck));
+    let board = Board::from_fen(Fen::try_from("4k3/8/8/8/8/8/4r3/4K2R w K - 0 1").unwrap());
+    assert_eq!(board.why_illegal(uci("e1g1")), Some(IllegalMoveReason::CastlingOutOfCheck));
+    let board = Board::from_fen(Fen::try_from("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap());
+    assert_eq!(board.why_illegal(uci("a7a8")), Some(IllegalMoveReason::InvalidPromotion));
+    assert_eq!(board.why_illegal(uci("a7a8q")), None);
+    let mut board = Board::default();
+    board.make_moves_san("f3 e5 g4 Qh4#").unwrap();
+    assert_eq!(board.why_illegal(uci("e2e4")), Some(IllegalMoveReason::GameOver));
+}
+
+#[test]
+fn hints() {
+    use super::{Hint, HintError};
+
+    // white can win the undefended queen on d5
+    let board = Board::from_fen(Fen::try_from("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap());
+    assert_eq!(board.hint(1).unwrap(), Hint::Piece(PieceType::R));
+    assert_eq!(board.hint(2).unwrap(), Hint::Square('d', '1'));
+    assert_eq!(
+        board.hint(3).unwrap(),
+        Hint::Move {
+            move_: Move(3, 35, None),
+            san: "Rxd5".to_owned()
+        }
+    );
+    assert!(matches!(board.hint(4), Err(HintError::InvalidLevel(4))));
+    // a checkmate is preferred over winning material
+    let board = Board::from_fen(Fen::try_from("6k1/5ppp/8/8/8/8/q7/4R1K1 w - - 0 1").unwrap());
+    assert!(matches!(board.hint(3).unwrap(), Hint::Move { san, .. } if san == "Re8#"));
+    let mut board = Board::default();
+    board.make_moves_san("f3 e5 g4 Qh4#").unwrap();
+    assert!(matches!(board.hint(1), Err(HintError::GameOver)));
+}
+
+#[test]
+fn mate_problems() {
+    use super::MateKind;
+
+    let solve = |fen: &str, plies: usize, kind: MateKind| {
+        let board = Board::from_fen(Fen::try_from(fen).unwrap());
+        let lines: Vec<Vec<String>> = board
+            .solve_mate(plies, kind)
+            .into_iter()
+            .map(|line| {
+                let mut board = board.clone();
+                line.into_iter()
+                    .map(|move_| {
+                        let san = board.move_to_san(move_).unwrap();
+                        board.make_move(move_).unwrap();
+                        san
+                    })
+                    .collect()
+            })
+            .collect();
+        lines
+    };
+    // #2 with two keys, each leaving the king a single move before the rook mates
+    assert_eq!(solve("k7/8/2K5/8/8/8/8/7R w - - 0 1", 3, MateKind::Direct), [["Kb6", "Kb8", "Rh8#"], ["Kc7", "Ka7", "Ra1#"]]);
+    assert!(solve("k7/8/2K5/8/8/8/8/7R w - - 0 1", 2, MateKind::Direct).is_empty());
+    assert_eq!(solve("k7/8/1K6/8/8/8/8/7R w - - 0 1", 1, MateKind::Direct), [["Rh8#"]]);
+    // h#1: black walks into the mate
+    assert_eq!(solve("k7/8/1K6/8/8/8/8/7R b - - 0 1", 2, MateKind::Helpmate), [["Kb8", "Rh8#"]]);
+    assert!(solve("k7/8/1K6/8/8/8/8/7R b - - 0 1", 1, MateKind::Helpmate).is_empty());
+    // s#1: the queen sacrifice forces the king to uncover a mate by its own rook
+    assert_eq!(solve("8/8/8/8/8/3Qp2B/6PP/r1k4K w - - 0 1", 2, MateKind::Selfmate), [["Qc2+", "Kxc2#"]]);
+    let mut board = Board::default();
+    board.make_moves_san("f3 e5 g4 Qh4#").unwrap();
+    assert!(board.solve_mate(4, MateKind::Helpmate).is_empty());
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn deterministic_rendering() {
+    use super::img;
+    use std::collections::HashMap;
+
+    assert_eq!(img::image_digest(&image::RgbaImage::new(0, 0)), "9d30c1f78465995be47dda5e4e4e77ed");
+    // pieces drawn as solid squares, so the whole render is done in Rust
+    let pieces: HashMap<_, _> = ["K", "Q", "R", "B", "N", "P"]
+        .into_iter()
+        .flat_map(|piece| [(format!("w{piece}"), 255), (format!("b{piece}"), 0)])
+        .map(|(piece, value)| (piece, image::RgbaImage::from_pixel(4, 4, image::Rgba([value, value, value, 255]))))
+        .collect();
+    let board = Board::from_fen(Fen::try_from("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap());
+    let props = img::PositionImageProperties {
+        piece_set: img::PieceSet::Custom(pieces),
+        size: 200,
+        arrows: vec![img::Arrow {
+            from: 5,
+            to: 33,
+            color: img::Rgb::new(0, 100, 0),
+        }],
+        coordinates: Some(img::Coordinates::default()),
+        evaluation_bar: Some(img::EvaluationBar::new(img::Evaluation::Centipawns(35))),
+        ..Default::default()
+    }
+    .with_theme(img::Theme::builtin("blue-marble").unwrap());
+    let image = img::board_to_image(&board, props.clone(), Color::White).unwrap();
+    assert_eq!(img::image_digest(&image), img::image_digest(&img::board_to_image(&board, props, Color::White).unwrap()));
+    assert_eq!(img::image_digest(&image), "a246636a2a31c43f6b93350cc4f209c5");
+    assert!((img::Evaluation::Centipawns(35).white_share() - 1. / (1. + (-0.00368208f64 * 35.).exp())).abs() < 1e-12);
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn piece_set_from_dir() {
+    use super::img;
+
+    let dir = std::env::temp_dir().join(format!("rschess-piece-set-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let builtin = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/pieces/cburnett");
+    for piece in ["wK", "wQ", "wR", "wB", "wN", "wP", "bK", "bQ", "bR", "bB", "bN"] {
+        std::fs::copy(builtin.join(format!("{piece}.svg")), dir.join(format!("{piece}.svg"))).unwrap();
+    }
+    assert!(matches!(img::PieceSet::from_dir(&dir), Err(super::errors::PieceSetLoadError::MissingPiece(p)) if p == "bP"));
+    image::RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255])).save(dir.join("bP.png")).unwrap();
+    let piece_set = img::PieceSet::from_dir(&dir).unwrap();
+    let board = Board::default();
+    let props = img::PositionImageProperties {
+        piece_set: piece_set.clone(),
+        size: 256,
+        ..Default::default()
+    };
+    let image = img::position_to_image(board.position(), props.clone(), Color::White).unwrap();
+    // SVG pieces are rasterized like the built-in set, and the PNG pawns are scaled to fill their squares
+    let builtin_props = img::PositionImageProperties { size: 256, ..Default::default() };
+    let builtin_image = img::position_to_image(board.position(), builtin_props, Color::White).unwrap();
+    assert_eq!(image.get_pixel(16, 16), builtin_image.get_pixel(16, 16));
+    assert_eq!(image.get_pixel(16, 48).0, [255, 0, 0, 255]);
+    // clones share the cache, so rendering again gives the same image
+    assert_eq!(img::position_to_image(board.position(), props.clone(), Color::White).unwrap(), image);
+    let svg = img::position_to_svg(board.position(), props, Color::White).unwrap();
+    assert!(svg.contains("data:image/svg+xml;base64,") && svg.contains("data:image/png;base64,"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn piece_set_from_zip() {
+    use super::{errors::PieceSetLoadError, img};
+    use std::io::Write;
+
+    let builtin = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/pieces/cburnett");
+    let zip = |pieces: &[&str]| {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        writer.start_file("README.txt", zip::write::SimpleFileOptions::default()).unwrap();
+        for piece in pieces {
+            writer.start_file(format!("cburnett/{piece}.svg"), zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(&std::fs::read(builtin.join(format!("{piece}.svg"))).unwrap()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    };
+    let pieces = ["wK", "wQ", "wR", "wB", "wN", "wP", "bK", "bQ", "bR", "bB", "bN", "bP"];
+    assert!(matches!(img::PieceSet::from_zip(&zip(&pieces[..11])), Err(PieceSetLoadError::MissingPiece(p)) if p == "bP"));
+    assert!(matches!(img::PieceSet::from_zip(&zip(&pieces[1..])), Err(PieceSetLoadError::MissingPiece(p)) if p == "wK"));
+    assert!(matches!(img::PieceSet::from_zip(b"not an archive"), Err(PieceSetLoadError::Archive(_))));
+    let piece_set = img::PieceSet::from_zip(&zip(&pieces)).unwrap();
+    let img::PieceSet::Directory(directory) = &piece_set else { panic!("expected a piece directory") };
+    assert_eq!(directory.path(), std::path::Path::new("cburnett"));
+    let props = img::PositionImageProperties {
+        piece_set,
+        size: 256,
+        ..Default::default()
+    };
+    let builtin_props = img::PositionImageProperties {
+        piece_set: img::PieceSet::Builtin("cburnett".to_owned()),
+        size: 256,
+        ..Default::default()
+    };
+    let position = Board::default().position().clone();
+    assert_eq!(
+        img::position_to_image(&position, props, Color::White).unwrap(),
+        img::position_to_image(&position, builtin_props, Color::White).unwrap()
+    );
+}
+
+#[cfg(feature = "img")]
+#[test]
+fn renderer() {
+    use super::img;
+
+    let mut board = Board::default();
+    board.make_moves_san("e4 d5 exd5 Qxd5 Nc3 Qe5+").unwrap();
+    let props = img::PositionImageProperties {
+        size: 256,
+        material_tray: Some(Default::default()),
+        ..Default::default()
+    };
+    let themed = props.clone().with_theme(img::Theme::builtin("blue-marble").unwrap());
+    let mut renderer = img::Renderer::new();
+    // the renderer draws the same images as the free functions, whether its cache is reused or cleared by a change of theme
+    for (props, perspective) in [(&props, Color::White), (&props, Color::Black), (&themed, Color::White), (&props, Color::White)] {
+        assert_eq!(
+            renderer.board_to_image(&board, props.clone(), perspective).unwrap(),
+            img::board_to_image(&board, props.clone(), perspective).unwrap()
+        );
+        assert_eq!(
+            renderer.position_to_image(board.position(), props.clone(), perspective).unwrap(),
+            img::position_to_image(board.position(), props.clone(), perspective).unwrap()
+        );
+    }
+}
+
+#[test]
+fn navigation() {
+    use super::{FuturePolicy, NavigationError};
+
+    let mut board = Board::default();
+    board.make_moves_san("e4 e5 Nf3 Nc6 Bb5").unwrap();
+    let end = board.clone();
+    board.back().unwrap();
+    board.back().unwrap();
+    assert_eq!(board.current_ply(), 3);
+    assert_eq!(board.future_moves(), [Move(57, 42, None), Move(5, 33, None)]);
+    board.forward().unwrap();
+    assert_eq!(board.current_ply(), 4);
+    board.goto_ply(0).unwrap();
+    assert!(board.same_position(&Board::default()));
+    assert!(matches!(board.back(), Err(NavigationError::AtStart)));
+    assert!(matches!(board.goto_ply(6), Err(NavigationError::PlyOutOfRange(6, 5))));
+    board.goto_ply(5).unwrap();
+    assert!(matches!(board.forward(), Err(NavigationError::AtEnd)));
+    assert_eq!(board.to_fen(), end.to_fen());
+    // playing the next move keeps the rest of the moves ahead
+    board.goto_ply(2).unwrap();
+    board.make_move_san("Nf3").unwrap();
+    assert_eq!(board.future_moves().len(), 2);
+    // playing a different move truncates them by default
+    board.make_move_san("d6").unwrap();
+    assert!(board.future_moves().is_empty() && board.branches().is_empty());
+    board.set_future_policy(FuturePolicy::Branch);
+    board.back().unwrap();
+    board.make_move_san("Nf6").unwrap();
+    assert_eq!(board.branches(), [(3, vec![Move(51, 43, None)])]);
+    // undoing a move discards the moves ahead
+    board.back().unwrap();
+    board.undo_move().unwrap();
+    assert!(board.future_moves().is_empty());
+}
+
+#[test]
+fn game_history() {
+    let mut board = Board::from_fen(Fen::try_from("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap());
+    board.make_moves_san("e5 Nf3 Nc6").unwrap();
+    let history = board.history();
+    assert_eq!(history.iter().map(|(_, san)| san.as_str()).collect::<Vec<_>>(), ["e5", "Nf3", "Nc6"]);
+    assert_eq!(history[0].0, Move(52, 36, None));
+    assert_eq!(board.move_history().len(), 3);
+    assert_eq!(board.position_at_ply(0), Some(board.initial_fen().position()));
+    assert_eq!(board.position_at_ply(3), Some(board.position()));
+    assert_eq!(board.position_at_ply(4), None);
+    let fens: Vec<_> = board.fens().map(|fen| fen.to_string()).collect();
+    assert_eq!(
+        fens,
+        [
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2",
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2",
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+        ]
+    );
+    assert_eq!(board.fens().last().unwrap(), board.to_fen());
+}
+
+#[cfg(feature = "proptest")]
+mod round_trips {
+    use super::super::{strategy, Board, Fen};
+    use proptest::prelude::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn fen_round_trip(board in strategy::boards(60)) {
+            let fen = board.to_fen();
+            prop_assert_eq!(Fen::try_from(fen.to_string().as_str()).unwrap(), fen);
+        }
+
+        #[test]
+        fn san_round_trip((board, move_) in strategy::boards_with_move(60)) {
+            let san = board.move_to_san(move_).unwrap();
+            prop_assert_eq!(board.san_to_move(&san).unwrap(), move_);
+            let mut replayed = Board::default();
+            replayed.make_moves_san(&board.history().into_iter().map(|(_, san)| san).collect::<Vec<_>>().join(" ")).unwrap();
+            prop_assert_eq!(replayed.position(), board.position());
+        }
+    }
+}
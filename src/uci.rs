@@ -0,0 +1,128 @@
+//! Handles commands sent to engines over the Universal Chess Interface (UCI).
+
+use super::{Board, Color, Fen, Move, UciCommandError};
+use std::time::Duration;
+
+/// The keywords which may appear in a UCI _go_ command.
+const GO_KEYWORDS: [&str; 12] = ["searchmoves", "ponder", "wtime", "btime", "winc", "binc", "movestogo", "depth", "nodes", "mate", "movetime", "infinite"];
+
+/// Represents the parameters of a UCI _go_ command, which tells an engine to start searching.
+#[derive(Eq, PartialEq, Hash, Clone, Debug, Default)]
+pub struct GoParams {
+    /// The moves to restrict the search to (all legal moves if empty), as UCI moves whose special move type may be unclear
+    pub searchmoves: Vec<Move>,
+    /// Whether to search in pondering mode
+    pub ponder: bool,
+    /// The time remaining for white
+    pub wtime: Option<Duration>,
+    /// The time remaining for black
+    pub btime: Option<Duration>,
+    /// The increment for white
+    pub winc: Option<Duration>,
+    /// The increment for black
+    pub binc: Option<Duration>,
+    /// The number of moves until the next time control
+    pub movestogo: Option<u32>,
+    /// The maximum depth to search, in plies
+    pub depth: Option<u32>,
+    /// The maximum number of nodes to search
+    pub nodes: Option<u64>,
+    /// The number of moves in which to search for a mate
+    pub mate: Option<u32>,
+    /// The exact time to search for
+    pub movetime: Option<Duration>,
+    /// Whether to search until told to stop
+    pub infinite: bool,
+}
+
+impl GoParams {
+    /// Returns the time remaining for the given side, if given.
+    pub fn time(&self, side: Color) -> Option<Duration> {
+        if side.is_white() {
+            self.wtime
+        } else {
+            self.btime
+        }
+    }
+
+    /// Returns the increment for the given side, if given.
+    pub fn increment(&self, side: Color) -> Option<Duration> {
+        if side.is_white() {
+            self.winc
+        } else {
+            self.binc
+        }
+    }
+}
+
+/// Parses a UCI _position_ command (such as `position startpos moves e2e4 e7e5` or `position fen <FEN> moves ...`),
+/// returning the board with the moves played, or an error if the command is invalid.
+pub fn parse_position_command(command: &str) -> Result<Board, UciCommandError> {
+    let mut tokens = command.split_whitespace();
+    if tokens.next() != Some("position") {
+        return Err(UciCommandError::UnexpectedCommand(command.trim().to_owned()));
+    }
+    let mut board = match tokens.next() {
+        Some("startpos") => Board::default(),
+        Some("fen") => {
+            let fields: Vec<_> = tokens.by_ref().take_while(|&t| t != "moves").collect();
+            let fen = Fen::try_from(fields.join(" ").as_str())?;
+            let mut board = Board::from_fen(fen);
+            board.make_moves_uci(&tokens.collect::<Vec<_>>().join(" "))?;
+            return Ok(board);
+        }
+        _ => return Err(UciCommandError::MissingPosition),
+    };
+    match tokens.next() {
+        None => (),
+        Some("moves") => board.make_moves_uci(&tokens.collect::<Vec<_>>().join(" "))?,
+        Some(token) => return Err(UciCommandError::UnexpectedToken(token.to_owned())),
+    }
+    Ok(board)
+}
+
+/// Parses a UCI _go_ command (such as `go wtime 300000 btime 300000 winc 2000 binc 2000`), returning an error if it is invalid.
+/// Times are given in milliseconds, and negative times (sent by some GUIs when a side is out of time) are read as zero.
+pub fn parse_go_command(command: &str) -> Result<GoParams, UciCommandError> {
+    let mut tokens = command.split_whitespace().peekable();
+    if tokens.next() != Some("go") {
+        return Err(UciCommandError::UnexpectedCommand(command.trim().to_owned()));
+    }
+    let mut params = GoParams::default();
+    while let Some(keyword) = tokens.next() {
+        let mut value = || tokens.next().ok_or_else(|| UciCommandError::MissingValue(keyword.to_owned()));
+        match keyword {
+            "searchmoves" => {
+                while let Some(token) = tokens.next_if(|t| !GO_KEYWORDS.contains(t)) {
+                    params
+                        .searchmoves
+                        .push(Move::from_uci(token).map_err(|_| UciCommandError::InvalidValue(keyword.to_owned(), token.to_owned()))?);
+                }
+            }
+            "ponder" => params.ponder = true,
+            "infinite" => params.infinite = true,
+            "wtime" => params.wtime = Some(parse_millis(keyword, value()?)?),
+            "btime" => params.btime = Some(parse_millis(keyword, value()?)?),
+            "winc" => params.winc = Some(parse_millis(keyword, value()?)?),
+            "binc" => params.binc = Some(parse_millis(keyword, value()?)?),
+            "movetime" => params.movetime = Some(parse_millis(keyword, value()?)?),
+            "movestogo" => params.movestogo = Some(parse_number(keyword, value()?)?),
+            "depth" => params.depth = Some(parse_number(keyword, value()?)?),
+            "nodes" => params.nodes = Some(parse_number(keyword, value()?)?),
+            "mate" => params.mate = Some(parse_number(keyword, value()?)?),
+            _ => return Err(UciCommandError::UnexpectedToken(keyword.to_owned())),
+        }
+    }
+    Ok(params)
+}
+
+/// Parses the value of a _go_ parameter as a number.
+fn parse_number<T: std::str::FromStr>(keyword: &str, value: &str) -> Result<T, UciCommandError> {
+    value.parse().map_err(|_| UciCommandError::InvalidValue(keyword.to_owned(), value.to_owned()))
+}
+
+/// Parses the value of a _go_ parameter as a (possibly negative) number of milliseconds.
+fn parse_millis(keyword: &str, value: &str) -> Result<Duration, UciCommandError> {
+    let millis: i64 = parse_number(keyword, value)?;
+    Ok(Duration::from_millis(millis.max(0) as u64))
+}
@@ -0,0 +1,261 @@
+use super::{helpers, Color, DrawType, Fen, GameResult, IllegalMoveError, InvalidFenError, InvalidUciMoveError, Move, NoMovesPlayedError, Piece, PieceType, Position, SpecialMoveType, WinType};
+use std::{fmt::Debug, hash::Hash};
+
+/// The rules of a chess variant, which determine how a [`VariantBoard`] is set up, which moves are legal, and how the game ends.
+///
+/// Every hook has a default implementation following the rules of standard chess (as in [`Standard`]), except [`Variant::starting_fen`].
+/// The seventy-five-move rule and fivefold repetition apply to every variant, and are handled by [`VariantBoard`].
+pub trait Variant: Clone + Eq + Hash + Debug + Default {
+    /// Additional game state tracked by the variant (such as the number of checks each side has given), which is updated after every move.
+    type State: Clone + Eq + Hash + Debug + Default;
+
+    /// Returns the FEN of the starting position.
+    fn starting_fen(&self) -> &'static str;
+
+    /// Parses an FEN under the constraints of the variant (such as which pieces a side must have), returning an error if it is invalid.
+    fn parse_fen(&self, fen: &str) -> Result<Fen, InvalidFenError> {
+        Fen::parse(fen)
+    }
+
+    /// Generates the legal moves in a position, assuming the game is ongoing.
+    fn legal_moves(&self, position: &Position) -> Vec<Move> {
+        position.gen_non_illegal_moves()
+    }
+
+    /// Returns the position which occurs after a legal move (returned by [`Variant::legal_moves`]) is played in a position.
+    fn position_after(&self, position: &Position, move_: Move) -> Position {
+        position.with_legal_move_made(move_)
+    }
+
+    /// Updates the additional state after a legal move is played in a position.
+    fn update_state(&self, _state: &mut Self::State, _position: &Position, _move: Move) {}
+
+    /// Determines the result of the game in a position, given the additional state and the legal moves in the position,
+    /// returning `None` if the game is not over by the rules of the variant.
+    fn result(&self, position: &Position, _state: &Self::State, legal_moves: &[Move]) -> Option<GameResult> {
+        if legal_moves.is_empty() {
+            Some(match position.checked_side() {
+                Some(side) => GameResult::Wins(!side, WinType::Checkmate),
+                None => GameResult::Draw(DrawType::Stalemate(position.side)),
+            })
+        } else if position.is_insufficient_material() {
+            Some(GameResult::Draw(DrawType::InsufficientMaterial))
+        } else {
+            None
+        }
+    }
+}
+
+/// The rules of standard chess, as a [`Variant`]. For standard games, [`Board`](crate::Board) offers much more (such as SAN, PGN, and takebacks).
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug, Default)]
+pub struct Standard;
+
+impl Variant for Standard {
+    type State = ();
+
+    fn starting_fen(&self) -> &'static str {
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    }
+}
+
+/// A game of a chess variant, played by the rules of `V` (see [`Variant`]).
+///
+/// This is a separate type from [`Board`](crate::Board) rather than a type parameter of it, because most of what `Board` offers assumes
+/// the rules of standard chess: SAN and PGN (which have no notation for variant moves such as Horde's double pawn pushes from the first rank),
+/// [`Rules`](crate::Rules) and draw claims, and the invariant checks of [`Board::verify_invariants`](crate::Board::verify_invariants).
+/// A variant game therefore offers only the core of a game: making moves in UCI, undoing them, FEN, and the result
+/// (with the seventy-five-move rule and fivefold repetition). It has no SAN or PGN, clocks or time controls, metadata,
+/// navigation through the moves (`back`, `forward`, and branches), resignation, draw offers or claims, takebacks, or events.
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub struct VariantBoard<V: Variant> {
+    /// The rules of the variant
+    variant: V,
+    /// The position on the board
+    position: Position,
+    /// The additional state of the variant
+    state: V::State,
+    /// The number of halfmoves played since the last pawn push or capture
+    halfmove_clock: usize,
+    /// The current fullmove number
+    fullmove_number: usize,
+    /// The FEN representing the initial game state
+    initial_fen: Fen,
+    /// The list of positions that have occurred on the board
+    position_history: Vec<Position>,
+    /// The list of additional states that have occurred
+    state_history: Vec<V::State>,
+    /// The list of moves that have occurred on the board
+    move_history: Vec<Move>,
+    /// The halfmove clock values that have occurred
+    halfmove_clock_history: Vec<usize>,
+    /// The result of the game, if it has ended
+    result: Option<GameResult>,
+}
+
+impl<V: Variant> VariantBoard<V> {
+    /// Creates a game from the starting position of the variant.
+    pub fn new() -> Self {
+        Self::with_variant(V::default())
+    }
+
+    /// Creates a game with the given rules from the starting position of the variant.
+    pub fn with_variant(variant: V) -> Self {
+        let fen = variant.starting_fen();
+        Self::from_fen_with_variant(variant, fen).unwrap()
+    }
+
+    /// Creates a game from an FEN, returning an error if it is invalid under the constraints of the variant (see [`Variant::parse_fen`]).
+    pub fn from_fen(fen: &str) -> Result<Self, InvalidFenError> {
+        Self::from_fen_with_variant(V::default(), fen)
+    }
+
+    /// Creates a game with the given rules from an FEN, returning an error if it is invalid under the constraints of the variant.
+    pub fn from_fen_with_variant(variant: V, fen: &str) -> Result<Self, InvalidFenError> {
+        let fen = variant.parse_fen(fen)?;
+        let mut game = Self {
+            variant,
+            position: fen.position.clone(),
+            state: V::State::default(),
+            halfmove_clock: fen.halfmove_clock,
+            fullmove_number: fen.fullmove_number,
+            initial_fen: fen,
+            position_history: Vec::new(),
+            state_history: Vec::new(),
+            move_history: Vec::new(),
+            halfmove_clock_history: Vec::new(),
+            result: None,
+        };
+        game.update_result();
+        Ok(game)
+    }
+
+    /// Returns the rules of the variant.
+    pub fn variant(&self) -> &V {
+        &self.variant
+    }
+
+    /// Returns the current position.
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Returns the additional state of the variant.
+    pub fn state(&self) -> &V::State {
+        &self.state
+    }
+
+    /// Returns an FEN representing the current game state.
+    pub fn to_fen(&self) -> Fen {
+        Fen {
+            position: self.position.clone(),
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+        }
+    }
+
+    /// Returns the FEN representing the initial game state.
+    pub fn initial_fen(&self) -> &Fen {
+        &self.initial_fen
+    }
+
+    /// Returns the side to move.
+    pub fn side_to_move(&self) -> Color {
+        self.position.side
+    }
+
+    /// Generates the legal moves in the position (which is empty if the game is over).
+    pub fn gen_legal_moves(&self) -> Vec<Move> {
+        if self.result.is_some() {
+            return Vec::new();
+        }
+        self.variant.legal_moves(&self.position)
+    }
+
+    /// Checks whether a move is legal in the position.
+    pub fn is_legal(&self, move_: Move) -> bool {
+        helpers::as_legal(move_, &self.gen_legal_moves()).is_some()
+    }
+
+    /// Plays the given move, returning an error if the move is illegal.
+    pub fn make_move(&mut self, move_: Move) -> Result<(), IllegalMoveError> {
+        let move_ = helpers::as_legal(move_, &self.gen_legal_moves()).ok_or(IllegalMoveError(move_))?;
+        let is_capture = self.position.captures(&move_);
+        let is_pawn_move = matches!(self.position.content[move_.0], Some(Piece(PieceType::P, _)));
+        let mut state = self.state.clone();
+        self.variant.update_state(&mut state, &self.position, move_);
+        let position = self.variant.position_after(&self.position, move_);
+        self.position_history.push(std::mem::replace(&mut self.position, position));
+        self.state_history.push(std::mem::replace(&mut self.state, state));
+        self.halfmove_clock_history.push(self.halfmove_clock);
+        self.move_history.push(move_);
+        if self.position.side.is_white() {
+            self.fullmove_number += 1;
+        }
+        self.halfmove_clock = if is_capture || is_pawn_move { 0 } else { self.halfmove_clock + 1 };
+        self.update_result();
+        Ok(())
+    }
+
+    /// Attempts to parse the UCI representation of a move (where promotions to kings, in variants which allow them, end in `k`) and play it,
+    /// returning an error if the move is invalid or illegal.
+    pub fn make_move_uci(&mut self, uci: &str) -> Result<(), InvalidUciMoveError> {
+        let move_ = match uci.strip_suffix('k') {
+            Some(stripped) if uci.len() == 5 => Move::from_uci(stripped).map(|Move(src, dest, _)| Move(src, dest, Some(SpecialMoveType::Promotion(PieceType::K)))),
+            _ => Move::from_uci(uci),
+        }
+        .map_err(|_| InvalidUciMoveError::InvalidUci(uci.to_owned()))?;
+        self.make_move(move_).map_err(|_| InvalidUciMoveError::IllegalMove(uci.to_owned()))
+    }
+
+    /// Undoes the most recent move, returning an error if no moves have been played. If the game had ended, it is ongoing again.
+    pub fn undo_move(&mut self) -> Result<(), NoMovesPlayedError> {
+        self.position = self.position_history.pop().ok_or(NoMovesPlayedError)?;
+        self.state = self.state_history.pop().unwrap();
+        self.move_history.pop();
+        self.halfmove_clock = self.halfmove_clock_history.pop().unwrap();
+        if self.position.side.is_black() {
+            self.fullmove_number -= 1;
+        }
+        self.result = None;
+        Ok(())
+    }
+
+    /// Returns the moves that have been played, in order.
+    pub fn move_history(&self) -> &[Move] {
+        &self.move_history
+    }
+
+    /// Checks whether the game is still in progress.
+    pub fn is_ongoing(&self) -> bool {
+        self.result.is_none()
+    }
+
+    /// Returns the result of the game, or `None` if it is ongoing.
+    pub fn game_result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    /// Determines whether the game has ended in the current position.
+    fn update_result(&mut self) {
+        let legal_moves = self.variant.legal_moves(&self.position);
+        self.result = self.variant.result(&self.position, &self.state, &legal_moves).or(if self.halfmove_clock >= 150 {
+            Some(GameResult::Draw(DrawType::SeventyFiveMoveRule))
+        } else if self.repetition_count() >= 5 {
+            Some(GameResult::Draw(DrawType::FivefoldRepetition))
+        } else {
+            None
+        });
+    }
+
+    /// Counts the occurrences of the current position (the same placement of pieces, side to move, and additional state) in the game.
+    fn repetition_count(&self) -> usize {
+        let same = |(other, state): &(&Position, &V::State)| other.content == self.position.content && other.side == self.position.side && *state == &self.state;
+        self.position_history.iter().zip(&self.state_history).filter(same).count() + 1
+    }
+}
+
+impl<V: Variant> Default for VariantBoard<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,65 @@
+//! Utilities for verifying user-provided chess code and games against rschess.
+
+use super::{errors::ProofGameError, Board, Move, Position};
+
+/// Represents the point of view from which an evaluation function scores positions.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum EvalPerspective {
+    /// Positive scores are good for white.
+    White,
+    /// Positive scores are good for the side to move.
+    SideToMove,
+}
+
+/// Represents a position which an evaluation function does not score symmetrically with its color-swapped mirror image.
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub struct EvalAsymmetry {
+    /// The FEN of the position (without the move counters)
+    pub fen: String,
+    /// The FEN of the color-swapped mirror image of the position
+    pub mirrored_fen: String,
+    /// The score of the position
+    pub score: i32,
+    /// The score of the mirror image
+    pub mirrored_score: i32,
+}
+
+/// Checks that an evaluation function is color-symmetric, by evaluating every position in a corpus along with its color-swapped mirror
+/// image (see [`Position::color_swapped`]) and returning the positions whose scores do not correspond, in the order of the corpus.
+/// With [`EvalPerspective::White`], the scores of a position and its mirror image must be opposite; with [`EvalPerspective::SideToMove`], equal.
+pub fn eval_asymmetries<'a, F>(positions: impl IntoIterator<Item = &'a Position>, perspective: EvalPerspective, mut eval: F) -> Vec<EvalAsymmetry>
+where
+    F: FnMut(&Position) -> i32,
+{
+    let mut asymmetries = Vec::new();
+    for position in positions {
+        let mirrored = position.color_swapped();
+        let (score, mirrored_score) = (eval(position), eval(&mirrored));
+        let symmetric = match perspective {
+            EvalPerspective::White => score == -mirrored_score,
+            EvalPerspective::SideToMove => score == mirrored_score,
+        };
+        if !symmetric {
+            asymmetries.push(EvalAsymmetry {
+                fen: position.to_fen(),
+                mirrored_fen: mirrored.to_fen(),
+                score,
+                mirrored_score,
+            });
+        }
+    }
+    asymmetries
+}
+
+/// Verifies that a sequence of moves is a legal game from the standard starting position (a proof game), returning the board after the last move.
+/// Moves are resolved as with [`Board::make_move`], so unclear special move types are accepted. Every move but the last must leave the game ongoing.
+pub fn verify_proof_game(moves: &[Move]) -> Result<Board, ProofGameError> {
+    let mut board = Board::default();
+    for (ply, &move_) in moves.iter().enumerate() {
+        if !board.is_ongoing() {
+            return Err(ProofGameError::GameOver(ply));
+        }
+        board.make_move(move_).map_err(|_| ProofGameError::IllegalMove(ply, move_))?;
+    }
+    Ok(board)
+}
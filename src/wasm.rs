@@ -0,0 +1,171 @@
+//! JavaScript bindings for the core of rschess, for running the same rules in the browser as on the server.
+//!
+//! Build a crate depending on rschess with the `wasm` feature for `wasm32-unknown-unknown` with `wasm-pack` (or `wasm-bindgen`),
+//! and the [`Board`](WasmBoard) and [`Move`](WasmMove) classes and the FEN functions below are exported to JavaScript.
+//! Moves are passed to and from JavaScript in UCI or SAN, and errors are thrown as JavaScript `Error`s.
+//!
+//! `wasm32-unknown-unknown` has no clock and no file system, so clock times are not measured: search time limits are ignored,
+//! engine matches do not consume time, and progress reports no elapsed time. Functions which open files are unavailable,
+//! and so are the methods of [`Session`](crate::Session) which read the clock, leaving only the variants taking an
+//! [`Instant`](std::time::Instant) (such as [`Session::make_move_at`](crate::Session::make_move_at)).
+//! The `img` and `zstd` features depend on C libraries, and the `rand` feature needs a source of randomness configured for the target.
+
+use super::{Board, Fen, Move};
+use wasm_bindgen::prelude::*;
+
+/// A chess game, exported to JavaScript as `Board`.
+#[wasm_bindgen(js_name = Board)]
+pub struct WasmBoard {
+    board: Board,
+}
+
+#[wasm_bindgen(js_class = Board)]
+impl WasmBoard {
+    /// Creates a game from a FEN, or from the standard starting position if none is given.
+    #[wasm_bindgen(constructor)]
+    pub fn new(fen: Option<String>) -> Result<WasmBoard, JsError> {
+        let board = match fen {
+            Some(fen) => Board::from_fen(Fen::try_from(fen.as_str())?),
+            None => Board::default(),
+        };
+        Ok(Self { board })
+    }
+
+    /// Returns the FEN of the current position.
+    pub fn fen(&self) -> String {
+        self.board.to_fen().to_string()
+    }
+
+    /// Returns the side to move, `"w"` or `"b"`.
+    #[wasm_bindgen(js_name = sideToMove)]
+    pub fn side_to_move(&self) -> String {
+        if self.board.side_to_move().is_white() { "w" } else { "b" }.to_owned()
+    }
+
+    /// Returns the legal moves in the current position.
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Vec<WasmMove> {
+        self.board.gen_legal_moves().into_iter().map(WasmMove).collect()
+    }
+
+    /// Returns the SAN representations of the legal moves in the current position.
+    #[wasm_bindgen(js_name = legalMovesSan)]
+    pub fn legal_moves_san(&self) -> Vec<String> {
+        self.board.legal_moves_san()
+    }
+
+    /// Plays a move.
+    #[wasm_bindgen(js_name = makeMove)]
+    pub fn make_move(&mut self, move_: &WasmMove) -> Result<(), JsError> {
+        Ok(self.board.make_move(move_.0)?)
+    }
+
+    /// Plays a move given in UCI (such as `e2e4` or `e7e8q`).
+    #[wasm_bindgen(js_name = makeMoveUci)]
+    pub fn make_move_uci(&mut self, uci: &str) -> Result<(), JsError> {
+        Ok(self.board.make_move_uci(uci)?)
+    }
+
+    /// Plays a move given in SAN (such as `e4` or `Nxf7+`).
+    #[wasm_bindgen(js_name = makeMoveSan)]
+    pub fn make_move_san(&mut self, san: &str) -> Result<(), JsError> {
+        Ok(self.board.make_move_san(san)?)
+    }
+
+    /// Undoes the last move.
+    #[wasm_bindgen(js_name = undoMove)]
+    pub fn undo_move(&mut self) -> Result<(), JsError> {
+        Ok(self.board.undo_move()?)
+    }
+
+    /// Returns the SAN representation of a legal move in the current position.
+    #[wasm_bindgen(js_name = moveToSan)]
+    pub fn move_to_san(&self, move_: &WasmMove) -> Result<String, JsError> {
+        Ok(self.board.move_to_san(move_.0)?)
+    }
+
+    /// Returns whether the side to move is in check.
+    #[wasm_bindgen(js_name = isCheck)]
+    pub fn is_check(&self) -> bool {
+        self.board.is_check()
+    }
+
+    /// Returns whether the side to move has been checkmated.
+    #[wasm_bindgen(js_name = isCheckmate)]
+    pub fn is_checkmate(&self) -> bool {
+        self.board.is_checkmate()
+    }
+
+    /// Returns whether the game is over.
+    #[wasm_bindgen(js_name = isGameOver)]
+    pub fn is_game_over(&self) -> bool {
+        self.board.is_game_over()
+    }
+
+    /// Returns the result of the game as a PGN result token (`"1-0"`, `"0-1"`, `"1/2-1/2"`, or `"*"` if the game is ongoing).
+    pub fn result(&self) -> String {
+        self.board.outcome().to_pgn_token().to_owned()
+    }
+
+    /// Returns the moves played, in UCI and separated by spaces (as in a UCI `position` command).
+    #[wasm_bindgen(js_name = uciMoves)]
+    pub fn uci_moves(&self) -> String {
+        self.board.to_uci_moves()
+    }
+
+    /// Returns the PGN movetext of the game.
+    pub fn movetext(&self) -> String {
+        self.board.gen_movetext()
+    }
+}
+
+/// A chess move, exported to JavaScript as `Move`.
+#[wasm_bindgen(js_name = Move)]
+#[derive(Copy, Clone)]
+pub struct WasmMove(Move);
+
+#[wasm_bindgen(js_class = Move)]
+impl WasmMove {
+    /// Creates a move from its UCI representation (such as `e2e4` or `e7e8q`).
+    #[wasm_bindgen(js_name = fromUci)]
+    pub fn from_uci(uci: &str) -> Result<WasmMove, JsError> {
+        Ok(Self(Move::from_uci(uci)?))
+    }
+
+    /// Returns the name of the source square of the move (such as `e2`).
+    #[wasm_bindgen(getter)]
+    pub fn from(&self) -> String {
+        let (file, rank) = self.0.from_square();
+        format!("{file}{rank}")
+    }
+
+    /// Returns the name of the destination square of the move (such as `e4`).
+    #[wasm_bindgen(getter)]
+    pub fn to(&self) -> String {
+        let (file, rank) = self.0.to_square();
+        format!("{file}{rank}")
+    }
+
+    /// Returns the letter of the piece type the move promotes to (`q`, `r`, `b`, or `n`), if it is a promotion.
+    #[wasm_bindgen(getter)]
+    pub fn promotion(&self) -> Option<String> {
+        self.0.promotion().map(|piece_type| char::from(piece_type).to_ascii_lowercase().to_string())
+    }
+
+    /// Returns the UCI representation of the move.
+    pub fn uci(&self) -> String {
+        self.0.to_uci()
+    }
+}
+
+/// Returns whether a FEN is valid.
+#[wasm_bindgen(js_name = isValidFen)]
+pub fn is_valid_fen(fen: &str) -> bool {
+    Fen::try_from(fen).is_ok()
+}
+
+/// Returns a FEN in its normalized form (see [`Fen::normalized`]), throwing an error if it is invalid.
+#[wasm_bindgen(js_name = normalizeFen)]
+pub fn normalize_fen(fen: &str) -> Result<String, JsError> {
+    Ok(Fen::try_from(fen)?.normalized().to_string())
+}